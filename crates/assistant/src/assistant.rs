@@ -16,6 +16,7 @@ mod streaming_diff;
 mod terminal_inline_assistant;
 mod tools;
 
+use anyhow::{anyhow, bail, Result};
 pub use assistant_panel::{AssistantPanel, AssistantPanelEvent};
 use assistant_settings::AssistantSettings;
 use assistant_slash_command::SlashCommandRegistry;
@@ -23,16 +24,19 @@ use assistant_tool::ToolRegistry;
 use client::{proto, Client};
 use command_palette_hooks::CommandPaletteFilter;
 pub use context::*;
-use context_servers::ContextServerRegistry;
+use context_servers::{manager::ContextServerManager, types, ContextServerRegistry};
 pub use context_store::*;
 use feature_flags::FeatureFlagAppExt;
 use fs::Fs;
-use gpui::{actions, AppContext, Global, SharedString, UpdateGlobal};
+use futures::StreamExt;
+use gpui::{actions, AppContext, Global, PromptLevel, SharedString, Task, UpdateGlobal};
 use gpui::{impl_actions, Context as _};
 use indexed_docs::IndexedDocsRegistry;
 pub(crate) use inline_assistant::*;
 use language_model::{
-    LanguageModelId, LanguageModelProviderId, LanguageModelRegistry, LanguageModelResponseMessage,
+    LanguageModel, LanguageModelCompletionEvent, LanguageModelId, LanguageModelProviderId,
+    LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage,
+    LanguageModelResponseMessage, Role, StopReason,
 };
 pub(crate) use model_selector::*;
 pub use patch::*;
@@ -45,8 +49,8 @@ use slash_command::workflow_command::WorkflowSlashCommand;
 use slash_command::{
     auto_command, cargo_workspace_command, context_server_command, default_command, delta_command,
     diagnostics_command, docs_command, fetch_command, file_command, now_command, project_command,
-    prompt_command, search_command, symbols_command, tab_command, terminal_command,
-    workflow_command,
+    prompt_command, resource_command, search_command, symbols_command, tab_command,
+    terminal_command, workflow_command,
 };
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -68,11 +72,13 @@ actions!(
         InsertActivePrompt,
         DeployHistory,
         DeployPromptLibrary,
+        DeployTokenUsage,
         ConfirmCommand,
         NewContext,
         ToggleModelSelector,
         CycleNextInlineAssist,
-        CyclePreviousInlineAssist
+        CyclePreviousInlineAssist,
+        InlineAssistAllExcerpts
     ]
 );
 
@@ -228,7 +234,7 @@ pub fn init(
     .detach();
 
     context_store::init(&client.clone().into());
-    prompt_library::init(cx);
+    prompt_library::init(fs.clone(), cx);
     init_language_model_settings(cx);
     assistant_slash_command::init(cx);
     assistant_tool::init(cx);
@@ -283,6 +289,10 @@ pub fn init(
 }
 
 fn register_context_server_handlers(cx: &mut AppContext) {
+    ContextServerManager::global(cx).update(cx, |manager, _| {
+        manager.set_sampling_handler(Arc::new(sample_with_language_model));
+    });
+
     cx.subscribe(
         &context_servers::manager::ContextServerManager::global(cx),
         |manager, event, cx| match event {
@@ -291,6 +301,7 @@ fn register_context_server_handlers(cx: &mut AppContext) {
                     &manager,
                     |manager: &mut context_servers::manager::ContextServerManager, cx| {
                         let slash_command_registry = SlashCommandRegistry::global(cx);
+                        let tool_registry = ToolRegistry::global(cx);
                         let context_server_registry = ContextServerRegistry::global(cx);
                         if let Some(server) = manager.get_server(server_id) {
                             cx.spawn(|_, _| async move {
@@ -298,6 +309,51 @@ fn register_context_server_handlers(cx: &mut AppContext) {
                                     return;
                                 };
 
+                                if protocol
+                                    .capable(context_servers::protocol::ServerCapability::Tools)
+                                {
+                                    if let Some(server_tools) =
+                                        protocol.list_tools().await.log_err()
+                                    {
+                                        for tool in server_tools {
+                                            let tool_id =
+                                                tools::context_server_tool::ContextServerTool::id(
+                                                    &server.id, &tool.name,
+                                                );
+                                            // Ids only keep the characters providers
+                                            // accept, so distinct tools may share one.
+                                            if let Some((server_id, tool_name)) =
+                                                context_server_registry.tool(&tool_id)
+                                            {
+                                                log::warn!(
+                                                    "skipping context server tool {:?}, its id \
+                                                     {:?} is used by {:?} of {:?}",
+                                                    tool.name,
+                                                    tool_id,
+                                                    tool_name,
+                                                    server_id
+                                                );
+                                                continue;
+                                            }
+                                            log::info!(
+                                                "registering context server tool: {:?}",
+                                                tool_id
+                                            );
+                                            context_server_registry.register_tool(
+                                                server.id.clone(),
+                                                &tool_id,
+                                                &tool.name,
+                                            );
+                                            tool_registry.register_tool(
+                                                tools::context_server_tool::ContextServerTool::new(
+                                                    server.id.clone(),
+                                                    tool,
+                                                ),
+                                            );
+                                        }
+                                    }
+                                }
+
                                 if let Some(prompts) = protocol.list_prompts().await.log_err() {
                                     for prompt in prompts
                                         .into_iter()
@@ -325,6 +381,7 @@ fn register_context_server_handlers(cx: &mut AppContext) {
                     },
                 );
             }
+            context_servers::manager::Event::ResourceUpdated { .. } => {}
             context_servers::manager::Event::ServerStopped { server_id } => {
                 let slash_command_registry = SlashCommandRegistry::global(cx);
                 let context_server_registry = ContextServerRegistry::global(cx);
@@ -334,12 +391,199 @@ fn register_context_server_handlers(cx: &mut AppContext) {
                         context_server_registry.unregister_command(&server_id, &command_name);
                     }
                 }
+
+                let tool_registry = ToolRegistry::global(cx);
+                if let Some(tools) = context_server_registry.get_tools(server_id) {
+                    for tool_id in tools {
+                        tool_registry.unregister_tool_by_name(&tool_id);
+                        context_server_registry.unregister_tool(&server_id, &tool_id);
+                    }
+                }
             }
         },
     )
     .detach();
 }
 
+/// Answers a context server's `sampling/createMessage` request with the language model the
+/// server prefers, or the active one, once the user allowed it.
+fn sample_with_language_model(
+    server_id: String,
+    params: types::CreateMessageParams,
+    cx: &mut AppContext,
+) -> Task<Result<types::CreateMessageResult>> {
+    let Some(model) = sampling_model(params.model_preferences.as_ref(), cx) else {
+        return Task::ready(Err(anyhow!("no language model is configured")));
+    };
+
+    let mut messages = Vec::new();
+    if let Some(system_prompt) = params.system_prompt {
+        messages.push(LanguageModelRequestMessage {
+            role: Role::System,
+            content: vec![system_prompt.into()],
+            cache: false,
+        });
+    }
+    for message in params.messages {
+        let types::SamplingContent::Text { text } = message.content else {
+            return Task::ready(Err(anyhow!("only text messages can be sampled")));
+        };
+        messages.push(LanguageModelRequestMessage {
+            role: match message.role {
+                types::SamplingRole::User => Role::User,
+                types::SamplingRole::Assistant => Role::Assistant,
+            },
+            content: vec![text.into()],
+            cache: false,
+        });
+    }
+    let mut request = LanguageModelRequest {
+        messages,
+        tools: Vec::new(),
+        stop: params.stop_sequences.unwrap_or_default(),
+        temperature: params.temperature,
+        max_tokens: Some(params.max_tokens),
+    };
+
+    // Only the resources in open contexts are shared with servers, as they were already chosen
+    // to be sent to the language model.
+    let manager = ContextServerManager::global(cx);
+    let manager = manager.read(cx);
+    let resources = manager
+        .subscribed_resources()
+        .filter(|(resource_server_id, _)| match params.include_context {
+            Some(types::IncludeContext::ThisServer) => *resource_server_id == server_id,
+            Some(types::IncludeContext::AllServers) => true,
+            Some(types::IncludeContext::None) | None => false,
+        })
+        .filter_map(|(resource_server_id, uri)| {
+            let protocol = manager
+                .get_server(resource_server_id)?
+                .client
+                .read()
+                .clone()?;
+            Some((protocol, uri.clone()))
+        })
+        .collect::<Vec<_>>();
+
+    let Some(window) = cx.active_window() else {
+        return Task::ready(Err(anyhow!("no window to ask for approval")));
+    };
+    let mut detail = format!(
+        "The server wants to send the following to {}:",
+        model.name().0
+    );
+    for message in &request.messages {
+        let text = util::truncate_and_trailoff(&message.string_contents(), 500);
+        detail.push_str(&format!("\n\n{}: {text}", message.role));
+    }
+    if !resources.is_empty() {
+        detail.push_str("\n\nIt also wants to include these resources of your contexts:");
+        for (_, uri) in &resources {
+            detail.push_str(&format!("\n{uri}"));
+        }
+    }
+    let answer = window.update(cx, |_, cx| {
+        cx.prompt(
+            PromptLevel::Info,
+            &format!("Allow the context server \"{server_id}\" to use the language model?"),
+            Some(&detail),
+            &["Allow", "Deny"],
+        )
+    });
+    let answer = match answer {
+        Ok(answer) => answer,
+        Err(error) => return Task::ready(Err(error)),
+    };
+
+    cx.spawn(|cx| async move {
+        if answer.await? != 0 {
+            bail!("the user declined the sampling request");
+        }
+
+        let mut context = String::new();
+        for (protocol, uri) in resources {
+            let Some(response) = protocol.read_resource(uri.clone()).await.log_err() else {
+                continue;
+            };
+            for content in response.contents {
+                if let Some(mut text) = content.text {
+                    context_server_command::normalize_line_endings(&mut text);
+                    context.push_str(&format!("<resource uri=\"{uri}\">\n{text}\n</resource>\n"));
+                }
+            }
+        }
+        if !context.is_empty() {
+            request.messages.insert(
+                0,
+                LanguageModelRequestMessage {
+                    role: Role::System,
+                    content: vec![context.into()],
+                    cache: false,
+                },
+            );
+        }
+
+        let mut events = model.stream_completion(request, &cx).await?;
+        let mut text = String::new();
+        let mut stop_reason = None;
+        while let Some(event) = events.next().await {
+            match event? {
+                LanguageModelCompletionEvent::Text(chunk) => text.push_str(&chunk),
+                LanguageModelCompletionEvent::Stop(reason) => {
+                    stop_reason = Some(match reason {
+                        StopReason::EndTurn => "endTurn",
+                        StopReason::MaxTokens => "maxTokens",
+                        StopReason::ToolUse => "toolUse",
+                    });
+                }
+                LanguageModelCompletionEvent::ToolUse(_)
+                | LanguageModelCompletionEvent::UsageUpdate(_) => {}
+            }
+        }
+        Ok(types::CreateMessageResult {
+            role: types::SamplingRole::Assistant,
+            content: types::SamplingContent::Text { text },
+            model: model.id().0.to_string(),
+            stop_reason: stop_reason.map(Into::into),
+        })
+    })
+}
+
+/// Picks the first model matching the hints of a sampling request, in order, falling back to the
+/// active model. The active model and the models of its provider are preferred.
+fn sampling_model(
+    preferences: Option<&types::ModelPreferences>,
+    cx: &AppContext,
+) -> Option<Arc<dyn LanguageModel>> {
+    let registry = LanguageModelRegistry::read_global(cx);
+    let active_model = registry.active_model();
+    let active_provider_id = active_model.as_ref().map(|model| model.provider_id());
+    let mut models = registry
+        .providers()
+        .into_iter()
+        .filter(|provider| provider.is_authenticated(cx))
+        .flat_map(|provider| provider.provided_models(cx))
+        .collect::<Vec<_>>();
+    models.sort_by_key(|model| Some(model.provider_id()) != active_provider_id);
+
+    preferences
+        .into_iter()
+        .flat_map(|preferences| &preferences.hints)
+        .filter_map(|hint| Some(hint.name.as_ref()?.to_lowercase()))
+        .find_map(|hint| {
+            active_model
+                .iter()
+                .chain(&models)
+                .find(|model| {
+                    model.id().0.to_lowercase().contains(&hint)
+                        || model.name().0.to_lowercase().contains(&hint)
+                })
+                .cloned()
+        })
+        .or(active_model)
+}
+
 fn init_language_model_settings(cx: &mut AppContext) {
     update_active_language_model_from_settings(cx);
 
@@ -395,6 +639,7 @@ fn register_slash_commands(prompt_builder: Option<Arc<PromptBuilder>>, cx: &mut
     slash_command_registry.register_command(diagnostics_command::DiagnosticsSlashCommand, true);
     slash_command_registry.register_command(fetch_command::FetchSlashCommand, false);
     slash_command_registry.register_command(fetch_command::FetchSlashCommand, false);
+    slash_command_registry.register_command(resource_command::ResourceSlashCommand, false);
 
     if let Some(prompt_builder) = prompt_builder {
         cx.observe_global::<SettingsStore>({
@@ -475,6 +720,12 @@ fn update_slash_commands_from_settings(cx: &mut AppContext) {
 fn register_tools(cx: &mut AppContext) {
     let tool_registry = ToolRegistry::global(cx);
     tool_registry.register_tool(tools::now_tool::NowTool);
+    tool_registry.register_tool(tools::read_file_tool::ReadFileTool);
+    tool_registry.register_tool(tools::list_directory_tool::ListDirectoryTool);
+    tool_registry.register_tool(tools::search_project_tool::SearchProjectTool);
+    tool_registry.register_tool(tools::find_symbol_tool::FindSymbolTool);
+    tool_registry.register_tool(tools::diagnostics_tool::DiagnosticsTool);
+    tool_registry.register_tool(tools::propose_edit_tool::ProposeEditTool);
 }
 
 pub fn humanize_token_count(count: usize) -> String {
@@ -7,22 +7,26 @@ use crate::{
         default_command::DefaultSlashCommand,
         docs_command::{DocsSlashCommand, DocsSlashCommandArgs},
         file_command::{self, codeblock_fence_for_path},
+        resource_command::{self, ResourceMetadata},
         SlashCommandCompletionProvider, SlashCommandRegistry,
     },
     slash_command_picker,
     terminal_inline_assistant::TerminalInlineAssistant,
     Assist, AssistantPatch, AssistantPatchStatus, CacheStatus, ConfirmCommand, Content, Context,
     ContextEvent, ContextId, ContextStore, ContextStoreEvent, CopyCode, CycleMessageRole,
-    DeployHistory, DeployPromptLibrary, InlineAssistant, InsertDraggedFiles, InsertIntoEditor,
-    Message, MessageId, MessageMetadata, MessageStatus, ModelPickerDelegate, ModelSelector,
-    NewContext, PendingSlashCommand, PendingSlashCommandStatus, QuoteSelection,
-    RemoteContextMetadata, SavedContextMetadata, Split, ToggleFocus, ToggleModelSelector,
+    DeployHistory, DeployPromptLibrary, DeployTokenUsage, InlineAssistAllExcerpts, InlineAssistant,
+    InsertDraggedFiles, InsertIntoEditor, Message, MessageId, MessageMetadata, MessageStatus,
+    ModelPickerDelegate, ModelSelector, NewContext, PendingSlashCommand, PendingSlashCommandStatus,
+    QuoteSelection, RemoteContextMetadata, SavedContextMetadata, Split, ToggleFocus,
+    ToggleModelSelector,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use assistant_slash_command::{SlashCommand, SlashCommandOutputSection};
 use assistant_tool::ToolRegistry;
 use client::{proto, zed_urls, Client, Status};
 use collections::{BTreeSet, HashMap, HashSet};
+use context_servers::manager::ContextServerManager;
+use db::token_usage::{TokenUsageRow, TOKEN_USAGE_STORE};
 use editor::{
     actions::{FoldAt, MoveToEndOfLine, Newline, ShowCompletions, UnfoldAt},
     display_map::{
@@ -30,15 +34,14 @@ use editor::{
         CustomBlockId, FoldId, RenderBlock, ToDisplayPoint,
     },
     scroll::{Autoscroll, AutoscrollStrategy},
-    Anchor, Editor, EditorEvent, ProposedChangeLocation, ProposedChangesEditor, RowExt,
-    ToOffset as _, ToPoint,
+    Anchor, Editor, EditorEvent, ProposedChangesEditor, RowExt, ToOffset as _, ToPoint,
 };
 use editor::{display_map::CreaseId, FoldPlaceholder};
 use fs::Fs;
 use futures::FutureExt;
 use gpui::{
     canvas, div, img, percentage, point, pulsating_between, size, Action, Animation, AnimationExt,
-    AnyElement, AnyView, AppContext, AsyncWindowContext, ClipboardEntry, ClipboardItem,
+    AnyElement, AnyView, AppContext, AsyncWindowContext, ClickEvent, ClipboardEntry, ClipboardItem,
     CursorStyle, Empty, Entity, EventEmitter, ExternalPaths, FocusHandle, FocusableView,
     FontWeight, InteractiveElement, IntoElement, Model, ParentElement, Pixels, Render, RenderImage,
     SharedString, Size, StatefulInteractiveElement, Styled, Subscription, Task, Transformation,
@@ -65,6 +68,7 @@ use smol::stream::StreamExt;
 use std::{
     borrow::Cow,
     cmp,
+    fmt::Write as _,
     ops::{ControlFlow, Range},
     path::PathBuf,
     sync::Arc,
@@ -79,6 +83,7 @@ use ui::{
     Avatar, ButtonLike, ContextMenu, Disclosure, ElevationIndex, KeyBinding, ListItem,
     ListItemSpacing, PopoverMenu, PopoverMenuHandle, Tooltip,
 };
+use url::Url;
 use util::{maybe, ResultExt};
 use workspace::{
     dock::{DockPosition, Panel, PanelEvent},
@@ -106,6 +111,7 @@ pub fn init(cx: &mut AppContext) {
                     workspace.toggle_panel_focus::<AssistantPanel>(cx);
                 })
                 .register_action(AssistantPanel::inline_assist)
+                .register_action(AssistantPanel::inline_assist_all_excerpts)
                 .register_action(ContextEditor::quote_selection)
                 .register_action(ContextEditor::insert_selection)
                 .register_action(ContextEditor::copy_code)
@@ -476,6 +482,7 @@ impl AssistantPanel {
                                         .action("New Context", Box::new(NewContext))
                                         .action("History", Box::new(DeployHistory))
                                         .action("Prompt Library", Box::new(DeployPromptLibrary))
+                                        .action("Token Usage", Box::new(DeployTokenUsage))
                                         .action("Configure", Box::new(ShowConfiguration))
                                         .action(zoom_label, Box::new(ToggleZoom))
                                 }))
@@ -783,6 +790,25 @@ impl AssistantPanel {
         workspace: &mut Workspace,
         action: &InlineAssist,
         cx: &mut ViewContext<Workspace>,
+    ) {
+        Self::deploy_inline_assist(workspace, action.prompt.clone(), false, cx);
+    }
+
+    /// Starts an inline assist on every excerpt of the active editor, e.g. on each result of
+    /// a project search.
+    pub fn inline_assist_all_excerpts(
+        workspace: &mut Workspace,
+        _: &InlineAssistAllExcerpts,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        Self::deploy_inline_assist(workspace, None, true, cx);
+    }
+
+    fn deploy_inline_assist(
+        workspace: &mut Workspace,
+        initial_prompt: Option<String>,
+        all_excerpts: bool,
+        cx: &mut ViewContext<Workspace>,
     ) {
         let settings = AssistantSettings::get_global(cx);
         if !settings.enabled {
@@ -799,19 +825,29 @@ impl AssistantPanel {
             return;
         };
 
-        let initial_prompt = action.prompt.clone();
-
         if assistant_panel.update(cx, |assistant, cx| assistant.is_authenticated(cx)) {
             match inline_assist_target {
                 InlineAssistTarget::Editor(active_editor, include_context) => {
                     InlineAssistant::update_global(cx, |assistant, cx| {
-                        assistant.assist(
-                            &active_editor,
-                            Some(cx.view().downgrade()),
-                            include_context.then_some(&assistant_panel),
-                            initial_prompt,
-                            cx,
-                        )
+                        let workspace = Some(cx.view().downgrade());
+                        let assistant_panel = include_context.then_some(&assistant_panel);
+                        if all_excerpts {
+                            assistant.assist_all_excerpts(
+                                &active_editor,
+                                workspace,
+                                assistant_panel,
+                                initial_prompt,
+                                cx,
+                            )
+                        } else {
+                            assistant.assist(
+                                &active_editor,
+                                workspace,
+                                assistant_panel,
+                                initial_prompt,
+                                cx,
+                            )
+                        }
                     })
                 }
                 InlineAssistTarget::Terminal(active_terminal) => {
@@ -859,13 +895,23 @@ impl AssistantPanel {
                                 None
                             };
                             InlineAssistant::update_global(cx, |assistant, cx| {
-                                assistant.assist(
-                                    &active_editor,
-                                    Some(workspace),
-                                    assistant_panel.as_ref(),
-                                    initial_prompt,
-                                    cx,
-                                )
+                                if all_excerpts {
+                                    assistant.assist_all_excerpts(
+                                        &active_editor,
+                                        Some(workspace),
+                                        assistant_panel.as_ref(),
+                                        initial_prompt,
+                                        cx,
+                                    )
+                                } else {
+                                    assistant.assist(
+                                        &active_editor,
+                                        Some(workspace),
+                                        assistant_panel.as_ref(),
+                                        initial_prompt,
+                                        cx,
+                                    )
+                                }
                             })
                         }
                         InlineAssistTarget::Terminal(active_terminal) => {
@@ -1176,6 +1222,32 @@ impl AssistantPanel {
         open_prompt_library(self.languages.clone(), cx).detach_and_log_err(cx);
     }
 
+    fn deploy_token_usage(&mut self, _: &DeployTokenUsage, cx: &mut ViewContext<Self>) {
+        let usage_item_ix = self
+            .pane
+            .read(cx)
+            .items()
+            .position(|item| item.downcast::<TokenUsageView>().is_some());
+
+        if let Some(usage_item_ix) = usage_item_ix {
+            self.pane.update(cx, |pane, cx| {
+                if let Some(usage) = pane
+                    .item_for_index(usage_item_ix)
+                    .and_then(|item| item.downcast::<TokenUsageView>())
+                {
+                    usage.update(cx, |usage, cx| usage.reload(cx));
+                }
+                pane.activate_item(usage_item_ix, true, true, cx);
+            });
+        } else {
+            let fs = self.fs.clone();
+            let usage = cx.new_view(|cx| TokenUsageView::new(fs, cx));
+            self.pane.update(cx, |pane, cx| {
+                pane.add_item(Box::new(usage), true, true, None, cx);
+            });
+        }
+    }
+
     fn toggle_model_selector(&mut self, _: &ToggleModelSelector, cx: &mut ViewContext<Self>) {
         self.model_selector_menu_handle.toggle(cx);
     }
@@ -1285,6 +1357,85 @@ impl AssistantPanel {
         })
     }
 
+    fn fork_context(
+        &mut self,
+        context: Model<Context>,
+        message_id: MessageId,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        let forked_context = self
+            .context_store
+            .update(cx, |store, cx| store.fork_context(&context, message_id, cx));
+        let fs = self.fs.clone();
+        let workspace = self.workspace.clone();
+        let lsp_adapter_delegate = make_lsp_adapter_delegate(&self.project, cx)
+            .log_err()
+            .flatten();
+
+        cx.spawn(|this, mut cx| async move {
+            let forked_context = forked_context.await?;
+            let assistant_panel = this.clone();
+            this.update(&mut cx, |this, cx| {
+                let editor = cx.new_view(|cx| {
+                    ContextEditor::for_context(
+                        forked_context,
+                        fs,
+                        workspace,
+                        this.project.clone(),
+                        lsp_adapter_delegate,
+                        assistant_panel,
+                        cx,
+                    )
+                });
+                this.show_context(editor, cx);
+            })
+        })
+    }
+
+    fn open_context_branch(
+        &mut self,
+        id: ContextId,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        let existing_context = self.pane.read(cx).items().find_map(|item| {
+            item.downcast::<ContextEditor>()
+                .filter(|editor| *editor.read(cx).context.read(cx).id() == id)
+        });
+        if let Some(existing_context) = existing_context {
+            return cx.spawn(|this, mut cx| async move {
+                this.update(&mut cx, |this, cx| this.show_context(existing_context, cx))
+            });
+        }
+
+        let context = self
+            .context_store
+            .update(cx, |store, cx| store.open_context_by_id(id, cx));
+        let fs = self.fs.clone();
+        let workspace = self.workspace.clone();
+        let lsp_adapter_delegate = make_lsp_adapter_delegate(&self.project, cx)
+            .log_err()
+            .flatten();
+
+        cx.spawn(|this, mut cx| async move {
+            let context = context.await?;
+            let assistant_panel = this.clone();
+            this.update(&mut cx, |this, cx| {
+                let editor = cx.new_view(|cx| {
+                    ContextEditor::for_context(
+                        context,
+                        fs,
+                        workspace,
+                        this.project.clone(),
+                        lsp_adapter_delegate,
+                        assistant_panel,
+                        cx,
+                    )
+                });
+                this.show_context(editor, cx);
+            })
+        })
+    }
+
     fn is_authenticated(&mut self, cx: &mut ViewContext<Self>) -> bool {
         LanguageModelRegistry::read_global(cx)
             .active_provider()
@@ -1325,6 +1476,7 @@ impl Render for AssistantPanel {
             )
             .on_action(cx.listener(AssistantPanel::deploy_history))
             .on_action(cx.listener(AssistantPanel::deploy_prompt_library))
+            .on_action(cx.listener(AssistantPanel::deploy_token_usage))
             .on_action(cx.listener(AssistantPanel::toggle_model_selector))
             .child(registrar.size_full().child(self.pane.clone()))
             .into_any_element()
@@ -1457,7 +1609,167 @@ struct PatchEditorState {
     opened_patch: AssistantPatch,
 }
 
-type MessageHeader = MessageMetadata;
+#[derive(Clone, PartialEq)]
+struct MessageHeader {
+    metadata: MessageMetadata,
+    /// The contexts that were forked from this message.
+    branches: Vec<ContextId>,
+    /// The context this one was forked from, if it was forked at this message.
+    forked_from: Option<ContextId>,
+}
+
+impl MessageHeader {
+    fn new(message: &Message, context: &Context) -> Self {
+        Self {
+            metadata: MessageMetadata::from(message),
+            branches: context
+                .branches()
+                .iter()
+                .filter(|branch| branch.message_id == message.id)
+                .map(|branch| branch.context_id.clone())
+                .collect(),
+            forked_from: context
+                .forked_from()
+                .filter(|forked_from| forked_from.message_id == message.id)
+                .map(|forked_from| forked_from.context_id.clone()),
+        }
+    }
+
+    fn render_controls(
+        &self,
+        context: &Model<Context>,
+        assistant_panel: &WeakView<AssistantPanel>,
+    ) -> impl IntoElement {
+        let message_id = MessageId(self.metadata.timestamp);
+        let is_pending = self.metadata.status == MessageStatus::Pending;
+        let response_count = self.metadata.alternatives.len() + 1;
+        let active_ix = self.metadata.active_alternative;
+
+        let select_response = |ix: usize| {
+            let context = context.clone();
+            move |_: &ClickEvent, cx: &mut WindowContext| {
+                context.update(cx, |context, cx| {
+                    context.select_alternative(message_id, ix, cx)
+                })
+            }
+        };
+        let open_branch = |context_id: ContextId| {
+            let assistant_panel = assistant_panel.clone();
+            move |cx: &mut WindowContext| {
+                assistant_panel
+                    .update(cx, |panel, cx| {
+                        panel
+                            .open_context_branch(context_id.clone(), cx)
+                            .detach_and_log_err(cx)
+                    })
+                    .ok();
+            }
+        };
+
+        h_flex()
+            .ml_auto()
+            .pr_2()
+            .gap_1()
+            .when(response_count > 1, |this| {
+                this.child(
+                    IconButton::new("previous-response", IconName::ChevronLeft)
+                        .icon_size(IconSize::XSmall)
+                        .disabled(active_ix == 0 || is_pending)
+                        .tooltip(|cx| Tooltip::text("Previous Response", cx))
+                        .on_click(select_response(active_ix.saturating_sub(1))),
+                )
+                .child(
+                    Label::new(format!("{}/{}", active_ix + 1, response_count))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(
+                    IconButton::new("next-response", IconName::ChevronRight)
+                        .icon_size(IconSize::XSmall)
+                        .disabled(active_ix + 1 >= response_count || is_pending)
+                        .tooltip(|cx| Tooltip::text("Next Response", cx))
+                        .on_click(select_response(active_ix + 1)),
+                )
+            })
+            .when(
+                self.metadata.role == Role::Assistant && !is_pending,
+                |this| {
+                    this.child(
+                        IconButton::new("regenerate", IconName::RotateCw)
+                            .icon_size(IconSize::XSmall)
+                            .tooltip(|cx| Tooltip::text("Regenerate Response", cx))
+                            .on_click({
+                                let context = context.clone();
+                                move |_, cx| {
+                                    context.update(cx, |context, cx| {
+                                        context.regenerate(message_id, cx);
+                                    })
+                                }
+                            }),
+                    )
+                },
+            )
+            .children(self.forked_from.clone().map(|forked_from| {
+                let open_branch = open_branch(forked_from);
+                Button::new("forked-from", "Forked")
+                    .icon(IconName::ArrowUpRight)
+                    .icon_size(IconSize::XSmall)
+                    .icon_position(IconPosition::End)
+                    .label_size(LabelSize::Small)
+                    .color(Color::Muted)
+                    .tooltip(|cx| Tooltip::text("Open Original Context", cx))
+                    .on_click(move |_, cx| open_branch(cx))
+            }))
+            .when(!self.branches.is_empty(), |this| {
+                let branch_handlers = self
+                    .branches
+                    .iter()
+                    .cloned()
+                    .map(open_branch)
+                    .collect::<Vec<_>>();
+                this.child(
+                    PopoverMenu::new(("message-branches", message_id.as_u64()))
+                        .trigger(
+                            Button::new("branches", format!("{} Branches", self.branches.len()))
+                                .label_size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                        .menu(move |cx| {
+                            let branch_handlers = branch_handlers.clone();
+                            Some(ContextMenu::build(cx, move |mut menu, _| {
+                                for (ix, open_branch) in branch_handlers.into_iter().enumerate() {
+                                    menu =
+                                        menu.entry(format!("Branch {}", ix + 1), None, move |cx| {
+                                            open_branch(cx)
+                                        });
+                                }
+                                menu
+                            }))
+                        }),
+                )
+            })
+            .when(!is_pending, |this| {
+                this.child(
+                    IconButton::new("fork", IconName::Split)
+                        .icon_size(IconSize::XSmall)
+                        .tooltip(|cx| Tooltip::text("Fork Conversation Here", cx))
+                        .on_click({
+                            let context = context.clone();
+                            let assistant_panel = assistant_panel.clone();
+                            move |_, cx| {
+                                assistant_panel
+                                    .update(cx, |panel, cx| {
+                                        panel
+                                            .fork_context(context.clone(), message_id, cx)
+                                            .detach_and_log_err(cx);
+                                    })
+                                    .ok();
+                            }
+                        }),
+                )
+            })
+    }
+}
 
 #[derive(Clone)]
 enum AssistError {
@@ -1480,6 +1792,8 @@ pub struct ContextEditor {
     pending_slash_command_creases: HashMap<Range<language::Anchor>, CreaseId>,
     pending_slash_command_blocks: HashMap<Range<language::Anchor>, CustomBlockId>,
     pending_tool_use_creases: HashMap<Range<language::Anchor>, CreaseId>,
+    /// The context server resources in the context, whose updates are subscribed to.
+    subscribed_resources: HashSet<(String, Url)>,
     _subscriptions: Vec<Subscription>,
     patches: HashMap<Range<language::Anchor>, PatchViewState>,
     active_patch: Option<Range<language::Anchor>>,
@@ -1533,6 +1847,17 @@ impl ContextEditor {
             cx.subscribe(&context, Self::handle_context_event),
             cx.subscribe(&editor, Self::handle_editor_event),
             cx.subscribe(&editor, Self::handle_editor_search_event),
+            cx.subscribe(
+                &ContextServerManager::global(cx),
+                Self::handle_context_server_event,
+            ),
+            cx.on_release(|this, _, cx| {
+                ContextServerManager::global(cx).update(cx, |manager, cx| {
+                    for (server_id, uri) in this.subscribed_resources.drain() {
+                        manager.unsubscribe_from_resource(&server_id, uri, cx);
+                    }
+                })
+            }),
         ];
 
         let sections = context.read(cx).slash_command_output_sections().to_vec();
@@ -1551,6 +1876,7 @@ impl ContextEditor {
             pending_slash_command_creases: HashMap::default(),
             pending_slash_command_blocks: HashMap::default(),
             pending_tool_use_creases: HashMap::default(),
+            subscribed_resources: HashSet::default(),
             _subscriptions,
             patches: HashMap::default(),
             active_patch: None,
@@ -1562,6 +1888,8 @@ impl ContextEditor {
         };
         this.update_message_headers(cx);
         this.update_image_blocks(cx);
+        this.insert_resource_images(&sections, cx);
+        this.update_resource_subscriptions(cx);
         this.insert_slash_command_output_sections(sections, false, cx);
         this.patches_updated(&Vec::new(), &patch_ranges, cx);
         this
@@ -1801,6 +2129,7 @@ impl ContextEditor {
             ContextEvent::MessagesEdited => {
                 self.update_message_headers(cx);
                 self.update_image_blocks(cx);
+                self.update_resource_subscriptions(cx);
                 self.context.update(cx, |context, cx| {
                     context.save(Some(Duration::from_millis(500)), self.fs.clone(), cx);
                 });
@@ -2043,6 +2372,8 @@ impl ContextEditor {
                 run_commands_in_output,
                 expand_result,
             } => {
+                self.insert_resource_images(sections, cx);
+                self.update_resource_subscriptions(cx);
                 self.insert_slash_command_output_sections(
                     sections.iter().cloned(),
                     *expand_result,
@@ -2082,13 +2413,41 @@ impl ContextEditor {
 
                 for tool_use in pending_tool_uses {
                     let tool_registry = ToolRegistry::global(cx);
-                    if let Some(tool) = tool_registry.tool(&tool_use.name) {
-                        let task = tool.run(tool_use.input, self.workspace.clone(), cx);
+                    let Some(tool) = tool_registry.tool(&tool_use.name) else {
+                        continue;
+                    };
 
+                    let needs_confirmation = AssistantSettings::get_global(cx)
+                        .tool_needs_confirmation(&tool.name(), tool.needs_confirmation());
+                    if !needs_confirmation {
+                        let task = tool.run(tool_use.input, self.workspace.clone(), cx);
                         self.context.update(cx, |context, cx| {
                             context.insert_tool_output(tool_use.id.clone(), task, cx);
                         });
+                        continue;
                     }
+
+                    let input = serde_json::to_string_pretty(&tool_use.input).unwrap_or_default();
+                    let answer = cx.prompt(
+                        gpui::PromptLevel::Info,
+                        &format!("Allow the assistant to run the \"{}\" tool?", tool_use.name),
+                        Some(&input),
+                        &["Run", "Cancel"],
+                    );
+                    cx.spawn(|this, mut cx| async move {
+                        let confirmed = answer.await? == 0;
+                        this.update(&mut cx, |this, cx| {
+                            let task = if confirmed {
+                                tool.run(tool_use.input, this.workspace.clone(), cx)
+                            } else {
+                                Task::ready(Err(anyhow!("The user declined to run the tool")))
+                            };
+                            this.context.update(cx, |context, cx| {
+                                context.insert_tool_output(tool_use.id.clone(), task, cx);
+                            });
+                        })
+                    })
+                    .detach_and_log_err(cx);
                 }
             }
             ContextEvent::ToolFinished {
@@ -2345,6 +2704,110 @@ impl ContextEditor {
         });
     }
 
+    /// Inserts the images of the context server resources in the given sections.
+    fn insert_resource_images(
+        &mut self,
+        sections: &[SlashCommandOutputSection<language::Anchor>],
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.context.update(cx, |context, cx| {
+            for section in sections {
+                let Some(metadata) = ResourceMetadata::for_section(section) else {
+                    continue;
+                };
+                let section_start = section.range.start.to_offset(context.buffer().read(cx));
+                for resource_image in metadata.images {
+                    let Some(image) = resource_image.to_image().log_err() else {
+                        continue;
+                    };
+                    let Some(render_image) = image.to_image_data(cx).log_err() else {
+                        continue;
+                    };
+                    let anchor = context
+                        .buffer()
+                        .read(cx)
+                        .anchor_before(section_start + resource_image.offset);
+                    let image_id = image.id();
+                    let image_task = LanguageModelImage::from_image(image, cx).shared();
+                    context.insert_content(
+                        Content::Image {
+                            anchor,
+                            image_id,
+                            image: image_task,
+                            render_image,
+                        },
+                        cx,
+                    );
+                }
+            }
+        });
+    }
+
+    /// Subscribes to updates of the context server resources in the context, and unsubscribes
+    /// from the ones whose sections were removed.
+    fn update_resource_subscriptions(&mut self, cx: &mut ViewContext<Self>) {
+        let context = self.context.read(cx);
+        let buffer = context.buffer().read(cx);
+        let resources = context
+            .slash_command_output_sections()
+            .iter()
+            .filter(|section| {
+                section.range.start.to_offset(buffer) < section.range.end.to_offset(buffer)
+            })
+            .filter_map(|section| ResourceMetadata::for_section(section)?.resource())
+            .collect::<HashSet<_>>();
+        if resources == self.subscribed_resources {
+            return;
+        }
+
+        ContextServerManager::global(cx).update(cx, |manager, cx| {
+            for (server_id, uri) in resources.difference(&self.subscribed_resources) {
+                manager.subscribe_to_resource(server_id, uri.clone(), cx);
+            }
+            for (server_id, uri) in self.subscribed_resources.difference(&resources) {
+                manager.unsubscribe_from_resource(server_id, uri.clone(), cx);
+            }
+        });
+        self.subscribed_resources = resources;
+    }
+
+    fn handle_context_server_event(
+        &mut self,
+        _: Model<ContextServerManager>,
+        event: &context_servers::manager::Event,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let context_servers::manager::Event::ResourceUpdated { server_id, uri } = event else {
+            return;
+        };
+        let resource = (server_id.clone(), uri.clone());
+        if !self.subscribed_resources.contains(&resource) {
+            return;
+        }
+
+        // Read the resource again and replace its outdated contents.
+        let outdated_sections = self
+            .context
+            .read(cx)
+            .slash_command_output_sections()
+            .iter()
+            .filter(|section| {
+                ResourceMetadata::for_section(section)
+                    .and_then(|metadata| metadata.resource())
+                    .as_ref()
+                    == Some(&resource)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        for section in outdated_sections {
+            let output =
+                resource_command::read_resource(Some(server_id.clone()), uri.to_string(), cx);
+            self.context.update(cx, |context, cx| {
+                context.insert_command_output(section.range, output, false, false, cx)
+            });
+        }
+    }
+
     fn handle_editor_event(
         &mut self,
         _: View<Editor>,
@@ -2451,17 +2914,7 @@ impl ContextEditor {
         let editor = cx.new_view(|cx| {
             let editor = ProposedChangesEditor::new(
                 patch.title.clone(),
-                resolved_patch
-                    .edit_groups
-                    .iter()
-                    .map(|(buffer, groups)| ProposedChangeLocation {
-                        buffer: buffer.clone(),
-                        ranges: groups
-                            .iter()
-                            .map(|group| group.context_range.clone())
-                            .collect(),
-                    })
-                    .collect(),
+                resolved_patch.locations(),
                 Some(project.clone()),
                 cx,
             );
@@ -2498,17 +2951,7 @@ impl ContextEditor {
         this.update(&mut cx, |this, cx| {
             let patch_state = this.patches.get_mut(&patch.range)?;
 
-            let locations = resolved_patch
-                .edit_groups
-                .iter()
-                .map(|(buffer, groups)| ProposedChangeLocation {
-                    buffer: buffer.clone(),
-                    ranges: groups
-                        .iter()
-                        .map(|group| group.context_range.clone())
-                        .collect(),
-                })
-                .collect();
+            let locations = resolved_patch.locations();
 
             if let Some(state) = &mut patch_state.editor {
                 if let Some(editor) = state.editor.upgrade() {
@@ -2576,10 +3019,12 @@ impl ContextEditor {
                 .collect();
             let mut blocks_to_replace: HashMap<_, RenderBlock> = Default::default();
 
-            let render_block = |message: MessageMetadata| -> RenderBlock {
+            let render_block = |header: MessageHeader| -> RenderBlock {
                 Box::new({
                     let context = self.context.clone();
+                    let assistant_panel = self.assistant_panel.clone();
                     move |cx| {
+                        let message = &header.metadata;
                         let message_id = MessageId(message.timestamp);
                         let show_spinner = message.role == Role::Assistant
                             && message.status == MessageStatus::Pending;
@@ -2724,24 +3169,28 @@ impl ContextEditor {
                                 ),
                                 _ => None,
                             })
+                            .child(header.render_controls(&context, &assistant_panel))
                             .into_any_element()
                     }
                 })
             };
-            let create_block_properties = |message: &Message| BlockProperties {
-                height: 2,
-                style: BlockStyle::Sticky,
-                placement: BlockPlacement::Above(
-                    buffer
-                        .anchor_in_excerpt(excerpt_id, message.anchor_range.start)
-                        .unwrap(),
-                ),
-                priority: usize::MAX,
-                render: render_block(MessageMetadata::from(message)),
-            };
+            let create_block_properties =
+                |message: &Message, header: MessageHeader| BlockProperties {
+                    height: 2,
+                    style: BlockStyle::Sticky,
+                    placement: BlockPlacement::Above(
+                        buffer
+                            .anchor_in_excerpt(excerpt_id, message.anchor_range.start)
+                            .unwrap(),
+                    ),
+                    priority: usize::MAX,
+                    render: render_block(header),
+                };
             let mut new_blocks = vec![];
             let mut block_index_to_message = vec![];
-            for message in self.context.read(cx).messages(cx) {
+            let context = self.context.read(cx);
+            for message in context.messages(cx) {
+                let message_header = MessageHeader::new(&message, context);
                 if let Some(_) = blocks_to_remove.remove(&message.id) {
                     // This is an old message that we might modify.
                     let Some((meta, block_id)) = old_blocks.get_mut(&message.id) else {
@@ -2752,15 +3201,14 @@ impl ContextEditor {
                         continue;
                     };
                     // Should we modify it?
-                    let message_meta = MessageMetadata::from(&message);
-                    if meta != &message_meta {
-                        blocks_to_replace.insert(*block_id, render_block(message_meta.clone()));
-                        *meta = message_meta;
+                    if meta != &message_header {
+                        blocks_to_replace.insert(*block_id, render_block(message_header.clone()));
+                        *meta = message_header;
                     }
                 } else {
                     // This is a new message.
-                    new_blocks.push(create_block_properties(&message));
-                    block_index_to_message.push((message.id, MessageMetadata::from(&message)));
+                    new_blocks.push(create_block_properties(&message, message_header.clone()));
+                    block_index_to_message.push((message.id, message_header));
                 }
             }
             editor.replace_blocks(blocks_to_replace, None, cx);
@@ -4294,9 +4742,25 @@ impl ContextEditorToolbarItem {
                 (color, token_count, max_token_count)
             }
         };
+        let token_usage = context.read(cx).token_usage();
         Some(
             h_flex()
+                .id("token-count")
                 .gap_0p5()
+                .when(!token_usage.is_empty(), |this| {
+                    this.tooltip(move |cx| {
+                        Tooltip::text(
+                            format!(
+                                "Tokens used by this context: {} input, {} output",
+                                token_usage.input_tokens
+                                    + token_usage.cache_creation_input_tokens
+                                    + token_usage.cache_read_input_tokens,
+                                token_usage.output_tokens
+                            ),
+                            cx,
+                        )
+                    })
+                })
                 .child(
                     Label::new(humanize_token_count(token_count))
                         .size(LabelSize::Small)
@@ -4581,6 +5045,233 @@ impl Item for ContextHistory {
     }
 }
 
+/// Lists the tokens consumed by assistant requests per day, project and model, along
+/// with their estimated cost.
+pub struct TokenUsageView {
+    focus_handle: FocusHandle,
+    fs: Arc<dyn Fs>,
+    rows: Vec<TokenUsageRow>,
+    _load_rows: Task<()>,
+}
+
+impl TokenUsageView {
+    fn new(fs: Arc<dyn Fs>, cx: &mut ViewContext<Self>) -> Self {
+        let mut this = Self {
+            focus_handle: cx.focus_handle(),
+            fs,
+            rows: Vec::new(),
+            _load_rows: Task::ready(()),
+        };
+        this.reload(cx);
+        this
+    }
+
+    fn reload(&mut self, cx: &mut ViewContext<Self>) {
+        let rows = cx
+            .background_executor()
+            .spawn(async move { TOKEN_USAGE_STORE.usage_by_day() });
+        self._load_rows = cx.spawn(|this, mut cx| async move {
+            if let Some(rows) = rows.await.log_err() {
+                this.update(&mut cx, |this, cx| {
+                    this.rows = rows;
+                    cx.notify();
+                })
+                .ok();
+            }
+        });
+    }
+
+    fn cost(row: &TokenUsageRow, cx: &AppContext) -> Option<f64> {
+        let price = AssistantSettings::get_global(cx).model_price(&row.model)?;
+        Some(price.cost(
+            row.input_tokens,
+            row.output_tokens,
+            row.cache_creation_input_tokens,
+            row.cache_read_input_tokens,
+        ))
+    }
+
+    fn to_csv(&self, cx: &AppContext) -> String {
+        let mut csv = String::from(
+            "day,project,provider,model,input_tokens,output_tokens,\
+             cache_creation_input_tokens,cache_read_input_tokens,cost_usd\n",
+        );
+        for row in &self.rows {
+            let cost = Self::cost(row, cx)
+                .map(|cost| format!("{cost:.6}"))
+                .unwrap_or_default();
+            writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{},{}",
+                csv_field(&row.day),
+                csv_field(&row.project),
+                csv_field(&row.provider),
+                csv_field(&row.model),
+                row.input_tokens,
+                row.output_tokens,
+                row.cache_creation_input_tokens,
+                row.cache_read_input_tokens,
+                cost
+            )
+            .ok();
+        }
+        csv
+    }
+
+    fn export_csv(&mut self, _: &ClickEvent, cx: &mut ViewContext<Self>) {
+        let csv = self.to_csv(cx);
+        let fs = self.fs.clone();
+        let path = cx.prompt_for_new_path(paths::home_dir());
+        cx.spawn(|_, _| async move {
+            if let Some(path) = path.await?? {
+                fs.atomic_write(path, csv).await?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn render_row(cells: [SharedString; 8], color: Color) -> impl IntoElement {
+        h_flex()
+            .w_full()
+            .gap_2()
+            .children(cells.into_iter().enumerate().map(move |(ix, cell)| {
+                // The project column takes up the remaining space.
+                div()
+                    .map(|this| {
+                        if ix == 1 {
+                            this.flex_1()
+                        } else {
+                            this.w(rems(8.))
+                        }
+                    })
+                    .overflow_x_hidden()
+                    .child(Label::new(cell).size(LabelSize::Small).color(color))
+            }))
+    }
+}
+
+/// Quotes a CSV field if it contains a separator, a quote or a line break.
+fn csv_field(field: &str) -> Cow<str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+impl Render for TokenUsageView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let header = Self::render_row(
+            [
+                "Day".into(),
+                "Project".into(),
+                "Provider".into(),
+                "Model".into(),
+                "Input".into(),
+                "Output".into(),
+                "Cache Write".into(),
+                "Cache Read".into(),
+            ],
+            Color::Muted,
+        );
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                let cost = Self::cost(row, cx);
+                let row_element = Self::render_row(
+                    [
+                        row.day.clone().into(),
+                        row.project.clone().into(),
+                        row.provider.clone().into(),
+                        row.model.clone().into(),
+                        row.input_tokens.to_string().into(),
+                        row.output_tokens.to_string().into(),
+                        row.cache_creation_input_tokens.to_string().into(),
+                        row.cache_read_input_tokens.to_string().into(),
+                    ],
+                    Color::Default,
+                );
+                h_flex().gap_2().child(row_element).child(
+                    div().w(rems(6.)).child(
+                        Label::new(cost.map_or("-".to_string(), |cost| format!("${cost:.2}")))
+                            .size(LabelSize::Small),
+                    ),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .id("assistant-token-usage-view")
+            .track_focus(&self.focus_handle)
+            .bg(cx.theme().colors().editor_background)
+            .size_full()
+            .overflow_y_scroll()
+            .child(
+                h_flex()
+                    .p(Spacing::XXLarge.rems(cx))
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(
+                        v_flex()
+                            .gap_1()
+                            .child(Headline::new("Token Usage").size(HeadlineSize::Medium))
+                            .child(
+                                Label::new(
+                                    "Costs are estimated from the prices in the \
+                                     `model_prices` setting.",
+                                )
+                                .color(Color::Muted),
+                            ),
+                    )
+                    .child(
+                        Button::new("export-token-usage", "Export CSV")
+                            .icon(IconName::Download)
+                            .icon_position(IconPosition::Start)
+                            .style(ButtonStyle::Filled)
+                            .disabled(self.rows.is_empty())
+                            .on_click(cx.listener(Self::export_csv)),
+                    ),
+            )
+            .child(
+                v_flex()
+                    .p(Spacing::XXLarge.rems(cx))
+                    .gap_1()
+                    .child(
+                        h_flex().gap_2().child(header).child(
+                            div().w(rems(6.)).child(
+                                Label::new("Cost")
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            ),
+                        ),
+                    )
+                    .when(self.rows.is_empty(), |this| {
+                        this.child(Label::new("No usage recorded yet.").color(Color::Muted))
+                    })
+                    .children(rows),
+            )
+    }
+}
+
+impl EventEmitter<()> for TokenUsageView {}
+
+impl FocusableView for TokenUsageView {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for TokenUsageView {
+    type Event = ();
+
+    fn tab_content_text(&self, _cx: &WindowContext) -> Option<SharedString> {
+        Some("Token Usage".into())
+    }
+}
+
 pub struct ConfigurationView {
     focus_handle: FocusHandle,
     configuration_views: HashMap<LanguageModelProviderId, AnyView>,
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use ::open_ai::Model as OpenAiModel;
 use anthropic::Model as AnthropicModel;
+use collections::HashMap;
 use feature_flags::FeatureFlagAppExt;
 use fs::Fs;
 use gpui::{AppContext, Pixels};
@@ -63,12 +64,74 @@ pub struct AssistantSettings {
     pub inline_alternatives: Vec<LanguageModelSelection>,
     pub using_outdated_settings_version: bool,
     pub enable_experimental_live_diffs: bool,
+    pub tool_approvals: HashMap<String, ToolApproval>,
+    pub model_prices: HashMap<String, ModelPrice>,
+    pub project_prompt_directories: Vec<String>,
 }
 
 impl AssistantSettings {
     pub fn are_live_diffs_enabled(&self, cx: &AppContext) -> bool {
         cx.is_staff() || self.enable_experimental_live_diffs
     }
+
+    /// Returns whether the tool with the given name must be confirmed by the user before it
+    /// runs, falling back to the tool's own default when no policy is configured for it.
+    pub fn tool_needs_confirmation(&self, tool_name: &str, default: bool) -> bool {
+        match self.tool_approvals.get(tool_name) {
+            Some(ToolApproval::Auto) => false,
+            Some(ToolApproval::Confirm) => true,
+            None => default,
+        }
+    }
+
+    /// Returns the configured price for the model with the given id, if any.
+    pub fn model_price(&self, model_id: &str) -> Option<&ModelPrice> {
+        self.model_prices.get(model_id)
+    }
+}
+
+/// The price of a model's tokens, in US dollars per million tokens.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ModelPrice {
+    /// The price of input tokens.
+    #[serde(default)]
+    pub input: f64,
+    /// The price of output tokens.
+    #[serde(default)]
+    pub output: f64,
+    /// The price of input tokens written to the prompt cache.
+    #[serde(default)]
+    pub cache_write: f64,
+    /// The price of input tokens read from the prompt cache.
+    #[serde(default)]
+    pub cache_read: f64,
+}
+
+impl ModelPrice {
+    /// Returns the cost in US dollars of the given token counts.
+    pub fn cost(
+        &self,
+        input_tokens: u64,
+        output_tokens: u64,
+        cache_creation_input_tokens: u64,
+        cache_read_input_tokens: u64,
+    ) -> f64 {
+        (input_tokens as f64 * self.input
+            + output_tokens as f64 * self.output
+            + cache_creation_input_tokens as f64 * self.cache_write
+            + cache_read_input_tokens as f64 * self.cache_read)
+            / 1_000_000.
+    }
+}
+
+/// Whether a tool can run without the user's approval.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolApproval {
+    /// Run the tool without asking.
+    Auto,
+    /// Ask the user before each use of the tool.
+    Confirm,
 }
 
 /// Assistant panel settings
@@ -247,6 +310,9 @@ impl AssistantSettingsContent {
                         }),
                     inline_alternatives: None,
                     enable_experimental_live_diffs: None,
+                    tool_approvals: None,
+                    model_prices: None,
+                    project_prompt_directories: None,
                 },
                 VersionedAssistantSettingsContent::V2(settings) => settings.clone(),
             },
@@ -267,6 +333,9 @@ impl AssistantSettingsContent {
                 }),
                 inline_alternatives: None,
                 enable_experimental_live_diffs: None,
+                tool_approvals: None,
+                model_prices: None,
+                project_prompt_directories: None,
             },
         }
     }
@@ -384,6 +453,9 @@ impl Default for VersionedAssistantSettingsContent {
             default_model: None,
             inline_alternatives: None,
             enable_experimental_live_diffs: None,
+            tool_approvals: None,
+            model_prices: None,
+            project_prompt_directories: None,
         })
     }
 }
@@ -418,6 +490,23 @@ pub struct AssistantSettingsContentV2 {
     ///
     /// Default: false
     enable_experimental_live_diffs: Option<bool>,
+    /// Whether each of the assistant's tools, by name, runs automatically or asks for
+    /// confirmation first. Context server tools are named `<server id>__<tool name>`, with
+    /// characters other than ASCII letters, digits, `_` and `-` replaced by `_`.
+    /// Tools without a policy use their own default.
+    ///
+    /// Default: {}
+    tool_approvals: Option<HashMap<String, ToolApproval>>,
+    /// The price of each model's tokens, keyed by model id, used to estimate the cost of
+    /// assistant requests.
+    ///
+    /// Default: {}
+    model_prices: Option<HashMap<String, ModelPrice>>,
+    /// Directories, relative to each worktree's root, from which to load prompts stored as
+    /// markdown files into the prompt library.
+    ///
+    /// Default: []
+    project_prompt_directories: Option<Vec<String>>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
@@ -428,7 +517,7 @@ pub struct LanguageModelSelection {
 }
 
 fn providers_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
-    schemars::schema::SchemaObject {
+    let built_in_providers = schemars::schema::SchemaObject {
         enum_values: Some(vec![
             "anthropic".into(),
             "google".into(),
@@ -438,6 +527,27 @@ fn providers_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema:
             "copilot_chat".into(),
         ]),
         ..Default::default()
+    };
+    let open_ai_compatible_providers = schemars::schema::SchemaObject {
+        instance_type: Some(schemars::schema::InstanceType::String.into()),
+        string: Some(Box::new(schemars::schema::StringValidation {
+            pattern: Some(format!(
+                "^{}",
+                language_model::provider::open_ai_compatible::PROVIDER_ID_PREFIX
+            )),
+            ..Default::default()
+        })),
+        ..Default::default()
+    };
+    schemars::schema::SchemaObject {
+        subschemas: Some(Box::new(schemars::schema::SubschemaValidation {
+            any_of: Some(vec![
+                built_in_providers.into(),
+                open_ai_compatible_providers.into(),
+            ]),
+            ..Default::default()
+        })),
+        ..Default::default()
     }
     .into()
 }
@@ -544,6 +654,16 @@ impl Settings for AssistantSettings {
                 &mut settings.enable_experimental_live_diffs,
                 value.enable_experimental_live_diffs,
             );
+            settings
+                .tool_approvals
+                .extend(value.tool_approvals.unwrap_or_default());
+            settings
+                .model_prices
+                .extend(value.model_prices.unwrap_or_default());
+            merge(
+                &mut settings.project_prompt_directories,
+                value.project_prompt_directories,
+            );
         }
 
         Ok(settings)
@@ -603,6 +723,9 @@ mod tests {
                             default_width: None,
                             default_height: None,
                             enable_experimental_live_diffs: None,
+                            tool_approvals: None,
+                            model_prices: None,
+                            project_prompt_directories: None,
                         }),
                     )
                 },
@@ -13,6 +13,7 @@ use assistant_tool::ToolRegistry;
 use client::{self, proto, telemetry::Telemetry};
 use clock::ReplicaId;
 use collections::{HashMap, HashSet};
+use db::token_usage::{TokenUsageRecord, TOKEN_USAGE_STORE};
 use feature_flags::{FeatureFlag, FeatureFlagAppExt};
 use fs::{Fs, RemoveOptions};
 use futures::{future::Shared, FutureExt, StreamExt};
@@ -27,7 +28,7 @@ use language_model::{
     LanguageModel, LanguageModelCacheConfiguration, LanguageModelCompletionEvent,
     LanguageModelImage, LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage,
     LanguageModelRequestTool, LanguageModelToolResult, LanguageModelToolUse, MessageContent, Role,
-    StopReason,
+    StopReason, TokenUsage,
 };
 use open_ai::Model as OpenAiModel;
 use paths::contexts_dir;
@@ -49,7 +50,7 @@ use text::BufferSnapshot;
 use util::{post_inc, ResultExt, TryFutureExt};
 use uuid::Uuid;
 
-#[derive(Clone, Eq, PartialEq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct ContextId(String);
 
 impl ContextId {
@@ -66,6 +67,32 @@ impl ContextId {
     }
 }
 
+/// A message in another context that a context was forked from, or that a
+/// fork of this context branched off at.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ContextBranchPoint {
+    pub context_id: ContextId,
+    pub message_id: MessageId,
+}
+
+impl ContextBranchPoint {
+    pub fn from_proto(branch_point: proto::ContextBranchPoint) -> Result<Self> {
+        Ok(Self {
+            context_id: ContextId::from_proto(branch_point.context_id),
+            message_id: MessageId(language::proto::deserialize_timestamp(
+                branch_point.message_id.context("invalid message id")?,
+            )),
+        })
+    }
+
+    pub fn to_proto(&self) -> proto::ContextBranchPoint {
+        proto::ContextBranchPoint {
+            context_id: self.context_id.to_proto(),
+            message_id: Some(language::proto::serialize_timestamp(self.message_id.0)),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum ContextOperation {
     InsertMessage {
@@ -88,6 +115,12 @@ pub enum ContextOperation {
         sections: Vec<SlashCommandOutputSection<language::Anchor>>,
         version: clock::Global,
     },
+    UpdateBranches {
+        forked_from: Option<ContextBranchPoint>,
+        branches: Vec<ContextBranchPoint>,
+        timestamp: clock::Lamport,
+        version: clock::Global,
+    },
     BufferOperation(language::Operation),
 }
 
@@ -114,6 +147,8 @@ impl ContextOperation {
                         ),
                         timestamp: id.0,
                         cache: None,
+                        alternatives: message.alternatives.into_iter().map(Into::into).collect(),
+                        active_alternative: message.active_alternative as usize,
                     },
                     version: language::proto::deserialize_version(&insert.version),
                 })
@@ -129,6 +164,8 @@ impl ContextOperation {
                         update.timestamp.context("invalid timestamp")?,
                     ),
                     cache: None,
+                    alternatives: update.alternatives.into_iter().map(Into::into).collect(),
+                    active_alternative: update.active_alternative as usize,
                 },
                 version: language::proto::deserialize_version(&update.version),
             }),
@@ -169,6 +206,21 @@ impl ContextOperation {
                     version: language::proto::deserialize_version(&finished.version),
                 })
             }
+            proto::context_operation::Variant::UpdateBranches(update) => Ok(Self::UpdateBranches {
+                forked_from: update
+                    .forked_from
+                    .map(ContextBranchPoint::from_proto)
+                    .transpose()?,
+                branches: update
+                    .branches
+                    .into_iter()
+                    .map(ContextBranchPoint::from_proto)
+                    .collect::<Result<Vec<_>>>()?,
+                timestamp: language::proto::deserialize_timestamp(
+                    update.timestamp.context("invalid timestamp")?,
+                ),
+                version: language::proto::deserialize_version(&update.version),
+            }),
             proto::context_operation::Variant::BufferOperation(op) => Ok(Self::BufferOperation(
                 language::proto::deserialize_operation(
                     op.operation.context("invalid buffer operation")?,
@@ -191,6 +243,12 @@ impl ContextOperation {
                             start: Some(language::proto::serialize_anchor(&anchor.start)),
                             role: metadata.role.to_proto() as i32,
                             status: Some(metadata.status.to_proto()),
+                            alternatives: metadata
+                                .alternatives
+                                .iter()
+                                .map(|text| text.to_string())
+                                .collect(),
+                            active_alternative: metadata.active_alternative as u32,
                         }),
                         version: language::proto::serialize_version(version),
                     },
@@ -208,6 +266,12 @@ impl ContextOperation {
                         status: Some(metadata.status.to_proto()),
                         timestamp: Some(language::proto::serialize_timestamp(metadata.timestamp)),
                         version: language::proto::serialize_version(version),
+                        alternatives: metadata
+                            .alternatives
+                            .iter()
+                            .map(|text| text.to_string())
+                            .collect(),
+                        active_alternative: metadata.active_alternative as u32,
                     },
                 )),
             },
@@ -253,6 +317,21 @@ impl ContextOperation {
                     },
                 )),
             },
+            Self::UpdateBranches {
+                forked_from,
+                branches,
+                timestamp,
+                version,
+            } => proto::ContextOperation {
+                variant: Some(proto::context_operation::Variant::UpdateBranches(
+                    proto::context_operation::UpdateBranches {
+                        forked_from: forked_from.as_ref().map(ContextBranchPoint::to_proto),
+                        branches: branches.iter().map(ContextBranchPoint::to_proto).collect(),
+                        timestamp: Some(language::proto::serialize_timestamp(*timestamp)),
+                        version: language::proto::serialize_version(version),
+                    },
+                )),
+            },
             Self::BufferOperation(operation) => proto::ContextOperation {
                 variant: Some(proto::context_operation::Variant::BufferOperation(
                     proto::context_operation::BufferOperation {
@@ -269,6 +348,7 @@ impl ContextOperation {
             Self::UpdateMessage { metadata, .. } => metadata.timestamp,
             Self::UpdateSummary { summary, .. } => summary.timestamp,
             Self::SlashCommandFinished { id, .. } => id.0,
+            Self::UpdateBranches { timestamp, .. } => *timestamp,
             Self::BufferOperation(_) => {
                 panic!("reading the timestamp of a buffer operation is not supported")
             }
@@ -281,7 +361,8 @@ impl ContextOperation {
             Self::InsertMessage { version, .. }
             | Self::UpdateMessage { version, .. }
             | Self::UpdateSummary { version, .. }
-            | Self::SlashCommandFinished { version, .. } => version,
+            | Self::SlashCommandFinished { version, .. }
+            | Self::UpdateBranches { version, .. } => version,
             Self::BufferOperation(_) => {
                 panic!("reading the version of a buffer operation is not supported")
             }
@@ -353,6 +434,14 @@ pub struct MessageMetadata {
     pub(crate) timestamp: clock::Lamport,
     #[serde(skip)]
     pub cache: Option<MessageCacheMetadata>,
+    /// Other responses generated for this message, excluding the one currently
+    /// in the buffer.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<Arc<str>>,
+    /// The position of the response in the buffer among all of the message's
+    /// responses.
+    #[serde(default)]
+    pub active_alternative: usize,
 }
 
 impl From<&Message> for MessageMetadata {
@@ -362,6 +451,8 @@ impl From<&Message> for MessageMetadata {
             status: message.status.clone(),
             timestamp: message.id.0,
             cache: message.cache.clone(),
+            alternatives: message.alternatives.clone(),
+            active_alternative: message.active_alternative,
         }
     }
 }
@@ -391,6 +482,15 @@ pub struct Message {
     pub role: Role,
     pub status: MessageStatus,
     pub cache: Option<MessageCacheMetadata>,
+    pub alternatives: Vec<Arc<str>>,
+    pub active_alternative: usize,
+}
+
+impl Message {
+    /// The number of responses that can be cycled through for this message.
+    pub fn response_count(&self) -> usize {
+        self.alternatives.len() + 1
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -477,11 +577,15 @@ pub struct Context {
     contents: Vec<Content>,
     messages_metadata: HashMap<MessageId, MessageMetadata>,
     summary: Option<ContextSummary>,
+    forked_from: Option<ContextBranchPoint>,
+    branches: Vec<ContextBranchPoint>,
+    branches_timestamp: clock::Lamport,
     pending_summary: Task<Option<()>>,
     completion_count: usize,
     pending_completions: Vec<PendingCompletion>,
     token_count: Option<usize>,
     pending_token_count: Task<Option<()>>,
+    token_usage: TokenUsage,
     pending_save: Task<Result<()>>,
     pending_cache_warming_task: Task<Option<()>>,
     path: Option<PathBuf>,
@@ -576,11 +680,15 @@ impl Context {
             slash_command_output_sections: Vec::new(),
             edits_since_last_parse: edits_since_last_slash_command_parse,
             summary: None,
+            forked_from: None,
+            branches: Vec::new(),
+            branches_timestamp: clock::Lamport::default(),
             pending_summary: Task::ready(None),
             completion_count: Default::default(),
             pending_completions: Default::default(),
             token_count: None,
             pending_token_count: Task::ready(None),
+            token_usage: TokenUsage::default(),
             pending_cache_warming_task: Task::ready(None),
             _subscriptions: vec![cx.subscribe(&buffer, Self::handle_buffer_event)],
             pending_save: Task::ready(Ok(())),
@@ -609,6 +717,8 @@ impl Context {
                 status: MessageStatus::Done,
                 timestamp: first_message_id.0,
                 cache: None,
+                alternatives: Vec::new(),
+                active_alternative: 0,
             },
         );
         this.message_anchors.push(message);
@@ -655,13 +765,16 @@ impl Context {
                     }
                 })
                 .collect(),
+            forked_from: self.forked_from.clone(),
+            branches: self.branches.clone(),
+            token_usage: self.token_usage,
         }
     }
 
     #[allow(clippy::too_many_arguments)]
     pub fn deserialize(
         saved_context: SavedContext,
-        path: PathBuf,
+        path: Option<PathBuf>,
         language_registry: Arc<LanguageRegistry>,
         prompt_builder: Arc<PromptBuilder>,
         project: Option<Model<Project>>,
@@ -679,7 +792,8 @@ impl Context {
             telemetry,
             cx,
         );
-        this.path = Some(path);
+        this.path = path;
+        this.token_usage = saved_context.token_usage;
         this.buffer.update(cx, |buffer, cx| {
             buffer.set_text(saved_context.text.as_str(), cx)
         });
@@ -773,6 +887,7 @@ impl Context {
     fn flush_ops(&mut self, cx: &mut ModelContext<Context>) {
         let mut changed_messages = HashSet::default();
         let mut summary_changed = false;
+        let mut branches_changed = false;
 
         self.pending_ops.sort_unstable_by_key(|op| op.timestamp());
         for op in mem::take(&mut self.pending_ops) {
@@ -837,6 +952,19 @@ impl Context {
                         });
                     }
                 }
+                ContextOperation::UpdateBranches {
+                    forked_from,
+                    branches,
+                    timestamp,
+                    ..
+                } => {
+                    if timestamp > self.branches_timestamp {
+                        self.forked_from = forked_from;
+                        self.branches = branches;
+                        self.branches_timestamp = timestamp;
+                        branches_changed = true;
+                    }
+                }
                 ContextOperation::BufferOperation(_) => unreachable!(),
             }
 
@@ -849,6 +977,9 @@ impl Context {
             self.message_roles_updated(changed_messages, cx);
             cx.emit(ContextEvent::MessagesEdited);
             cx.notify();
+        } else if branches_changed {
+            cx.emit(ContextEvent::MessagesEdited);
+            cx.notify();
         }
 
         if summary_changed {
@@ -871,7 +1002,9 @@ impl Context {
             ContextOperation::UpdateMessage { message_id, .. } => {
                 self.messages_metadata.contains_key(message_id)
             }
-            ContextOperation::UpdateSummary { .. } => true,
+            ContextOperation::UpdateSummary { .. } | ContextOperation::UpdateBranches { .. } => {
+                true
+            }
             ContextOperation::SlashCommandFinished {
                 output_range,
                 sections,
@@ -927,6 +1060,66 @@ impl Context {
         self.summary.as_ref()
     }
 
+    pub fn forked_from(&self) -> Option<&ContextBranchPoint> {
+        self.forked_from.as_ref()
+    }
+
+    pub fn branches(&self) -> &[ContextBranchPoint] {
+        &self.branches
+    }
+
+    /// Records that a fork of this context was created at the given message.
+    pub(crate) fn add_branch(
+        &mut self,
+        context_id: ContextId,
+        message_id: MessageId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.branches.push(ContextBranchPoint {
+            context_id,
+            message_id,
+        });
+
+        let version = self.version.clone();
+        let timestamp = self.next_timestamp();
+        self.branches_timestamp = timestamp;
+        self.push_op(
+            ContextOperation::UpdateBranches {
+                forked_from: self.forked_from.clone(),
+                branches: self.branches.clone(),
+                timestamp,
+                version,
+            },
+            cx,
+        );
+        cx.emit(ContextEvent::MessagesEdited);
+        cx.notify();
+    }
+
+    /// Returns a copy of this context that ends with the given message, to be
+    /// opened as a new branch of the conversation.
+    pub(crate) fn fork_at(&self, message_id: MessageId, cx: &AppContext) -> Option<SavedContext> {
+        let message = self.messages(cx).find(|message| message.id == message_id)?;
+        let fork_end = Self::message_content_range(&message).end;
+
+        let mut saved_context = self.serialize(cx);
+        saved_context.id = Some(ContextId::new());
+        saved_context.text.truncate(fork_end);
+        saved_context
+            .messages
+            .retain(|message| message.start < fork_end || message.id == message_id);
+        saved_context
+            .slash_command_output_sections
+            .retain(|section| section.range.end <= fork_end);
+        saved_context.forked_from = Some(ContextBranchPoint {
+            context_id: self.id.clone(),
+            message_id,
+        });
+        saved_context.branches.clear();
+        saved_context.token_usage = TokenUsage::default();
+        Some(saved_context)
+    }
+
     pub(crate) fn patch_containing(
         &self,
         position: Point,
@@ -1027,6 +1220,54 @@ impl Context {
         self.token_count
     }
 
+    /// Returns the tokens consumed by all the completions requested from this context.
+    pub fn token_usage(&self) -> TokenUsage {
+        self.token_usage
+    }
+
+    /// Adds the tokens consumed by a completion to this context's total and records them
+    /// in the usage database, keyed by day, project and model.
+    fn record_token_usage(
+        &mut self,
+        model: &Arc<dyn LanguageModel>,
+        usage: TokenUsage,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if usage.is_empty() {
+            return;
+        }
+
+        self.token_usage += usage;
+        cx.notify();
+
+        let project = self
+            .project
+            .as_ref()
+            .map(|project| {
+                project
+                    .read(cx)
+                    .visible_worktrees(cx)
+                    .map(|worktree| worktree.read(cx).abs_path().to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .unwrap_or_default();
+        let record = TokenUsageRecord {
+            day: chrono::Local::now().format("%Y-%m-%d").to_string(),
+            project,
+            context_id: self.id.0.clone(),
+            provider: model.provider_id().0.to_string(),
+            model: model.id().0.to_string(),
+            input_tokens: usage.input_tokens.into(),
+            output_tokens: usage.output_tokens.into(),
+            cache_creation_input_tokens: usage.cache_creation_input_tokens.into(),
+            cache_read_input_tokens: usage.cache_read_input_tokens.into(),
+        };
+        db::write_and_log(cx, move || async move {
+            TOKEN_USAGE_STORE.record_usage(record).await
+        });
+    }
+
     pub(crate) fn count_remaining_tokens(&mut self, cx: &mut ModelContext<Self>) {
         let request = self.to_completion_request(cx);
         let Some(model) = LanguageModelRegistry::read_global(cx).active_model() else {
@@ -1713,6 +1954,16 @@ impl Context {
                             let start = command_range.start.to_offset(buffer);
                             let old_end = command_range.end.to_offset(buffer);
                             let new_end = start + output.text.len();
+
+                            // Drop the sections of any output that is being replaced, such as
+                            // when a resource is refreshed.
+                            if start < old_end {
+                                this.slash_command_output_sections.retain(|section| {
+                                    let range = section.range.to_offset(buffer);
+                                    range.start < start || range.end > old_end
+                                });
+                            }
+
                             buffer.edit([(start..old_end, output.text)], None, cx);
 
                             let mut sections = output
@@ -1872,8 +2123,122 @@ impl Context {
         // Compute which messages to cache, including the last one.
         self.mark_cache_anchors(&model.cache_configuration(), false, cx);
 
-        let mut request = self.to_completion_request(cx);
+        let request = self.to_completion_request(cx);
+
+        let assistant_message = self
+            .insert_message_after(last_message_id, Role::Assistant, MessageStatus::Pending, cx)
+            .unwrap();
+
+        // Queue up the user's next reply.
+        let user_message = self
+            .insert_message_after(assistant_message.id, Role::User, MessageStatus::Done, cx)
+            .unwrap();
+
+        self.stream_response(assistant_message.id, request, model, cx);
+
+        Some(user_message)
+    }
+
+    /// Requests a new response for the given assistant message. The message's
+    /// current text is kept as an alternative that can be switched back to.
+    pub fn regenerate(&mut self, message_id: MessageId, cx: &mut ModelContext<Self>) -> bool {
+        let model_registry = LanguageModelRegistry::read_global(cx);
+        let Some(provider) = model_registry.active_provider() else {
+            return false;
+        };
+        let Some(model) = model_registry.active_model() else {
+            return false;
+        };
+        if !provider.is_authenticated(cx) {
+            log::info!("completion provider has no credentials");
+            return false;
+        }
+
+        let Some(message) = self.messages(cx).find(|message| message.id == message_id) else {
+            return false;
+        };
+        if message.role != Role::Assistant || message.status == MessageStatus::Pending {
+            return false;
+        }
+
+        let request = self.to_completion_request_until(Some(message_id), cx);
+        let content_range = Self::message_content_range(&message);
+        let previous_response: Arc<str> = self
+            .buffer
+            .read(cx)
+            .text_for_range(content_range.clone())
+            .collect::<String>()
+            .into();
+        self.buffer.update(cx, |buffer, cx| {
+            buffer.edit([(content_range, "")], None, cx);
+        });
+        self.update_metadata(message_id, cx, |metadata| {
+            let ix = metadata.active_alternative.min(metadata.alternatives.len());
+            metadata.alternatives.insert(ix, previous_response);
+            metadata.active_alternative = metadata.alternatives.len();
+            metadata.status = MessageStatus::Pending;
+        });
+
+        self.stream_response(message_id, request, model, cx);
+        true
+    }
+
+    /// Replaces the text of the given message with another one of its responses.
+    pub fn select_alternative(
+        &mut self,
+        message_id: MessageId,
+        ix: usize,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(message) = self.messages(cx).find(|message| message.id == message_id) else {
+            return;
+        };
+        if message.status == MessageStatus::Pending
+            || ix == message.active_alternative
+            || ix >= message.response_count()
+        {
+            return;
+        }
+
+        let content_range = Self::message_content_range(&message);
+        let current_response: Arc<str> = self
+            .buffer
+            .read(cx)
+            .text_for_range(content_range.clone())
+            .collect::<String>()
+            .into();
+        let mut responses = message.alternatives;
+        responses.insert(
+            message.active_alternative.min(responses.len()),
+            current_response,
+        );
+        let selected_response = responses.remove(ix);
+        self.buffer.update(cx, |buffer, cx| {
+            buffer.edit([(content_range, selected_response)], None, cx);
+        });
+        self.update_metadata(message_id, cx, |metadata| {
+            metadata.alternatives = responses;
+            metadata.active_alternative = ix;
+        });
+    }
+
+    /// Returns the range of the message's text, excluding the newline that
+    /// separates it from the next message.
+    fn message_content_range(message: &Message) -> Range<usize> {
+        if message.anchor_range.end == language::Anchor::MAX {
+            message.offset_range.clone()
+        } else {
+            message.offset_range.start..message.offset_range.end.saturating_sub(1)
+        }
+    }
 
+    fn stream_response(
+        &mut self,
+        assistant_message_id: MessageId,
+        mut request: LanguageModelRequest,
+        model: Arc<dyn LanguageModel>,
+        cx: &mut ModelContext<Self>,
+    ) {
         if cx.has_flag::<ToolUseFeatureFlag>() {
             let tool_registry = ToolRegistry::global(cx);
             request.tools = tool_registry
@@ -1887,22 +2252,13 @@ impl Context {
                 .collect();
         }
 
-        let assistant_message = self
-            .insert_message_after(last_message_id, Role::Assistant, MessageStatus::Pending, cx)
-            .unwrap();
-
-        // Queue up the user's next reply.
-        let user_message = self
-            .insert_message_after(assistant_message.id, Role::User, MessageStatus::Done, cx)
-            .unwrap();
-
         let pending_completion_id = post_inc(&mut self.completion_count);
 
         let task = cx.spawn({
             |this, mut cx| async move {
                 let stream = model.stream_completion(request, &cx);
-                let assistant_message_id = assistant_message.id;
                 let mut response_latency = None;
+                let mut request_usage = TokenUsage::default();
                 let stream_completion = async {
                     let request_start = Instant::now();
                     let mut events = stream.await?;
@@ -1931,6 +2287,9 @@ impl Context {
                                     LanguageModelCompletionEvent::Stop(reason) => {
                                         stop_reason = reason;
                                     }
+                                    LanguageModelCompletionEvent::UsageUpdate(usage) => {
+                                        request_usage = usage;
+                                    }
                                     LanguageModelCompletionEvent::Text(chunk) => {
                                         buffer.edit(
                                             [(
@@ -2002,6 +2361,8 @@ impl Context {
                 let result = stream_completion.await;
 
                 this.update(&mut cx, |this, cx| {
+                    this.record_token_usage(&model, request_usage, cx);
+
                     let error_message = if let Some(error) = result.as_ref().err() {
                         if error.is::<PaymentRequiredError>() {
                             cx.emit(ContextEvent::ShowPaymentRequiredError);
@@ -2067,14 +2428,22 @@ impl Context {
 
         self.pending_completions.push(PendingCompletion {
             id: pending_completion_id,
-            assistant_message_id: assistant_message.id,
+            assistant_message_id,
             _task: task,
         });
-
-        Some(user_message)
     }
 
     pub fn to_completion_request(&self, cx: &AppContext) -> LanguageModelRequest {
+        self.to_completion_request_until(None, cx)
+    }
+
+    /// Builds a completion request from the messages preceding `end_message_id`,
+    /// or from all messages if it is `None`.
+    fn to_completion_request_until(
+        &self,
+        end_message_id: Option<MessageId>,
+        cx: &AppContext,
+    ) -> LanguageModelRequest {
         let buffer = self.buffer.read(cx);
 
         let mut contents = self.contents(cx).peekable();
@@ -2093,8 +2462,12 @@ impl Context {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            max_tokens: None,
         };
         for message in self.messages(cx) {
+            if Some(message.id) == end_message_id {
+                break;
+            }
             if message.status != MessageStatus::Done {
                 continue;
             }
@@ -2274,6 +2647,8 @@ impl Context {
                 status,
                 timestamp: anchor.id.0,
                 cache: None,
+                alternatives: Vec::new(),
+                active_alternative: 0,
             };
             self.insert_message(anchor.clone(), metadata.clone(), cx);
             self.push_op(
@@ -2369,6 +2744,8 @@ impl Context {
                 status: MessageStatus::Done,
                 timestamp: suffix.id.0,
                 cache: None,
+                alternatives: Vec::new(),
+                active_alternative: 0,
             };
             self.insert_message(suffix.clone(), suffix_metadata.clone(), cx);
             self.push_op(
@@ -2419,6 +2796,8 @@ impl Context {
                         status: MessageStatus::Done,
                         timestamp: selection.id.0,
                         cache: None,
+                        alternatives: Vec::new(),
+                        active_alternative: 0,
                     };
                     self.insert_message(selection.clone(), selection_metadata.clone(), cx);
                     self.push_op(
@@ -2490,7 +2869,8 @@ impl Context {
             self.pending_summary = cx.spawn(|this, mut cx| {
                 async move {
                     let stream = model.stream_completion_text(request, &cx);
-                    let mut messages = stream.await?;
+                    let text_stream = stream.await?;
+                    let mut messages = text_stream.stream;
 
                     let mut replaced = !replace_old;
                     while let Some(message) = messages.next().await {
@@ -2520,7 +2900,10 @@ impl Context {
                         }
                     }
 
+                    let usage = *text_stream.last_token_usage.lock();
                     this.update(&mut cx, |this, cx| {
+                        this.record_token_usage(&model, usage, cx);
+
                         let version = this.version.clone();
                         let timestamp = this.next_timestamp();
                         if let Some(summary) = this.summary.as_mut() {
@@ -2627,6 +3010,8 @@ impl Context {
                     role: metadata.role,
                     status: metadata.status.clone(),
                     cache: metadata.cache.clone(),
+                    alternatives: metadata.alternatives.clone(),
+                    active_alternative: metadata.active_alternative,
                 });
             }
             None
@@ -2818,6 +3203,12 @@ pub struct SavedContext {
     pub summary: String,
     pub slash_command_output_sections:
         Vec<assistant_slash_command::SlashCommandOutputSection<usize>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forked_from: Option<ContextBranchPoint>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub branches: Vec<ContextBranchPoint>,
+    #[serde(default, skip_serializing_if = "TokenUsage::is_empty")]
+    pub token_usage: TokenUsage,
 }
 
 impl SavedContext {
@@ -2878,6 +3269,8 @@ impl SavedContext {
                         status: message.metadata.status,
                         timestamp: message.metadata.timestamp,
                         cache: None,
+                        alternatives: message.metadata.alternatives,
+                        active_alternative: message.metadata.active_alternative,
                     },
                     version: version.clone(),
                 });
@@ -2895,6 +3288,8 @@ impl SavedContext {
                     status: metadata.status,
                     timestamp,
                     cache: None,
+                    alternatives: metadata.alternatives,
+                    active_alternative: metadata.active_alternative,
                 },
                 version: version.clone(),
             });
@@ -2934,6 +3329,17 @@ impl SavedContext {
         });
         version.observe(timestamp);
 
+        if self.forked_from.is_some() || !self.branches.is_empty() {
+            let timestamp = next_timestamp.tick();
+            operations.push(ContextOperation::UpdateBranches {
+                forked_from: self.forked_from,
+                branches: self.branches,
+                timestamp,
+                version: version.clone(),
+            });
+            version.observe(timestamp);
+        }
+
         operations
     }
 }
@@ -2991,12 +3397,17 @@ impl SavedContextV0_3_0 {
                             status: metadata.status.clone(),
                             timestamp,
                             cache: None,
+                            alternatives: Vec::new(),
+                            active_alternative: 0,
                         },
                     })
                 })
                 .collect(),
             summary: self.summary,
             slash_command_output_sections: self.slash_command_output_sections,
+            forked_from: None,
+            branches: Vec::new(),
+            token_usage: TokenUsage::default(),
         }
     }
 }
@@ -3062,6 +3473,7 @@ impl SavedContextV0_1_0 {
 
 #[derive(Clone)]
 pub struct SavedContextMetadata {
+    pub id: Option<ContextId>,
     pub title: String,
     pub path: PathBuf,
     pub mtime: chrono::DateTime<chrono::Local>,
@@ -1,7 +1,8 @@
 use super::{AssistantEdit, MessageCacheMetadata};
 use crate::{
     assistant_panel, prompt_library, slash_command::file_command, AssistantEditKind, CacheStatus,
-    Context, ContextEvent, ContextId, ContextOperation, MessageId, MessageStatus, PromptBuilder,
+    Context, ContextBranchPoint, ContextEvent, ContextId, ContextOperation, ContextStore,
+    ContextVersion, MessageId, MessageStatus, PromptBuilder,
 };
 use anyhow::Result;
 use assistant_slash_command::{
@@ -477,7 +478,8 @@ async fn test_slash_commands(cx: &mut TestAppContext) {
 
 #[gpui::test]
 async fn test_workflow_step_parsing(cx: &mut TestAppContext) {
-    cx.update(prompt_library::init);
+    let fs = FakeFs::new(cx.executor());
+    cx.update(|cx| prompt_library::init(fs.clone(), cx));
     let mut settings_store = cx.update(SettingsStore::test);
     cx.update(|cx| {
         settings_store
@@ -490,7 +492,6 @@ async fn test_workflow_step_parsing(cx: &mut TestAppContext) {
     cx.set_global(settings_store);
     cx.update(language::init);
     cx.update(Project::init_settings);
-    let fs = FakeFs::new(cx.executor());
     let project = Project::test(fs, [Path::new("/root")], cx).await;
     cx.update(LanguageModelRegistry::test);
 
@@ -938,6 +939,276 @@ async fn test_serialization(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_message_alternatives(cx: &mut TestAppContext) {
+    let settings_store = cx.update(SettingsStore::test);
+    cx.set_global(settings_store);
+    cx.update(LanguageModelRegistry::test);
+    cx.update(assistant_panel::init);
+    let registry = Arc::new(LanguageRegistry::test(cx.executor()));
+    let prompt_builder = Arc::new(PromptBuilder::new(None).unwrap());
+    let context =
+        cx.new_model(|cx| Context::local(registry.clone(), None, None, prompt_builder.clone(), cx));
+    let buffer = context.read_with(cx, |context, _| context.buffer.clone());
+    let message_0 = context.read_with(cx, |context, _| context.message_anchors[0].id);
+    let message_1 = context.update(cx, |context, cx| {
+        context
+            .insert_message_after(message_0, Role::Assistant, MessageStatus::Done, cx)
+            .unwrap()
+    });
+    let message_2 = context.update(cx, |context, cx| {
+        context
+            .insert_message_after(message_1.id, Role::User, MessageStatus::Done, cx)
+            .unwrap()
+    });
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit([(0..0, "hi"), (1..1, "first"), (2..2, "next")], None, cx);
+    });
+    assert_eq!(
+        buffer.read_with(cx, |buffer, _| buffer.text()),
+        "hi\nfirst\nnext"
+    );
+
+    context.update(cx, |context, cx| {
+        context.update_metadata(message_1.id, cx, |metadata| {
+            metadata.alternatives = vec!["second response".into()];
+        });
+        context.select_alternative(message_1.id, 1, cx);
+    });
+    assert_eq!(
+        buffer.read_with(cx, |buffer, _| buffer.text()),
+        "hi\nsecond response\nnext"
+    );
+    assert_eq!(
+        cx.read(|cx| messages(&context, cx)),
+        [
+            (message_0, Role::User, 0..3),
+            (message_1.id, Role::Assistant, 3..19),
+            (message_2.id, Role::User, 19..23),
+        ]
+    );
+    assert_eq!(
+        cx.read(|cx| alternatives(&context, message_1.id, cx)),
+        (vec!["first".into()], 1)
+    );
+
+    // Selecting the active response does nothing.
+    context.update(cx, |context, cx| {
+        context.select_alternative(message_1.id, 1, cx)
+    });
+    assert_eq!(
+        buffer.read_with(cx, |buffer, _| buffer.text()),
+        "hi\nsecond response\nnext"
+    );
+
+    // Edits to the active response are kept when switching away from it.
+    buffer.update(cx, |buffer, cx| buffer.edit([(18..18, "!")], None, cx));
+    context.update(cx, |context, cx| {
+        context.select_alternative(message_1.id, 0, cx)
+    });
+    assert_eq!(
+        buffer.read_with(cx, |buffer, _| buffer.text()),
+        "hi\nfirst\nnext"
+    );
+    assert_eq!(
+        cx.read(|cx| alternatives(&context, message_1.id, cx)),
+        (vec!["second response!".into()], 0)
+    );
+
+    // Alternatives survive serialization.
+    let serialized_context = context.read_with(cx, |context, cx| context.serialize(cx));
+    let deserialized_context = cx.new_model(|cx| {
+        Context::deserialize(
+            serialized_context,
+            Default::default(),
+            registry.clone(),
+            prompt_builder.clone(),
+            None,
+            None,
+            cx,
+        )
+    });
+    assert_eq!(
+        cx.read(|cx| alternatives(&deserialized_context, message_1.id, cx)),
+        (vec!["second response!".into()], 0)
+    );
+}
+
+#[gpui::test]
+async fn test_forking_context(cx: &mut TestAppContext) {
+    let settings_store = cx.update(SettingsStore::test);
+    cx.set_global(settings_store);
+    cx.update(LanguageModelRegistry::test);
+    cx.update(assistant_panel::init);
+    let registry = Arc::new(LanguageRegistry::test(cx.executor()));
+    let prompt_builder = Arc::new(PromptBuilder::new(None).unwrap());
+    let context =
+        cx.new_model(|cx| Context::local(registry.clone(), None, None, prompt_builder.clone(), cx));
+    let buffer = context.read_with(cx, |context, _| context.buffer.clone());
+    let message_0 = context.read_with(cx, |context, _| context.message_anchors[0].id);
+    let message_1 = context.update(cx, |context, cx| {
+        context
+            .insert_message_after(message_0, Role::Assistant, MessageStatus::Done, cx)
+            .unwrap()
+    });
+    let _message_2 = context.update(cx, |context, cx| {
+        context
+            .insert_message_after(message_1.id, Role::User, MessageStatus::Done, cx)
+            .unwrap()
+    });
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit([(0..0, "a"), (1..1, "b"), (2..2, "c")], None, cx);
+    });
+
+    let saved_fork = context
+        .read_with(cx, |context, cx| context.fork_at(message_1.id, cx))
+        .unwrap();
+    let fork = cx.new_model(|cx| {
+        Context::deserialize(
+            saved_fork,
+            Default::default(),
+            registry.clone(),
+            prompt_builder.clone(),
+            None,
+            None,
+            cx,
+        )
+    });
+    let context_id = context.read_with(cx, |context, _| context.id().clone());
+    let fork_id = fork.read_with(cx, |fork, _| fork.id().clone());
+    assert_ne!(fork_id, context_id);
+    assert_eq!(
+        fork.read_with(cx, |fork, cx| fork.buffer.read(cx).text()),
+        "a\nb"
+    );
+    assert_eq!(
+        cx.read(|cx| messages(&fork, cx)),
+        [
+            (message_0, Role::User, 0..2),
+            (message_1.id, Role::Assistant, 2..3),
+        ]
+    );
+    assert_eq!(
+        fork.read_with(cx, |fork, _| fork.forked_from().cloned()),
+        Some(ContextBranchPoint {
+            context_id: context_id.clone(),
+            message_id: message_1.id,
+        })
+    );
+
+    context.update(cx, |context, cx| {
+        context.add_branch(fork_id.clone(), message_1.id, cx)
+    });
+    let expected_branches = vec![ContextBranchPoint {
+        context_id: fork_id,
+        message_id: message_1.id,
+    }];
+
+    // Branches survive serialization.
+    let serialized_context = context.read_with(cx, |context, cx| context.serialize(cx));
+    let deserialized_context = cx.new_model(|cx| {
+        Context::deserialize(
+            serialized_context,
+            Default::default(),
+            registry.clone(),
+            prompt_builder.clone(),
+            None,
+            None,
+            cx,
+        )
+    });
+    assert_eq!(
+        deserialized_context.read_with(cx, |context, _| context.branches().to_vec()),
+        expected_branches
+    );
+
+    // Branches are replicated to collaborators.
+    let operations = context
+        .read_with(cx, |context, cx| {
+            context.serialize_ops(&ContextVersion::default(), cx)
+        })
+        .await;
+    let replica = cx.new_model(|cx| {
+        Context::new(
+            context_id,
+            1,
+            language::Capability::ReadWrite,
+            registry.clone(),
+            prompt_builder.clone(),
+            None,
+            None,
+            cx,
+        )
+    });
+    replica.update(cx, |replica, cx| {
+        replica.apply_ops(
+            operations
+                .into_iter()
+                .map(|op| ContextOperation::from_proto(op).unwrap()),
+            cx,
+        )
+    });
+    assert_eq!(
+        replica.read_with(cx, |replica, _| replica.branches().to_vec()),
+        expected_branches
+    );
+}
+
+#[gpui::test]
+async fn test_opening_saved_context_by_id(cx: &mut TestAppContext) {
+    let settings_store = cx.update(SettingsStore::test);
+    cx.set_global(settings_store);
+    cx.update(language::init);
+    cx.update(Project::init_settings);
+    cx.update(LanguageModelRegistry::test);
+    cx.update(assistant_panel::init);
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/root", json!({})).await;
+    let project = Project::test(fs.clone(), [Path::new("/root")], cx).await;
+    let registry = project.read_with(cx, |project, _| project.languages().clone());
+    let prompt_builder = Arc::new(PromptBuilder::new(None).unwrap());
+
+    let context =
+        cx.new_model(|cx| Context::local(registry.clone(), None, None, prompt_builder.clone(), cx));
+    let buffer = context.read_with(cx, |context, _| context.buffer.clone());
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "saved")], None, cx));
+    let context_id = context.read_with(cx, |context, _| context.id().clone());
+    let saved_context = context.read_with(cx, |context, cx| context.serialize(cx));
+    fs.insert_tree(
+        paths::contexts_dir(),
+        json!({ "Saved - 1.zed.json": serde_json::to_string(&saved_context).unwrap() }),
+    )
+    .await;
+    let saved_path = paths::contexts_dir().join("Saved - 1.zed.json");
+
+    let context_store = cx
+        .update(|cx| ContextStore::new(project.clone(), prompt_builder.clone(), cx))
+        .await
+        .unwrap();
+
+    // The context isn't loaded by the store, so it's found through the saved contexts.
+    let opened_context = context_store
+        .update(cx, |store, cx| {
+            store.open_context_by_id(context_id.clone(), cx)
+        })
+        .await
+        .unwrap();
+    opened_context.read_with(cx, |opened_context, cx| {
+        assert_eq!(opened_context.id(), &context_id);
+        assert_eq!(opened_context.path(), Some(saved_path.as_path()));
+        assert_eq!(
+            opened_context.buffer.read(cx).text(),
+            buffer.read(cx).text()
+        );
+    });
+
+    assert!(context_store
+        .update(cx, |store, cx| store
+            .open_context_by_id(ContextId::new(), cx))
+        .await
+        .is_err());
+}
+
 #[gpui::test(iterations = 100)]
 async fn test_random_context_collaboration(cx: &mut TestAppContext, mut rng: StdRng) {
     let min_peers = env::var("MIN_PEERS")
@@ -1368,6 +1639,19 @@ fn messages(context: &Model<Context>, cx: &AppContext) -> Vec<(MessageId, Role,
         .collect()
 }
 
+fn alternatives(
+    context: &Model<Context>,
+    message_id: MessageId,
+    cx: &AppContext,
+) -> (Vec<Arc<str>>, usize) {
+    let message = context
+        .read(cx)
+        .messages(cx)
+        .find(|message| message.id == message_id)
+        .unwrap();
+    (message.alternatives, message.active_alternative)
+}
+
 fn messages_cache(
     context: &Model<Context>,
     cx: &AppContext,
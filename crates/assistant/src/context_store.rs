@@ -1,10 +1,11 @@
 use crate::{
     prompts::PromptBuilder, Context, ContextEvent, ContextId, ContextOperation, ContextVersion,
-    SavedContext, SavedContextMetadata,
+    MessageId, MessageStatus, SavedContext, SavedContextMetadata,
 };
 use anyhow::{anyhow, Context as _, Result};
 use client::{proto, telemetry::Telemetry, Client, TypedEnvelope};
 use clock::ReplicaId;
+use collections::HashMap;
 use fs::Fs;
 use futures::StreamExt;
 use fuzzy::StringMatchCandidate;
@@ -12,10 +13,12 @@ use gpui::{
     AppContext, AsyncAppContext, Context as _, EventEmitter, Model, ModelContext, Task, WeakModel,
 };
 use language::LanguageRegistry;
+use language_model::Role;
 use paths::contexts_dir;
 use project::Project;
 use regex::Regex;
 use rpc::AnyProtoClient;
+use serde::Deserialize;
 use std::{
     cmp::Reverse,
     ffi::OsStr,
@@ -30,6 +33,7 @@ pub fn init(client: &AnyProtoClient) {
     client.add_model_message_handler(ContextStore::handle_advertise_contexts);
     client.add_model_request_handler(ContextStore::handle_open_context);
     client.add_model_request_handler(ContextStore::handle_create_context);
+    client.add_model_request_handler(ContextStore::handle_fork_context);
     client.add_model_message_handler(ContextStore::handle_update_context);
     client.add_model_request_handler(ContextStore::handle_synchronize_contexts);
 }
@@ -161,23 +165,23 @@ impl ContextStore {
         mut cx: AsyncAppContext,
     ) -> Result<proto::OpenContextResponse> {
         let context_id = ContextId::from_proto(envelope.payload.context_id);
-        let operations = this.update(&mut cx, |this, cx| {
-            if this.project.read(cx).is_via_collab() {
-                return Err(anyhow!("only the host contexts can be opened"));
-            }
+        // Contexts that aren't loaded yet, such as branches of the context being
+        // edited, are read from disk.
+        let context = this
+            .update(&mut cx, |this, cx| {
+                if this.project.read(cx).is_via_collab() {
+                    return Err(anyhow!("only the host contexts can be opened"));
+                }
 
-            let context = this
-                .loaded_context_for_id(&context_id, cx)
-                .context("context not found")?;
-            if context.read(cx).replica_id() != ReplicaId::default() {
+                anyhow::Ok(this.open_context_by_id(context_id, cx))
+            })??
+            .await?;
+        let operations = context.read_with(&cx, |context, cx| {
+            if context.replica_id() != ReplicaId::default() {
                 return Err(anyhow!("context must be opened via the host"));
             }
 
-            anyhow::Ok(
-                context
-                    .read(cx)
-                    .serialize_ops(&ContextVersion::default(), cx),
-            )
+            anyhow::Ok(context.serialize_ops(&ContextVersion::default(), cx))
         })??;
         let operations = operations.await;
         Ok(proto::OpenContextResponse {
@@ -213,6 +217,46 @@ impl ContextStore {
         })
     }
 
+    async fn handle_fork_context(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ForkContext>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ForkContextResponse> {
+        let context_id = ContextId::from_proto(envelope.payload.context_id);
+        let message_id = MessageId(language::proto::deserialize_timestamp(
+            envelope.payload.message_id.context("invalid message id")?,
+        ));
+        let context = this
+            .update(&mut cx, |this, cx| {
+                if this.project.read(cx).is_via_collab() {
+                    return Err(anyhow!("can only fork contexts as the host"));
+                }
+
+                anyhow::Ok(this.open_context_by_id(context_id, cx))
+            })??
+            .await?;
+        let forked_context = this
+            .update(&mut cx, |this, cx| {
+                this.fork_context(&context, message_id, cx)
+            })?
+            .await?;
+        let (context_id, operations) = this.update(&mut cx, |_, cx| {
+            let context_id = forked_context.read(cx).id().clone();
+            cx.emit(ContextStoreEvent::ContextCreated(context_id.clone()));
+            (
+                context_id,
+                forked_context
+                    .read(cx)
+                    .serialize_ops(&ContextVersion::default(), cx),
+            )
+        })?;
+        let operations = operations.await;
+        Ok(proto::ForkContextResponse {
+            context_id: context_id.to_proto(),
+            context: Some(proto::Context { operations }),
+        })
+    }
+
     async fn handle_update_context(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::UpdateContext>,
@@ -431,7 +475,7 @@ impl ContextStore {
             let context = cx.new_model(|cx| {
                 Context::deserialize(
                     saved_context,
-                    path.clone(),
+                    Some(path.clone()),
                     languages,
                     prompt_builder,
                     Some(project),
@@ -450,6 +494,146 @@ impl ContextStore {
         })
     }
 
+    /// Opens the context with the given id, reading it from the host when the
+    /// project is remote, or looking it up among the saved contexts otherwise.
+    pub fn open_context_by_id(
+        &mut self,
+        context_id: ContextId,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Model<Context>>> {
+        if let Some(context) = self.loaded_context_for_id(&context_id, cx) {
+            return Task::ready(Ok(context));
+        }
+
+        if self.project.read(cx).is_via_collab() {
+            return self.open_remote_context(context_id, cx);
+        }
+
+        let Some(path) = self.contexts_metadata.iter().find_map(|metadata| {
+            (metadata.id.as_ref() == Some(&context_id)).then(|| metadata.path.clone())
+        }) else {
+            return Task::ready(Err(anyhow!("no saved context with the given id")));
+        };
+        self.open_local_context(path, cx)
+    }
+
+    /// Creates a new context containing the messages of `context` up to the
+    /// given message, and records it as a branch of `context`.
+    pub fn fork_context(
+        &mut self,
+        context: &Model<Context>,
+        message_id: MessageId,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Model<Context>>> {
+        if self.project.read(cx).is_via_collab() {
+            return self.fork_remote_context(context, message_id, cx);
+        }
+
+        let Some(saved_context) = context.read(cx).fork_at(message_id, cx) else {
+            return Task::ready(Err(anyhow!("message not found")));
+        };
+        let forked_context = cx.new_model(|cx| {
+            let mut forked_context = Context::deserialize(
+                saved_context,
+                None,
+                self.languages.clone(),
+                self.prompt_builder.clone(),
+                Some(self.project.clone()),
+                Some(self.telemetry.clone()),
+                cx,
+            );
+
+            // Leave room for the user to continue the conversation after a response.
+            let last_message = forked_context.messages(cx).last();
+            if let Some(last_message) = last_message {
+                if last_message.role == Role::Assistant {
+                    forked_context.insert_message_after(
+                        last_message.id,
+                        Role::User,
+                        MessageStatus::Done,
+                        cx,
+                    );
+                }
+            }
+            forked_context
+        });
+
+        let forked_context_id = forked_context.read(cx).id().clone();
+        context.update(cx, |context, cx| {
+            context.add_branch(forked_context_id, message_id, cx)
+        });
+        self.register_context(&forked_context, cx);
+
+        for context in [context, &forked_context] {
+            context.update(cx, |context, cx| context.save(None, self.fs.clone(), cx));
+        }
+
+        Task::ready(Ok(forked_context))
+    }
+
+    /// Asks the host to fork a shared context, since only the host can save the
+    /// new context and record it as a branch.
+    fn fork_remote_context(
+        &mut self,
+        context: &Model<Context>,
+        message_id: MessageId,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Model<Context>>> {
+        let project = self.project.read(cx);
+        let Some(project_id) = project.remote_id() else {
+            return Task::ready(Err(anyhow!("project was not remote")));
+        };
+
+        let replica_id = project.replica_id();
+        let capability = project.capability();
+        let language_registry = self.languages.clone();
+        let project = self.project.clone();
+        let telemetry = self.telemetry.clone();
+        let prompt_builder = self.prompt_builder.clone();
+        let request = self.client.request(proto::ForkContext {
+            project_id,
+            context_id: context.read(cx).id().to_proto(),
+            message_id: Some(language::proto::serialize_timestamp(message_id.0)),
+        });
+        cx.spawn(|this, mut cx| async move {
+            let response = request.await?;
+            let context_id = ContextId::from_proto(response.context_id);
+            let context_proto = response.context.context("invalid context")?;
+            let context = cx.new_model(|cx| {
+                Context::new(
+                    context_id.clone(),
+                    replica_id,
+                    capability,
+                    language_registry,
+                    prompt_builder,
+                    Some(project),
+                    Some(telemetry),
+                    cx,
+                )
+            })?;
+            let operations = cx
+                .background_executor()
+                .spawn(async move {
+                    context_proto
+                        .operations
+                        .into_iter()
+                        .map(ContextOperation::from_proto)
+                        .collect::<Result<Vec<_>>>()
+                })
+                .await?;
+            context.update(&mut cx, |context, cx| context.apply_ops(operations, cx))?;
+            this.update(&mut cx, |this, cx| {
+                if let Some(existing_context) = this.loaded_context_for_id(&context_id, cx) {
+                    existing_context
+                } else {
+                    this.register_context(&context, cx);
+                    this.synchronize_contexts(cx);
+                    context
+                }
+            })
+        })
+    }
+
     fn loaded_context_for_path(&self, path: &Path, cx: &AppContext) -> Option<Model<Context>> {
         self.contexts.iter().find_map(|context| {
             let context = context.upgrade()?;
@@ -702,7 +886,18 @@ impl ContextStore {
     }
 
     fn reload(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        #[derive(Deserialize)]
+        struct SavedContextId {
+            id: Option<ContextId>,
+        }
+
         let fs = self.fs.clone();
+        // Only read the ids of the contexts that changed since the last reload.
+        let indexed_ids = self
+            .contexts_metadata
+            .iter()
+            .map(|metadata| (metadata.path.clone(), (metadata.mtime, metadata.id.clone())))
+            .collect::<HashMap<_, _>>();
         cx.spawn(|this, mut cx| async move {
             fs.create_dir(contexts_dir()).await?;
 
@@ -729,10 +924,23 @@ impl ContextStore {
                     }
 
                     if let Some(title) = re.replace(file_name, "").lines().next() {
+                        let mtime: chrono::DateTime<chrono::Local> = metadata.mtime.into();
+                        let id = match indexed_ids.get(&path) {
+                            Some((indexed_mtime, id)) if *indexed_mtime == mtime => id.clone(),
+                            _ => fs
+                                .load(&path)
+                                .await
+                                .log_err()
+                                .and_then(|saved_context| {
+                                    serde_json::from_str::<SavedContextId>(&saved_context).ok()
+                                })
+                                .and_then(|saved_context| saved_context.id),
+                        };
                         contexts.push(SavedContextMetadata {
+                            id,
                             title: title.to_string(),
                             path,
-                            mtime: metadata.mtime.into(),
+                            mtime,
                         });
                     }
                 }
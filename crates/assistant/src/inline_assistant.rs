@@ -235,9 +235,94 @@ impl InlineAssistant {
                 text_anchor: buffer.anchor_after(buffer_range.end),
             };
             codegen_ranges.push(start..end);
+        }
 
-            if let Some(telemetry) = self.telemetry.as_ref() {
-                if let Some(model) = LanguageModelRegistry::read_global(cx).active_model() {
+        let range_to_focus = codegen_ranges.iter().position(|range| {
+            if newest_selection.reversed {
+                range.start.to_point(&snapshot) == newest_selection.start
+            } else {
+                range.end.to_point(&snapshot) == newest_selection.end
+            }
+        });
+
+        self.assist_ranges(
+            editor,
+            codegen_ranges,
+            range_to_focus,
+            workspace,
+            assistant_panel,
+            initial_prompt,
+            cx,
+        );
+    }
+
+    /// Starts an inline assist on every excerpt of the editor's buffer, all driven by the same
+    /// prompt, so that a single instruction can be applied across the results of a project
+    /// search or the excerpts of a diagnostics view. Each excerpt's transformation streams
+    /// concurrently and is accepted or rejected on its own.
+    pub fn assist_all_excerpts(
+        &mut self,
+        editor: &View<Editor>,
+        workspace: Option<WeakView<Workspace>>,
+        assistant_panel: Option<&View<AssistantPanel>>,
+        initial_prompt: Option<String>,
+        cx: &mut WindowContext,
+    ) {
+        let snapshot = editor.read(cx).buffer().read(cx).snapshot(cx);
+        let excerpt_to_focus = editor.read(cx).selections.newest_anchor().head().excerpt_id;
+
+        let mut codegen_ranges = Vec::new();
+        let mut range_to_focus = None;
+        for (excerpt_id, buffer, excerpt_range) in snapshot.excerpts() {
+            if excerpt_id == excerpt_to_focus {
+                range_to_focus = Some(codegen_ranges.len());
+            }
+
+            let start = Anchor {
+                buffer_id: Some(buffer.remote_id()),
+                excerpt_id,
+                text_anchor: excerpt_range.context.start.bias_left(buffer),
+            };
+            let end = Anchor {
+                buffer_id: Some(buffer.remote_id()),
+                excerpt_id,
+                text_anchor: excerpt_range.context.end.bias_right(buffer),
+            };
+            codegen_ranges.push(start..end);
+        }
+
+        self.assist_ranges(
+            editor,
+            codegen_ranges,
+            range_to_focus.or(Some(0)),
+            workspace,
+            assistant_panel,
+            initial_prompt,
+            cx,
+        );
+    }
+
+    /// Creates a group of linked assists, one for each of the given ranges, that share a
+    /// single prompt.
+    #[allow(clippy::too_many_arguments)]
+    fn assist_ranges(
+        &mut self,
+        editor: &View<Editor>,
+        codegen_ranges: Vec<Range<Anchor>>,
+        range_to_focus: Option<usize>,
+        workspace: Option<WeakView<Workspace>>,
+        assistant_panel: Option<&View<AssistantPanel>>,
+        initial_prompt: Option<String>,
+        cx: &mut WindowContext,
+    ) {
+        if codegen_ranges.is_empty() {
+            return;
+        }
+
+        if let Some(telemetry) = self.telemetry.as_ref() {
+            if let Some(model) = LanguageModelRegistry::read_global(cx).active_model() {
+                let snapshot = editor.read(cx).buffer().read(cx).snapshot(cx);
+                for range in &codegen_ranges {
                     telemetry.report_assistant_event(AssistantEvent {
                         conversation_id: None,
                         kind: AssistantKind::Inline,
@@ -246,7 +331,9 @@ impl InlineAssistant {
                         model_provider: model.provider_id().to_string(),
                         response_latency: None,
                         error_message: None,
-                        language_name: buffer.language().map(|language| language.name().to_proto()),
+                        language_name: snapshot
+                            .language_at(range.start)
+                            .map(|language| language.name().to_proto()),
                     });
                 }
             }
@@ -259,7 +346,7 @@ impl InlineAssistant {
 
         let mut assists = Vec::new();
         let mut assist_to_focus = None;
-        for range in codegen_ranges {
+        for (ix, range) in codegen_ranges.into_iter().enumerate() {
             let assist_id = self.next_assist_id.post_inc();
             let codegen = cx.new_model(|cx| {
                 Codegen::new(
@@ -288,15 +375,8 @@ impl InlineAssistant {
                 )
             });
 
-            if assist_to_focus.is_none() {
-                let focus_assist = if newest_selection.reversed {
-                    range.start.to_point(&snapshot) == newest_selection.start
-                } else {
-                    range.end.to_point(&snapshot) == newest_selection.end
-                };
-                if focus_assist {
-                    assist_to_focus = Some(assist_id);
-                }
+            if range_to_focus == Some(ix) {
+                assist_to_focus = Some(assist_id);
             }
 
             let [prompt_block_id, end_block_id] =
@@ -2659,7 +2739,7 @@ impl CodegenAlternative {
 
                 let chunks = cx
                     .spawn(|_, cx| async move { model.stream_completion_text(request, &cx).await });
-                async move { Ok(chunks.await?.boxed()) }.boxed_local()
+                async move { Ok(chunks.await?.stream) }.boxed_local()
             };
         self.handle_stream(telemetry_id, provider_id.to_string(), chunks, cx);
         Ok(())
@@ -2719,6 +2799,7 @@ impl CodegenAlternative {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            max_tokens: None,
         })
     }
 
@@ -3450,6 +3531,7 @@ fn merge_ranges(ranges: &mut Vec<Range<Anchor>>, buffer: &MultiBufferSnapshot) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use fs::FakeFs;
     use futures::stream::{self};
     use gpui::{Context, TestAppContext};
     use indoc::indoc;
@@ -3458,6 +3540,7 @@ mod tests {
         Point,
     };
     use language_model::LanguageModelRegistry;
+    use project::Project;
     use rand::prelude::*;
     use serde::Serialize;
     use settings::SettingsStore;
@@ -3829,6 +3912,137 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_assist_all_excerpts(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            workspace::init_settings(cx);
+            editor::init(cx);
+            AssistantSettings::register(cx);
+            LanguageModelRegistry::test(cx);
+            cx.set_global(InlineAssistant {
+                next_assist_id: InlineAssistId::default(),
+                next_assist_group_id: InlineAssistGroupId::default(),
+                assists: HashMap::default(),
+                assists_by_editor: HashMap::default(),
+                assist_groups: HashMap::default(),
+                confirmed_assists: HashMap::default(),
+                prompt_history: VecDeque::default(),
+                prompt_builder: Arc::new(PromptBuilder::new(None).unwrap()),
+                telemetry: None,
+                fs: FakeFs::new(cx.background_executor().clone()),
+            });
+        });
+
+        let buffers = ["one\ntwo", "three\nfour", "five\nsix"]
+            .map(|text| cx.new_model(|cx| Buffer::local(text, cx)));
+        let multibuffer = cx.new_model(|cx| {
+            let mut multibuffer = MultiBuffer::new(language::Capability::ReadWrite);
+            for buffer in &buffers {
+                multibuffer.push_excerpts(
+                    buffer.clone(),
+                    [ExcerptRange {
+                        context: Point::new(0, 0)..buffer.read(cx).max_point(),
+                        primary: None,
+                    }],
+                    cx,
+                );
+            }
+            multibuffer
+        });
+        let excerpt_ids = multibuffer.read_with(cx, |multibuffer, _| multibuffer.excerpt_ids());
+        let (editor, cx) =
+            cx.add_window_view(|cx| Editor::for_multibuffer(multibuffer.clone(), None, true, cx));
+
+        // Place the newest selection in the second excerpt.
+        editor.update(cx, |editor, cx| {
+            let snapshot = multibuffer.read(cx).snapshot(cx);
+            let position = snapshot
+                .anchor_in_excerpt(excerpt_ids[1], buffers[1].read(cx).anchor_before(3))
+                .unwrap();
+            editor.change_selections(None, cx, |selections| {
+                selections.select_anchor_ranges([position..position])
+            });
+        });
+
+        cx.update(|cx| {
+            InlineAssistant::update_global(cx, |inline_assistant, cx| {
+                inline_assistant.assist_all_excerpts(
+                    &editor,
+                    None,
+                    None,
+                    Some("Make it shorter".into()),
+                    cx,
+                )
+            })
+        });
+        cx.run_until_parked();
+
+        let assist_ids = cx.update(|cx| {
+            let inline_assistant = cx.global::<InlineAssistant>();
+            assert_eq!(inline_assistant.assist_groups.len(), 1);
+            let assist_ids = inline_assistant
+                .assist_groups
+                .values()
+                .next()
+                .unwrap()
+                .assist_ids
+                .clone();
+            assert_eq!(
+                assist_ids
+                    .iter()
+                    .map(|assist_id| inline_assistant.assists[assist_id].range.start.excerpt_id)
+                    .collect::<Vec<_>>(),
+                excerpt_ids
+            );
+
+            let focused_assists = assist_ids
+                .iter()
+                .filter(|assist_id| {
+                    inline_assistant.assists[*assist_id]
+                        .decorations
+                        .as_ref()
+                        .unwrap()
+                        .prompt_editor
+                        .focus_handle(cx)
+                        .is_focused(cx)
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(focused_assists, [&assist_ids[1]]);
+            assist_ids
+        });
+
+        // Starting any assist of the group starts them all, after which each one is accepted
+        // on its own.
+        cx.update(|cx| {
+            InlineAssistant::update_global(cx, |inline_assistant, cx| {
+                inline_assistant.start_assist(assist_ids[0], cx);
+                inline_assistant.finish_assist(assist_ids[0], false, cx);
+            })
+        });
+        cx.run_until_parked();
+
+        cx.update(|cx| {
+            let inline_assistant = cx.global::<InlineAssistant>();
+            assert!(!inline_assistant.assists.contains_key(&assist_ids[0]));
+            assert!(inline_assistant
+                .confirmed_assists
+                .contains_key(&assist_ids[0]));
+            for assist_id in &assist_ids[1..] {
+                let assist = &inline_assistant.assists[assist_id];
+                assert!(assist.decorations.is_some());
+                assert!(matches!(
+                    assist.codegen.read(cx).status(cx),
+                    CodegenStatus::Pending
+                ));
+            }
+        });
+    }
+
     #[gpui::test]
     async fn test_strip_invalid_spans_from_codeblock() {
         assert_chunks("Lorem ipsum dolor", "Lorem ipsum dolor").await;
@@ -1,6 +1,6 @@
 use anyhow::{anyhow, Context as _, Result};
 use collections::HashMap;
-use editor::ProposedChangesEditor;
+use editor::{ProposedChangeLocation, ProposedChangesEditor};
 use futures::{future, TryFutureExt as _};
 use gpui::{AppContext, AsyncAppContext, Model, SharedString};
 use language::{AutoindentMode, Buffer, BufferSnapshot};
@@ -121,6 +121,69 @@ impl SearchMatrix {
 }
 
 impl ResolvedPatch {
+    /// Resolves the given edits against the project's buffers, grouping edits whose context
+    /// ranges overlap.
+    pub(crate) async fn resolve(
+        edits: &[Result<AssistantEdit>],
+        project: Model<Project>,
+        cx: &mut AsyncAppContext,
+    ) -> Self {
+        let mut resolve_tasks = Vec::new();
+        for (ix, edit) in edits.iter().enumerate() {
+            if let Ok(edit) = edit.as_ref() {
+                resolve_tasks.push(
+                    edit.resolve(project.clone(), cx.clone())
+                        .map_err(move |error| (ix, error)),
+                );
+            }
+        }
+
+        let edits = future::join_all(resolve_tasks).await;
+        let mut errors = Vec::new();
+        let mut edits_by_buffer = HashMap::default();
+        for entry in edits {
+            match entry {
+                Ok((buffer, edit)) => {
+                    edits_by_buffer
+                        .entry(buffer)
+                        .or_insert_with(Vec::new)
+                        .push(edit);
+                }
+                Err((edit_ix, error)) => errors.push(AssistantPatchResolutionError {
+                    edit_ix,
+                    message: error.to_string(),
+                }),
+            }
+        }
+
+        // Expand the context ranges of each edit and group edits with overlapping context ranges.
+        let mut edit_groups_by_buffer = HashMap::default();
+        for (buffer, edits) in edits_by_buffer {
+            if let Ok(snapshot) = buffer.update(cx, |buffer, _| buffer.text_snapshot()) {
+                edit_groups_by_buffer.insert(buffer, AssistantPatch::group_edits(edits, &snapshot));
+            }
+        }
+
+        ResolvedPatch {
+            edit_groups: edit_groups_by_buffer,
+            errors,
+        }
+    }
+
+    /// Returns the locations to show in a [`ProposedChangesEditor`] for this patch.
+    pub fn locations(&self) -> Vec<ProposedChangeLocation> {
+        self.edit_groups
+            .iter()
+            .map(|(buffer, groups)| ProposedChangeLocation {
+                buffer: buffer.clone(),
+                ranges: groups
+                    .iter()
+                    .map(|group| group.context_range.clone())
+                    .collect(),
+            })
+            .collect()
+    }
+
     pub fn apply(&self, editor: &ProposedChangesEditor, cx: &mut AppContext) {
         for (buffer, groups) in &self.edit_groups {
             let branch = editor.branch_buffer_for_base(buffer).unwrap();
@@ -430,46 +493,7 @@ impl AssistantPatch {
         project: Model<Project>,
         cx: &mut AsyncAppContext,
     ) -> ResolvedPatch {
-        let mut resolve_tasks = Vec::new();
-        for (ix, edit) in self.edits.iter().enumerate() {
-            if let Ok(edit) = edit.as_ref() {
-                resolve_tasks.push(
-                    edit.resolve(project.clone(), cx.clone())
-                        .map_err(move |error| (ix, error)),
-                );
-            }
-        }
-
-        let edits = future::join_all(resolve_tasks).await;
-        let mut errors = Vec::new();
-        let mut edits_by_buffer = HashMap::default();
-        for entry in edits {
-            match entry {
-                Ok((buffer, edit)) => {
-                    edits_by_buffer
-                        .entry(buffer)
-                        .or_insert_with(Vec::new)
-                        .push(edit);
-                }
-                Err((edit_ix, error)) => errors.push(AssistantPatchResolutionError {
-                    edit_ix,
-                    message: error.to_string(),
-                }),
-            }
-        }
-
-        // Expand the context ranges of each edit and group edits with overlapping context ranges.
-        let mut edit_groups_by_buffer = HashMap::default();
-        for (buffer, edits) in edits_by_buffer {
-            if let Ok(snapshot) = buffer.update(cx, |buffer, _| buffer.text_snapshot()) {
-                edit_groups_by_buffer.insert(buffer, Self::group_edits(edits, &snapshot));
-            }
-        }
-
-        ResolvedPatch {
-            edit_groups: edit_groups_by_buffer,
-            errors,
-        }
+        ResolvedPatch::resolve(&self.edits, project, cx).await
     }
 
     fn group_edits(
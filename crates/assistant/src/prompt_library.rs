@@ -1,17 +1,22 @@
-use crate::{slash_command::SlashCommandCompletionProvider, AssistantPanel, InlineAssistant};
+use crate::{
+    assistant_settings::AssistantSettings, slash_command::SlashCommandCompletionProvider,
+    AssistantPanel, InlineAssistant,
+};
 use anyhow::{anyhow, Result};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Local, Utc};
 use collections::{HashMap, HashSet};
 use editor::{actions::Tab, CurrentLineHighlight, Editor, EditorElement, EditorEvent, EditorStyle};
+use fs::Fs;
 use futures::{
     future::{self, BoxFuture, Shared},
-    FutureExt,
+    FutureExt, StreamExt,
 };
 use fuzzy::StringMatchCandidate;
 use gpui::{
     actions, point, size, transparent_black, Action, AppContext, BackgroundExecutor, Bounds,
-    EventEmitter, Global, HighlightStyle, PromptLevel, ReadGlobal, Subscription, Task, TextStyle,
-    TitlebarOptions, UpdateGlobal, View, WindowBounds, WindowHandle, WindowOptions,
+    EventEmitter, Global, HighlightStyle, Model, ModelContext, PathPromptOptions, PromptLevel,
+    ReadGlobal, Subscription, Task, TextStyle, TitlebarOptions, UpdateGlobal, View, WeakModel,
+    WindowBounds, WindowHandle, WindowOptions,
 };
 use heed::{
     types::{SerdeBincode, SerdeJson, Str},
@@ -21,24 +26,25 @@ use language::{language_settings::SoftWrap, Buffer, LanguageRegistry};
 use language_model::{
     LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage, Role,
 };
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use picker::{Picker, PickerDelegate};
+use project::Project;
 use release_channel::ReleaseChannel;
 use rope::Rope;
 use serde::{Deserialize, Serialize};
-use settings::Settings;
+use settings::{Settings, SettingsStore};
 use std::{
     cmp::Reverse,
     future::Future,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{atomic::AtomicBool, Arc},
     time::Duration,
 };
 use text::LineEnding;
 use theme::ThemeSettings;
 use ui::{
-    div, prelude::*, IconButtonShape, KeyBinding, ListItem, ListItemSpacing, ParentElement, Render,
-    SharedString, Styled, Tooltip, ViewContext, VisualContext,
+    div, prelude::*, ContextMenu, IconButtonShape, KeyBinding, ListItem, ListItemSpacing,
+    ParentElement, PopoverMenu, Render, SharedString, Styled, Tooltip, ViewContext, VisualContext,
 };
 use util::{ResultExt, TryFutureExt};
 use uuid::Uuid;
@@ -51,19 +57,145 @@ actions!(
         NewPrompt,
         DeletePrompt,
         DuplicatePrompt,
-        ToggleDefaultPrompt
+        ToggleDefaultPrompt,
+        ImportPrompts,
+        ExportPrompts
     ]
 );
 
 /// Init starts loading the PromptStore in the background and assigns
 /// a shared future to a global.
-pub fn init(cx: &mut AppContext) {
+pub fn init(fs: Arc<dyn Fs>, cx: &mut AppContext) {
     let db_path = paths::prompts_dir().join("prompts-library-db.0.mdb");
-    let prompt_store_future = PromptStore::new(db_path, cx.background_executor().clone())
-        .then(|result| future::ready(result.map(Arc::new).map_err(Arc::new)))
-        .boxed()
-        .shared();
-    cx.set_global(GlobalPromptStore(prompt_store_future))
+    let prompt_store_future =
+        PromptStore::new(db_path, fs.clone(), cx.background_executor().clone())
+            .then(|result| future::ready(result.map(Arc::new).map_err(Arc::new)))
+            .boxed()
+            .shared();
+    cx.set_global(GlobalPromptStore(prompt_store_future));
+
+    cx.observe_new_views(move |workspace: &mut Workspace, cx| {
+        let project = workspace.project().clone();
+        if !project.read(cx).is_local() {
+            return;
+        }
+
+        let project_prompts = cx.new_model(|cx| ProjectPrompts::new(&project, fs.clone(), cx));
+        AppContext::observe_release(cx, &project, move |_, cx| {
+            project_prompts.update(cx, |project_prompts, cx| project_prompts.unload(cx));
+        })
+        .detach();
+    })
+    .detach();
+}
+
+const PROMPT_DIRECTORY_WATCH_LATENCY: Duration = Duration::from_millis(100);
+
+/// Keeps the prompts in a project's prompt directories, as configured by the
+/// `project_prompt_directories` setting, loaded into the prompt store while the
+/// project is open.
+struct ProjectPrompts {
+    project: WeakModel<Project>,
+    fs: Arc<dyn Fs>,
+    directories: HashMap<PathBuf, Task<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ProjectPrompts {
+    fn new(project: &Model<Project>, fs: Arc<dyn Fs>, cx: &mut ModelContext<Self>) -> Self {
+        let mut this = Self {
+            project: project.downgrade(),
+            fs,
+            directories: HashMap::default(),
+            _subscriptions: vec![
+                cx.observe_global::<SettingsStore>(Self::update_directories),
+                cx.subscribe(project, |this, _, event, cx| {
+                    if let project::Event::WorktreeAdded | project::Event::WorktreeRemoved(_) =
+                        event
+                    {
+                        this.update_directories(cx);
+                    }
+                }),
+            ],
+        };
+        this.update_directories(cx);
+        this
+    }
+
+    fn update_directories(&mut self, cx: &mut ModelContext<Self>) {
+        let directories = &AssistantSettings::get_global(cx).project_prompt_directories;
+        let prompt_dirs = self
+            .project
+            .upgrade()
+            .map(|project| {
+                project
+                    .read(cx)
+                    .visible_worktrees(cx)
+                    .flat_map(|worktree| {
+                        let root = worktree.read(cx).abs_path();
+                        directories
+                            .iter()
+                            .map(move |directory| root.join(directory))
+                    })
+                    .collect::<HashSet<_>>()
+            })
+            .unwrap_or_default();
+
+        let removed_dirs = self
+            .directories
+            .keys()
+            .filter(|dir| !prompt_dirs.contains(*dir))
+            .cloned()
+            .collect::<Vec<_>>();
+        self.remove_directories(removed_dirs, cx);
+
+        for prompt_dir in prompt_dirs {
+            if !self.directories.contains_key(&prompt_dir) {
+                let task = self.watch_directory(prompt_dir.clone(), cx);
+                self.directories.insert(prompt_dir, task);
+            }
+        }
+    }
+
+    /// Loads the prompts in the given directory and reloads them whenever it changes.
+    fn watch_directory(&self, dir: PathBuf, cx: &mut ModelContext<Self>) -> Task<()> {
+        let fs = self.fs.clone();
+        let store = PromptStore::global(cx);
+        cx.spawn(|_, _| async move {
+            let Some(store) = store.await.log_err() else {
+                return;
+            };
+            let (mut events, _watcher) = fs.watch(&dir, PROMPT_DIRECTORY_WATCH_LATENCY).await;
+            store.add_directory(dir.clone()).await.log_err();
+            while events.next().await.is_some() {
+                store.load_directory(dir.clone()).await.log_err();
+            }
+        })
+    }
+
+    fn unload(&mut self, cx: &mut ModelContext<Self>) {
+        let dirs = self.directories.keys().cloned().collect();
+        self.remove_directories(dirs, cx);
+    }
+
+    fn remove_directories(&mut self, dirs: Vec<PathBuf>, cx: &mut ModelContext<Self>) {
+        if dirs.is_empty() {
+            return;
+        }
+
+        for dir in &dirs {
+            self.directories.remove(dir);
+        }
+        let store = PromptStore::global(cx);
+        cx.spawn(|_, _| async move {
+            let store = store.await?;
+            for dir in dirs {
+                store.remove_directory(&dir);
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
 }
 
 const BUILT_IN_TOOLTIP_TEXT: &'static str = concat!(
@@ -71,6 +203,11 @@ const BUILT_IN_TOOLTIP_TEXT: &'static str = concat!(
     "It's read-only, but you can remove it from your default prompt."
 );
 
+const PROJECT_PROMPT_TOOLTIP_TEXT: &'static str = concat!(
+    "This prompt is loaded from one of the project's prompt directories.\n",
+    "Remove its file to delete it. It can't be added to your default prompt."
+);
+
 /// This function opens a new prompt library window if one doesn't exist already.
 /// If one exists, it brings it to the foreground.
 ///
@@ -132,6 +269,7 @@ struct PromptEditor {
     pending_token_count: Task<Option<()>>,
     next_title_and_body_to_save: Option<(String, Rope)>,
     pending_save: Option<Task<Option<()>>>,
+    history: Vec<PromptVersion>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -228,6 +366,7 @@ impl PickerDelegate for PromptPickerDelegate {
         let prompt = self.matches.get(ix)?;
         let default = prompt.default;
         let prompt_id = prompt.id;
+        let is_project_prompt = prompt.path.is_some();
         let element = ListItem::new(ix)
             .inset(true)
             .spacing(ListItemSpacing::Sparse)
@@ -261,6 +400,19 @@ impl PickerDelegate for PromptPickerDelegate {
                                 )
                             })
                             .into_any()
+                    } else if is_project_prompt {
+                        div()
+                            .id("project-prompt")
+                            .child(Icon::new(IconName::Folder).color(Color::Muted))
+                            .tooltip(move |cx| {
+                                Tooltip::with_meta(
+                                    "Project prompt",
+                                    None,
+                                    PROJECT_PROMPT_TOOLTIP_TEXT,
+                                    cx,
+                                )
+                            })
+                            .into_any()
                     } else {
                         IconButton::new("delete-prompt", IconName::Trash)
                             .icon_color(Color::Muted)
@@ -271,26 +423,28 @@ impl PickerDelegate for PromptPickerDelegate {
                             }))
                             .into_any_element()
                     })
-                    .child(
-                        IconButton::new("toggle-default-prompt", IconName::Sparkle)
-                            .selected(default)
-                            .selected_icon(IconName::SparkleFilled)
-                            .icon_color(if default { Color::Accent } else { Color::Muted })
-                            .shape(IconButtonShape::Square)
-                            .tooltip(move |cx| {
-                                Tooltip::text(
-                                    if default {
-                                        "Remove from Default Prompt"
-                                    } else {
-                                        "Add to Default Prompt"
-                                    },
-                                    cx,
-                                )
-                            })
-                            .on_click(cx.listener(move |_, _, cx| {
-                                cx.emit(PromptPickerEvent::ToggledDefault { prompt_id })
-                            })),
-                    ),
+                    .when(!is_project_prompt, |this| {
+                        this.child(
+                            IconButton::new("toggle-default-prompt", IconName::Sparkle)
+                                .selected(default)
+                                .selected_icon(IconName::SparkleFilled)
+                                .icon_color(if default { Color::Accent } else { Color::Muted })
+                                .shape(IconButtonShape::Square)
+                                .tooltip(move |cx| {
+                                    Tooltip::text(
+                                        if default {
+                                            "Remove from Default Prompt"
+                                        } else {
+                                            "Add to Default Prompt"
+                                        },
+                                        cx,
+                                    )
+                                })
+                                .on_click(cx.listener(move |_, _, cx| {
+                                    cx.emit(PromptPickerEvent::ToggledDefault { prompt_id })
+                                })),
+                        )
+                    }),
             );
         Some(element)
     }
@@ -428,6 +582,7 @@ impl PromptLibrary {
                                 .log_err();
                             this.update(&mut cx, |this, cx| {
                                 this.picker.update(cx, |picker, cx| picker.refresh(cx));
+                                this.load_history(prompt_id, cx);
                                 cx.notify();
                             })?;
 
@@ -467,7 +622,11 @@ impl PromptLibrary {
     }
 
     pub fn toggle_default_for_prompt(&mut self, prompt_id: PromptId, cx: &mut ViewContext<Self>) {
-        if let Some(prompt_metadata) = self.store.metadata(prompt_id) {
+        if let Some(prompt_metadata) = self
+            .store
+            .metadata(prompt_id)
+            .filter(|metadata| metadata.path.is_none())
+        {
             self.store
                 .save_metadata(prompt_id, prompt_metadata.title, !prompt_metadata.default)
                 .detach_and_log_err(cx);
@@ -546,11 +705,13 @@ impl PromptLibrary {
                                 pending_save: None,
                                 token_count: None,
                                 pending_token_count: Task::ready(None),
+                                history: Vec::new(),
                                 _subscriptions,
                             },
                         );
                         this.set_active_prompt(Some(prompt_id), cx);
                         this.count_tokens(prompt_id, cx);
+                        this.load_history(prompt_id, cx);
                     }
                     Err(error) => {
                         // TODO: we should show the error in the UI.
@@ -562,6 +723,78 @@ impl PromptLibrary {
         }
     }
 
+    fn load_history(&mut self, prompt_id: PromptId, cx: &mut ViewContext<Self>) {
+        if prompt_id.is_built_in() {
+            return;
+        }
+
+        let history = self.store.history(prompt_id);
+        cx.spawn(|this, mut cx| async move {
+            let history = history.await?;
+            this.update(&mut cx, |this, cx| {
+                if let Some(prompt_editor) = this.prompt_editors.get_mut(&prompt_id) {
+                    prompt_editor.history = history;
+                    cx.notify();
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Replaces the prompt's title and body with those of the given version, which saves
+    /// them as the latest version.
+    pub fn restore_prompt_version(
+        &mut self,
+        prompt_id: PromptId,
+        version: PromptVersion,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(prompt_editor) = self.prompt_editors.get(&prompt_id) {
+            prompt_editor.title_editor.update(cx, |editor, cx| {
+                editor.set_text(version.title.unwrap_or_default(), cx)
+            });
+            prompt_editor
+                .body_editor
+                .update(cx, |editor, cx| editor.set_text(version.body, cx));
+        }
+    }
+
+    pub fn import_prompts(&mut self, cx: &mut ViewContext<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: true,
+            multiple: true,
+        });
+        let store = self.store.clone();
+        cx.spawn(|this, mut cx| async move {
+            let Some(paths) = paths.await?? else {
+                return Ok(());
+            };
+            store.import(paths).await?;
+            this.update(&mut cx, |this, cx| {
+                this.picker.update(cx, |picker, cx| picker.refresh(cx));
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    pub fn export_prompts(&mut self, cx: &mut ViewContext<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+        });
+        let store = self.store.clone();
+        cx.spawn(|_, _| async move {
+            let Some(dir) = paths.await??.and_then(|paths| paths.into_iter().next()) else {
+                return Ok(());
+            };
+            store.export_to_directory(dir).await
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn set_active_prompt(&mut self, prompt_id: Option<PromptId>, cx: &mut ViewContext<Self>) {
         self.active_prompt_id = prompt_id;
         self.picker.update(cx, |picker, cx| {
@@ -591,7 +824,11 @@ impl PromptLibrary {
     }
 
     pub fn delete_prompt(&mut self, prompt_id: PromptId, cx: &mut ViewContext<Self>) {
-        if let Some(metadata) = self.store.metadata(prompt_id) {
+        if let Some(metadata) = self
+            .store
+            .metadata(prompt_id)
+            .filter(|metadata| metadata.path.is_none())
+        {
             let confirmation = cx.prompt(
                 PromptLevel::Warning,
                 &format!(
@@ -797,6 +1034,7 @@ impl PromptLibrary {
                                     tools: Vec::new(),
                                     stop: Vec::new(),
                                     temperature: None,
+                                    max_tokens: None,
                                 },
                                 cx,
                             )
@@ -830,6 +1068,29 @@ impl PromptLibrary {
                     .w_full()
                     .flex_none()
                     .justify_end()
+                    .gap_1()
+                    .child(
+                        IconButton::new("import-prompts", IconName::Download)
+                            .style(ButtonStyle::Transparent)
+                            .shape(IconButtonShape::Square)
+                            .tooltip(move |cx| {
+                                Tooltip::for_action("Import Prompts", &ImportPrompts, cx)
+                            })
+                            .on_click(|_, cx| {
+                                cx.dispatch_action(Box::new(ImportPrompts));
+                            }),
+                    )
+                    .child(
+                        IconButton::new("export-prompts", IconName::Save)
+                            .style(ButtonStyle::Transparent)
+                            .shape(IconButtonShape::Square)
+                            .tooltip(move |cx| {
+                                Tooltip::for_action("Export Prompts", &ExportPrompts, cx)
+                            })
+                            .on_click(|_, cx| {
+                                cx.dispatch_action(Box::new(ExportPrompts));
+                            }),
+                    )
                     .child(
                         IconButton::new("new-prompt", IconName::Plus)
                             .style(ButtonStyle::Transparent)
@@ -843,6 +1104,52 @@ impl PromptLibrary {
             .child(div().flex_grow().child(self.picker.clone()))
     }
 
+    fn render_history_menu(
+        prompt_id: PromptId,
+        history: Vec<PromptVersion>,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let prompt_library = cx.view().downgrade();
+        PopoverMenu::new("prompt-history-menu")
+            .trigger(
+                IconButton::new("prompt-history", IconName::HistoryRerun)
+                    .style(ButtonStyle::Transparent)
+                    .shape(IconButtonShape::Square)
+                    .size(ButtonSize::Large)
+                    .tooltip(|cx| Tooltip::text("Restore a Previous Version", cx)),
+            )
+            .menu(move |cx| {
+                let history = history.clone();
+                let prompt_library = prompt_library.clone();
+                Some(ContextMenu::build(cx, move |mut menu, _| {
+                    menu = menu.header("Versions");
+                    for version in history.into_iter().rev() {
+                        let label = format!(
+                            "{} · {}",
+                            version
+                                .saved_at
+                                .with_timezone(&Local)
+                                .format("%b %-d, %H:%M"),
+                            version.title.clone().unwrap_or("Untitled".into())
+                        );
+                        let prompt_library = prompt_library.clone();
+                        menu = menu.entry(label, None, move |cx| {
+                            prompt_library
+                                .update(cx, |prompt_library, cx| {
+                                    prompt_library.restore_prompt_version(
+                                        prompt_id,
+                                        version.clone(),
+                                        cx,
+                                    )
+                                })
+                                .ok();
+                        });
+                    }
+                    menu
+                }))
+            })
+    }
+
     fn render_active_prompt(&mut self, cx: &mut ViewContext<PromptLibrary>) -> gpui::Stateful<Div> {
         div()
             .w_2_3()
@@ -855,6 +1162,7 @@ impl PromptLibrary {
             .min_w_64()
             .children(self.active_prompt_id.and_then(|prompt_id| {
                 let prompt_metadata = self.store.metadata(prompt_id)?;
+                let is_project_prompt = prompt_metadata.path.is_some();
                 let prompt_editor = &self.prompt_editors[&prompt_id];
                 let focus_handle = prompt_editor.body_editor.focus_handle(cx);
                 let model = LanguageModelRegistry::read_global(cx).active_model();
@@ -985,6 +1293,13 @@ impl PromptLibrary {
                                                             )
                                                     },
                                                 ))
+                                                .when(!prompt_editor.history.is_empty(), |this| {
+                                                    this.child(Self::render_history_menu(
+                                                        prompt_id,
+                                                        prompt_editor.history.clone(),
+                                                        cx,
+                                                    ))
+                                                })
                                                 .child(if prompt_id.is_built_in() {
                                                     div()
                                                         .id("built-in-prompt")
@@ -1001,6 +1316,22 @@ impl PromptLibrary {
                                                             )
                                                         })
                                                         .into_any()
+                                                } else if is_project_prompt {
+                                                    div()
+                                                        .id("project-prompt")
+                                                        .child(
+                                                            Icon::new(IconName::Folder)
+                                                                .color(Color::Muted),
+                                                        )
+                                                        .tooltip(move |cx| {
+                                                            Tooltip::with_meta(
+                                                                "Project prompt",
+                                                                None,
+                                                                PROJECT_PROMPT_TOOLTIP_TEXT,
+                                                                cx,
+                                                            )
+                                                        })
+                                                        .into_any()
                                                 } else {
                                                     IconButton::new(
                                                         "delete-prompt",
@@ -1044,37 +1375,39 @@ impl PromptLibrary {
                                                         ));
                                                     }),
                                                 )
-                                                .child(
-                                                    IconButton::new(
-                                                        "toggle-default-prompt",
-                                                        IconName::Sparkle,
-                                                    )
-                                                    .style(ButtonStyle::Transparent)
-                                                    .selected(prompt_metadata.default)
-                                                    .selected_icon(IconName::SparkleFilled)
-                                                    .icon_color(if prompt_metadata.default {
-                                                        Color::Accent
-                                                    } else {
-                                                        Color::Muted
-                                                    })
-                                                    .shape(IconButtonShape::Square)
-                                                    .size(ButtonSize::Large)
-                                                    .tooltip(move |cx| {
-                                                        Tooltip::text(
-                                                            if prompt_metadata.default {
-                                                                "Remove from Default Prompt"
-                                                            } else {
-                                                                "Add to Default Prompt"
-                                                            },
-                                                            cx,
+                                                .when(!is_project_prompt, |this| {
+                                                    this.child(
+                                                        IconButton::new(
+                                                            "toggle-default-prompt",
+                                                            IconName::Sparkle,
                                                         )
-                                                    })
-                                                    .on_click(|_, cx| {
-                                                        cx.dispatch_action(Box::new(
-                                                            ToggleDefaultPrompt,
-                                                        ));
-                                                    }),
-                                                ),
+                                                        .style(ButtonStyle::Transparent)
+                                                        .selected(prompt_metadata.default)
+                                                        .selected_icon(IconName::SparkleFilled)
+                                                        .icon_color(if prompt_metadata.default {
+                                                            Color::Accent
+                                                        } else {
+                                                            Color::Muted
+                                                        })
+                                                        .shape(IconButtonShape::Square)
+                                                        .size(ButtonSize::Large)
+                                                        .tooltip(move |cx| {
+                                                            Tooltip::text(
+                                                                if prompt_metadata.default {
+                                                                    "Remove from Default Prompt"
+                                                                } else {
+                                                                    "Add to Default Prompt"
+                                                                },
+                                                                cx,
+                                                            )
+                                                        })
+                                                        .on_click(|_, cx| {
+                                                            cx.dispatch_action(Box::new(
+                                                                ToggleDefaultPrompt,
+                                                            ));
+                                                        }),
+                                                    )
+                                                }),
                                         ),
                                 ),
                         )
@@ -1106,6 +1439,8 @@ impl Render for PromptLibrary {
             .on_action(cx.listener(|this, &ToggleDefaultPrompt, cx| {
                 this.toggle_default_for_active_prompt(cx)
             }))
+            .on_action(cx.listener(|this, &ImportPrompts, cx| this.import_prompts(cx)))
+            .on_action(cx.listener(|this, &ExportPrompts, cx| this.export_prompts(cx)))
             .size_full()
             .overflow_hidden()
             .font(ui_font)
@@ -1169,6 +1504,207 @@ pub struct PromptMetadata {
     pub title: Option<SharedString>,
     pub default: bool,
     pub saved_at: DateTime<Utc>,
+    /// The markdown file this prompt was loaded from, for prompts that live in one of the
+    /// project's prompt directories rather than in the library's database.
+    #[serde(skip)]
+    pub path: Option<Arc<Path>>,
+}
+
+/// A saved state of a prompt, recorded as it is edited so that it can be restored later.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PromptVersion {
+    pub title: Option<SharedString>,
+    pub body: String,
+    /// When the first edit of this version was saved.
+    pub started_at: DateTime<Utc>,
+    /// When the last edit of this version was saved.
+    pub saved_at: DateTime<Utc>,
+}
+
+/// Edits saved within this many minutes of the start of the latest version are merged into
+/// it, rather than creating a new version.
+const PROMPT_VERSION_INTERVAL_MINUTES: i64 = 10;
+const MAX_PROMPT_VERSIONS: usize = 50;
+
+/// Appends the given state of a prompt to its history.
+fn record_prompt_version(history: &mut Vec<PromptVersion>, mut version: PromptVersion) {
+    if let Some(latest) = history.last_mut() {
+        if latest.title == version.title && latest.body == version.body {
+            return;
+        }
+
+        let interval = chrono::Duration::minutes(PROMPT_VERSION_INTERVAL_MINUTES);
+        if version.saved_at - latest.started_at < interval {
+            version.started_at = latest.started_at;
+            *latest = version;
+            return;
+        }
+    }
+
+    history.push(version);
+    if history.len() > MAX_PROMPT_VERSIONS {
+        history.remove(0);
+    }
+}
+
+/// A prompt stored as a markdown file, with its metadata in front matter, so that prompts
+/// can be shared and reviewed alongside the code they're used with:
+///
+/// ```markdown
+/// ---
+/// id: 2b4e3c2e-6f0c-4b8e-9d4a-5f1f2f7c8d9e
+/// title: "Rust Expert"
+/// default: false
+/// ---
+///
+/// You are an expert Rust programmer.
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PromptFile {
+    pub id: Option<Uuid>,
+    pub title: Option<SharedString>,
+    pub default: bool,
+    pub body: String,
+}
+
+impl PromptFile {
+    const FRONT_MATTER_DELIMITER: &'static str = "---";
+
+    /// Parses a prompt file. Files without front matter are treated as a prompt body.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut text = text.to_string();
+        LineEnding::normalize(&mut text);
+
+        let mut prompt = PromptFile::default();
+        let Some(rest) = text
+            .strip_prefix(Self::FRONT_MATTER_DELIMITER)
+            .and_then(|rest| rest.strip_prefix('\n'))
+        else {
+            prompt.body = text;
+            return Ok(prompt);
+        };
+
+        let mut lines = rest.split_inclusive('\n');
+        let mut front_matter_len = Self::FRONT_MATTER_DELIMITER.len() + 1;
+        let mut closed = false;
+        for line in lines.by_ref() {
+            front_matter_len += line.len();
+            let line = line.trim_end();
+            if line == Self::FRONT_MATTER_DELIMITER {
+                closed = true;
+                break;
+            }
+
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "id" => prompt.id = Some(Uuid::parse_str(value)?),
+                "title" => {
+                    let title = if value.starts_with('"') {
+                        serde_json::from_str::<String>(value)?
+                    } else {
+                        value.to_string()
+                    };
+                    prompt.title = (!title.is_empty()).then(|| title.into());
+                }
+                "default" => prompt.default = value.parse()?,
+                _ => {}
+            }
+        }
+        if !closed {
+            return Err(anyhow!("unterminated front matter"));
+        }
+
+        let body = &text[front_matter_len..];
+        prompt.body = body.strip_prefix('\n').unwrap_or(body).to_string();
+        Ok(prompt)
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::new();
+        markdown.push_str(Self::FRONT_MATTER_DELIMITER);
+        markdown.push('\n');
+        if let Some(id) = self.id {
+            markdown.push_str(&format!("id: {id}\n"));
+        }
+        if let Some(title) = self.title.as_ref() {
+            let title = serde_json::to_string(title.as_ref()).unwrap_or_default();
+            markdown.push_str(&format!("title: {title}\n"));
+        }
+        markdown.push_str(&format!("default: {}\n", self.default));
+        markdown.push_str(Self::FRONT_MATTER_DELIMITER);
+        markdown.push_str("\n\n");
+        markdown.push_str(&self.body);
+        markdown
+    }
+}
+
+/// Reads and parses the markdown files in the given directory, skipping files that fail to
+/// parse. A missing directory contains no prompts.
+async fn read_prompt_files(
+    fs: &dyn Fs,
+    dir: &Path,
+) -> Result<Vec<(PathBuf, PromptFile, DateTime<Utc>)>> {
+    if !fs.is_dir(dir).await {
+        return Ok(Vec::new());
+    }
+
+    let mut prompts = Vec::new();
+    let mut paths = fs.read_dir(dir).await?;
+    while let Some(path) = paths.next().await {
+        let path = path?;
+        if path.extension().map_or(true, |extension| extension != "md") {
+            continue;
+        }
+        if let Some(prompt) = read_prompt_file(fs, &path).await.log_err() {
+            prompts.push(prompt);
+        }
+    }
+    Ok(prompts)
+}
+
+async fn read_prompt_file(
+    fs: &dyn Fs,
+    path: &Path,
+) -> Result<(PathBuf, PromptFile, DateTime<Utc>)> {
+    let text = fs.load(path).await?;
+    let mut prompt = PromptFile::parse(&text)
+        .map_err(|error| anyhow!("failed to parse prompt {path:?}: {error}"))?;
+    if prompt.title.is_none() {
+        prompt.title = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned().into());
+    }
+    let modified = fs
+        .metadata(path)
+        .await?
+        .ok_or_else(|| anyhow!("prompt file not found: {path:?}"))?
+        .mtime
+        .into();
+    Ok((path.to_path_buf(), prompt, modified))
+}
+
+/// Returns a file name for the given prompt title that is safe to use on any platform.
+fn prompt_file_name(title: Option<&str>) -> String {
+    let file_name = title
+        .unwrap_or_default()
+        .chars()
+        .map(|char| {
+            if char.is_alphanumeric() || matches!(char, ' ' | '-' | '_') {
+                char
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+    let file_name = file_name.trim();
+    if file_name.is_empty() {
+        "Untitled".to_string()
+    } else {
+        file_name.to_string()
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -1192,10 +1728,14 @@ impl PromptId {
 
 pub struct PromptStore {
     executor: BackgroundExecutor,
+    fs: Arc<dyn Fs>,
     env: heed::Env,
     metadata_cache: RwLock<MetadataCache>,
     metadata: Database<SerdeJson<PromptId>, SerdeJson<PromptMetadata>>,
     bodies: Database<SerdeJson<PromptId>, Str>,
+    history: Database<SerdeJson<PromptId>, SerdeJson<Vec<PromptVersion>>>,
+    /// The number of open projects each loaded prompt directory belongs to.
+    prompt_directories: Mutex<HashMap<PathBuf, usize>>,
 }
 
 #[derive(Default)]
@@ -1249,22 +1789,28 @@ impl PromptStore {
         async move { store.await.map_err(|err| anyhow!(err)) }
     }
 
-    pub fn new(db_path: PathBuf, executor: BackgroundExecutor) -> Task<Result<Self>> {
+    pub fn new(
+        db_path: PathBuf,
+        fs: Arc<dyn Fs>,
+        executor: BackgroundExecutor,
+    ) -> Task<Result<Self>> {
         executor.spawn({
             let executor = executor.clone();
             async move {
+                // The database is opened by heed, which always works with the real file system.
                 std::fs::create_dir_all(&db_path)?;
 
                 let db_env = unsafe {
                     heed::EnvOpenOptions::new()
                         .map_size(1024 * 1024 * 1024) // 1GB
-                        .max_dbs(4) // Metadata and bodies (possibly v1 of both as well)
+                        .max_dbs(5) // Metadata, bodies (possibly v1 of both as well) and history
                         .open(db_path)?
                 };
 
                 let mut txn = db_env.write_txn()?;
                 let metadata = db_env.create_database(&mut txn, Some("metadata.v2"))?;
                 let bodies = db_env.create_database(&mut txn, Some("bodies.v2"))?;
+                let history = db_env.create_database(&mut txn, Some("history.v1"))?;
 
                 // Remove edit workflow prompt, as we decided to opt into it using
                 // a slash command instead.
@@ -1281,10 +1827,13 @@ impl PromptStore {
 
                 Ok(PromptStore {
                     executor,
+                    fs,
                     env: db_env,
                     metadata_cache: RwLock::new(metadata_cache),
                     metadata,
                     bodies,
+                    history,
+                    prompt_directories: Mutex::default(),
                 })
             }
         })
@@ -1353,6 +1902,7 @@ impl PromptStore {
                         title: metadata_v1.title.clone(),
                         default: metadata_v1.default,
                         saved_at: metadata_v1.saved_at,
+                        path: None,
                     },
                 )?;
                 bodies_db.put(&mut txn, &prompt_id_v2, &body_v1)?;
@@ -1365,19 +1915,178 @@ impl PromptStore {
     }
 
     pub fn load(&self, id: PromptId) -> Task<Result<String>> {
+        let path = self.metadata(id).and_then(|metadata| metadata.path);
+        let fs = self.fs.clone();
         let env = self.env.clone();
         let bodies = self.bodies;
         self.executor.spawn(async move {
-            let txn = env.read_txn()?;
-            let mut prompt = bodies
-                .get(&txn, &id)?
-                .ok_or_else(|| anyhow!("prompt not found"))?
-                .into();
+            let mut prompt = if let Some(path) = path {
+                PromptFile::parse(&fs.load(&path).await?)?.body
+            } else {
+                let txn = env.read_txn()?;
+                bodies
+                    .get(&txn, &id)?
+                    .ok_or_else(|| anyhow!("prompt not found"))?
+                    .into()
+            };
             LineEnding::normalize(&mut prompt);
             Ok(prompt)
         })
     }
 
+    /// Returns the saved versions of the prompt, oldest first.
+    pub fn history(&self, id: PromptId) -> Task<Result<Vec<PromptVersion>>> {
+        let env = self.env.clone();
+        let history = self.history;
+        self.executor.spawn(async move {
+            let txn = env.read_txn()?;
+            Ok(history.get(&txn, &id)?.unwrap_or_default())
+        })
+    }
+
+    /// Loads the prompts stored as markdown files in the given project prompt directory,
+    /// keeping them loaded until every project that added the directory removes it.
+    pub async fn add_directory(&self, dir: PathBuf) -> Result<()> {
+        *self
+            .prompt_directories
+            .lock()
+            .entry(dir.clone())
+            .or_default() += 1;
+        self.load_directory(dir).await
+    }
+
+    /// Unloads the prompts in the given project prompt directory once no open project uses
+    /// it anymore.
+    pub fn remove_directory(&self, dir: &Path) {
+        let mut prompt_directories = self.prompt_directories.lock();
+        let Some(count) = prompt_directories.get_mut(dir) else {
+            return;
+        };
+        *count -= 1;
+        if *count == 0 {
+            prompt_directories.remove(dir);
+            drop(prompt_directories);
+            Self::remove_directory_prompts(&mut self.metadata_cache.write(), dir);
+        }
+    }
+
+    /// Reloads the prompts stored as markdown files in the given project prompt directory,
+    /// replacing the ones previously loaded from it. Files whose id is already used by a
+    /// prompt in the library are skipped.
+    pub async fn load_directory(&self, dir: PathBuf) -> Result<()> {
+        let prompts = read_prompt_files(self.fs.as_ref(), &dir).await?;
+
+        let mut cache = self.metadata_cache.write();
+        Self::remove_directory_prompts(&mut cache, &dir);
+        if !self.prompt_directories.lock().contains_key(&dir) {
+            return Ok(());
+        }
+
+        for (path, prompt, modified) in prompts {
+            let uuid = prompt.id.unwrap_or_else(|| {
+                Uuid::new_v5(&Uuid::NAMESPACE_URL, path.to_string_lossy().as_bytes())
+            });
+            let id = PromptId::User { uuid };
+            if cache.metadata_by_id.contains_key(&id) {
+                log::warn!("skipping prompt {path:?}, its id is already used by another prompt");
+                continue;
+            }
+
+            // Project prompts are never part of the default prompt, so that opening a
+            // project can't change what's sent with every conversation.
+            cache.insert(PromptMetadata {
+                id,
+                title: prompt.title,
+                default: false,
+                saved_at: modified,
+                path: Some(path.into()),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn remove_directory_prompts(cache: &mut MetadataCache, dir: &Path) {
+        let stale_ids = cache
+            .metadata
+            .iter()
+            .filter(|metadata| {
+                metadata
+                    .path
+                    .as_ref()
+                    .map_or(false, |path| path.parent() == Some(dir))
+            })
+            .map(|metadata| metadata.id)
+            .collect::<Vec<_>>();
+        for id in stale_ids {
+            cache.remove(id);
+        }
+    }
+
+    /// Writes every prompt in the library to the given directory as a markdown file.
+    pub async fn export_to_directory(&self, dir: PathBuf) -> Result<()> {
+        let prompts = self.metadata_cache.read().metadata.clone();
+        let mut files = Vec::new();
+        let mut file_names = HashSet::default();
+        for metadata in prompts {
+            let PromptId::User { uuid } = metadata.id else {
+                continue;
+            };
+
+            let body = self.load(metadata.id).await?;
+            let base_name = prompt_file_name(metadata.title.as_deref());
+            let mut file_name = format!("{base_name}.md");
+            let mut suffix = 1;
+            while !file_names.insert(file_name.clone()) {
+                file_name = format!("{base_name} {suffix}.md");
+                suffix += 1;
+            }
+
+            let prompt = PromptFile {
+                id: Some(uuid),
+                title: metadata.title,
+                default: metadata.default,
+                body,
+            };
+            files.push((dir.join(file_name), prompt.to_markdown()));
+        }
+
+        self.fs.create_dir(&dir).await?;
+        for (path, markdown) in files {
+            self.fs.atomic_write(path, markdown).await?;
+        }
+        Ok(())
+    }
+
+    /// Adds the prompts in the given markdown files, or in the markdown files contained in the
+    /// given directories, to the library. Prompts that were exported from the library replace
+    /// the prompt they were exported from.
+    pub async fn import(&self, paths: Vec<PathBuf>) -> Result<usize> {
+        let mut prompts = Vec::new();
+        for path in paths {
+            if self.fs.is_dir(&path).await {
+                prompts.extend(read_prompt_files(self.fs.as_ref(), &path).await?);
+            } else {
+                prompts.push(read_prompt_file(self.fs.as_ref(), &path).await?);
+            }
+        }
+
+        let prompt_count = prompts.len();
+        for (_, prompt, _) in prompts {
+            let id = prompt
+                .id
+                .map(|uuid| PromptId::User { uuid })
+                .filter(|id| {
+                    self.metadata(*id)
+                        .map_or(true, |metadata| metadata.path.is_none())
+                })
+                .unwrap_or_else(PromptId::new);
+            self.save(id, prompt.title, prompt.default, prompt.body.into())
+                .await?;
+        }
+        Ok(prompt_count)
+    }
+
     pub fn default_prompt_metadata(&self) -> Vec<PromptMetadata> {
         return self
             .metadata_cache
@@ -1389,18 +2098,27 @@ impl PromptStore {
             .collect::<Vec<_>>();
     }
 
+    /// Deletes a prompt from the library. Prompts loaded from a project's prompt directory
+    /// belong to the project and can't be deleted.
     pub fn delete(&self, id: PromptId) -> Task<Result<()>> {
+        if let Some(path) = self.metadata(id).and_then(|metadata| metadata.path) {
+            return Task::ready(Err(anyhow!(
+                "cannot delete project prompt {path:?}, remove its file instead"
+            )));
+        }
         self.metadata_cache.write().remove(id);
 
         let db_connection = self.env.clone();
         let bodies = self.bodies;
         let metadata = self.metadata;
+        let history = self.history;
 
         self.executor.spawn(async move {
             let mut txn = db_connection.write_txn()?;
 
             metadata.delete(&mut txn, &id)?;
             bodies.delete(&mut txn, &id)?;
+            history.delete(&mut txn, &id)?;
 
             txn.commit()?;
             Ok(())
@@ -1472,23 +2190,45 @@ impl PromptStore {
             return Task::ready(Err(anyhow!("built-in prompts cannot be saved")));
         }
 
+        let path = self.metadata(id).and_then(|metadata| metadata.path);
         let prompt_metadata = PromptMetadata {
             id,
             title,
-            default,
+            default: default && path.is_none(),
             saved_at: Utc::now(),
+            path,
         };
         self.metadata_cache.write().insert(prompt_metadata.clone());
 
+        let fs = self.fs.clone();
         let db_connection = self.env.clone();
         let bodies = self.bodies;
         let metadata = self.metadata;
+        let history = self.history;
 
         self.executor.spawn(async move {
+            let body = body.to_string();
+            if let Some(path) = prompt_metadata.path.as_ref() {
+                write_prompt_file(fs.as_ref(), path, &prompt_metadata, body.clone()).await?;
+            }
+
             let mut txn = db_connection.write_txn()?;
+            if prompt_metadata.path.is_none() {
+                metadata.put(&mut txn, &id, &prompt_metadata)?;
+                bodies.put(&mut txn, &id, &body)?;
+            }
 
-            metadata.put(&mut txn, &id, &prompt_metadata)?;
-            bodies.put(&mut txn, &id, &body.to_string())?;
+            let mut versions = history.get(&txn, &id)?.unwrap_or_default();
+            record_prompt_version(
+                &mut versions,
+                PromptVersion {
+                    title: prompt_metadata.title.clone(),
+                    body,
+                    started_at: prompt_metadata.saved_at,
+                    saved_at: prompt_metadata.saved_at,
+                },
+            );
+            history.put(&mut txn, &id, &versions)?;
 
             txn.commit()?;
 
@@ -1511,19 +2251,30 @@ impl PromptStore {
                 .and_then(|metadata| metadata.title.clone());
         }
 
+        let path = cache
+            .metadata_by_id
+            .get(&id)
+            .and_then(|metadata| metadata.path.clone());
         let prompt_metadata = PromptMetadata {
             id,
             title,
-            default,
+            default: default && path.is_none(),
             saved_at: Utc::now(),
+            path,
         };
 
         cache.insert(prompt_metadata.clone());
 
+        let fs = self.fs.clone();
         let db_connection = self.env.clone();
         let metadata = self.metadata;
 
         self.executor.spawn(async move {
+            if let Some(path) = prompt_metadata.path.as_ref() {
+                let body = PromptFile::parse(&fs.load(path).await?)?.body;
+                return write_prompt_file(fs.as_ref(), path, &prompt_metadata, body).await;
+            }
+
             let mut txn = db_connection.write_txn()?;
             metadata.put(&mut txn, &id, &prompt_metadata)?;
             txn.commit()?;
@@ -1537,9 +2288,267 @@ impl PromptStore {
     }
 }
 
+/// Writes a project prompt back to its file, keeping the file's own `default` value, which
+/// the library ignores.
+async fn write_prompt_file(
+    fs: &dyn Fs,
+    path: &Path,
+    metadata: &PromptMetadata,
+    body: String,
+) -> Result<()> {
+    let default = fs
+        .load(path)
+        .await
+        .ok()
+        .and_then(|text| PromptFile::parse(&text).ok())
+        .map_or(false, |prompt| prompt.default);
+    let prompt = PromptFile {
+        id: match metadata.id {
+            PromptId::User { uuid } => Some(uuid),
+            PromptId::EditWorkflow => None,
+        },
+        title: metadata.title.clone(),
+        default,
+        body,
+    };
+    fs.atomic_write(path.to_path_buf(), prompt.to_markdown())
+        .await
+}
+
 /// Wraps a shared future to a prompt store so it can be assigned as a context global.
 pub struct GlobalPromptStore(
     Shared<BoxFuture<'static, Result<Arc<PromptStore>, Arc<anyhow::Error>>>>,
 );
 
 impl Global for GlobalPromptStore {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    #[test]
+    fn test_prompt_file_round_trip() {
+        let prompt = PromptFile {
+            id: Some(Uuid::new_v4()),
+            title: Some("Review: \"strict\" mode".into()),
+            default: true,
+            body: "Be thorough.\n\n---\n\nAnd kind.".into(),
+        };
+        assert_eq!(PromptFile::parse(&prompt.to_markdown()).unwrap(), prompt);
+
+        let prompt = PromptFile::parse("---\r\ntitle: Plain title\r\n---\r\nBody\r\n").unwrap();
+        assert_eq!(prompt.id, None);
+        assert_eq!(prompt.title, Some("Plain title".into()));
+        assert!(!prompt.default);
+        assert_eq!(prompt.body, "Body\n");
+
+        let prompt = PromptFile::parse("Just a body").unwrap();
+        assert_eq!(prompt.title, None);
+        assert_eq!(prompt.body, "Just a body");
+
+        assert!(PromptFile::parse("---\ntitle: Unterminated\nBody").is_err());
+        assert!(PromptFile::parse("---\nid: not-a-uuid\n---\nBody").is_err());
+    }
+
+    #[test]
+    fn test_record_prompt_version() {
+        let start = Utc::now();
+        let version = |minutes: i64, body: &str| PromptVersion {
+            title: Some("Prompt".into()),
+            body: body.into(),
+            started_at: start + chrono::Duration::minutes(minutes),
+            saved_at: start + chrono::Duration::minutes(minutes),
+        };
+
+        let mut history = Vec::new();
+        record_prompt_version(&mut history, version(0, "a"));
+        record_prompt_version(&mut history, version(1, "ab"));
+        record_prompt_version(&mut history, version(2, "ab"));
+        assert_eq!(
+            history,
+            vec![PromptVersion {
+                started_at: start,
+                ..version(1, "ab")
+            }]
+        );
+
+        record_prompt_version(&mut history, version(11, "abc"));
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1], version(11, "abc"));
+
+        for minutes in 0..MAX_PROMPT_VERSIONS as i64 {
+            record_prompt_version(
+                &mut history,
+                version(30 + minutes * 10, &minutes.to_string()),
+            );
+        }
+        assert_eq!(history.len(), MAX_PROMPT_VERSIONS);
+        assert_eq!(history[0].body, "0");
+    }
+
+    #[test]
+    fn test_prompt_file_name() {
+        assert_eq!(
+            prompt_file_name(Some("Rust: Expert/Reviewer")),
+            "Rust- Expert-Reviewer"
+        );
+        assert_eq!(prompt_file_name(Some("  ")), "Untitled");
+        assert_eq!(prompt_file_name(None), "Untitled");
+    }
+
+    #[gpui::test]
+    async fn test_project_prompt_directory(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/project/.zed/prompts",
+            json!({
+                "review.md": "---\ntitle: Review\ndefault: true\n---\n\nBe thorough.",
+            }),
+        )
+        .await;
+        let store = test_prompt_store(fs.clone(), cx).await;
+        let dir = PathBuf::from("/project/.zed/prompts");
+
+        store.add_directory(dir.clone()).await.unwrap();
+        let id = store.id_for_title("Review").unwrap();
+        assert!(!store.metadata(id).unwrap().default);
+        assert!(store.default_prompt_metadata().is_empty());
+        assert_eq!(store.load(id).await.unwrap(), "Be thorough.");
+
+        store
+            .save_metadata(id, Some("Review".into()), true)
+            .await
+            .unwrap();
+        assert!(store.default_prompt_metadata().is_empty());
+        let file = fs
+            .load("/project/.zed/prompts/review.md".as_ref())
+            .await
+            .unwrap();
+        assert!(PromptFile::parse(&file).unwrap().default);
+
+        assert!(store.delete(id).await.is_err());
+        assert!(store.metadata(id).is_some());
+        assert!(fs.is_file("/project/.zed/prompts/review.md".as_ref()).await);
+
+        // The directory stays loaded until every project that added it removes it.
+        store.add_directory(dir.clone()).await.unwrap();
+        store.remove_directory(&dir);
+        assert!(store.metadata(id).is_some());
+        store.remove_directory(&dir);
+        assert!(store.metadata(id).is_none());
+
+        store.load_directory(dir).await.unwrap();
+        assert!(store.metadata(id).is_none());
+    }
+
+    #[gpui::test]
+    async fn test_project_prompts(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+            AssistantSettings::register(cx);
+        });
+        set_project_prompt_directories(&[".zed/prompts"], cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/project",
+            json!({
+                ".zed": {
+                    "prompts": {
+                        "review.md": "Be thorough.",
+                    },
+                },
+            }),
+        )
+        .await;
+        let store = test_prompt_store(fs.clone(), cx).await;
+        cx.update(|cx| {
+            cx.set_global(GlobalPromptStore(
+                future::ready(Ok(store.clone())).boxed().shared(),
+            ))
+        });
+
+        let project = Project::test(fs.clone(), ["/project".as_ref()], cx).await;
+        let project_prompts =
+            cx.new_model(|cx| ProjectPrompts::new(&project, fs.clone() as Arc<dyn Fs>, cx));
+        cx.run_until_parked();
+        assert_eq!(prompt_titles(&store), ["review"]);
+
+        // Prompts are reloaded when their directory changes.
+        fs.insert_file("/project/.zed/prompts/explain.md", b"Explain it.".to_vec())
+            .await;
+        cx.run_until_parked();
+        assert_eq!(prompt_titles(&store), ["explain", "review"]);
+
+        // Prompts are unloaded when their directory is removed from the settings.
+        set_project_prompt_directories(&[], cx);
+        cx.run_until_parked();
+        assert!(prompt_titles(&store).is_empty());
+
+        set_project_prompt_directories(&[".zed/prompts"], cx);
+        cx.run_until_parked();
+        assert_eq!(prompt_titles(&store), ["explain", "review"]);
+
+        // Prompts are unloaded when their worktree is removed.
+        project.update(cx, |project, cx| {
+            let worktree_id = project.worktrees(cx).next().unwrap().read(cx).id();
+            project.remove_worktree(worktree_id, cx);
+        });
+        cx.run_until_parked();
+        assert!(prompt_titles(&store).is_empty());
+
+        // Closing the project unloads the prompts in all of its directories.
+        project
+            .update(cx, |project, cx| {
+                project.find_or_create_worktree("/project", true, cx)
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(prompt_titles(&store), ["explain", "review"]);
+        project_prompts.update(cx, |project_prompts, cx| project_prompts.unload(cx));
+        cx.run_until_parked();
+        assert!(prompt_titles(&store).is_empty());
+    }
+
+    async fn test_prompt_store(fs: Arc<FakeFs>, cx: &mut TestAppContext) -> Arc<PromptStore> {
+        let db_path = std::env::temp_dir().join(format!("prompt-library-test-{}", Uuid::new_v4()));
+        let store = PromptStore::new(db_path, fs, cx.executor()).await.unwrap();
+        Arc::new(store)
+    }
+
+    fn set_project_prompt_directories(directories: &[&str], cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store
+                    .set_user_settings(
+                        &json!({
+                            "assistant": {
+                                "version": "2",
+                                "project_prompt_directories": directories,
+                            }
+                        })
+                        .to_string(),
+                        cx,
+                    )
+                    .unwrap();
+            })
+        });
+    }
+
+    fn prompt_titles(store: &PromptStore) -> Vec<String> {
+        store
+            .metadata_cache
+            .read()
+            .metadata
+            .iter()
+            .filter_map(|metadata| Some(metadata.title.as_ref()?.to_string()))
+            .collect()
+    }
+}
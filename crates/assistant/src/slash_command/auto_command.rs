@@ -221,6 +221,7 @@ async fn commands_for_summaries(
         tools: Vec::new(),
         stop: Vec::new(),
         temperature: None,
+        max_tokens: None,
     };
 
     while let Some(current_summaries) = stack.pop() {
@@ -169,8 +169,7 @@ impl SlashCommand for ContextServerSlashCommand {
                     .collect::<Vec<String>>()
                     .join("\n\n");
 
-                // We must normalize the line endings here, since servers might return CR characters.
-                LineEnding::normalize(&mut prompt);
+                normalize_line_endings(&mut prompt);
 
                 Ok(SlashCommandOutput {
                     sections: vec![SlashCommandOutputSection {
@@ -194,6 +193,12 @@ impl SlashCommand for ContextServerSlashCommand {
     }
 }
 
+/// Normalizes the line endings of text returned by a context server, since servers might return
+/// CR characters.
+pub(crate) fn normalize_line_endings(text: &mut String) {
+    LineEnding::normalize(text);
+}
+
 fn completion_argument(prompt: &Prompt, arguments: &[String]) -> Result<(String, String)> {
     if arguments.is_empty() {
         return Err(anyhow!("No arguments given"));
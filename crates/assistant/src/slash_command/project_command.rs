@@ -109,6 +109,7 @@ impl SlashCommand for ProjectSlashCommand {
                         tools: vec![],
                         stop: vec![],
                         temperature: None,
+                        max_tokens: None,
                     },
                     cx.deref_mut(),
                 )
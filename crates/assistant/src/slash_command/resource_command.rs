@@ -0,0 +1,262 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use assistant_slash_command::{
+    AfterCompletion, ArgumentCompletion, SlashCommand, SlashCommandOutput,
+    SlashCommandOutputSection, SlashCommandResult,
+};
+use base64::prelude::*;
+use context_servers::{
+    manager::ContextServerManager, protocol::ServerCapability, types::ResourcesReadResponse,
+};
+use gpui::{Image, ImageFormat, Task, WeakView};
+use language::{BufferSnapshot, CodeLabel, HighlightId, LspAdapterDelegate};
+use serde::{Deserialize, Serialize};
+use ui::prelude::*;
+use url::Url;
+use util::ResultExt;
+use workspace::Workspace;
+
+use super::context_server_command::normalize_line_endings;
+
+pub(crate) struct ResourceSlashCommand;
+
+/// The metadata of a section containing a context server resource, used to insert its images
+/// and to refresh it when the resource changes.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct ResourceMetadata {
+    pub server_id: String,
+    /// The URI of the resource, as formatted after parsing it, so that it can be compared with
+    /// the URIs of resource updates.
+    pub uri: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<ResourceImage>,
+}
+
+impl ResourceMetadata {
+    pub fn for_section<T>(section: &SlashCommandOutputSection<T>) -> Option<Self> {
+        serde_json::from_value(section.metadata.clone()?).ok()
+    }
+
+    /// The id of the server providing the resource, and the resource's URI.
+    pub fn resource(&self) -> Option<(String, Url)> {
+        Some((self.server_id.clone(), self.uri.parse().ok()?))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct ResourceImage {
+    /// The offset of the image's placeholder line within the section.
+    pub offset: usize,
+    pub mime_type: String,
+    /// The base64-encoded image data.
+    pub data: String,
+}
+
+impl ResourceImage {
+    pub fn to_image(&self) -> Result<Image> {
+        let format = match self.mime_type.as_str() {
+            "image/png" => ImageFormat::Png,
+            "image/jpeg" => ImageFormat::Jpeg,
+            "image/webp" => ImageFormat::Webp,
+            "image/gif" => ImageFormat::Gif,
+            "image/svg+xml" => ImageFormat::Svg,
+            "image/bmp" => ImageFormat::Bmp,
+            "image/tiff" => ImageFormat::Tiff,
+            mime_type => bail!("unsupported image type {mime_type}"),
+        };
+        let bytes = BASE64_STANDARD.decode(&self.data)?;
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        Ok(Image {
+            format,
+            bytes,
+            id: hasher.finish(),
+        })
+    }
+}
+
+impl SlashCommand for ResourceSlashCommand {
+    fn name(&self) -> String {
+        "resource".into()
+    }
+
+    fn description(&self) -> String {
+        "Insert a resource from a context server".into()
+    }
+
+    fn menu_text(&self) -> String {
+        self.description()
+    }
+
+    fn requires_argument(&self) -> bool {
+        true
+    }
+
+    fn complete_argument(
+        self: Arc<Self>,
+        _arguments: &[String],
+        _cancel: Arc<AtomicBool>,
+        _workspace: Option<WeakView<Workspace>>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<Vec<ArgumentCompletion>>> {
+        let servers = ContextServerManager::global(cx).read(cx).servers();
+        let comment_id = cx.theme().syntax().highlight_id("comment").map(HighlightId);
+        cx.foreground_executor().spawn(async move {
+            let mut completions = Vec::new();
+            for server in servers {
+                let Some(protocol) = server.client.read().clone() else {
+                    continue;
+                };
+                if !protocol.capable(ServerCapability::Resources) {
+                    continue;
+                }
+
+                if let Some(response) = protocol.list_resources().await.log_err() {
+                    completions.extend(response.resources.into_iter().map(|resource| {
+                        ArgumentCompletion {
+                            label: resource_label(
+                                &resource.name,
+                                resource.uri.as_str(),
+                                comment_id,
+                            ),
+                            new_text: resource.uri.to_string(),
+                            after_completion: AfterCompletion::Run,
+                            replace_previous_arguments: true,
+                        }
+                    }));
+                }
+                // Resource templates are optional, so servers may not implement listing them.
+                if let Ok(response) = protocol.list_resource_templates().await {
+                    completions.extend(response.resource_templates.into_iter().map(|template| {
+                        ArgumentCompletion {
+                            label: resource_label(
+                                &template.name,
+                                &template.uri_template,
+                                comment_id,
+                            ),
+                            new_text: template.uri_template,
+                            after_completion: AfterCompletion::Compose,
+                            replace_previous_arguments: true,
+                        }
+                    }));
+                }
+            }
+            Ok(completions)
+        })
+    }
+
+    fn run(
+        self: Arc<Self>,
+        arguments: &[String],
+        _context_slash_command_output_sections: &[SlashCommandOutputSection<language::Anchor>],
+        _context_buffer: BufferSnapshot,
+        _workspace: WeakView<Workspace>,
+        _delegate: Option<Arc<dyn LspAdapterDelegate>>,
+        cx: &mut WindowContext,
+    ) -> Task<SlashCommandResult> {
+        let Some(uri) = arguments.first() else {
+            return Task::ready(Err(anyhow!("missing resource URI")));
+        };
+        read_resource(None, uri.clone(), cx)
+    }
+}
+
+/// Reads the resource with the given URI from the given context server, or from the first
+/// running context server that provides it.
+pub(crate) fn read_resource(
+    server_id: Option<String>,
+    uri: String,
+    cx: &mut WindowContext,
+) -> Task<SlashCommandResult> {
+    let servers = ContextServerManager::global(cx)
+        .read(cx)
+        .servers()
+        .into_iter()
+        .filter(|server| server_id.as_ref().map_or(true, |id| *id == server.id))
+        .collect::<Vec<_>>();
+
+    cx.foreground_executor().spawn(async move {
+        let uri = uri.parse::<Url>()?;
+        let mut last_error = None;
+        for server in servers {
+            let Some(protocol) = server.client.read().clone() else {
+                continue;
+            };
+            if !protocol.capable(ServerCapability::Resources) {
+                continue;
+            }
+
+            match protocol.read_resource(uri.clone()).await {
+                Ok(response) => {
+                    let output = resource_output(&server.id, uri.as_str(), response);
+                    return Ok(output.to_event_stream());
+                }
+                Err(error) => last_error = Some(error),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| anyhow!("no context server provides {uri}")))
+    })
+}
+
+fn resource_output(
+    server_id: &str,
+    uri: &str,
+    response: ResourcesReadResponse,
+) -> SlashCommandOutput {
+    let mut text = String::new();
+    let mut images = Vec::new();
+    for content in response.contents {
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+
+        let mime_type = content.mime_type.unwrap_or_default();
+        match (content.text, content.blob) {
+            (Some(mut content), _) => {
+                normalize_line_endings(&mut content);
+                text.push_str(&content);
+            }
+            (None, Some(data)) if mime_type.starts_with("image/") => {
+                images.push(ResourceImage {
+                    offset: text.len(),
+                    mime_type: mime_type.clone(),
+                    data,
+                });
+                text.push_str(&format!("[{mime_type} image]"));
+            }
+            (None, Some(_)) => log::warn!("skipping binary content of {uri} ({mime_type})"),
+            (None, None) => {}
+        }
+    }
+    if text.is_empty() {
+        text.push_str(uri);
+    }
+
+    let metadata = ResourceMetadata {
+        server_id: server_id.to_string(),
+        uri: uri.to_string(),
+        images,
+    };
+    SlashCommandOutput {
+        sections: vec![SlashCommandOutputSection {
+            range: 0..text.len(),
+            icon: IconName::FileText,
+            label: format!("resource {uri}").into(),
+            metadata: serde_json::to_value(metadata).log_err(),
+        }],
+        text,
+        run_commands_in_text: false,
+    }
+}
+
+fn resource_label(name: &str, uri: &str, comment_id: Option<HighlightId>) -> CodeLabel {
+    let mut label = CodeLabel::default();
+    label.push_str(name, None);
+    label.push_str(" ", None);
+    label.push_str(uri, comment_id);
+    label.filter_range = 0..label.text.len();
+    label
+}
@@ -286,6 +286,7 @@ impl TerminalInlineAssistant {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            max_tokens: None,
         })
     }
 
@@ -1041,7 +1042,10 @@ impl Codegen {
         self.generation = cx.spawn(|this, mut cx| async move {
             let model_telemetry_id = model.telemetry_id();
             let model_provider_id = model.provider_id();
-            let response = model.stream_completion_text(prompt, &cx).await;
+            let response = model
+                .stream_completion_text(prompt, &cx)
+                .await
+                .map(|response| response.stream);
             let generate = async {
                 let (mut hunks_tx, mut hunks_rx) = mpsc::channel(1);
 
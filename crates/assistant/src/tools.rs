@@ -1 +1,8 @@
+pub mod context_server_tool;
+pub mod diagnostics_tool;
+pub mod find_symbol_tool;
+pub mod list_directory_tool;
 pub mod now_tool;
+pub mod propose_edit_tool;
+pub mod read_file_tool;
+pub mod search_project_tool;
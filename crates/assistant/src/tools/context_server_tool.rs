@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use assistant_tool::Tool;
+use context_servers::manager::ContextServerManager;
+use context_servers::types::{self, ToolResponseContent};
+use gpui::{Task, WeakView, WindowContext};
+use workspace::Workspace;
+
+use crate::slash_command::context_server_command::normalize_line_endings;
+
+/// The longest tool name language model providers accept.
+const MAX_TOOL_NAME_LEN: usize = 64;
+
+/// A tool provided by a context server.
+pub struct ContextServerTool {
+    server_id: String,
+    tool: types::Tool,
+}
+
+impl ContextServerTool {
+    pub fn new(server_id: impl Into<String>, tool: types::Tool) -> Self {
+        Self {
+            server_id: server_id.into(),
+            tool,
+        }
+    }
+
+    /// The name the tool is registered under, `<server id>__<tool name>`. It's namespaced by
+    /// its server, as several servers may provide tools with the same name, and only uses
+    /// the characters language model providers accept in tool names, at most 64 of them.
+    pub fn id(server_id: &str, tool_name: &str) -> String {
+        let mut id = format!("{server_id}__{tool_name}")
+            .chars()
+            .map(|char| {
+                if char.is_ascii_alphanumeric() || char == '_' || char == '-' {
+                    char
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+        id.truncate(MAX_TOOL_NAME_LEN);
+        id
+    }
+}
+
+impl Tool for ContextServerTool {
+    fn name(&self) -> String {
+        Self::id(&self.server_id, &self.tool.name)
+    }
+
+    fn description(&self) -> String {
+        self.tool.description.clone().unwrap_or_default()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        match &self.tool.input_schema {
+            serde_json::Value::Null => {
+                serde_json::json!({ "type": "object", "properties": {} })
+            }
+            input_schema => input_schema.clone(),
+        }
+    }
+
+    fn needs_confirmation(&self) -> bool {
+        // Context servers are external programs, so their tools may have arbitrary side effects.
+        true
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        _workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let manager = ContextServerManager::global(cx);
+        let manager = manager.read(cx);
+        let Some(server) = manager.get_server(&self.server_id) else {
+            return Task::ready(Err(anyhow!("Context server not found")));
+        };
+
+        let tool_name = self.tool.name.clone();
+        cx.foreground_executor().spawn(async move {
+            let Some(protocol) = server.client.read().clone() else {
+                bail!("Context server not initialized");
+            };
+
+            let arguments = match input {
+                serde_json::Value::Object(arguments) => Some(arguments.into_iter().collect()),
+                serde_json::Value::Null => None,
+                _ => bail!("Tool input must be a JSON object"),
+            };
+            log::trace!("running tool {tool_name} with arguments: {arguments:?}");
+            let response = protocol.run_tool(&tool_name, arguments).await?;
+
+            let mut output = response
+                .content
+                .into_iter()
+                .map(|content| match content {
+                    ToolResponseContent::Text { text } => text,
+                    ToolResponseContent::Image { mime_type, .. } => {
+                        format!("[{mime_type} image]")
+                    }
+                    ToolResponseContent::Resource { resource } => resource
+                        .text
+                        .unwrap_or_else(|| format!("[resource {}]", resource.uri)),
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            normalize_line_endings(&mut output);
+
+            if response.is_error == Some(true) {
+                Err(anyhow!(output))
+            } else {
+                Ok(output)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    #[test]
+    fn test_tool_ids_are_valid_tool_names() {
+        let valid_name = Regex::new("^[a-zA-Z0-9_-]{1,64}$").unwrap();
+        let long_tool_name = "long_name".repeat(10);
+        for (server_id, tool_name, expected_id) in [
+            ("my-server", "fetch", Some("my-server__fetch")),
+            (
+                "postgres.local",
+                "run query",
+                Some("postgres_local__run_query"),
+            ),
+            ("sérveur", "outil/lire", Some("s_rveur__outil_lire")),
+            ("server", long_tool_name.as_str(), None),
+        ] {
+            let id = ContextServerTool::id(server_id, tool_name);
+            assert!(valid_name.is_match(&id), "invalid tool name {id:?}");
+            if let Some(expected_id) = expected_id {
+                assert_eq!(id, expected_id);
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{AppContext, Model, Task, WeakView, WindowContext};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::paths::PathMatcher;
+use workspace::Workspace;
+
+use crate::slash_command::diagnostics_command::{collect_diagnostics, Options};
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DiagnosticsToolInput {
+    /// A glob pattern restricting the files to report diagnostics for, e.g. `root/src/**/*.rs`.
+    /// Defaults to all the files of the project.
+    path: Option<String>,
+    /// Whether to report warnings in addition to errors.
+    #[serde(default)]
+    include_warnings: bool,
+}
+
+pub struct DiagnosticsTool;
+
+impl Tool for DiagnosticsTool {
+    fn name(&self) -> String {
+        "diagnostics".into()
+    }
+
+    fn description(&self) -> String {
+        "Returns the errors and warnings reported by the language servers of the project.".into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(DiagnosticsToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: DiagnosticsToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+
+        diagnostics(workspace.read(cx).project().clone(), input, cx)
+    }
+}
+
+fn diagnostics(
+    project: Model<Project>,
+    input: DiagnosticsToolInput,
+    cx: &mut AppContext,
+) -> Task<Result<String>> {
+    let path_matcher = match input.path.map(|path| PathMatcher::new(&[path])).transpose() {
+        Ok(path_matcher) => path_matcher,
+        Err(err) => return Task::ready(Err(anyhow!(err))),
+    };
+
+    let options = Options {
+        include_warnings: input.include_warnings,
+        path_matcher,
+    };
+    let diagnostics = collect_diagnostics(project, options, cx);
+    cx.background_executor().spawn(async move {
+        Ok(diagnostics
+            .await?
+            .map_or_else(|| "No diagnostics found.".to_string(), |output| output.text))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use lsp::{DiagnosticSeverity, LanguageServerId};
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    use super::*;
+
+    #[gpui::test]
+    async fn test_diagnostics(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a.rs": "fn a() {\n    b();\n}\n",
+                "b.rs": "fn b() {\n    let x = 1;\n}\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+
+        let run = |path: Option<&str>, include_warnings, cx: &mut TestAppContext| {
+            let input = DiagnosticsToolInput {
+                path: path.map(Into::into),
+                include_warnings,
+            };
+            cx.update(|cx| diagnostics(project.clone(), input, cx))
+        };
+
+        assert_eq!(run(None, true, cx).await.unwrap(), "No diagnostics found.");
+
+        project.update(cx, |project, cx| {
+            for (path, line, severity, message) in [
+                (
+                    "/root/a.rs",
+                    1,
+                    DiagnosticSeverity::ERROR,
+                    "cannot find `b`",
+                ),
+                ("/root/b.rs", 1, DiagnosticSeverity::WARNING, "unused `x`"),
+            ] {
+                project
+                    .update_diagnostics(
+                        LanguageServerId(0),
+                        lsp::PublishDiagnosticsParams {
+                            uri: lsp::Url::from_file_path(path).unwrap(),
+                            version: None,
+                            diagnostics: vec![lsp::Diagnostic {
+                                range: lsp::Range::new(
+                                    lsp::Position::new(line, 4),
+                                    lsp::Position::new(line, 5),
+                                ),
+                                severity: Some(severity),
+                                message: message.to_string(),
+                                ..Default::default()
+                            }],
+                        },
+                        &[],
+                        cx,
+                    )
+                    .unwrap();
+            }
+        });
+
+        let errors = run(None, false, cx).await.unwrap();
+        assert!(errors.contains("root/a.rs"), "{errors}");
+        assert!(errors.contains("error: cannot find `b`"), "{errors}");
+        assert!(!errors.contains("unused `x`"), "{errors}");
+
+        let all = run(None, true, cx).await.unwrap();
+        assert!(all.contains("error: cannot find `b`"), "{all}");
+        assert!(all.contains("warning: unused `x`"), "{all}");
+
+        let filtered = run(Some("root/b.rs"), true, cx).await.unwrap();
+        assert!(filtered.contains("warning: unused `x`"), "{filtered}");
+        assert!(!filtered.contains("cannot find `b`"), "{filtered}");
+
+        assert_eq!(
+            run(Some("root/b.rs"), false, cx).await.unwrap(),
+            "No diagnostics found."
+        );
+    }
+}
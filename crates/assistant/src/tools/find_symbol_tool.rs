@@ -0,0 +1,184 @@
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{AppContext, Model, Task, WeakView, WindowContext};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+/// The maximum number of symbols returned by a query.
+const MAX_SYMBOLS: usize = 50;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FindSymbolToolInput {
+    /// The name, or part of the name, of the symbols to find.
+    query: String,
+}
+
+pub struct FindSymbolTool;
+
+impl Tool for FindSymbolTool {
+    fn name(&self) -> String {
+        "find_symbol".into()
+    }
+
+    fn description(&self) -> String {
+        "Finds the definitions of symbols in the project, using its language servers.".into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(FindSymbolToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: FindSymbolToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+
+        find_symbol(workspace.read(cx).project().clone(), input, cx)
+    }
+}
+
+fn find_symbol(
+    project: Model<Project>,
+    input: FindSymbolToolInput,
+    cx: &mut AppContext,
+) -> Task<Result<String>> {
+    let symbols = project.update(cx, |project, cx| project.symbols(&input.query, cx));
+    cx.spawn(|cx| async move {
+        let symbols = symbols.await?;
+        project.read_with(&cx, |project, cx| {
+            let mut output = String::new();
+            for symbol in symbols.iter().take(MAX_SYMBOLS) {
+                let Some(worktree) = project.worktree_for_id(symbol.path.worktree_id, cx) else {
+                    continue;
+                };
+                let path = Path::new(worktree.read(cx).root_name()).join(&symbol.path.path);
+                writeln!(
+                    output,
+                    "{} {}:{}",
+                    symbol.label.text,
+                    path.display(),
+                    symbol.range.start.0.row + 1
+                )?;
+            }
+            if symbols.len() > MAX_SYMBOLS {
+                writeln!(output, "(more symbols were omitted)")?;
+            }
+            if output.is_empty() {
+                output.push_str("No symbols found.");
+            }
+            anyhow::Ok(output)
+        })?
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use fs::FakeFs;
+    use futures::StreamExt as _;
+    use gpui::TestAppContext;
+    use language::{FakeLspAdapter, Language, LanguageConfig, LanguageMatcher};
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    use super::*;
+
+    #[gpui::test]
+    async fn test_find_symbol(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({ "lib.rs": "fn one() {}\nfn two() {}\n", "main.rs": "fn main() {}\n" }),
+        )
+        .await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        language_registry.add(Arc::new(Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            None,
+        )));
+        let mut fake_servers =
+            language_registry.register_fake_lsp("Rust", FakeLspAdapter::default());
+        let _buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/root/main.rs", cx)
+            })
+            .await
+            .unwrap();
+
+        let fake_symbols = [
+            symbol("one", "/root/lib.rs", 0),
+            symbol("two", "/root/lib.rs", 1),
+            symbol("main", "/root/main.rs", 0),
+        ];
+        let fake_server = fake_servers.next().await.unwrap();
+        fake_server.handle_request::<lsp::WorkspaceSymbolRequest, _, _>(
+            move |params: lsp::WorkspaceSymbolParams, _| {
+                let symbols = fake_symbols
+                    .iter()
+                    .filter(|symbol| symbol.name.contains(&params.query))
+                    .cloned()
+                    .collect();
+                async move { Ok(Some(lsp::WorkspaceSymbolResponse::Flat(symbols))) }
+            },
+        );
+
+        let run = |query: &str, cx: &mut TestAppContext| {
+            let input = FindSymbolToolInput {
+                query: query.into(),
+            };
+            cx.update(|cx| find_symbol(project.clone(), input, cx))
+        };
+
+        assert_eq!(run("two", cx).await.unwrap(), "two root/lib.rs:2\n");
+        assert_eq!(
+            run("o", cx).await.unwrap(),
+            "one root/lib.rs:1\ntwo root/lib.rs:2\n"
+        );
+        assert_eq!(run("missing", cx).await.unwrap(), "No symbols found.");
+    }
+
+    fn symbol(name: &str, path: &str, line: u32) -> lsp::SymbolInformation {
+        #[allow(deprecated)]
+        lsp::SymbolInformation {
+            name: name.to_string(),
+            kind: lsp::SymbolKind::FUNCTION,
+            tags: None,
+            deprecated: None,
+            container_name: None,
+            location: lsp::Location::new(
+                lsp::Url::from_file_path(path).unwrap(),
+                lsp::Range::new(lsp::Position::new(line, 3), lsp::Position::new(line, 6)),
+            ),
+        }
+    }
+}
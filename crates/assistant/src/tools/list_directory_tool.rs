@@ -0,0 +1,144 @@
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use assistant_tool::Tool;
+use gpui::{AppContext, Model, Task, WeakView, WindowContext};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListDirectoryToolInput {
+    /// The path of the directory, starting with the name of the worktree root it belongs to.
+    /// Leave it empty to list the worktree roots of the project.
+    #[serde(default)]
+    path: String,
+}
+
+pub struct ListDirectoryTool;
+
+impl Tool for ListDirectoryTool {
+    fn name(&self) -> String {
+        "list_directory".into()
+    }
+
+    fn description(&self) -> String {
+        "Lists the files and directories in a directory of the project. Directories end with a slash."
+            .into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(ListDirectoryToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: ListDirectoryToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+
+        Task::ready(list_directory(
+            workspace.read(cx).project().clone(),
+            input,
+            cx,
+        ))
+    }
+}
+
+fn list_directory(
+    project: Model<Project>,
+    input: ListDirectoryToolInput,
+    cx: &AppContext,
+) -> Result<String> {
+    let project = project.read(cx);
+    let mut output = String::new();
+
+    let path = input.path.trim_end_matches('/');
+    if path.is_empty() || path == "." {
+        for worktree in project.visible_worktrees(cx) {
+            writeln!(output, "{}/", worktree.read(cx).root_name())?;
+        }
+        return Ok(output);
+    }
+
+    let project_path = project
+        .find_project_path(Path::new(path), cx)
+        .ok_or_else(|| anyhow!("directory not found: {path}"))?;
+    let worktree = project
+        .worktree_for_id(project_path.worktree_id, cx)
+        .ok_or_else(|| anyhow!("worktree not found for {path}"))?;
+    let worktree = worktree.read(cx);
+    match worktree.entry_for_path(&project_path.path) {
+        Some(entry) if entry.is_dir() => {}
+        Some(_) => bail!("{path} is not a directory"),
+        None => bail!("directory not found: {path}"),
+    }
+
+    for entry in worktree.child_entries(&project_path.path) {
+        let Some(name) = entry.path.file_name() else {
+            continue;
+        };
+        write!(output, "{}", name.to_string_lossy())?;
+        if entry.is_dir() {
+            output.push('/');
+        }
+        output.push('\n');
+    }
+    if output.is_empty() {
+        output.push_str("The directory is empty.");
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    use super::*;
+
+    #[gpui::test]
+    async fn test_list_directory(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "src": { "lib.rs": "", "util": { "mod.rs": "" } },
+                "Cargo.toml": "",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+
+        let list = |path: &str, cx: &mut TestAppContext| {
+            let input = ListDirectoryToolInput { path: path.into() };
+            cx.update(|cx| list_directory(project.clone(), input, cx))
+        };
+
+        assert_eq!(list("", cx).unwrap(), "root/\n");
+        assert_eq!(list("root", cx).unwrap(), "Cargo.toml\nsrc/\n");
+        assert_eq!(list("root/src/", cx).unwrap(), "lib.rs\nutil/\n");
+        assert!(list("root/Cargo.toml", cx).is_err());
+        assert!(list("root/missing", cx).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+use std::fmt::Write;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use assistant_tool::Tool;
+use editor::ProposedChangesEditor;
+use gpui::{Task, WeakView, WindowContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+use crate::patch::{AssistantEdit, ResolvedPatch};
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ProposeEditToolInput {
+    /// A short title describing the changes as a whole.
+    title: String,
+    /// The edits to propose.
+    edits: Vec<ProposedEdit>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ProposedEdit {
+    /// The path of the file, starting with the name of the worktree root it belongs to.
+    path: String,
+    operation: ProposedEditOperation,
+    /// The text to find in the file. Required by every operation except `create`.
+    old_text: Option<String>,
+    /// The text to insert. Required by every operation except `delete`.
+    new_text: Option<String>,
+    /// A short description of the edit.
+    description: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposedEditOperation {
+    /// Replaces `old_text` with `new_text`.
+    Update,
+    /// Creates a new file containing `new_text`.
+    Create,
+    /// Inserts `new_text` before `old_text`.
+    InsertBefore,
+    /// Inserts `new_text` after `old_text`.
+    InsertAfter,
+    /// Deletes `old_text`.
+    Delete,
+}
+
+impl ProposedEditOperation {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Update => "update",
+            Self::Create => "create",
+            Self::InsertBefore => "insert_before",
+            Self::InsertAfter => "insert_after",
+            Self::Delete => "delete",
+        }
+    }
+}
+
+pub struct ProposeEditTool;
+
+impl Tool for ProposeEditTool {
+    fn name(&self) -> String {
+        "propose_edit".into()
+    }
+
+    fn description(&self) -> String {
+        "Proposes edits to files in the project. The edits are shown to the user for review and are only applied once accepted.".into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(ProposeEditToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn needs_confirmation(&self) -> bool {
+        true
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: ProposeEditToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(project) = workspace
+            .upgrade()
+            .map(|workspace| workspace.read(cx).project().clone())
+        else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+
+        let edits = input
+            .edits
+            .into_iter()
+            .map(|edit| {
+                AssistantEdit::new(
+                    Some(edit.path),
+                    Some(edit.operation.as_str().to_string()),
+                    edit.old_text,
+                    edit.new_text,
+                    edit.description,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        cx.spawn(|mut cx| async move {
+            let resolved_patch = ResolvedPatch::resolve(&edits, project.clone(), &mut cx).await;
+
+            let mut output = String::new();
+            for (ix, edit) in edits.iter().enumerate() {
+                if let Err(error) = edit {
+                    writeln!(output, "Edit {} is invalid: {error}", ix + 1)?;
+                }
+            }
+            for error in &resolved_patch.errors {
+                writeln!(
+                    output,
+                    "Edit {} failed: {}",
+                    error.edit_ix + 1,
+                    error.message
+                )?;
+            }
+            if resolved_patch.edit_groups.is_empty() {
+                bail!("no edits could be applied\n{output}");
+            }
+
+            let editor = cx.new_view(|cx| {
+                let editor = ProposedChangesEditor::new(
+                    input.title.into(),
+                    resolved_patch.locations(),
+                    Some(project),
+                    cx,
+                );
+                resolved_patch.apply(&editor, cx);
+                editor
+            })?;
+            workspace.update(&mut cx, |workspace, cx| {
+                workspace.add_item_to_active_pane(Box::new(editor), None, false, cx)
+            })?;
+
+            let edit_count = edits.len()
+                - edits.iter().filter(|edit| edit.is_err()).count()
+                - resolved_patch.errors.len();
+            output.insert_str(
+                0,
+                &format!(
+                    "Proposed {edit_count} edit(s) across {} file(s) for the user to review.\n",
+                    resolved_patch.edit_groups.len()
+                ),
+            );
+            Ok(output)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fs::{FakeFs, Fs as _};
+    use gpui::{SemanticVersion, TestAppContext};
+    use project::Project;
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    use super::*;
+
+    #[gpui::test]
+    async fn test_propose_edit(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            release_channel::init(SemanticVersion::default(), cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            workspace::init_settings(cx);
+            editor::init(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({ "src": { "main.rs": "fn main() {\n    println!(\"hi\");\n}\n" } }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        let input = json!({
+            "title": "Say bye",
+            "edits": [
+                {
+                    "path": "root/src/main.rs",
+                    "operation": "update",
+                    "old_text": "    println!(\"hi\");",
+                    "new_text": "    println!(\"bye\");",
+                },
+                {
+                    "path": "root/src/main.rs",
+                    "operation": "update",
+                    "old_text": "fn main() {",
+                },
+            ],
+        });
+        let output = cx
+            .update(|cx| Arc::new(ProposeEditTool).run(input, workspace.downgrade(), cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            "Proposed 1 edit(s) across 1 file(s) for the user to review.\n\
+             Edit 2 is invalid: missing new_text\n"
+        );
+
+        let editor = workspace
+            .update(cx, |workspace, cx| {
+                workspace.active_item_as::<ProposedChangesEditor>(cx)
+            })
+            .expect("proposed changes editor was not opened");
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/root/src/main.rs", cx)
+            })
+            .await
+            .unwrap();
+        let branch_text = editor.update(cx, |editor, cx| {
+            let branch = editor.branch_buffer_for_base(&buffer).unwrap();
+            branch.read(cx).text()
+        });
+        assert_eq!(branch_text, "fn main() {\n    println!(\"bye\");\n}\n");
+
+        // The edits are only proposed, so neither the buffer nor the file are changed.
+        buffer.read_with(cx, |buffer, _| {
+            assert_eq!(buffer.text(), "fn main() {\n    println!(\"hi\");\n}\n");
+        });
+        assert_eq!(
+            fs.load("/root/src/main.rs".as_ref()).await.unwrap(),
+            "fn main() {\n    println!(\"hi\");\n}\n"
+        );
+
+        let input = json!({
+            "title": "Nothing",
+            "edits": [
+                {
+                    "path": "root/src/missing.rs",
+                    "operation": "delete",
+                    "old_text": "fn main() {",
+                },
+            ],
+        });
+        let result = cx
+            .update(|cx| Arc::new(ProposeEditTool).run(input, workspace.downgrade(), cx))
+            .await;
+        assert!(result.is_err());
+    }
+}
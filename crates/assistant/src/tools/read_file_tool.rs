@@ -0,0 +1,174 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{AppContext, Model, Task, WeakView, WindowContext};
+use language::Point;
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+use crate::slash_command::file_command::codeblock_fence_for_path;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReadFileToolInput {
+    /// The path of the file, starting with the name of the worktree root it belongs to.
+    path: String,
+    /// The first line to read, starting at 1. Defaults to the start of the file.
+    start_line: Option<u32>,
+    /// The last line to read, inclusive. Defaults to the end of the file.
+    end_line: Option<u32>,
+}
+
+pub struct ReadFileTool;
+
+impl Tool for ReadFileTool {
+    fn name(&self) -> String {
+        "read_file".into()
+    }
+
+    fn description(&self) -> String {
+        "Reads a range of lines from a file in the project.".into()
+    }
+
+    fn needs_confirmation(&self) -> bool {
+        true
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(ReadFileToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: ReadFileToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+
+        read_file(workspace.read(cx).project().clone(), input, cx)
+    }
+}
+
+fn read_file(
+    project: Model<Project>,
+    input: ReadFileToolInput,
+    cx: &mut AppContext,
+) -> Task<Result<String>> {
+    let Some(project_path) = project
+        .read(cx)
+        .find_project_path(Path::new(&input.path), cx)
+    else {
+        return Task::ready(Err(anyhow!("file not found: {}", input.path)));
+    };
+    let open_buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+
+    cx.spawn(|cx| async move {
+        let buffer = open_buffer.await?;
+        let (is_private, snapshot) = buffer.read_with(&cx, |buffer, _| {
+            let is_private = buffer.file().map_or(false, |file| file.is_private());
+            (is_private, buffer.snapshot())
+        })?;
+        if is_private {
+            return Err(anyhow!("cannot read private file: {}", input.path));
+        }
+
+        let max_row = snapshot.max_point().row;
+        let start_row = input.start_line.unwrap_or(1).saturating_sub(1).min(max_row);
+        let end_row = input
+            .end_line
+            .map_or(max_row, |line| line.saturating_sub(1).min(max_row))
+            .max(start_row);
+
+        let mut text =
+            codeblock_fence_for_path(Some(Path::new(&input.path)), Some(start_row..=end_row));
+        text.extend(snapshot.text_for_range(
+            Point::new(start_row, 0)..Point::new(end_row, snapshot.line_len(end_row)),
+        ));
+        text.push_str("\n```");
+        Ok(text)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    use super::*;
+
+    #[gpui::test]
+    async fn test_read_file(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                ".env": "SECRET=1\n",
+                "src": { "main.rs": "fn main() {\n    println!(\"hi\");\n}\n" },
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+
+        let read = |start_line, end_line, cx: &mut TestAppContext| {
+            let input = ReadFileToolInput {
+                path: "root/src/main.rs".into(),
+                start_line,
+                end_line,
+            };
+            cx.update(|cx| read_file(project.clone(), input, cx))
+        };
+
+        assert_eq!(
+            read(Some(2), Some(2), cx).await.unwrap(),
+            "```rs root/src/main.rs:2-2\n    println!(\"hi\");\n```"
+        );
+        assert_eq!(
+            read(Some(2), Some(100), cx).await.unwrap(),
+            "```rs root/src/main.rs:2-4\n    println!(\"hi\");\n}\n\n```"
+        );
+        assert_eq!(
+            read(None, Some(1), cx).await.unwrap(),
+            "```rs root/src/main.rs:1-1\nfn main() {\n```"
+        );
+
+        let input = ReadFileToolInput {
+            path: "root/missing.rs".into(),
+            start_line: None,
+            end_line: None,
+        };
+        assert!(cx
+            .update(|cx| read_file(project.clone(), input, cx))
+            .await
+            .is_err());
+
+        let input = ReadFileToolInput {
+            path: "root/.env".into(),
+            start_line: None,
+            end_line: None,
+        };
+        let error = cx
+            .update(|cx| read_file(project.clone(), input, cx))
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "cannot read private file: root/.env");
+    }
+}
@@ -0,0 +1,208 @@
+use std::fmt::Write;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use collections::HashSet;
+use gpui::{AppContext, Model, Task, WeakView, WindowContext};
+use language::{Point, ToPoint};
+use project::{
+    search::{SearchQuery, SearchResult},
+    Project,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::paths::PathMatcher;
+use workspace::Workspace;
+
+/// The maximum number of matching lines returned by a search.
+const MAX_MATCHING_LINES: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SearchProjectToolInput {
+    /// The text to search for.
+    query: String,
+    /// Whether the query is a regular expression.
+    #[serde(default)]
+    regex: bool,
+    /// Whether the search is case-sensitive.
+    #[serde(default)]
+    case_sensitive: bool,
+    /// Glob patterns restricting the files to search, e.g. `src/**/*.rs`.
+    #[serde(default)]
+    include: Vec<String>,
+}
+
+pub struct SearchProjectTool;
+
+impl Tool for SearchProjectTool {
+    fn name(&self) -> String {
+        "search_project".into()
+    }
+
+    fn description(&self) -> String {
+        "Searches the files of the project for a text or a regular expression, returning the matching lines.".into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(SearchProjectToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: SearchProjectToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+
+        search_project(workspace.read(cx).project().clone(), input, cx)
+    }
+}
+
+fn search_project(
+    project: Model<Project>,
+    input: SearchProjectToolInput,
+    cx: &mut AppContext,
+) -> Task<Result<String>> {
+    let query = PathMatcher::new(&input.include)
+        .map_err(anyhow::Error::from)
+        .and_then(|files_to_include| {
+            if input.regex {
+                SearchQuery::regex(
+                    &input.query,
+                    false,
+                    input.case_sensitive,
+                    false,
+                    files_to_include,
+                    PathMatcher::default(),
+                    None,
+                )
+            } else {
+                SearchQuery::text(
+                    &input.query,
+                    false,
+                    input.case_sensitive,
+                    false,
+                    files_to_include,
+                    PathMatcher::default(),
+                    None,
+                )
+            }
+        });
+    let query = match query {
+        Ok(query) => query,
+        Err(error) => return Task::ready(Err(error)),
+    };
+    let results = project.update(cx, |project, cx| project.search(query, cx));
+
+    cx.spawn(|cx| async move {
+        let mut output = String::new();
+        let mut matching_lines = 0;
+        'results: while let Ok(result) = results.recv().await {
+            match result {
+                SearchResult::Buffer { buffer, ranges } => {
+                    let (path, snapshot) = buffer.read_with(&cx, |buffer, cx| {
+                        let path = buffer.file().map(|file| file.full_path(cx));
+                        (path, buffer.snapshot())
+                    })?;
+                    let path = path.map_or_else(
+                        || "untitled".to_string(),
+                        |path| path.to_string_lossy().to_string(),
+                    );
+
+                    let mut rows = HashSet::default();
+                    for range in ranges {
+                        let row = range.start.to_point(&snapshot).row;
+                        if !rows.insert(row) {
+                            continue;
+                        }
+                        if matching_lines == MAX_MATCHING_LINES {
+                            writeln!(output, "(more matches were omitted)")?;
+                            break 'results;
+                        }
+                        matching_lines += 1;
+
+                        let line = snapshot
+                            .text_for_range(
+                                Point::new(row, 0)..Point::new(row, snapshot.line_len(row)),
+                            )
+                            .collect::<String>();
+                        writeln!(output, "{path}:{}: {}", row + 1, line.trim())?;
+                    }
+                }
+                SearchResult::LimitReached => {
+                    writeln!(output, "(more matches were omitted)")?;
+                    break;
+                }
+            }
+        }
+
+        if output.is_empty() {
+            output.push_str("No matches found.");
+        }
+        Ok(output)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    use super::*;
+
+    #[gpui::test]
+    async fn test_search_project(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a.rs": "fn one() {}\nfn two() { one(); one(); }\n",
+                "b.txt": "One\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+
+        let search = |query: &str, regex, include: &[&str], cx: &mut TestAppContext| {
+            let input = SearchProjectToolInput {
+                query: query.into(),
+                regex,
+                case_sensitive: true,
+                include: include.iter().map(|glob| glob.to_string()).collect(),
+            };
+            cx.update(|cx| search_project(project.clone(), input, cx))
+        };
+
+        let output = search("one", false, &[], cx).await.unwrap();
+        assert_eq!(
+            output.lines().collect::<Vec<_>>(),
+            [
+                "root/a.rs:1: fn one() {}",
+                "root/a.rs:2: fn two() { one(); one(); }"
+            ]
+        );
+
+        let output = search("[Oo]ne", true, &["*.txt"], cx).await.unwrap();
+        assert_eq!(output, "root/b.txt:1: One\n");
+
+        let output = search("three", false, &[], cx).await.unwrap();
+        assert_eq!(output, "No matches found.");
+    }
+}
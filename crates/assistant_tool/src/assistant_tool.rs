@@ -25,6 +25,11 @@ pub trait Tool: 'static + Send + Sync {
         serde_json::Value::Object(serde_json::Map::default())
     }
 
+    /// Returns whether the user must confirm each use of the tool before it runs.
+    fn needs_confirmation(&self) -> bool {
+        false
+    }
+
     /// Runs the tool with the provided input.
     fn run(
         self: Arc<Self>,
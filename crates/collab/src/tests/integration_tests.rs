@@ -6,7 +6,7 @@ use crate::{
     },
 };
 use anyhow::{anyhow, Result};
-use assistant::{ContextStore, PromptBuilder};
+use assistant::{ContextBranchPoint, ContextStore, PromptBuilder};
 use call::{room, ActiveCall, ParticipantLocation, Room};
 use client::{User, RECEIVE_TIMEOUT};
 use collections::{HashMap, HashSet};
@@ -6575,3 +6575,102 @@ async fn test_context_collaboration_with_reconnect(
         assert!(context.buffer().read(cx).read_only());
     });
 }
+
+#[gpui::test]
+async fn test_context_forking_as_guest(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a.fs().insert_tree("/a", Default::default()).await;
+    let (project_a, _) = client_a.build_local_project("/a", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.join_remote_project(project_id, cx_b).await;
+    executor.run_until_parked();
+
+    let prompt_builder = Arc::new(PromptBuilder::new(None).unwrap());
+    let context_store_a = cx_a
+        .update(|cx| ContextStore::new(project_a.clone(), prompt_builder.clone(), cx))
+        .await
+        .unwrap();
+    let context_store_b = cx_b
+        .update(|cx| ContextStore::new(project_b.clone(), prompt_builder.clone(), cx))
+        .await
+        .unwrap();
+
+    let context_a = context_store_a.update(cx_a, |store, cx| store.create(cx));
+    context_a.update(cx_a, |context, cx| {
+        context
+            .buffer()
+            .update(cx, |buffer, cx| buffer.edit([(0..0, "Hello")], None, cx))
+    });
+    let context_id = context_a.read_with(cx_a, |context, _| context.id().clone());
+    executor.run_until_parked();
+
+    let context_b = context_store_b
+        .update(cx_b, |store, cx| {
+            store.open_remote_context(context_id.clone(), cx)
+        })
+        .await
+        .unwrap();
+    let message_id =
+        context_b.read_with(cx_b, |context, cx| context.messages(cx).next().unwrap().id);
+
+    // The guest asks the host to fork the context.
+    let fork_b = context_store_b
+        .update(cx_b, |store, cx| {
+            store.fork_context(&context_b, message_id, cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+
+    let fork_id = fork_b.read_with(cx_b, |fork, _| fork.id().clone());
+    let expected_branches = vec![ContextBranchPoint {
+        context_id: fork_id.clone(),
+        message_id,
+    }];
+    assert_eq!(
+        context_a.read_with(cx_a, |context, _| context.branches().to_vec()),
+        expected_branches
+    );
+    assert_eq!(
+        context_b.read_with(cx_b, |context, _| context.branches().to_vec()),
+        expected_branches
+    );
+
+    // The fork is owned by the host and shared with the guest.
+    let fork_a = context_store_a
+        .update(cx_a, |store, cx| store.open_context_by_id(fork_id, cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        fork_a.read_with(cx_a, |fork, _| fork.forked_from().cloned()),
+        Some(ContextBranchPoint {
+            context_id,
+            message_id,
+        })
+    );
+    assert_eq!(
+        fork_b.read_with(cx_b, |fork, _| fork.forked_from().cloned()),
+        fork_a.read_with(cx_a, |fork, _| fork.forked_from().cloned())
+    );
+    assert_eq!(
+        fork_b.read_with(cx_b, |fork, cx| fork.buffer().read(cx).text()),
+        fork_a.read_with(cx_a, |fork, cx| fork.buffer().read(cx).text())
+    );
+    assert!(fork_b
+        .read_with(cx_b, |fork, cx| fork.buffer().read(cx).text())
+        .starts_with("Hello"));
+}
@@ -0,0 +1,166 @@
+//! A minimal context server used to test the Model Context Protocol client.
+//!
+//! It advertises an `echo` tool, which returns the `text` argument it was called with, and a
+//! single `file:///notes.txt` resource, which it reports as updated as soon as a client
+//! subscribes to it. Its `ask_client` tool sends the request described by its `method` and
+//! `params` arguments to the client, and returns the client's response. Each tool is listed on a
+//! page of its own, to exercise pagination.
+
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+
+fn main() -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    let mut lines = io::stdin().lock().lines();
+    while let Some(line) = lines.next() {
+        let Ok(message) = serde_json::from_str::<Value>(&line?) else {
+            continue;
+        };
+        // Notifications don't have an id and don't expect a response.
+        let Some(id) = message.get("id").cloned() else {
+            continue;
+        };
+
+        let response = match message["method"].as_str() {
+            Some("initialize") => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": {
+                    "protocolVersion": "2024-10-07",
+                    "capabilities": {
+                        "tools": {},
+                        "resources": { "subscribe": true },
+                    },
+                    "serverInfo": { "name": "stub", "version": "0.1.0" },
+                },
+            }),
+            Some("tools/list") if message["params"]["cursor"].is_null() => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": {
+                    "tools": [{
+                        "name": "echo",
+                        "description": "Echoes the given text.",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": { "text": { "type": "string" } },
+                            "required": ["text"],
+                        },
+                    }],
+                    "nextCursor": "ask_client",
+                },
+            }),
+            Some("tools/list") if message["params"]["cursor"] == "ask_client" => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": {
+                    "tools": [{
+                        "name": "ask_client",
+                        "description": "Sends a request to the client.",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "method": { "type": "string" },
+                                "params": { "type": "object" },
+                            },
+                            "required": ["method"],
+                        },
+                    }],
+                },
+            }),
+            Some("tools/call") if message["params"]["name"] == "echo" => {
+                match message["params"]["arguments"]["text"].as_str() {
+                    Some(text) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": { "content": [{ "type": "text", "text": text }] },
+                    }),
+                    None => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {
+                            "content": [{ "type": "text", "text": "missing text argument" }],
+                            "isError": true,
+                        },
+                    }),
+                }
+            }
+            Some("tools/call") if message["params"]["name"] == "ask_client" => {
+                let arguments = &message["params"]["arguments"];
+                let request = json!({
+                    "jsonrpc": "2.0",
+                    "id": "stub",
+                    "method": arguments["method"],
+                    "params": arguments["params"],
+                });
+                writeln!(stdout, "{request}")?;
+                stdout.flush()?;
+
+                let mut response = Value::Null;
+                for line in lines.by_ref() {
+                    match serde_json::from_str::<Value>(&line?) {
+                        Ok(message) if message["id"] == "stub" => {
+                            response = message;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "result": { "content": [{ "type": "text", "text": response.to_string() }] },
+                })
+            }
+            Some("resources/list") => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": {
+                    "resources": [{
+                        "uri": "file:///notes.txt",
+                        "name": "Notes",
+                        "mimeType": "text/plain",
+                    }],
+                },
+            }),
+            Some("resources/templates/list") => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": {
+                    "resourceTemplates": [{
+                        "uriTemplate": "file:///{path}",
+                        "name": "Files",
+                    }],
+                },
+            }),
+            Some("resources/read") if message["params"]["uri"] == "file:///notes.txt" => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": {
+                    "contents": [{
+                        "uri": "file:///notes.txt",
+                        "mimeType": "text/plain",
+                        "text": "Remember the milk\r\n",
+                    }],
+                },
+            }),
+            Some("resources/subscribe") => {
+                let response = json!({ "jsonrpc": "2.0", "id": id, "result": {} });
+                writeln!(stdout, "{response}")?;
+                json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/resources/updated",
+                    "params": { "uri": message["params"]["uri"] },
+                })
+            }
+            method => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "message": format!("unsupported method {method:?}") },
+            }),
+        };
+        writeln!(stdout, "{response}")?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
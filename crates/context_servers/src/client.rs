@@ -1,9 +1,14 @@
 use anyhow::{anyhow, Context, Result};
 use collections::HashMap;
-use futures::{channel::oneshot, io::BufWriter, select, AsyncRead, AsyncWrite, FutureExt};
+use futures::{
+    channel::{mpsc, oneshot},
+    io::BufWriter,
+    select, AsyncRead, AsyncWrite, Future, FutureExt, TryStreamExt,
+};
 use gpui::{AsyncAppContext, BackgroundExecutor, Task};
+use http_client::{http, AsyncBody, HttpClient};
 use parking_lot::Mutex;
-use postage::barrier;
+use postage::{barrier, prelude::Stream as _, watch};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::{value::RawValue, Value};
 use smol::{
@@ -20,13 +25,20 @@ use std::{
     },
     time::{Duration, Instant},
 };
+use url::Url;
 use util::TryFutureExt;
 
 const JSON_RPC_VERSION: &str = "2.0";
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// How long to wait before reconnecting to the event stream of a context server running as an
+/// HTTP service. The delay doubles after each failed attempt, up to [`MAX_RECONNECT_DELAY`].
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
 
 type ResponseHandler = Box<dyn Send + FnOnce(Result<String, Error>)>;
 type NotificationHandler = Box<dyn Send + FnMut(Value, AsyncAppContext)>;
+type RequestHandler = Box<dyn Send + FnMut(Value, AsyncAppContext) -> Task<Result<Value>>>;
+type DisconnectHandler = Box<dyn Send + FnOnce(AsyncAppContext)>;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -41,7 +53,9 @@ pub struct Client {
     outbound_tx: channel::Sender<String>,
     name: Arc<str>,
     notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
+    request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
     response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+    disconnect_handler: Arc<Mutex<Option<DisconnectHandler>>>,
     #[allow(clippy::type_complexity)]
     #[allow(dead_code)]
     io_tasks: Mutex<Option<(Task<Option<()>>, Task<Option<()>>)>>,
@@ -63,6 +77,26 @@ struct Request<'a, T> {
     params: T,
 }
 
+#[derive(Deserialize)]
+struct AnyRequest<'a> {
+    #[allow(dead_code)]
+    jsonrpc: &'a str,
+    id: RequestId,
+    method: String,
+    #[serde(default)]
+    params: Option<Value>,
+}
+
+#[derive(Serialize)]
+struct OutgoingResponse {
+    jsonrpc: &'static str,
+    id: RequestId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<Error>,
+}
+
 #[derive(Serialize, Deserialize)]
 struct AnyResponse<'a> {
     jsonrpc: &'a str,
@@ -109,9 +143,16 @@ struct AnyNotification<'a> {
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Error {
+    #[serde(default)]
+    code: i32,
     message: String,
 }
 
+/// The JSON-RPC error code for requests that the client does not handle.
+const METHOD_NOT_FOUND: i32 = -32601;
+/// The JSON-RPC error code for requests that the client failed to handle.
+const INTERNAL_ERROR: i32 = -32603;
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct ModelContextServerBinary {
     pub executable: PathBuf,
@@ -119,6 +160,15 @@ pub struct ModelContextServerBinary {
     pub env: Option<HashMap<String, String>>,
 }
 
+/// The location of a context server running as an HTTP service.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModelContextServerEndpoint {
+    /// The URL of the server's event stream.
+    pub url: Url,
+    /// The headers sent with every request, e.g. for authentication.
+    pub headers: HashMap<String, String>,
+}
+
 impl Client {
     /// Creates a new Client instance for a context server.
     ///
@@ -156,66 +206,168 @@ impl Client {
         let stdout = server.stdout.take().unwrap();
         let stderr = server.stderr.take().unwrap();
 
+        let name = binary
+            .executable
+            .file_name()
+            .map(|name| name.to_string_lossy().into())
+            .unwrap_or_else(|| "".into());
+        let stderr_input_task = cx.spawn(|_| Self::handle_stderr(stderr).log_err());
+        Ok(Self::new_with_io(
+            server_id,
+            name,
+            stdout,
+            stderr_input_task,
+            |outbound_rx, output_done_tx, response_handlers| {
+                Self::handle_output(stdin, outbound_rx, output_done_tx, response_handlers)
+            },
+            Some(server),
+            cx,
+        ))
+    }
+
+    /// Creates a new Client instance for a context server running as an HTTP service.
+    ///
+    /// Messages from the server are received as server-sent events from the given URL, and
+    /// messages to the server are POSTed to the endpoint that the server announces in its
+    /// first event. Connecting to the events is retried until the server announces its endpoint,
+    /// and the client disconnects once their stream ends after that.
+    pub fn new_http(
+        server_id: ContextServerId,
+        endpoint: ModelContextServerEndpoint,
+        http_client: Arc<dyn HttpClient>,
+        cx: AsyncAppContext,
+    ) -> Result<Self> {
+        log::info!("connecting to context server (url={})", endpoint.url);
+
+        let (messages_tx, messages_rx) = mpsc::unbounded::<std::io::Result<Vec<u8>>>();
+        let (post_url_tx, post_url_rx) = watch::channel();
+        let name = endpoint.url.host_str().unwrap_or_default().into();
+        let events_task = cx.spawn({
+            let http_client = http_client.clone();
+            let endpoint = endpoint.clone();
+            move |cx| {
+                Self::handle_events(
+                    http_client,
+                    endpoint,
+                    messages_tx,
+                    post_url_tx,
+                    cx.background_executor().clone(),
+                )
+                .log_err()
+            }
+        });
+        Ok(Self::new_with_io(
+            server_id,
+            name,
+            messages_rx.into_async_read(),
+            events_task,
+            |outbound_rx, output_done_tx, response_handlers| {
+                Self::handle_http_output(
+                    http_client,
+                    endpoint.headers,
+                    post_url_rx,
+                    outbound_rx,
+                    output_done_tx,
+                    response_handlers,
+                )
+            },
+            None,
+            cx,
+        ))
+    }
+
+    /// Sets up the handlers and the tasks that exchange messages with the server, given the
+    /// server's output, a task reading its diagnostics, and a function writing to the server.
+    fn new_with_io<Input, Output>(
+        server_id: ContextServerId,
+        name: Arc<str>,
+        input: Input,
+        diagnostics_task: Task<Option<()>>,
+        output: impl FnOnce(
+            channel::Receiver<String>,
+            barrier::Sender,
+            Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+        ) -> Output,
+        server: Option<Child>,
+        cx: AsyncAppContext,
+    ) -> Self
+    where
+        Input: AsyncRead + Unpin + Send + 'static,
+        Output: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
         let (outbound_tx, outbound_rx) = channel::unbounded::<String>();
         let (output_done_tx, output_done_rx) = barrier::channel();
 
         let notification_handlers =
             Arc::new(Mutex::new(HashMap::<_, NotificationHandler>::default()));
+        let request_handlers = Arc::new(Mutex::new(HashMap::<_, RequestHandler>::default()));
         let response_handlers =
             Arc::new(Mutex::new(Some(HashMap::<_, ResponseHandler>::default())));
+        let disconnect_handler = Arc::new(Mutex::new(None::<DisconnectHandler>));
 
         let stdout_input_task = cx.spawn({
             let notification_handlers = notification_handlers.clone();
+            let request_handlers = request_handlers.clone();
             let response_handlers = response_handlers.clone();
+            let outbound_tx = outbound_tx.clone();
             move |cx| {
-                Self::handle_input(stdout, notification_handlers, response_handlers, cx).log_err()
+                Self::handle_input(
+                    input,
+                    notification_handlers,
+                    request_handlers,
+                    response_handlers,
+                    outbound_tx,
+                    cx,
+                )
+                .log_err()
             }
         });
-        let stderr_input_task = cx.spawn(|_| Self::handle_stderr(stderr).log_err());
-        let input_task = cx.spawn(|_| async move {
-            let (stdout, stderr) = futures::join!(stdout_input_task, stderr_input_task);
-            stdout.or(stderr)
-        });
-        let output_task = cx.background_executor().spawn({
-            Self::handle_output(
-                stdin,
-                outbound_rx,
-                output_done_tx,
-                response_handlers.clone(),
-            )
-            .log_err()
+        let input_task = cx.spawn({
+            let response_handlers = response_handlers.clone();
+            let disconnect_handler = disconnect_handler.clone();
+            move |cx| async move {
+                let (stdout, diagnostics) = futures::join!(stdout_input_task, diagnostics_task);
+                // Nothing can answer the pending requests once the server's output has ended.
+                response_handlers.lock().take();
+                if let Some(handler) = disconnect_handler.lock().take() {
+                    handler(cx);
+                }
+                stdout.or(diagnostics)
+            }
         });
+        let output_task = cx
+            .background_executor()
+            .spawn(output(outbound_rx, output_done_tx, response_handlers.clone()).log_err());
 
-        let mut context_server = Self {
+        Self {
             server_id,
             notification_handlers,
+            request_handlers,
             response_handlers,
-            name: "".into(),
+            disconnect_handler,
+            name,
             next_id: Default::default(),
             outbound_tx,
             executor: cx.background_executor().clone(),
             io_tasks: Mutex::new(Some((input_task, output_task))),
             output_done_rx: Mutex::new(Some(output_done_rx)),
-            server: Arc::new(Mutex::new(Some(server))),
-        };
-
-        if let Some(name) = binary.executable.file_name() {
-            context_server.name = name.to_string_lossy().into();
+            server: Arc::new(Mutex::new(server)),
         }
-
-        Ok(context_server)
     }
 
     /// Handles input from the server's stdout.
     ///
     /// This function continuously reads lines from the provided stdout stream,
-    /// parses them as JSON-RPC responses or notifications, and dispatches them
-    /// to the appropriate handlers. It processes both responses (which are matched
-    /// to pending requests) and notifications (which trigger registered handlers).
+    /// parses them as JSON-RPC requests, responses or notifications, and dispatches them
+    /// to the appropriate handlers. It processes requests (which are answered by registered
+    /// handlers), responses (which are matched to pending requests) and notifications
+    /// (which trigger registered handlers).
     async fn handle_input<Stdout>(
         stdout: Stdout,
         notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
+        request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
         response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+        outbound_tx: channel::Sender<String>,
         cx: AsyncAppContext,
     ) -> anyhow::Result<()>
     where
@@ -233,7 +385,23 @@ impl Client {
             let content = buffer.trim();
 
             if !content.is_empty() {
-                if let Ok(response) = serde_json::from_str::<AnyResponse>(content) {
+                if let Ok(request) = serde_json::from_str::<AnyRequest>(content) {
+                    let response = request_handlers
+                        .lock()
+                        .get_mut(request.method.as_str())
+                        .map(|handler| handler(request.params.unwrap_or(Value::Null), cx.clone()));
+                    cx.foreground_executor()
+                        .spawn(
+                            Self::send_response(
+                                request.id,
+                                request.method,
+                                response,
+                                outbound_tx.clone(),
+                            )
+                            .log_err(),
+                        )
+                        .detach();
+                } else if let Ok(response) = serde_json::from_str::<AnyResponse>(content) {
                     if let Some(handlers) = response_handlers.lock().as_mut() {
                         if let Some(handler) = handlers.remove(&response.id) {
                             handler(Ok(content.to_string()));
@@ -253,6 +421,42 @@ impl Client {
         }
     }
 
+    /// Sends the result of a request that the server sent to the client back to the server.
+    async fn send_response(
+        id: RequestId,
+        method: String,
+        response: Option<Task<Result<Value>>>,
+        outbound_tx: channel::Sender<String>,
+    ) -> anyhow::Result<()> {
+        let (result, error) = match response {
+            Some(response) => match response.await {
+                Ok(result) => (Some(result), None),
+                Err(error) => {
+                    let error = Error {
+                        code: INTERNAL_ERROR,
+                        message: error.to_string(),
+                    };
+                    (None, Some(error))
+                }
+            },
+            None => {
+                let error = Error {
+                    code: METHOD_NOT_FOUND,
+                    message: format!("unhandled method {method}"),
+                };
+                (None, Some(error))
+            }
+        };
+        let response = serde_json::to_string(&OutgoingResponse {
+            jsonrpc: JSON_RPC_VERSION,
+            id,
+            result,
+            error,
+        })?;
+        outbound_tx.try_send(response)?;
+        Ok(())
+    }
+
     /// Handles the stderr output from the context server.
     /// Continuously reads and logs any error messages from the server.
     async fn handle_stderr<Stderr>(stderr: Stderr) -> anyhow::Result<()>
@@ -302,6 +506,156 @@ impl Client {
         Ok(())
     }
 
+    /// Handles the server-sent events of a context server running as an HTTP service, connecting
+    /// to them again with a growing delay until the server announces its endpoint.
+    async fn handle_events(
+        http_client: Arc<dyn HttpClient>,
+        endpoint: ModelContextServerEndpoint,
+        messages_tx: mpsc::UnboundedSender<std::io::Result<Vec<u8>>>,
+        mut post_url_tx: watch::Sender<Option<Url>>,
+        executor: BackgroundExecutor,
+    ) -> anyhow::Result<()> {
+        let mut reconnect_delay = INITIAL_RECONNECT_DELAY;
+        loop {
+            match Self::read_events(&http_client, &endpoint, &messages_tx, &mut post_url_tx).await {
+                Ok(()) => log::info!("context server at {} closed its events", endpoint.url),
+                Err(error) => {
+                    log::error!(
+                        "lost the events of context server at {}: {error:#}",
+                        endpoint.url
+                    )
+                }
+            }
+            if messages_tx.is_closed() {
+                return Ok(());
+            }
+
+            // Each connection is a new session, which forgets the requests of the previous one
+            // and has to be initialized again, so the client disconnects once it had a session.
+            if post_url_tx.borrow_mut().take().is_some() {
+                return Ok(());
+            }
+            executor.timer(reconnect_delay).await;
+            reconnect_delay = (reconnect_delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+    }
+
+    /// Reads the server-sent events of a context server until their stream ends.
+    ///
+    /// The `endpoint` event carries the URL that messages must be POSTed to, and `message`
+    /// events carry the JSON-RPC messages, which are forwarded one per line to the input handler.
+    async fn read_events(
+        http_client: &Arc<dyn HttpClient>,
+        endpoint: &ModelContextServerEndpoint,
+        messages_tx: &mpsc::UnboundedSender<std::io::Result<Vec<u8>>>,
+        post_url_tx: &mut watch::Sender<Option<Url>>,
+    ) -> anyhow::Result<()> {
+        let mut request = http::Request::builder()
+            .uri(endpoint.url.as_str())
+            .header("Accept", "text/event-stream");
+        for (name, value) in &endpoint.headers {
+            request = request.header(name, value);
+        }
+        let response = http_client.send(request.body(AsyncBody::empty())?).await?;
+        if !response.status().is_success() {
+            anyhow::bail!("failed to connect: {}", response.status());
+        }
+
+        let mut events = BufReader::new(response.into_body());
+        let mut line = String::new();
+        let mut event = String::new();
+        let mut data = String::new();
+        loop {
+            line.clear();
+            if events.read_line(&mut line).await? == 0 {
+                return Ok(());
+            }
+
+            let line = line.trim_end_matches(['\r', '\n']);
+            if let Some(value) = line.strip_prefix("event:") {
+                event = value.trim_start().to_string();
+            } else if let Some(value) = line.strip_prefix("data:") {
+                if !data.is_empty() {
+                    data.push('\n');
+                }
+                data.push_str(value.strip_prefix(' ').unwrap_or(value));
+            } else if line.is_empty() && !data.is_empty() {
+                match event.as_str() {
+                    "endpoint" => {
+                        *post_url_tx.borrow_mut() = Some(endpoint.url.join(&data)?);
+                    }
+                    "" | "message" => {
+                        // Messages are read one per line, and newlines can only be whitespace
+                        // in JSON anyway.
+                        let message = format!("{}\n", data.replace('\n', " "));
+                        messages_tx.unbounded_send(Ok(message.into_bytes()))?;
+                    }
+                    event => log::trace!("ignoring context server event {event:?}"),
+                }
+                event.clear();
+                data.clear();
+            }
+        }
+    }
+
+    /// Handles the output to a context server running as an HTTP service.
+    /// This function POSTs each message of the outbound channel to the endpoint announced by
+    /// the server, once there is one, and manages the lifecycle of response handlers. Messages
+    /// that can't be delivered are logged and dropped.
+    async fn handle_http_output(
+        http_client: Arc<dyn HttpClient>,
+        headers: HashMap<String, String>,
+        mut post_url_rx: watch::Receiver<Option<Url>>,
+        outbound_rx: channel::Receiver<String>,
+        output_done_tx: barrier::Sender,
+        response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+    ) -> anyhow::Result<()> {
+        let _clear_response_handlers = util::defer({
+            let response_handlers = response_handlers.clone();
+            move || {
+                response_handlers.lock().take();
+            }
+        });
+        while let Ok(message) = outbound_rx.recv().await {
+            log::trace!("outgoing message: {}", message);
+
+            let post_url = loop {
+                if let Some(post_url) = post_url_rx.borrow().clone() {
+                    break post_url;
+                }
+                if post_url_rx.recv().await.is_none() {
+                    anyhow::bail!("context server did not announce its endpoint");
+                }
+            };
+            if let Err(error) = Self::post_message(&http_client, &headers, &post_url, message).await
+            {
+                log::error!("failed to send message to context server at {post_url}: {error:#}");
+            }
+        }
+        drop(output_done_tx);
+        Ok(())
+    }
+
+    async fn post_message(
+        http_client: &Arc<dyn HttpClient>,
+        headers: &HashMap<String, String>,
+        post_url: &Url,
+        message: String,
+    ) -> anyhow::Result<()> {
+        let mut request = http::Request::builder()
+            .method(http::Method::POST)
+            .uri(post_url.as_str())
+            .header("Content-Type", "application/json");
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        let response = http_client.send(request.body(message.into())?).await?;
+        if !response.status().is_success() {
+            anyhow::bail!("the message was rejected with {}", response.status());
+        }
+        Ok(())
+    }
+
     /// Sends a JSON-RPC request to the context server and waits for a response.
     /// This function handles serialization, deserialization, timeout, and error handling.
     pub async fn request<T: DeserializeOwned>(
@@ -391,6 +745,23 @@ impl Client {
             .insert(method, Box::new(f));
     }
 
+    /// Registers a handler for requests that the server sends to the client.
+    pub fn on_request<F>(&self, method: &'static str, f: F)
+    where
+        F: 'static + Send + FnMut(Value, AsyncAppContext) -> Task<Result<Value>>,
+    {
+        self.request_handlers.lock().insert(method, Box::new(f));
+    }
+
+    /// Registers a handler that is called once the server's output ends, e.g. because the server
+    /// exited or the connection to it was lost. The client's pending requests have failed by then.
+    pub fn on_disconnect<F>(&self, f: F)
+    where
+        F: 'static + Send + FnOnce(AsyncAppContext),
+    {
+        *self.disconnect_handler.lock() = Some(Box::new(f));
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
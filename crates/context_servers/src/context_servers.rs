@@ -21,8 +21,13 @@ pub fn init(cx: &mut AppContext) {
     ContextServerRegistry::register(cx);
 
     cx.observe_new_views(
-        |workspace: &mut Workspace, _cx: &mut ViewContext<Workspace>| {
+        |workspace: &mut Workspace, cx: &mut ViewContext<Workspace>| {
             workspace.register_action(restart_servers);
+
+            let project = workspace.project().clone();
+            ContextServerManager::global(cx).update(cx, |manager, cx| {
+                manager.register_project(&project, cx);
+            });
         },
     )
     .detach();
@@ -14,16 +14,23 @@
 //! The module also includes initialization logic to set up the context server system
 //! and react to changes in settings.
 
+use anyhow::anyhow;
 use collections::{HashMap, HashSet};
 use command_palette_hooks::CommandPaletteFilter;
-use gpui::{AppContext, AsyncAppContext, Context, EventEmitter, Global, Model, ModelContext, Task};
+use gpui::{
+    AppContext, AsyncAppContext, Context, EntityId, EventEmitter, Global, Model, ModelContext,
+    Subscription, Task, WeakModel,
+};
 use log;
 use parking_lot::RwLock;
+use project::Project;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources, SettingsStore};
 use std::path::Path;
 use std::sync::Arc;
+use url::Url;
+use util::ResultExt as _;
 
 use crate::CONTEXT_SERVERS_NAMESPACE;
 use crate::{
@@ -39,9 +46,26 @@ pub struct ContextServerSettings {
 #[derive(Deserialize, Serialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
 pub struct ServerConfig {
     pub id: String,
-    pub executable: String,
-    pub args: Vec<String>,
-    pub env: Option<HashMap<String, String>>,
+    #[serde(flatten)]
+    pub transport: ServerTransport,
+}
+
+/// How Zed talks to a context server.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(untagged)]
+pub enum ServerTransport {
+    /// A local process, spoken to over its stdin and stdout.
+    Stdio {
+        executable: String,
+        args: Vec<String>,
+        env: Option<HashMap<String, String>>,
+    },
+    /// A shared HTTP service, which sends its messages as server-sent events.
+    Http {
+        url: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
 }
 
 impl Settings for ContextServerSettings {
@@ -72,17 +96,60 @@ impl ContextServer {
         }
     }
 
-    async fn start(&self, cx: &AsyncAppContext) -> anyhow::Result<()> {
+    async fn start(
+        self: &Arc<Self>,
+        manager: WeakModel<ContextServerManager>,
+        cx: &AsyncAppContext,
+    ) -> anyhow::Result<()> {
         log::info!("starting context server {}", self.config.id,);
-        let client = Client::new(
-            client::ContextServerId(self.config.id.clone()),
-            client::ModelContextServerBinary {
-                executable: Path::new(&self.config.executable).to_path_buf(),
-                args: self.config.args.clone(),
-                env: self.config.env.clone(),
-            },
-            cx.clone(),
-        )?;
+        let server_id = client::ContextServerId(self.config.id.clone());
+        let client = match &self.config.transport {
+            ServerTransport::Stdio {
+                executable,
+                args,
+                env,
+            } => Client::new(
+                server_id,
+                client::ModelContextServerBinary {
+                    executable: Path::new(executable).to_path_buf(),
+                    args: args.clone(),
+                    env: env.clone(),
+                },
+                cx.clone(),
+            )?,
+            ServerTransport::Http { url, headers } => Client::new_http(
+                server_id,
+                client::ModelContextServerEndpoint {
+                    url: url.parse()?,
+                    headers: headers.clone(),
+                },
+                cx.update(|cx| cx.http_client())?,
+                cx.clone(),
+            )?,
+        };
+        self.handle_server_requests(&client, manager.clone());
+        // A lost connection to an HTTP service loses the server's session, so the server is
+        // started again, which initializes a new session.
+        if let ServerTransport::Http { .. } = &self.config.transport {
+            let this = Arc::downgrade(self);
+            client.on_disconnect(move |mut cx| {
+                let Some(this) = this.upgrade() else {
+                    return;
+                };
+                manager
+                    .update(&mut cx, |manager, cx| {
+                        // The server may have been stopped or restarted already.
+                        if manager
+                            .get_server(&this.id)
+                            .map_or(false, |server| Arc::ptr_eq(&server, &this))
+                        {
+                            log::info!("context server {} disconnected, restarting it", this.id);
+                            manager.restart_server(&this.id, cx).detach_and_log_err(cx);
+                        }
+                    })
+                    .ok();
+            });
+        }
 
         let protocol = crate::protocol::ModelContextProtocol::new(client);
         let client_info = types::Implementation {
@@ -101,6 +168,44 @@ impl ContextServer {
         Ok(())
     }
 
+    /// Registers the handlers for the requests the server can send back to Zed.
+    ///
+    /// They must be registered before initializing, as servers may send requests as soon as they
+    /// are initialized.
+    fn handle_server_requests(&self, client: &Client, manager: WeakModel<ContextServerManager>) {
+        client.on_request(types::ServerRequestType::ListRoots.as_str(), {
+            let manager = manager.clone();
+            move |_, cx| {
+                let roots = manager.read_with(&cx, |manager, _| manager.roots.clone());
+                Task::ready(roots.and_then(|roots| {
+                    Ok(serde_json::to_value(types::ListRootsResponse { roots })?)
+                }))
+            }
+        });
+
+        let server_id = self.id.clone();
+        client.on_request(
+            types::ServerRequestType::CreateMessage.as_str(),
+            move |params, cx| {
+                let params = match serde_json::from_value::<types::CreateMessageParams>(params) {
+                    Ok(params) => params,
+                    Err(error) => return Task::ready(Err(error.into())),
+                };
+                // The handler is called outside of the manager, so that it can read it.
+                let handler = manager.read_with(&cx, |manager, _| manager.sampling_handler.clone());
+                let response = cx.update(|cx| match handler {
+                    Ok(Some(handler)) => handler(server_id.clone(), params, cx),
+                    Ok(None) => Task::ready(Err(anyhow!("sampling is not supported"))),
+                    Err(error) => Task::ready(Err(error)),
+                });
+                cx.foreground_executor().spawn(async move {
+                    let response = response?.await?;
+                    Ok(serde_json::to_value(response)?)
+                })
+            },
+        );
+    }
+
     async fn stop(&self) -> anyhow::Result<()> {
         let mut client = self.client.write();
         if let Some(protocol) = client.take() {
@@ -117,11 +222,36 @@ impl ContextServer {
 pub struct ContextServerManager {
     servers: HashMap<String, Arc<ContextServer>>,
     pending_servers: HashSet<String>,
+    /// The projects whose worktrees are exposed as roots, with the subscriptions that keep the
+    /// roots up to date.
+    projects: HashMap<EntityId, (WeakModel<Project>, [Subscription; 2])>,
+    roots: Vec<types::Root>,
+    sampling_handler: Option<SamplingHandler>,
+    /// The number of subscribers to each resource, by server id and resource URI.
+    resource_subscriptions: HashMap<(String, Url), usize>,
 }
 
+/// Answers the `sampling/createMessage` requests of the context server with the given id.
+pub type SamplingHandler = Arc<
+    dyn Fn(
+        String,
+        types::CreateMessageParams,
+        &mut AppContext,
+    ) -> Task<anyhow::Result<types::CreateMessageResult>>,
+>;
+
 pub enum Event {
-    ServerStarted { server_id: String },
-    ServerStopped { server_id: String },
+    ServerStarted {
+        server_id: String,
+    },
+    ServerStopped {
+        server_id: String,
+    },
+    /// A resource the server was subscribed to has changed.
+    ResourceUpdated {
+        server_id: String,
+        uri: Url,
+    },
 }
 
 impl Global for ContextServerManager {}
@@ -138,6 +268,10 @@ impl ContextServerManager {
         Self {
             servers: HashMap::default(),
             pending_servers: HashSet::default(),
+            projects: HashMap::default(),
+            roots: Vec::new(),
+            sampling_handler: None,
+            resource_subscriptions: HashMap::default(),
         }
     }
     pub fn global(cx: &AppContext) -> Model<Self> {
@@ -159,8 +293,10 @@ impl ContextServerManager {
             let server_id = server_id.clone();
             cx.spawn(|this, mut cx| async move {
                 let server = Arc::new(ContextServer::new(config));
-                server.start(&cx).await?;
+                server.start(this.clone(), &cx).await?;
+                Self::observe_resource_updates(&server, this.clone());
                 this.update(&mut cx, |this, cx| {
+                    this.resubscribe_to_resources(&server, cx);
                     this.servers.insert(server_id.clone(), server);
                     this.pending_servers.remove(&server_id);
                     cx.emit(Event::ServerStarted {
@@ -191,6 +327,8 @@ impl ContextServerManager {
             }
             this.update(&mut cx, |this, cx| {
                 this.pending_servers.remove(&id);
+                this.resource_subscriptions
+                    .retain(|(server_id, _), _| *server_id != id);
                 cx.emit(Event::ServerStopped {
                     server_id: id.clone(),
                 })
@@ -210,8 +348,10 @@ impl ContextServerManager {
                 server.stop().await?;
                 let config = server.config.clone();
                 let new_server = Arc::new(ContextServer::new(config));
-                new_server.start(&cx).await?;
+                new_server.start(this.clone(), &cx).await?;
+                Self::observe_resource_updates(&new_server, this.clone());
                 this.update(&mut cx, |this, cx| {
+                    this.resubscribe_to_resources(&new_server, cx);
                     this.servers.insert(id.clone(), new_server);
                     cx.emit(Event::ServerStopped {
                         server_id: id.clone(),
@@ -225,6 +365,189 @@ impl ContextServerManager {
         })
     }
 
+    /// Subscribes to updates of the resource with the given URI, which are emitted as
+    /// [`Event::ResourceUpdated`]. The server is only asked once, however many subscribers the
+    /// resource has.
+    pub fn subscribe_to_resource(
+        &mut self,
+        server_id: &str,
+        uri: Url,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let subscribers = self
+            .resource_subscriptions
+            .entry((server_id.to_string(), uri.clone()))
+            .or_default();
+        *subscribers += 1;
+        if *subscribers > 1 {
+            return;
+        }
+
+        let Some(protocol) = self
+            .get_server(server_id)
+            .and_then(|server| server.client.read().clone())
+        else {
+            return;
+        };
+        if protocol.can_subscribe_to_resources() {
+            cx.spawn(|_, _| async move { protocol.subscribe_to_resource(uri).await.log_err() })
+                .detach();
+        }
+    }
+
+    /// Drops a subscription to the resource with the given URI, unsubscribing from its updates
+    /// once it has no subscribers left.
+    pub fn unsubscribe_from_resource(
+        &mut self,
+        server_id: &str,
+        uri: Url,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let key = (server_id.to_string(), uri);
+        let Some(subscribers) = self.resource_subscriptions.get_mut(&key) else {
+            return;
+        };
+        *subscribers -= 1;
+        if *subscribers > 0 {
+            return;
+        }
+        self.resource_subscriptions.remove(&key);
+
+        let Some(protocol) = self
+            .get_server(server_id)
+            .and_then(|server| server.client.read().clone())
+        else {
+            return;
+        };
+        if protocol.can_subscribe_to_resources() {
+            let (_, uri) = key;
+            cx.spawn(|_, _| async move { protocol.unsubscribe_from_resource(uri).await.log_err() })
+                .detach();
+        }
+    }
+
+    /// Asks a newly started server for updates of the resources that are subscribed to, which
+    /// may have been subscribed to before the server started or with its previous session.
+    fn resubscribe_to_resources(&self, server: &ContextServer, cx: &mut ModelContext<Self>) {
+        let Some(protocol) = server.client.read().clone() else {
+            return;
+        };
+        if !protocol.can_subscribe_to_resources() {
+            return;
+        }
+        for uri in self
+            .resource_subscriptions
+            .keys()
+            .filter(|(server_id, _)| *server_id == server.id)
+            .map(|(_, uri)| uri.clone())
+        {
+            let protocol = protocol.clone();
+            cx.spawn(|_, _| async move { protocol.subscribe_to_resource(uri).await.log_err() })
+                .detach();
+        }
+    }
+
+    /// The resources that are subscribed to, such as those in open assistant contexts, by the
+    /// id of their server.
+    pub fn subscribed_resources(&self) -> impl Iterator<Item = (&str, &Url)> {
+        self.resource_subscriptions
+            .keys()
+            .map(|(server_id, uri)| (server_id.as_str(), uri))
+    }
+
+    fn observe_resource_updates(server: &ContextServer, this: WeakModel<Self>) {
+        let Some(protocol) = server.client.read().clone() else {
+            return;
+        };
+
+        let server_id = server.id.clone();
+        protocol.on_notification(
+            types::NotificationType::ResourcesUpdated.as_str(),
+            move |params, mut cx| {
+                let Some(params) =
+                    serde_json::from_value::<types::ResourcesUpdatedParams>(params).log_err()
+                else {
+                    return;
+                };
+                this.update(&mut cx, |_, cx| {
+                    cx.emit(Event::ResourceUpdated {
+                        server_id: server_id.clone(),
+                        uri: params.uri,
+                    })
+                })
+                .ok();
+            },
+        );
+    }
+
+    /// Sets the handler used to answer the sampling requests of context servers.
+    pub fn set_sampling_handler(&mut self, handler: SamplingHandler) {
+        self.sampling_handler = Some(handler);
+    }
+
+    /// The roots that context servers can operate on, one for each visible worktree of the
+    /// local projects.
+    pub fn roots(&self) -> &[types::Root] {
+        &self.roots
+    }
+
+    /// Tracks the worktrees of the given project, exposing them as roots to context servers.
+    pub fn register_project(&mut self, project: &Model<Project>, cx: &mut ModelContext<Self>) {
+        let project_id = project.entity_id();
+        let subscriptions = [
+            cx.subscribe(project, |this, _, event, cx| match event {
+                project::Event::WorktreeAdded | project::Event::WorktreeRemoved(_) => {
+                    this.update_roots(cx)
+                }
+                _ => {}
+            }),
+            cx.observe_release(project, move |this, _, cx| {
+                this.projects.remove(&project_id);
+                this.update_roots(cx);
+            }),
+        ];
+        self.projects
+            .insert(project_id, (project.downgrade(), subscriptions));
+        self.update_roots(cx);
+    }
+
+    fn update_roots(&mut self, cx: &mut ModelContext<Self>) {
+        let mut roots = Vec::new();
+        for project in self
+            .projects
+            .values()
+            .filter_map(|(project, _)| project.upgrade())
+        {
+            let project = project.read(cx);
+            if !project.is_local() {
+                continue;
+            }
+            for worktree in project.visible_worktrees(cx) {
+                let worktree = worktree.read(cx);
+                let Ok(uri) = Url::from_file_path(worktree.abs_path()) else {
+                    continue;
+                };
+                let root = types::Root {
+                    uri,
+                    name: Some(worktree.root_name().to_string()),
+                };
+                if !roots.contains(&root) {
+                    roots.push(root);
+                }
+            }
+        }
+
+        if roots == self.roots {
+            return;
+        }
+        self.roots = roots;
+        for server in self.servers.values() {
+            if let Some(protocol) = server.client.read().as_ref() {
+                protocol.notify_roots_list_changed().log_err();
+            }
+        }
+    }
+
     pub fn servers(&self) -> Vec<Arc<ContextServer>> {
         self.servers.values().cloned().collect()
     }
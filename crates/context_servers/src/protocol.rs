@@ -7,6 +7,9 @@
 
 use anyhow::Result;
 use collections::HashMap;
+use gpui::AsyncAppContext;
+use serde_json::Value;
+use url::Url;
 
 use crate::client::Client;
 use crate::types;
@@ -37,7 +40,10 @@ impl ModelContextProtocol {
             protocol_version: types::ProtocolVersion::VersionString(PROTOCOL_VERSION.to_string()),
             capabilities: types::ClientCapabilities {
                 experimental: None,
-                sampling: None,
+                sampling: Some(serde_json::json!({})),
+                roots: Some(types::RootsCapabilities {
+                    list_changed: Some(true),
+                }),
             },
             client_info,
         };
@@ -131,6 +137,67 @@ impl InitializedContextServerProtocol {
         Ok(response)
     }
 
+    /// List the MCP resource templates.
+    pub async fn list_resource_templates(&self) -> Result<types::ResourceTemplatesListResponse> {
+        self.check_capability(ServerCapability::Resources)?;
+
+        let response: types::ResourceTemplatesListResponse = self
+            .inner
+            .request(types::RequestType::ResourcesTemplatesList.as_str(), ())
+            .await?;
+
+        Ok(response)
+    }
+
+    /// Read the contents of the MCP resource with the given URI.
+    pub async fn read_resource(&self, uri: Url) -> Result<types::ResourcesReadResponse> {
+        self.check_capability(ServerCapability::Resources)?;
+
+        let params = types::ResourcesReadParams { uri };
+        let response: types::ResourcesReadResponse = self
+            .inner
+            .request(types::RequestType::ResourcesRead.as_str(), params)
+            .await?;
+
+        Ok(response)
+    }
+
+    /// Whether the server notifies subscribers when a resource changes.
+    pub fn can_subscribe_to_resources(&self) -> bool {
+        self.initialize
+            .capabilities
+            .resources
+            .as_ref()
+            .and_then(|resources| resources.subscribe)
+            .unwrap_or(false)
+    }
+
+    /// Subscribe to `notifications/resources/updated` for the resource with the given URI.
+    pub async fn subscribe_to_resource(&self, uri: Url) -> Result<()> {
+        self.check_capability(ServerCapability::Resources)?;
+
+        let params = types::ResourcesSubscribeParams { uri };
+        let _: Value = self
+            .inner
+            .request(types::RequestType::ResourcesSubscribe.as_str(), params)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Unsubscribe from updates for the resource with the given URI.
+    pub async fn unsubscribe_from_resource(&self, uri: Url) -> Result<()> {
+        self.check_capability(ServerCapability::Resources)?;
+
+        let params = types::ResourcesUnsubscribeParams { uri };
+        let _: Value = self
+            .inner
+            .request(types::RequestType::ResourcesUnsubscribe.as_str(), params)
+            .await?;
+
+        Ok(())
+    }
+
     /// Executes a prompt with the given arguments and returns the result.
     pub async fn run_prompt<P: AsRef<str>>(
         &self,
@@ -152,6 +219,48 @@ impl InitializedContextServerProtocol {
         Ok(response)
     }
 
+    /// List the MCP tools, following the cursor of each page until the last one.
+    pub async fn list_tools(&self) -> Result<Vec<types::Tool>> {
+        self.check_capability(ServerCapability::Tools)?;
+
+        let mut tools = Vec::new();
+        let mut cursor = None;
+        loop {
+            let params = types::ListToolsParams { cursor };
+            let response: types::ListToolsResponse = self
+                .inner
+                .request(types::RequestType::ListTools.as_str(), params)
+                .await?;
+            tools.extend(response.tools);
+
+            cursor = response.next_cursor;
+            if cursor.is_none() {
+                return Ok(tools);
+            }
+        }
+    }
+
+    /// Executes a tool with the given arguments.
+    pub async fn run_tool<P: AsRef<str>>(
+        &self,
+        tool: P,
+        arguments: Option<HashMap<String, serde_json::Value>>,
+    ) -> Result<types::CallToolResponse> {
+        self.check_capability(ServerCapability::Tools)?;
+
+        let params = types::CallToolParams {
+            name: tool.as_ref().to_string(),
+            arguments,
+        };
+
+        let response: types::CallToolResponse = self
+            .inner
+            .request(types::RequestType::CallTool.as_str(), params)
+            .await?;
+
+        Ok(response)
+    }
+
     pub async fn completion<P: Into<String>>(
         &self,
         reference: types::CompletionReference,
@@ -190,4 +299,19 @@ impl InitializedContextServerProtocol {
     ) -> Result<R> {
         self.inner.request(method, params).await
     }
+
+    pub fn on_notification<F>(&self, method: &'static str, f: F)
+    where
+        F: 'static + Send + FnMut(Value, AsyncAppContext),
+    {
+        self.inner.on_notification(method, f)
+    }
+
+    /// Notify the server that the roots it can operate on have changed.
+    pub fn notify_roots_list_changed(&self) -> Result<()> {
+        self.inner.notify(
+            types::NotificationType::RootsListChanged.as_str(),
+            serde_json::json!({}),
+        )
+    }
 }
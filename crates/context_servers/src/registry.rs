@@ -9,7 +9,10 @@ struct GlobalContextServerRegistry(Arc<ContextServerRegistry>);
 impl Global for GlobalContextServerRegistry {}
 
 pub struct ContextServerRegistry {
-    registry: RwLock<HashMap<String, Vec<Arc<str>>>>,
+    command_registry: RwLock<HashMap<String, Vec<Arc<str>>>>,
+    tool_registry: RwLock<HashMap<String, Vec<Arc<str>>>>,
+    /// The server id and tool name of each registered tool, by the id it was registered under.
+    tools_by_id: RwLock<HashMap<Arc<str>, (String, String)>>,
 }
 
 impl ContextServerRegistry {
@@ -20,13 +23,15 @@ impl ContextServerRegistry {
     pub fn register(cx: &mut AppContext) {
         cx.set_global(GlobalContextServerRegistry(Arc::new(
             ContextServerRegistry {
-                registry: RwLock::new(HashMap::default()),
+                command_registry: RwLock::new(HashMap::default()),
+                tool_registry: RwLock::new(HashMap::default()),
+                tools_by_id: RwLock::new(HashMap::default()),
             },
         )))
     }
 
     pub fn register_command(&self, server_id: String, command_name: &str) {
-        let mut registry = self.registry.write();
+        let mut registry = self.command_registry.write();
         registry
             .entry(server_id)
             .or_default()
@@ -34,14 +39,43 @@ impl ContextServerRegistry {
     }
 
     pub fn unregister_command(&self, server_id: &str, command_name: &str) {
-        let mut registry = self.registry.write();
+        let mut registry = self.command_registry.write();
         if let Some(commands) = registry.get_mut(server_id) {
             commands.retain(|name| name.as_ref() != command_name);
         }
     }
 
     pub fn get_commands(&self, server_id: &str) -> Option<Vec<Arc<str>>> {
-        let registry = self.registry.read();
+        let registry = self.command_registry.read();
+        registry.get(server_id).cloned()
+    }
+
+    /// Records a tool of the given server, by the id it was registered under in the
+    /// assistant's tool registry.
+    pub fn register_tool(&self, server_id: String, tool_id: &str, tool_name: &str) {
+        let tool_id: Arc<str> = tool_id.into();
+        self.tools_by_id
+            .write()
+            .insert(tool_id.clone(), (server_id.clone(), tool_name.to_string()));
+        let mut registry = self.tool_registry.write();
+        registry.entry(server_id).or_default().push(tool_id);
+    }
+
+    pub fn unregister_tool(&self, server_id: &str, tool_id: &str) {
+        self.tools_by_id.write().remove(tool_id);
+        let mut registry = self.tool_registry.write();
+        if let Some(tools) = registry.get_mut(server_id) {
+            tools.retain(|id| id.as_ref() != tool_id);
+        }
+    }
+
+    /// Returns the server id and tool name of the tool registered under the given id.
+    pub fn tool(&self, tool_id: &str) -> Option<(String, String)> {
+        self.tools_by_id.read().get(tool_id).cloned()
+    }
+
+    pub fn get_tools(&self, server_id: &str) -> Option<Vec<Arc<str>>> {
+        let registry = self.tool_registry.read();
         registry.get(server_id).cloned()
     }
 }
@@ -0,0 +1,34 @@
+use context_servers::{
+    client::{Client, ContextServerId, ModelContextServerBinary},
+    protocol::{InitializedContextServerProtocol, ModelContextProtocol},
+    types::Implementation,
+};
+use gpui::TestAppContext;
+
+/// Starts the stub context server and initializes a connection to it, after registering the
+/// client's request handlers with `on_client`.
+pub async fn start_stub_server(
+    cx: &mut TestAppContext,
+    on_client: impl FnOnce(&Client),
+) -> InitializedContextServerProtocol {
+    cx.executor().allow_parking();
+
+    let client = Client::new(
+        ContextServerId("stub".into()),
+        ModelContextServerBinary {
+            executable: env!("CARGO_BIN_EXE_stub_context_server").into(),
+            args: Vec::new(),
+            env: None,
+        },
+        cx.to_async(),
+    )
+    .unwrap();
+    on_client(&client);
+    ModelContextProtocol::new(client)
+        .initialize(Implementation {
+            name: "Zed".into(),
+            version: "0.0.0".into(),
+        })
+        .await
+        .unwrap()
+}
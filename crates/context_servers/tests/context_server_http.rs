@@ -0,0 +1,276 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering::SeqCst},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use collections::HashMap;
+use context_servers::{
+    client::{Client, ContextServerId, ModelContextServerEndpoint},
+    protocol::{ModelContextProtocol, ServerCapability},
+    types::{Implementation, ToolResponseContent},
+};
+use futures::{channel::mpsc, AsyncReadExt as _, TryStreamExt as _};
+use gpui::TestAppContext;
+use http_client::{http, AsyncBody, FakeHttpClient, HttpClient, Response};
+use serde_json::{json, Value};
+
+#[gpui::test]
+async fn test_context_server_over_http(cx: &mut TestAppContext) {
+    let (events_tx, events_rx) = mpsc::unbounded::<std::io::Result<Vec<u8>>>();
+    events_tx
+        .unbounded_send(Ok(
+            b"event: endpoint\ndata: /messages?session=1\n\n".to_vec()
+        ))
+        .unwrap();
+    let events_rx = Mutex::new(Some(events_rx));
+    let posted_methods = Arc::new(Mutex::new(Vec::new()));
+
+    let http_client = FakeHttpClient::create({
+        let posted_methods = posted_methods.clone();
+        move |request| {
+            assert_eq!(request.headers()["Authorization"], "Bearer secret");
+            let events_rx = if request.method() == http::Method::GET {
+                events_rx.lock().unwrap().take()
+            } else {
+                None
+            };
+            let events_tx = events_tx.clone();
+            let posted_methods = posted_methods.clone();
+            async move {
+                if request.method() == http::Method::GET {
+                    assert_eq!(request.uri(), "http://mcp.example/sse");
+                    let events = events_rx.expect("connected twice").into_async_read();
+                    return Ok(Response::new(AsyncBody::from_reader(events)));
+                }
+
+                assert_eq!(request.uri(), "http://mcp.example/messages?session=1");
+                let method = answer(request, &events_tx).await?;
+                posted_methods.lock().unwrap().push(method);
+                anyhow::Ok(Response::builder().status(202).body(AsyncBody::empty())?)
+            }
+        }
+    });
+
+    let client = Client::new_http(
+        ContextServerId("remote".into()),
+        ModelContextServerEndpoint {
+            url: "http://mcp.example/sse".parse().unwrap(),
+            headers: HashMap::from_iter([("Authorization".into(), "Bearer secret".into())]),
+        },
+        http_client as Arc<dyn HttpClient>,
+        cx.to_async(),
+    )
+    .unwrap();
+    assert_eq!(client.name(), "mcp.example");
+
+    let protocol = ModelContextProtocol::new(client)
+        .initialize(Implementation {
+            name: "Zed".into(),
+            version: "0.0.0".into(),
+        })
+        .await
+        .unwrap();
+    assert!(protocol.capable(ServerCapability::Tools));
+
+    let arguments = HashMap::from_iter([("text".to_string(), json!("hello"))]);
+    let response = protocol.run_tool("echo", Some(arguments)).await.unwrap();
+    assert!(matches!(
+        response.content.as_slice(),
+        [ToolResponseContent::Text { text }] if text == "hello"
+    ));
+    assert_eq!(
+        *posted_methods.lock().unwrap(),
+        ["initialize", "notifications/initialized", "tools/call"]
+    );
+}
+
+#[gpui::test]
+async fn test_context_server_http_reconnects(cx: &mut TestAppContext) {
+    let (events_tx, events_rx) = mpsc::unbounded::<std::io::Result<Vec<u8>>>();
+    events_tx
+        .unbounded_send(Ok(
+            b"event: endpoint\ndata: /messages?session=2\n\n".to_vec()
+        ))
+        .unwrap();
+    let events_rx = Mutex::new(Some(events_rx));
+    let connections = Arc::new(AtomicUsize::new(0));
+
+    let http_client = FakeHttpClient::create({
+        let connections = connections.clone();
+        move |request| {
+            let connection = if request.method() == http::Method::GET {
+                Some(connections.fetch_add(1, SeqCst))
+            } else {
+                None
+            };
+            let events_rx = if connection == Some(1) {
+                events_rx.lock().unwrap().take()
+            } else {
+                None
+            };
+            let events_tx = events_tx.clone();
+            async move {
+                match connection {
+                    // The server is still starting up when first connected to.
+                    Some(0) => {
+                        return Ok(Response::builder().status(503).body(AsyncBody::empty())?)
+                    }
+                    Some(_) => {
+                        let events = events_rx.expect("connected too often").into_async_read();
+                        return Ok(Response::new(AsyncBody::from_reader(events)));
+                    }
+                    None => {}
+                }
+
+                assert_eq!(request.uri(), "http://mcp.example/messages?session=2");
+                answer(request, &events_tx).await?;
+                anyhow::Ok(Response::builder().status(202).body(AsyncBody::empty())?)
+            }
+        }
+    });
+
+    let client = Client::new_http(
+        ContextServerId("remote".into()),
+        ModelContextServerEndpoint {
+            url: "http://mcp.example/sse".parse().unwrap(),
+            headers: HashMap::default(),
+        },
+        http_client as Arc<dyn HttpClient>,
+        cx.to_async(),
+    )
+    .unwrap();
+    let initialize = cx.spawn(|_| {
+        ModelContextProtocol::new(client).initialize(Implementation {
+            name: "Zed".into(),
+            version: "0.0.0".into(),
+        })
+    });
+    cx.run_until_parked();
+    assert_eq!(connections.load(SeqCst), 1);
+
+    cx.executor().advance_clock(Duration::from_secs(1));
+    let protocol = initialize.await.unwrap();
+    assert_eq!(connections.load(SeqCst), 2);
+    assert!(protocol.capable(ServerCapability::Tools));
+}
+
+#[gpui::test]
+async fn test_context_server_http_disconnects(cx: &mut TestAppContext) {
+    let (events_tx, events_rx) = mpsc::unbounded::<std::io::Result<Vec<u8>>>();
+    events_tx
+        .unbounded_send(Ok(
+            b"event: endpoint\ndata: /messages?session=3\n\n".to_vec()
+        ))
+        .unwrap();
+    let events_rx = Mutex::new(Some(events_rx));
+    let connections = Arc::new(AtomicUsize::new(0));
+    let posted_methods = Arc::new(Mutex::new(Vec::new()));
+
+    let http_client = FakeHttpClient::create({
+        let events_tx = events_tx.clone();
+        let connections = connections.clone();
+        let posted_methods = posted_methods.clone();
+        move |request| {
+            let events_rx = if request.method() == http::Method::GET {
+                connections.fetch_add(1, SeqCst);
+                events_rx.lock().unwrap().take()
+            } else {
+                None
+            };
+            let events_tx = events_tx.clone();
+            let posted_methods = posted_methods.clone();
+            async move {
+                if request.method() == http::Method::GET {
+                    let events = events_rx.expect("connected twice").into_async_read();
+                    return Ok(Response::new(AsyncBody::from_reader(events)));
+                }
+
+                let method = answer(request, &events_tx).await?;
+                posted_methods.lock().unwrap().push(method);
+                anyhow::Ok(Response::builder().status(202).body(AsyncBody::empty())?)
+            }
+        }
+    });
+
+    let client = Client::new_http(
+        ContextServerId("remote".into()),
+        ModelContextServerEndpoint {
+            url: "http://mcp.example/sse".parse().unwrap(),
+            headers: HashMap::default(),
+        },
+        http_client as Arc<dyn HttpClient>,
+        cx.to_async(),
+    )
+    .unwrap();
+    let disconnected = Arc::new(AtomicUsize::new(0));
+    client.on_disconnect({
+        let disconnected = disconnected.clone();
+        move |_| {
+            disconnected.fetch_add(1, SeqCst);
+        }
+    });
+    let protocol = ModelContextProtocol::new(client)
+        .initialize(Implementation {
+            name: "Zed".into(),
+            version: "0.0.0".into(),
+        })
+        .await
+        .unwrap();
+
+    // The server never answers pings, and drops the stream while one is pending.
+    let ping = cx.spawn(|_| async move { protocol.request::<Value>("ping", json!({})).await });
+    cx.run_until_parked();
+    assert_eq!(
+        *posted_methods.lock().unwrap(),
+        ["initialize", "notifications/initialized", "ping"]
+    );
+    assert_eq!(disconnected.load(SeqCst), 0);
+
+    events_tx
+        .unbounded_send(Err(std::io::ErrorKind::ConnectionReset.into()))
+        .unwrap();
+    cx.run_until_parked();
+    assert!(ping.await.is_err());
+    assert_eq!(disconnected.load(SeqCst), 1);
+
+    // The session is gone with the stream, so the client doesn't connect to a new one.
+    cx.executor().advance_clock(Duration::from_secs(60));
+    cx.run_until_parked();
+    assert_eq!(connections.load(SeqCst), 1);
+}
+
+/// Answers a message POSTed by the client with a server-sent event, returning its method.
+async fn answer(
+    request: http::Request<AsyncBody>,
+    events_tx: &mpsc::UnboundedSender<std::io::Result<Vec<u8>>>,
+) -> anyhow::Result<String> {
+    let mut body = String::new();
+    request.into_body().read_to_string(&mut body).await?;
+    let message = serde_json::from_str::<Value>(&body)?;
+    if let Some(result) = respond(&message) {
+        let response = json!({ "jsonrpc": "2.0", "id": message["id"], "result": result });
+        // Split the data over several lines, as servers are allowed to do.
+        let data = serde_json::to_string_pretty(&response)?.replace('\n', "\ndata: ");
+        let event = format!("event: message\ndata: {data}\n\n");
+        events_tx.unbounded_send(Ok(event.into_bytes()))?;
+    }
+    Ok(message["method"].as_str().unwrap_or_default().to_string())
+}
+
+/// Answers the requests of the client like a context server with an `echo` tool would.
+fn respond(message: &Value) -> Option<Value> {
+    match message["method"].as_str()? {
+        "initialize" => Some(json!({
+            "protocolVersion": "2024-10-07",
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "remote", "version": "0.1.0" },
+        })),
+        "tools/call" => Some(json!({
+            "content": [{ "type": "text", "text": message["params"]["arguments"]["text"] }],
+        })),
+        _ => None,
+    }
+}
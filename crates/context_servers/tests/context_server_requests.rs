@@ -0,0 +1,60 @@
+mod common;
+
+use collections::HashMap;
+use common::start_stub_server;
+use context_servers::{
+    protocol::InitializedContextServerProtocol,
+    types::{self, ToolResponseContent},
+};
+use gpui::{Task, TestAppContext};
+use serde_json::{json, Value};
+use url::Url;
+
+#[gpui::test]
+async fn test_context_server_requests(cx: &mut TestAppContext) {
+    let protocol = start_stub_server(cx, |client| {
+        client.on_request(types::ServerRequestType::ListRoots.as_str(), |_, _| {
+            let response = types::ListRootsResponse {
+                roots: vec![types::Root {
+                    uri: Url::parse("file:///project").unwrap(),
+                    name: Some("project".into()),
+                }],
+            };
+            Task::ready(Ok(serde_json::to_value(response).unwrap()))
+        });
+        client.on_request(types::ServerRequestType::CreateMessage.as_str(), |_, _| {
+            Task::ready(Err(anyhow::anyhow!("declined")))
+        });
+    })
+    .await;
+
+    let response = ask_client(&protocol, types::ServerRequestType::ListRoots.as_str()).await;
+    assert_eq!(
+        response["result"],
+        json!({ "roots": [{ "uri": "file:///project", "name": "project" }] })
+    );
+
+    let response = ask_client(&protocol, types::ServerRequestType::CreateMessage.as_str()).await;
+    assert_eq!(response["error"]["message"], "declined");
+    assert_eq!(response["error"]["code"], -32603);
+
+    let response = ask_client(&protocol, "unknown/method").await;
+    assert_eq!(response["error"]["code"], -32601);
+}
+
+/// Makes the stub server send a request with the given method to the client, and returns the
+/// client's response.
+async fn ask_client(protocol: &InitializedContextServerProtocol, method: &str) -> Value {
+    let arguments = HashMap::from_iter([
+        ("method".to_string(), json!(method)),
+        ("params".to_string(), json!({})),
+    ]);
+    let response = protocol
+        .run_tool("ask_client", Some(arguments))
+        .await
+        .unwrap();
+    match response.content.as_slice() {
+        [ToolResponseContent::Text { text }] => serde_json::from_str(text).unwrap(),
+        content => panic!("unexpected tool response {content:?}"),
+    }
+}
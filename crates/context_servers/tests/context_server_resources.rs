@@ -0,0 +1,52 @@
+mod common;
+
+use common::start_stub_server;
+use context_servers::{
+    protocol::ServerCapability,
+    types::{NotificationType, ResourcesUpdatedParams},
+};
+use futures::{channel::mpsc, StreamExt};
+use gpui::TestAppContext;
+
+#[gpui::test]
+async fn test_context_server_resources(cx: &mut TestAppContext) {
+    let protocol = start_stub_server(cx, |_| {}).await;
+    assert!(protocol.capable(ServerCapability::Resources));
+    assert!(protocol.can_subscribe_to_resources());
+
+    let resources = protocol.list_resources().await.unwrap().resources;
+    assert_eq!(resources.len(), 1);
+    assert_eq!(resources[0].uri.as_str(), "file:///notes.txt");
+    assert_eq!(resources[0].name, "Notes");
+
+    let templates = protocol
+        .list_resource_templates()
+        .await
+        .unwrap()
+        .resource_templates;
+    assert_eq!(templates.len(), 1);
+    assert_eq!(templates[0].uri_template, "file:///{path}");
+
+    let uri = resources[0].uri.clone();
+    let response = protocol.read_resource(uri.clone()).await.unwrap();
+    assert_eq!(response.contents.len(), 1);
+    assert_eq!(
+        response.contents[0].text.as_deref(),
+        Some("Remember the milk\r\n")
+    );
+    assert!(protocol
+        .read_resource("file:///missing.txt".parse().unwrap())
+        .await
+        .is_err());
+
+    let (updates_tx, mut updates_rx) = mpsc::unbounded();
+    protocol.on_notification(
+        NotificationType::ResourcesUpdated.as_str(),
+        move |params, _| {
+            let params = serde_json::from_value::<ResourcesUpdatedParams>(params).unwrap();
+            updates_tx.unbounded_send(params.uri).unwrap();
+        },
+    );
+    protocol.subscribe_to_resource(uri.clone()).await.unwrap();
+    assert_eq!(updates_rx.next().await, Some(uri));
+}
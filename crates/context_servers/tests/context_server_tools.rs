@@ -0,0 +1,34 @@
+mod common;
+
+use collections::HashMap;
+use common::start_stub_server;
+use context_servers::{protocol::ServerCapability, types::ToolResponseContent};
+use gpui::TestAppContext;
+use serde_json::json;
+
+#[gpui::test]
+async fn test_context_server_tools(cx: &mut TestAppContext) {
+    let protocol = start_stub_server(cx, |_| {}).await;
+    assert!(protocol.capable(ServerCapability::Tools));
+    assert!(!protocol.capable(ServerCapability::Prompts));
+
+    // The stub lists each of its tools on a page of its own.
+    let tools = protocol.list_tools().await.unwrap();
+    assert_eq!(tools.len(), 2);
+    assert_eq!(tools[0].name, "echo");
+    assert_eq!(tools[1].name, "ask_client");
+    assert_eq!(tools[0].input_schema["required"], json!(["text"]));
+
+    let arguments = HashMap::from_iter([("text".to_string(), json!("hello"))]);
+    let response = protocol.run_tool("echo", Some(arguments)).await.unwrap();
+    assert_eq!(response.is_error, None);
+    assert!(matches!(
+        response.content.as_slice(),
+        [ToolResponseContent::Text { text }] if text == "hello"
+    ));
+
+    let response = protocol.run_tool("echo", None).await.unwrap();
+    assert_eq!(response.is_error, Some(true));
+
+    assert!(protocol.run_tool("unknown", None).await.is_err());
+}
@@ -1,5 +1,6 @@
 pub mod kvp;
 pub mod query;
+pub mod token_usage;
 
 // Re-export
 pub use anyhow;
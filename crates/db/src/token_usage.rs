@@ -0,0 +1,186 @@
+use sqlez_macros::sql;
+
+use crate::{define_connection, query};
+
+define_connection!(pub static ref TOKEN_USAGE_STORE: TokenUsageStore<()> =
+    &[sql!(
+        CREATE TABLE IF NOT EXISTS token_usage(
+            day TEXT NOT NULL,
+            project TEXT NOT NULL,
+            context_id TEXT NOT NULL,
+            provider TEXT NOT NULL,
+            model TEXT NOT NULL,
+            input_tokens INTEGER NOT NULL,
+            output_tokens INTEGER NOT NULL,
+            cache_creation_input_tokens INTEGER NOT NULL,
+            cache_read_input_tokens INTEGER NOT NULL,
+            PRIMARY KEY(day, project, context_id, provider, model)
+        ) STRICT;
+    )];
+);
+
+/// Tokens consumed by one or more requests made from an assistant context.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TokenUsageRecord {
+    /// The local date the tokens were used on, formatted as `YYYY-MM-DD`.
+    pub day: String,
+    pub project: String,
+    pub context_id: String,
+    pub provider: String,
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+}
+
+/// Token usage summed over a day, project and model.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TokenUsageRow {
+    pub day: String,
+    pub project: String,
+    pub provider: String,
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+}
+
+impl TokenUsageStore {
+    /// Adds the given usage to the totals for its day, project, context and model.
+    pub async fn record_usage(&self, record: TokenUsageRecord) -> anyhow::Result<()> {
+        self.write(move |connection| {
+            let sql_stmt = sql!(
+                INSERT INTO token_usage(
+                    day,
+                    project,
+                    context_id,
+                    provider,
+                    model,
+                    input_tokens,
+                    output_tokens,
+                    cache_creation_input_tokens,
+                    cache_read_input_tokens
+                )
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                ON CONFLICT DO UPDATE SET
+                    input_tokens = input_tokens + ?6,
+                    output_tokens = output_tokens + ?7,
+                    cache_creation_input_tokens = cache_creation_input_tokens + ?8,
+                    cache_read_input_tokens = cache_read_input_tokens + ?9
+            );
+            connection.exec_bound(sql_stmt)?((
+                record.day,
+                record.project,
+                record.context_id,
+                record.provider,
+                record.model,
+                record.input_tokens,
+                record.output_tokens,
+                record.cache_creation_input_tokens,
+                record.cache_read_input_tokens,
+            ))
+        })
+        .await
+    }
+
+    query! {
+        fn usage_rows() -> Result<Vec<(String, String, String, String, u64, u64, u64, u64)>> {
+            SELECT
+                day,
+                project,
+                provider,
+                model,
+                SUM(input_tokens),
+                SUM(output_tokens),
+                SUM(cache_creation_input_tokens),
+                SUM(cache_read_input_tokens)
+            FROM token_usage
+            GROUP BY day, project, provider, model
+            ORDER BY day DESC, project, provider, model
+        }
+    }
+
+    /// Returns the recorded usage, aggregated per day, project and model, most
+    /// recent days first.
+    pub fn usage_by_day(&self) -> anyhow::Result<Vec<TokenUsageRow>> {
+        Ok(self
+            .usage_rows()?
+            .into_iter()
+            .map(
+                |(
+                    day,
+                    project,
+                    provider,
+                    model,
+                    input_tokens,
+                    output_tokens,
+                    cache_creation_input_tokens,
+                    cache_read_input_tokens,
+                )| TokenUsageRow {
+                    day,
+                    project,
+                    provider,
+                    model,
+                    input_tokens,
+                    output_tokens,
+                    cache_creation_input_tokens,
+                    cache_read_input_tokens,
+                },
+            )
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TokenUsageRecord, TokenUsageRow, TokenUsageStore};
+
+    #[gpui::test]
+    async fn test_token_usage() {
+        let db = TokenUsageStore(crate::open_test_db("test_token_usage").await);
+
+        for (day, project, context_id, input_tokens) in [
+            ("2024-10-01", "/a", "context-1", 10),
+            ("2024-10-01", "/a", "context-1", 5),
+            ("2024-10-01", "/a", "context-2", 1),
+            ("2024-10-01", "/b", "context-3", 7),
+            ("2024-10-02", "/a", "context-1", 3),
+        ] {
+            db.record_usage(TokenUsageRecord {
+                day: day.into(),
+                project: project.into(),
+                context_id: context_id.into(),
+                provider: "anthropic".into(),
+                model: "claude".into(),
+                input_tokens,
+                output_tokens: 2,
+                cache_creation_input_tokens: 1,
+                cache_read_input_tokens: 0,
+            })
+            .await
+            .unwrap();
+        }
+
+        let row =
+            |day: &str, project: &str, input_tokens, output_tokens, cache_creation| TokenUsageRow {
+                day: day.into(),
+                project: project.into(),
+                provider: "anthropic".into(),
+                model: "claude".into(),
+                input_tokens,
+                output_tokens,
+                cache_creation_input_tokens: cache_creation,
+                cache_read_input_tokens: 0,
+            };
+        assert_eq!(
+            db.usage_by_day().unwrap(),
+            vec![
+                row("2024-10-02", "/a", 3, 2, 1),
+                row("2024-10-01", "/a", 16, 6, 3),
+                row("2024-10-01", "/b", 7, 2, 1),
+            ]
+        );
+    }
+}
@@ -157,6 +157,38 @@ pub struct DeleteToPreviousWordStart {
 pub struct FoldAtLevel {
     pub level: u32,
 }
+
+/// Moves back through the buffer's undo tree, either by a number of states
+/// or, if `seconds` is non-zero, by an amount of time.
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct UndoTreeEarlier {
+    #[serde(default)]
+    pub steps: usize,
+    #[serde(default)]
+    pub seconds: u64,
+}
+
+/// Moves forward through the buffer's undo tree, either by a number of states
+/// or, if `seconds` is non-zero, by an amount of time.
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct UndoTreeLater {
+    #[serde(default)]
+    pub steps: usize,
+    #[serde(default)]
+    pub seconds: u64,
+}
+
+/// Replaces each selection with the next value of a sequence that begins at
+/// `start`, which is either a number or a single letter, and advances by
+/// `step`.
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct InsertSequence {
+    #[serde(default)]
+    pub start: Option<String>,
+    #[serde(default)]
+    pub step: i64,
+}
+
 impl_actions!(
     editor,
     [
@@ -186,7 +218,10 @@ impl_actions!(
         ToggleCodeActions,
         ToggleComments,
         UnfoldAt,
-        FoldAtLevel
+        FoldAtLevel,
+        UndoTreeEarlier,
+        UndoTreeLater,
+        InsertSequence
     ]
 );
 
@@ -198,8 +233,10 @@ gpui::actions!(
         AcceptPartialInlineCompletion,
         AddSelectionAbove,
         AddSelectionBelow,
+        AlignSelections,
         ApplyAllDiffHunks,
         ApplyDiffHunk,
+        ApplyRenamePreview,
         Backspace,
         Cancel,
         CancelLanguageServerWork,
@@ -233,6 +270,7 @@ gpui::actions!(
         DisplayCursorNames,
         DuplicateLineDown,
         DuplicateLineUp,
+        EvaluateSelections,
         ExpandAllHunkDiffs,
         ExpandMacroRecursively,
         FindAllReferences,
@@ -300,10 +338,13 @@ gpui::actions!(
         RestartLanguageServer,
         RevealInFileManager,
         ReverseLines,
+        ReverseSelections,
         RevertFile,
         ReloadFile,
         RevertSelectedHunks,
         Rewrap,
+        RotateSelectionsBackward,
+        RotateSelectionsForward,
         ScrollCursorBottom,
         ScrollCursorCenter,
         ScrollCursorCenterTopBottom,
@@ -312,11 +353,21 @@ gpui::actions!(
         SelectAllMatches,
         SelectDown,
         SelectEnclosingSymbol,
+        SelectFirstChildSyntaxNode,
         SelectLargerSyntaxNode,
         SelectLeft,
         SelectLine,
+        SelectNextArgument,
+        SelectNextClass,
+        SelectNextFunction,
+        SelectNextSiblingSyntaxNode,
         SelectPageDown,
         SelectPageUp,
+        SelectParentSyntaxNode,
+        SelectPreviousArgument,
+        SelectPreviousClass,
+        SelectPreviousFunction,
+        SelectPreviousSiblingSyntaxNode,
         SelectRight,
         SelectSmallerSyntaxNode,
         SelectToBeginning,
@@ -332,9 +383,13 @@ gpui::actions!(
         ShowInlineCompletion,
         ShowSignatureHelp,
         ShuffleLines,
+        ShuffleSelections,
         SortLinesCaseInsensitive,
         SortLinesCaseSensitive,
+        SortSelections,
         SplitSelectionIntoLines,
+        SwapWithNextSiblingSyntaxNode,
+        SwapWithPreviousSiblingSyntaxNode,
         SwitchSourceHeader,
         Tab,
         TabPrev,
@@ -347,6 +402,7 @@ gpui::actions!(
         ToggleInlineCompletions,
         ToggleLineNumbers,
         ToggleRelativeLineNumbers,
+        ToggleRenameOccurrence,
         ToggleSelectionMenu,
         ToggleSoftWrap,
         ToggleTabBar,
@@ -356,6 +412,7 @@ gpui::actions!(
         UnfoldAll,
         UnfoldLines,
         UnfoldRecursive,
+        UniqueSelections,
         UniqueLinesCaseInsensitive,
         UniqueLinesCaseSensitive,
     ]
@@ -363,4 +420,6 @@ gpui::actions!(
 
 action_as!(outline, ToggleOutline as Toggle);
 
+action_as!(undo_tree, ToggleUndoTree as Toggle);
+
 action_as!(go_to_line, ToggleGoToLine as Toggle);
@@ -0,0 +1,135 @@
+//! A small evaluator for the arithmetic expressions used by
+//! [`crate::Editor::evaluate_selections`].
+
+use std::{iter::Peekable, str::Chars};
+
+/// Evaluates an expression made of numbers, parentheses, and the `+`, `-`,
+/// `*`, `/`, `%` and `^` operators. Returns `None` if the expression can't be
+/// parsed, or if its result isn't a finite number.
+pub(crate) fn evaluate(expression: &str) -> Option<f64> {
+    let mut parser = Parser {
+        chars: expression.chars().peekable(),
+    };
+    let value = parser.parse_sum()?;
+    parser.skip_whitespace();
+    if parser.chars.peek().is_some() || !value.is_finite() {
+        return None;
+    }
+    Some(value)
+}
+
+/// Formats the result of [`evaluate`], omitting the fractional part of
+/// integral results.
+pub(crate) fn format_result(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < (1u64 << 53) as f64 {
+        format!("{}", value as i64)
+    } else {
+        format!("{value}")
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn next_operator(&mut self, operators: &[char]) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.next_if(|c| operators.contains(c))
+    }
+
+    fn parse_sum(&mut self) -> Option<f64> {
+        let mut value = self.parse_product()?;
+        while let Some(operator) = self.next_operator(&['+', '-']) {
+            let rhs = self.parse_product()?;
+            match operator {
+                '+' => value += rhs,
+                _ => value -= rhs,
+            }
+        }
+        Some(value)
+    }
+
+    fn parse_product(&mut self) -> Option<f64> {
+        let mut value = self.parse_unary()?;
+        while let Some(operator) = self.next_operator(&['*', '/', '%']) {
+            let rhs = self.parse_unary()?;
+            match operator {
+                '*' => value *= rhs,
+                '/' => value /= rhs,
+                _ => value %= rhs,
+            }
+        }
+        Some(value)
+    }
+
+    fn parse_unary(&mut self) -> Option<f64> {
+        match self.next_operator(&['+', '-']) {
+            Some('-') => Some(-self.parse_unary()?),
+            Some(_) => self.parse_unary(),
+            None => self.parse_power(),
+        }
+    }
+
+    fn parse_power(&mut self) -> Option<f64> {
+        let base = self.parse_atom()?;
+        if self.next_operator(&['^']).is_some() {
+            // Exponentiation is right-associative, and binds more tightly than
+            // a unary minus on its left but not on its right.
+            let exponent = self.parse_unary()?;
+            Some(base.powf(exponent))
+        } else {
+            Some(base)
+        }
+    }
+
+    fn parse_atom(&mut self) -> Option<f64> {
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&'(').is_some() {
+            let value = self.parse_sum()?;
+            self.skip_whitespace();
+            self.chars.next_if_eq(&')')?;
+            return Some(value);
+        }
+
+        let mut number = String::new();
+        while let Some(c) = self
+            .chars
+            .next_if(|c| c.is_ascii_digit() || *c == '.' || *c == '_')
+        {
+            if c != '_' {
+                number.push(c);
+            }
+        }
+        number.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate() {
+        assert_eq!(evaluate("1 + 2 * 3"), Some(7.));
+        assert_eq!(evaluate("(1 + 2) * 3"), Some(9.));
+        assert_eq!(evaluate("10 / 4"), Some(2.5));
+        assert_eq!(evaluate("10 % 4"), Some(2.));
+        assert_eq!(evaluate("-2 ^ 2"), Some(-4.));
+        assert_eq!(evaluate("2 ^ -1"), Some(0.5));
+        assert_eq!(evaluate("2 ^ 3 ^ 2"), Some(512.));
+        assert_eq!(evaluate(" 1_000 - -1 "), Some(1001.));
+        assert_eq!(evaluate("1 / 0"), None);
+        assert_eq!(evaluate("1 +"), None);
+        assert_eq!(evaluate("(1 + 2"), None);
+        assert_eq!(evaluate("foo"), None);
+        assert_eq!(evaluate(""), None);
+
+        assert_eq!(format_result(7.), "7");
+        assert_eq!(format_result(-2.5), "-2.5");
+    }
+}
@@ -13,6 +13,7 @@
 //!
 //! If you're looking to improve Vim mode, you should check out Vim crate that wraps Editor and overrides its behavior.
 pub mod actions;
+mod arithmetic;
 mod blame_entry_tooltip;
 mod blink_manager;
 mod clangd_ext;
@@ -35,7 +36,9 @@ mod lsp_ext;
 mod mouse_context_menu;
 pub mod movement;
 mod persistence;
+mod persistent_undo;
 mod proposed_changes_editor;
+mod rename_preview;
 mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
@@ -93,7 +96,7 @@ use language::{
     language_settings::{self, all_language_settings, language_settings, InlayHintSettings},
     markdown, point_from_lsp, AutoindentMode, BracketPair, Buffer, Capability, CharKind, CodeLabel,
     CursorShape, Diagnostic, Documentation, IndentKind, IndentSize, Language, OffsetRangeExt,
-    Point, Selection, SelectionGoal, TransactionId,
+    Point, Selection, SelectionGoal, TextObject, TransactionId,
 };
 use language::{
     point_to_lsp, BufferRow, CharClassifier, LanguageServerName, Runnable, RunnableRange,
@@ -151,7 +154,7 @@ use std::{
 };
 pub use sum_tree::Bias;
 use sum_tree::TreeMap;
-use text::{BufferId, OffsetUtf16, Rope};
+use text::{BufferId, OffsetUtf16, Rope, UndoTree};
 use theme::{
     observe_buffer_font_size_adjustment, ActiveTheme, PlayerColor, StatusColors, SyntaxTheme,
     ThemeColors, ThemeSettings,
@@ -160,6 +163,7 @@ use ui::{
     h_flex, prelude::*, ButtonSize, ButtonStyle, Disclosure, IconButton, IconName, IconSize,
     ListItem, Popover, PopoverMenuHandle, Tooltip,
 };
+use unicode_width::UnicodeWidthChar as _;
 use util::{defer, maybe, post_inc, RangeExt, ResultExt, TryFutureExt};
 use workspace::item::{ItemHandle, PreviewTabsSettings};
 use workspace::notifications::{DetachAndPromptErr, NotificationId, NotifyTaskExt};
@@ -589,6 +593,7 @@ pub struct Editor {
     linked_editing_range_task: Option<Task<Option<()>>>,
     linked_edit_ranges: linked_editing_ranges::LinkedEditingRanges,
     pending_rename: Option<RenameState>,
+    rename_preview: Option<rename_preview::RenamePreview>,
     searchable: bool,
     cursor_shape: CursorShape,
     current_line_highlight: Option<CurrentLineHighlight>,
@@ -1993,6 +1998,7 @@ impl Editor {
             document_highlights_task: Default::default(),
             linked_editing_range_task: Default::default(),
             pending_rename: Default::default(),
+            rename_preview: None,
             searchable: true,
             cursor_shape: EditorSettings::get_global(cx)
                 .cursor_shape
@@ -2110,6 +2116,9 @@ impl Editor {
         if self.pending_rename.is_some() {
             key_context.add("renaming");
         }
+        if self.rename_preview.is_some() {
+            key_context.add("rename_preview");
+        }
         if self.context_menu_visible() {
             match self.context_menu.read().as_ref() {
                 Some(ContextMenu::Completions(_)) => {
@@ -6553,6 +6562,230 @@ impl Editor {
         });
     }
 
+    /// Inserts whitespace before each selection so that the selections on
+    /// different lines start at the same column. When lines contain several
+    /// selections, the first selections on each line are aligned, then the
+    /// second ones, and so on.
+    pub fn align_selections(&mut self, _: &AlignSelections, cx: &mut ViewContext<Self>) {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let selections = self.selections.all::<Point>(cx);
+
+        let mut selections_by_row = BTreeMap::<u32, Vec<usize>>::new();
+        for (ix, selection) in selections.iter().enumerate() {
+            selections_by_row
+                .entry(selection.start.row)
+                .or_default()
+                .push(ix);
+        }
+        if selections_by_row.len() < 2 {
+            return;
+        }
+
+        let columns = selections
+            .iter()
+            .map(|selection| {
+                let tab_size = buffer.settings_at(selection.start, cx).tab_size.get() as usize;
+                display_width(
+                    buffer
+                        .text_for_range(Point::new(selection.start.row, 0)..selection.start)
+                        .flat_map(|chunk| chunk.chars()),
+                    tab_size,
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut padding = vec![0; selections.len()];
+        let mut row_shifts = vec![0; selections_by_row.len()];
+        let max_selections_per_row = selections_by_row.values().map(Vec::len).max().unwrap_or(0);
+        for k in 0..max_selections_per_row {
+            let target_column = selections_by_row
+                .values()
+                .zip(&row_shifts)
+                .filter_map(|(row_selections, shift)| {
+                    Some(columns[*row_selections.get(k)?] + shift)
+                })
+                .max()
+                .unwrap_or(0);
+            for (row_selections, shift) in selections_by_row.values().zip(&mut row_shifts) {
+                if let Some(&ix) = row_selections.get(k) {
+                    padding[ix] = target_column - (columns[ix] + *shift);
+                    *shift += padding[ix];
+                }
+            }
+        }
+
+        let mut edits = Vec::new();
+        let mut new_selections = Vec::new();
+        let mut row_delta = HashMap::<u32, u32>::default();
+        for (selection, padding) in selections.into_iter().zip(padding) {
+            let delta = row_delta.entry(selection.start.row).or_default();
+            *delta += padding as u32;
+            let start = Point::new(selection.start.row, selection.start.column + *delta);
+            let end = if selection.end.row == selection.start.row {
+                Point::new(selection.end.row, selection.end.column + *delta)
+            } else {
+                selection.end
+            };
+            if padding > 0 {
+                edits.push((selection.start..selection.start, " ".repeat(padding)));
+            }
+            new_selections.push(Selection {
+                start,
+                end,
+                ..selection
+            });
+        }
+        if edits.is_empty() {
+            return;
+        }
+
+        self.transact(cx, |this, cx| {
+            this.buffer.update(cx, |buffer, cx| {
+                buffer.edit(edits, None, cx);
+            });
+            this.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select(new_selections);
+            });
+        });
+    }
+
+    /// Replaces each selection with the next value of a sequence of numbers or
+    /// letters, in the order in which the selections appear in the buffer.
+    pub fn insert_sequence(&mut self, action: &InsertSequence, cx: &mut ViewContext<Self>) {
+        let step = if action.step == 0 { 1 } else { action.step };
+        let start = action.start.as_deref().unwrap_or("1");
+        if sequence_value(start, 0).is_none() {
+            if let Some(workspace) = self.workspace() {
+                workspace.update(cx, |workspace, cx| {
+                    struct InvalidSequenceStart;
+
+                    workspace.show_toast(
+                        Toast::new(
+                            NotificationId::unique::<InvalidSequenceStart>(),
+                            format!(
+                                "Invalid sequence start {start:?}: expected a number or a letter"
+                            ),
+                        ),
+                        cx,
+                    )
+                });
+            }
+            return;
+        }
+        self.manipulate_selection_texts(cx, |texts| {
+            for (ix, text) in texts.iter_mut().enumerate() {
+                *text = sequence_value(start, step.saturating_mul(ix as i64)).unwrap_or_default();
+            }
+        })
+    }
+
+    pub fn sort_selections(&mut self, _: &SortSelections, cx: &mut ViewContext<Self>) {
+        self.manipulate_selection_texts(cx, |texts| texts.sort())
+    }
+
+    pub fn reverse_selections(&mut self, _: &ReverseSelections, cx: &mut ViewContext<Self>) {
+        self.manipulate_selection_texts(cx, |texts| texts.reverse())
+    }
+
+    pub fn shuffle_selections(&mut self, _: &ShuffleSelections, cx: &mut ViewContext<Self>) {
+        self.manipulate_selection_texts(cx, |texts| texts.shuffle(&mut thread_rng()))
+    }
+
+    /// Clears every selection whose text is the same as an earlier selection's.
+    pub fn unique_selections(&mut self, _: &UniqueSelections, cx: &mut ViewContext<Self>) {
+        self.manipulate_selection_texts(cx, |texts| {
+            let mut seen = HashSet::default();
+            for text in texts.iter_mut() {
+                if !seen.insert(text.clone()) {
+                    text.clear();
+                }
+            }
+        })
+    }
+
+    /// Moves the text of each selection into the next selection, and the text
+    /// of the last selection into the first one.
+    pub fn rotate_selections_forward(
+        &mut self,
+        _: &RotateSelectionsForward,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.manipulate_selection_texts(cx, |texts| texts.rotate_right(1))
+    }
+
+    /// Moves the text of each selection into the previous selection, and the
+    /// text of the first selection into the last one.
+    pub fn rotate_selections_backward(
+        &mut self,
+        _: &RotateSelectionsBackward,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.manipulate_selection_texts(cx, |texts| texts.rotate_left(1))
+    }
+
+    /// Replaces each selected arithmetic expression with its result. Selections
+    /// that don't contain a valid expression are left unchanged.
+    pub fn evaluate_selections(&mut self, _: &EvaluateSelections, cx: &mut ViewContext<Self>) {
+        self.manipulate_selection_texts(cx, |texts| {
+            for text in texts.iter_mut() {
+                if let Some(value) = arithmetic::evaluate(text) {
+                    *text = arithmetic::format_result(value);
+                }
+            }
+        })
+    }
+
+    /// Replaces the text of every selection at once, in a single transaction.
+    /// The callback receives the selections' texts in buffer order, and may
+    /// change them but not their number.
+    fn manipulate_selection_texts<Fn>(&mut self, cx: &mut ViewContext<Self>, mut callback: Fn)
+    where
+        Fn: FnMut(&mut Vec<String>),
+    {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let selections = self.selections.all::<usize>(cx);
+        let old_texts = selections
+            .iter()
+            .map(|selection| {
+                buffer
+                    .text_for_range(selection.start..selection.end)
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        let mut new_texts = old_texts.clone();
+        callback(&mut new_texts);
+        if new_texts.len() != old_texts.len() || new_texts == old_texts {
+            return;
+        }
+
+        let mut edits = Vec::new();
+        let mut new_selections = Vec::new();
+        let mut selection_adjustment = 0isize;
+        for ((selection, old_text), new_text) in
+            selections.into_iter().zip(old_texts).zip(new_texts)
+        {
+            let start = (selection.start as isize + selection_adjustment) as usize;
+            new_selections.push(Selection {
+                start,
+                end: start + new_text.len(),
+                goal: SelectionGoal::None,
+                ..selection
+            });
+            selection_adjustment += new_text.len() as isize - old_text.len() as isize;
+            if new_text != old_text {
+                edits.push((selection.start..selection.end, new_text));
+            }
+        }
+
+        self.transact(cx, |this, cx| {
+            this.buffer.update(cx, |buffer, cx| {
+                buffer.edit(edits, None, cx);
+            });
+            this.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select(new_selections);
+            });
+        });
+    }
+
     pub fn duplicate_line(&mut self, upwards: bool, cx: &mut ViewContext<Self>) {
         let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
         let buffer = &display_map.buffer_snapshot;
@@ -7288,6 +7521,57 @@ impl Editor {
         }
     }
 
+    pub fn undo_tree_earlier(&mut self, action: &UndoTreeEarlier, cx: &mut ViewContext<Self>) {
+        self.travel_undo_tree(cx, |undo_tree| {
+            if action.seconds > 0 {
+                undo_tree.earlier_by_duration(Duration::from_secs(action.seconds))
+            } else {
+                undo_tree.earlier_by_steps(action.steps.max(1))
+            }
+        });
+    }
+
+    pub fn undo_tree_later(&mut self, action: &UndoTreeLater, cx: &mut ViewContext<Self>) {
+        self.travel_undo_tree(cx, |undo_tree| {
+            if action.seconds > 0 {
+                undo_tree.later_by_duration(Duration::from_secs(action.seconds))
+            } else {
+                undo_tree.later_by_steps(action.steps.max(1))
+            }
+        });
+    }
+
+    fn travel_undo_tree(
+        &mut self,
+        cx: &mut ViewContext<Self>,
+        target: impl FnOnce(&UndoTree) -> usize,
+    ) {
+        let Some(buffer) = self.buffer.read(cx).as_singleton() else {
+            return;
+        };
+        let ix = target(buffer.read(cx).undo_tree());
+        self.restore_undo_tree_node(ix, cx);
+    }
+
+    /// Returns the editor's buffer to the state described by the given node of
+    /// its undo tree. Only supported for editors containing a single buffer.
+    pub fn restore_undo_tree_node(&mut self, ix: usize, cx: &mut ViewContext<Self>) -> bool {
+        if self.read_only(cx) {
+            return false;
+        }
+        let Some(buffer) = self.buffer.read(cx).as_singleton() else {
+            return false;
+        };
+
+        let restored = buffer.update(cx, |buffer, cx| buffer.restore_undo_tree_node(ix, cx));
+        if restored {
+            self.request_autoscroll(Autoscroll::fit(), cx);
+            self.unmark_text(cx);
+            self.refresh_inline_completion(true, false, cx);
+        }
+        restored
+    }
+
     pub fn finalize_last_transaction(&mut self, cx: &mut ViewContext<Self>) {
         self.buffer
             .update(cx, |buffer, cx| buffer.finalize_last_transaction(cx));
@@ -9044,6 +9328,211 @@ impl Editor {
         self.select_larger_syntax_node_stack = stack;
     }
 
+    pub fn select_next_sibling_syntax_node(
+        &mut self,
+        _: &SelectNextSiblingSyntaxNode,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.select_syntax_ranges(cx, |buffer, range| {
+            Some(buffer.syntax_sibling_ranges(range, true)?.1)
+        });
+    }
+
+    pub fn select_previous_sibling_syntax_node(
+        &mut self,
+        _: &SelectPreviousSiblingSyntaxNode,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.select_syntax_ranges(cx, |buffer, range| {
+            Some(buffer.syntax_sibling_ranges(range, false)?.1)
+        });
+    }
+
+    pub fn select_parent_syntax_node(
+        &mut self,
+        _: &SelectParentSyntaxNode,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.select_syntax_ranges(cx, |buffer, range| buffer.range_for_syntax_parent(range));
+    }
+
+    pub fn select_first_child_syntax_node(
+        &mut self,
+        _: &SelectFirstChildSyntaxNode,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.select_syntax_ranges(cx, |buffer, range| {
+            buffer.range_for_syntax_first_child(range)
+        });
+    }
+
+    pub fn select_next_function(&mut self, _: &SelectNextFunction, cx: &mut ViewContext<Self>) {
+        self.select_adjacent_text_objects(TextObject::AroundFunction, true, cx);
+    }
+
+    pub fn select_previous_function(
+        &mut self,
+        _: &SelectPreviousFunction,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.select_adjacent_text_objects(TextObject::AroundFunction, false, cx);
+    }
+
+    pub fn select_next_class(&mut self, _: &SelectNextClass, cx: &mut ViewContext<Self>) {
+        self.select_adjacent_text_objects(TextObject::AroundClass, true, cx);
+    }
+
+    pub fn select_previous_class(&mut self, _: &SelectPreviousClass, cx: &mut ViewContext<Self>) {
+        self.select_adjacent_text_objects(TextObject::AroundClass, false, cx);
+    }
+
+    pub fn select_next_argument(&mut self, _: &SelectNextArgument, cx: &mut ViewContext<Self>) {
+        self.select_adjacent_text_objects(TextObject::Argument, true, cx);
+    }
+
+    pub fn select_previous_argument(
+        &mut self,
+        _: &SelectPreviousArgument,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.select_adjacent_text_objects(TextObject::Argument, false, cx);
+    }
+
+    fn select_adjacent_text_objects(
+        &mut self,
+        text_object: TextObject,
+        forward: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.select_syntax_ranges(cx, |buffer, range| {
+            buffer.range_for_adjacent_text_object(range, text_object, forward)
+        });
+    }
+
+    /// Replaces each selection with the range returned by the given callback,
+    /// leaving the selections for which it returns `None` unchanged.
+    fn select_syntax_ranges(
+        &mut self,
+        cx: &mut ViewContext<Self>,
+        mut range_for_selection: impl FnMut(&MultiBufferSnapshot, Range<usize>) -> Option<Range<usize>>,
+    ) {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let old_selections = self.selections.all::<usize>(cx);
+
+        let mut selected_new_range = false;
+        let new_selections = old_selections
+            .into_iter()
+            .map(|selection| {
+                let old_range = selection.start..selection.end;
+                match range_for_selection(&buffer, old_range.clone()) {
+                    Some(new_range) if new_range != old_range => {
+                        selected_new_range = true;
+                        Selection {
+                            id: selection.id,
+                            start: new_range.start,
+                            end: new_range.end,
+                            goal: SelectionGoal::None,
+                            reversed: false,
+                        }
+                    }
+                    _ => selection,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if selected_new_range {
+            self.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select(new_selections);
+            });
+        }
+    }
+
+    pub fn swap_with_next_sibling_syntax_node(
+        &mut self,
+        _: &SwapWithNextSiblingSyntaxNode,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.swap_with_sibling_syntax_node(true, cx);
+    }
+
+    pub fn swap_with_previous_sibling_syntax_node(
+        &mut self,
+        _: &SwapWithPreviousSiblingSyntaxNode,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.swap_with_sibling_syntax_node(false, cx);
+    }
+
+    /// Exchanges the syntax node under each selection with its next or previous
+    /// sibling, such as to reorder a function's parameters, and selects the
+    /// node at its new position.
+    fn swap_with_sibling_syntax_node(&mut self, forward: bool, cx: &mut ViewContext<Self>) {
+        if self.read_only(cx) {
+            return;
+        }
+
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let old_selections = self.selections.all::<usize>(cx);
+
+        let mut swapped_ranges: Vec<Range<usize>> = Vec::new();
+        let mut edits = Vec::new();
+        let mut new_selections = Vec::new();
+        for selection in old_selections {
+            let swap = buffer
+                .syntax_sibling_ranges(selection.start..selection.end, forward)
+                .filter(|(node_range, sibling_range)| {
+                    let swap_range = node_range.start.min(sibling_range.start)
+                        ..node_range.end.max(sibling_range.end);
+                    swapped_ranges
+                        .iter()
+                        .all(|range| range.end <= swap_range.start || range.start >= swap_range.end)
+                });
+            let Some((node_range, sibling_range)) = swap else {
+                new_selections.push(selection);
+                continue;
+            };
+
+            let node_text = buffer
+                .text_for_range(node_range.clone())
+                .collect::<String>();
+            let sibling_text = buffer
+                .text_for_range(sibling_range.clone())
+                .collect::<String>();
+            // Swapping the two texts doesn't change the length of the range that
+            // spans both nodes, so offsets outside of it remain valid.
+            let new_range = if forward {
+                sibling_range.end - node_text.len()..sibling_range.end
+            } else {
+                sibling_range.start..sibling_range.start + node_text.len()
+            };
+            swapped_ranges.push(
+                node_range.start.min(sibling_range.start)..node_range.end.max(sibling_range.end),
+            );
+            edits.push((node_range, sibling_text));
+            edits.push((sibling_range, node_text));
+            new_selections.push(Selection {
+                id: selection.id,
+                start: new_range.start,
+                end: new_range.end,
+                goal: SelectionGoal::None,
+                reversed: false,
+            });
+        }
+        if edits.is_empty() {
+            return;
+        }
+        edits.sort_by_key(|(range, _)| range.start);
+
+        self.transact(cx, |this, cx| {
+            this.buffer.update(cx, |buffer, cx| {
+                buffer.edit(edits, None, cx);
+            });
+            this.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select(new_selections);
+            });
+        });
+    }
+
     fn refresh_runnables(&mut self, cx: &mut ViewContext<Self>) -> Task<()> {
         if !EditorSettings::get_global(cx).gutter.runnables {
             self.clear_tasks();
@@ -10137,6 +10626,16 @@ impl Editor {
                     buffer_highlights
                         .next()
                         .map(|highlight| highlight.start.text_anchor..highlight.end.text_anchor)
+                        .or_else(|| {
+                            // Without a language server, fall back to the identifier under
+                            // the cursor, whose occurrences are found with Tree-sitter.
+                            let snapshot = cursor_buffer.read(cx).snapshot();
+                            let range = snapshot.identifier_range_at(cursor_buffer_offset)?;
+                            Some(
+                                snapshot.anchor_after(range.start)
+                                    ..snapshot.anchor_before(range.end),
+                            )
+                        })
                 })?
             };
             if let Some(rename_range) = rename_range {
@@ -10305,15 +10804,43 @@ impl Editor {
         let old_name = rename.old_name;
         let new_name = rename.editor.read(cx).text(cx);
 
-        let rename = self.semantics_provider.as_ref()?.perform_rename(
-            &buffer,
-            start,
-            new_name.clone(),
-            cx,
-        )?;
+        let provider = self.semantics_provider.clone()?;
+        let rename = provider.perform_rename(&buffer, start, new_name.clone(), cx)?;
 
         Some(cx.spawn(|editor, mut cx| async move {
-            let project_transaction = rename.await?;
+            let project_transaction = rename.await;
+            // When no language server renamed the symbol, preview the occurrences found with
+            // Tree-sitter instead, so that false positives can be deselected.
+            if project_transaction
+                .as_ref()
+                .map_or(true, |transaction| transaction.0.is_empty())
+            {
+                let occurrences =
+                    cx.update(|cx| provider.identifier_occurrences(&buffer, start, cx))?;
+                if let Some(occurrences) = occurrences {
+                    let occurrences = match occurrences.await {
+                        Ok(occurrences) => occurrences,
+                        Err(error) => {
+                            workspace
+                                .update(&mut cx, |workspace, cx| workspace.show_error(&error, cx))
+                                .ok();
+                            return Err(error);
+                        }
+                    };
+                    if !occurrences.is_empty() {
+                        return Self::open_rename_preview(
+                            workspace,
+                            occurrences,
+                            old_name,
+                            new_name,
+                            cx,
+                        )
+                        .await;
+                    }
+                }
+            }
+
+            let project_transaction = project_transaction?;
             Self::open_project_transaction(
                 &editor,
                 workspace,
@@ -13062,6 +13589,13 @@ pub trait SemanticsProvider {
         new_name: String,
         cx: &mut AppContext,
     ) -> Option<Task<Result<ProjectTransaction>>>;
+
+    fn identifier_occurrences(
+        &self,
+        buffer: &Model<Buffer>,
+        position: text::Anchor,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<HashMap<Model<Buffer>, Vec<Range<text::Anchor>>>>>>;
 }
 
 pub trait CompletionProvider {
@@ -13402,6 +13936,17 @@ impl SemanticsProvider for Model<Project> {
             project.perform_rename(buffer.clone(), position, new_name, cx)
         }))
     }
+
+    fn identifier_occurrences(
+        &self,
+        buffer: &Model<Buffer>,
+        position: text::Anchor,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<HashMap<Model<Buffer>, Vec<Range<text::Anchor>>>>>> {
+        Some(self.update(cx, |project, cx| {
+            project.identifier_occurrences(buffer, position, cx)
+        }))
+    }
 }
 
 fn inlay_hint_settings(
@@ -13414,6 +13959,60 @@ fn inlay_hint_settings(
     language_settings(language, file, cx).inlay_hints
 }
 
+/// Returns the value `offset` steps after `start` in a sequence of numbers or
+/// letters. Numeric starts keep their zero-padding, and letters continue past
+/// `z` with `aa`, `ab`, and so on, keeping the case of `start`.
+///
+/// Returns `None` if `start` is neither a number nor a single letter.
+fn sequence_value(start: &str, offset: i64) -> Option<String> {
+    let mut chars = start.chars();
+    if let (Some(letter), None) = (chars.next(), chars.next()) {
+        if letter.is_ascii_alphabetic() {
+            let base = if letter.is_ascii_uppercase() {
+                b'A'
+            } else {
+                b'a'
+            };
+            let mut ix = (letter as u8 - base) as i64 + offset;
+            if ix < 0 {
+                return Some(String::new());
+            }
+            let mut letters = Vec::new();
+            loop {
+                letters.push((base + (ix % 26) as u8) as char);
+                ix = ix / 26 - 1;
+                if ix < 0 {
+                    break;
+                }
+            }
+            return Some(letters.into_iter().rev().collect());
+        }
+    }
+
+    let number = start.parse::<i64>().ok()?;
+    let digits = start.trim_start_matches(['-', '+']);
+    let width = if digits.len() > 1 && digits.starts_with('0') {
+        digits.len()
+    } else {
+        0
+    };
+    let value = number.saturating_add(offset);
+    let sign = if value < 0 { "-" } else { "" };
+    Some(format!("{sign}{:0width$}", value.unsigned_abs()))
+}
+
+/// Returns the number of columns that the given characters occupy when
+/// displayed, counting wide characters as two columns and expanding tabs.
+fn display_width(chars: impl Iterator<Item = char>, tab_size: usize) -> usize {
+    chars.fold(0, |width, c| {
+        if c == '\t' {
+            width + tab_size - width % tab_size
+        } else {
+            width + c.width().unwrap_or(0)
+        }
+    })
+}
+
 fn consume_contiguous_rows(
     contiguous_row_selections: &mut Vec<Selection<Point>>,
     selection: &Selection<Point>,
@@ -34,6 +34,7 @@ pub struct EditorSettings {
     pub auto_signature_help: bool,
     pub show_signature_help_after_edits: bool,
     pub jupyter: Jupyter,
+    pub persistent_undo: PersistentUndo,
 }
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
@@ -90,6 +91,31 @@ pub struct JupyterContent {
     pub enabled: Option<bool>,
 }
 
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub struct PersistentUndo {
+    /// Whether the undo history of saved files is kept across restarts.
+    ///
+    /// Default: true
+    pub enabled: bool,
+    /// The maximum amount of text, in kilobytes, stored in the undo history of a single file.
+    ///
+    /// Default: 1024
+    pub max_size_kb: u64,
+}
+
+#[derive(Default, Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct PersistentUndoContent {
+    /// Whether the undo history of saved files is kept across restarts.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// The maximum amount of text, in kilobytes, stored in the undo history of a single file.
+    /// The oldest entries are dropped when the history exceeds this size.
+    ///
+    /// Default: 1024
+    pub max_size_kb: Option<u64>,
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct Toolbar {
     pub breadcrumbs: bool,
@@ -284,6 +310,9 @@ pub struct EditorSettingsContent {
 
     /// Jupyter REPL settings.
     pub jupyter: Option<JupyterContent>,
+
+    /// Settings for keeping the undo history of saved files across restarts.
+    pub persistent_undo: Option<PersistentUndoContent>,
 }
 
 // Toolbar related settings
@@ -22,7 +22,7 @@ use language::{
     FakeLspAdapter, IndentGuide, LanguageConfig, LanguageConfigOverride, LanguageMatcher,
     LanguageName, Override, ParsedMarkdown, Point,
 };
-use language_settings::{Formatter, FormatterList, IndentGuideSettings};
+use language_settings::{FormatOnSave, Formatter, FormatterList, IndentGuideSettings};
 use multi_buffer::MultiBufferIndentGuide;
 use parking_lot::Mutex;
 use project::FakeFs;
@@ -3539,6 +3539,136 @@ async fn test_manipulate_lines_with_multi_selection(cx: &mut TestAppContext) {
     "});
 }
 
+#[gpui::test]
+async fn test_manipulate_selection_texts(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+
+    cx.set_state("«cˇ», «aˇ», «bˇ»");
+    cx.update_editor(|e, cx| e.sort_selections(&SortSelections, cx));
+    cx.assert_editor_state("«aˇ», «bˇ», «cˇ»");
+    cx.update_editor(|e, cx| e.rotate_selections_forward(&RotateSelectionsForward, cx));
+    cx.assert_editor_state("«cˇ», «aˇ», «bˇ»");
+    cx.update_editor(|e, cx| e.rotate_selections_backward(&RotateSelectionsBackward, cx));
+    cx.assert_editor_state("«aˇ», «bˇ», «cˇ»");
+    cx.update_editor(|e, cx| e.reverse_selections(&ReverseSelections, cx));
+    cx.assert_editor_state("«cˇ», «bˇ», «aˇ»");
+
+    // Each operation is undone at once
+    cx.update_editor(|e, cx| e.undo(&Undo, cx));
+    cx.update_editor(|e, cx| assert_eq!(e.text(cx), "a, b, c"));
+
+    cx.set_state("«xˇ» «yyˇ» «xˇ» «yyˇ»");
+    cx.update_editor(|e, cx| e.unique_selections(&UniqueSelections, cx));
+    cx.assert_editor_state("«xˇ» «yyˇ» ˇ ˇ");
+
+    cx.set_state("«1 + 2ˇ», «fooˇ», «2 * (3 + 4) / 4ˇ»");
+    cx.update_editor(|e, cx| e.evaluate_selections(&EvaluateSelections, cx));
+    cx.assert_editor_state("«3ˇ», «fooˇ», «3.5ˇ»");
+}
+
+#[gpui::test]
+async fn test_insert_sequence(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+
+    cx.set_state("ˇ ˇ «xˇ»");
+    cx.update_editor(|e, cx| e.insert_sequence(&InsertSequence::default(), cx));
+    cx.assert_editor_state("«1ˇ» «2ˇ» «3ˇ»");
+
+    cx.set_state("ˇ ˇ ˇ");
+    cx.update_editor(|e, cx| {
+        e.insert_sequence(
+            &InsertSequence {
+                start: Some("08".into()),
+                step: 2,
+            },
+            cx,
+        )
+    });
+    cx.assert_editor_state("«08ˇ» «10ˇ» «12ˇ»");
+
+    cx.set_state("ˇ ˇ ˇ");
+    cx.update_editor(|e, cx| {
+        e.insert_sequence(
+            &InsertSequence {
+                start: Some("Y".into()),
+                step: 1,
+            },
+            cx,
+        )
+    });
+    cx.assert_editor_state("«Yˇ» «Zˇ» «AAˇ»");
+
+    cx.set_state("ˇ ˇ ˇ");
+    cx.update_editor(|e, cx| {
+        e.insert_sequence(
+            &InsertSequence {
+                start: Some("1".into()),
+                step: -1,
+            },
+            cx,
+        )
+    });
+    cx.assert_editor_state("«1ˇ» «0ˇ» «-1ˇ»");
+
+    // An invalid start is reported instead of being replaced with a default
+    cx.set_state("«aˇ» «bˇ»");
+    cx.update_editor(|e, cx| {
+        e.insert_sequence(
+            &InsertSequence {
+                start: Some("1.5".into()),
+                step: 1,
+            },
+            cx,
+        )
+    });
+    cx.assert_editor_state("«aˇ» «bˇ»");
+}
+
+#[gpui::test]
+async fn test_align_selections(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+
+    cx.set_state(indoc! {"
+        a ˇb
+        abc «dˇ»
+        ˇe
+    "});
+    cx.update_editor(|e, cx| e.align_selections(&AlignSelections, cx));
+    cx.assert_editor_state(indoc! {"
+        a   ˇb
+        abc «dˇ»
+            ˇe
+    "});
+
+    // With several selections per line, each column is aligned in turn
+    cx.set_state(indoc! {"
+        aˇ=1 ˇ# x
+        long ˇ= 2 ˇ# y
+    "});
+    cx.update_editor(|e, cx| e.align_selections(&AlignSelections, cx));
+    cx.assert_editor_state(indoc! {"
+        a    ˇ=1  ˇ# x
+        long ˇ= 2 ˇ# y
+    "});
+
+    // Wide characters are aligned by their display width
+    cx.set_state(indoc! {"
+        日本 ˇx
+        abcde ˇy
+    "});
+    cx.update_editor(|e, cx| e.align_selections(&AlignSelections, cx));
+    cx.assert_editor_state(indoc! {"
+        日本  ˇx
+        abcde ˇy
+    "});
+}
+
 #[gpui::test]
 async fn test_manipulate_text(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -5306,6 +5436,78 @@ async fn test_select_larger_smaller_syntax_node(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_syntax_node_navigation_and_swap(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let language = Arc::new(
+        Language::new(
+            LanguageConfig::default(),
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        )
+        .with_text_object_query(indoc! {r#"
+            (function_item) @function.around
+            (parameter) @parameter.inside
+        "#})
+        .unwrap(),
+    );
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(language), cx));
+    cx.set_state(indoc! {"
+        fn a(«x: u8ˇ», y: u16) {}
+        fn b() {}
+    "});
+    cx.executor().run_until_parked();
+
+    cx.update_editor(|e, cx| e.select_next_sibling_syntax_node(&SelectNextSiblingSyntaxNode, cx));
+    cx.assert_editor_state(indoc! {"
+        fn a(x: u8, «y: u16ˇ») {}
+        fn b() {}
+    "});
+    cx.update_editor(|e, cx| e.select_parent_syntax_node(&SelectParentSyntaxNode, cx));
+    cx.assert_editor_state(indoc! {"
+        fn a«(x: u8, y: u16)ˇ» {}
+        fn b() {}
+    "});
+    cx.update_editor(|e, cx| e.select_first_child_syntax_node(&SelectFirstChildSyntaxNode, cx));
+    cx.assert_editor_state(indoc! {"
+        fn a(«x: u8ˇ», y: u16) {}
+        fn b() {}
+    "});
+
+    cx.update_editor(|e, cx| {
+        e.swap_with_next_sibling_syntax_node(&SwapWithNextSiblingSyntaxNode, cx)
+    });
+    cx.assert_editor_state(indoc! {"
+        fn a(y: u16, «x: u8ˇ») {}
+        fn b() {}
+    "});
+    cx.update_editor(|e, cx| {
+        e.swap_with_previous_sibling_syntax_node(&SwapWithPreviousSiblingSyntaxNode, cx)
+    });
+    cx.assert_editor_state(indoc! {"
+        fn a(«x: u8ˇ», y: u16) {}
+        fn b() {}
+    "});
+
+    cx.update_editor(|e, cx| e.select_next_argument(&SelectNextArgument, cx));
+    cx.assert_editor_state(indoc! {"
+        fn a(x: u8, «y: u16ˇ») {}
+        fn b() {}
+    "});
+    cx.update_editor(|e, cx| e.select_next_function(&SelectNextFunction, cx));
+    cx.assert_editor_state(indoc! {"
+        fn a(x: u8, y: u16) {}
+        «fn b() {}ˇ»
+    "});
+    cx.update_editor(|e, cx| e.select_previous_function(&SelectPreviousFunction, cx));
+    cx.assert_editor_state(indoc! {"
+        «fn a(x: u8, y: u16) {}ˇ»
+        fn b() {}
+    "});
+}
+
 #[gpui::test]
 async fn test_autoindent_selections(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -7017,6 +7219,163 @@ async fn test_range_format_during_save(cx: &mut gpui::TestAppContext) {
     save.await;
 }
 
+#[gpui::test]
+async fn test_format_modified_lines_on_save(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.format_on_save = Some(FormatOnSave::ModifiedLines);
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_file("/file.rs", "one\ntwo\nthree\nfour\n".into())
+        .await;
+
+    let project = Project::test(fs, ["/file.rs".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                document_formatting_provider: Some(lsp::OneOf::Left(true)),
+                document_range_formatting_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/file.rs", cx))
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, cx| {
+        buffer.set_diff_base(Some("one\ntwo\nthree\nfour\n".into()), cx)
+    });
+
+    cx.executor().start_waiting();
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.handle_request::<lsp::request::Formatting, _, _>(move |_, _| async move {
+        panic!("the whole buffer should not be formatted");
+    });
+    let requested_ranges = Arc::new(Mutex::new(Vec::new()));
+    fake_server.handle_request::<lsp::request::RangeFormatting, _, _>({
+        let requested_ranges = requested_ranges.clone();
+        move |params, _| {
+            requested_ranges.lock().push(params.range);
+            async move { Ok(Some(vec![])) }
+        }
+    });
+
+    let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+    let (editor, cx) = cx.add_window_view(|cx| build_editor(buffer, cx));
+    editor.update(cx, |editor, cx| {
+        editor.set_text("one\nTWO\nthree\nfour\nfive\n", cx)
+    });
+
+    let save = editor
+        .update(cx, |editor, cx| editor.save(true, project.clone(), cx))
+        .unwrap();
+    cx.executor().start_waiting();
+    save.await;
+    assert_eq!(
+        *requested_ranges.lock(),
+        [
+            lsp::Range::new(lsp::Position::new(1, 0), lsp::Position::new(1, 3)),
+            lsp::Range::new(lsp::Position::new(4, 0), lsp::Position::new(4, 4)),
+        ]
+    );
+}
+
+#[gpui::test]
+async fn test_format_modified_lines_with_chained_formatters(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.format_on_save = Some(FormatOnSave::ModifiedLines);
+        settings.defaults.formatter =
+            Some(language_settings::SelectedFormatter::List(FormatterList(
+                vec![
+                    Formatter::LanguageServer { name: None },
+                    Formatter::LanguageServer { name: None },
+                ]
+                .into(),
+            )));
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_file("/file.rs", "zero\none\ntwo\nthree\n".into())
+        .await;
+
+    let project = Project::test(fs, ["/file.rs".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                document_formatting_provider: Some(lsp::OneOf::Left(true)),
+                document_range_formatting_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/file.rs", cx))
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, cx| {
+        buffer.set_diff_base(Some("zero\none\ntwo\nthree\n".into()), cx)
+    });
+
+    cx.executor().start_waiting();
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.handle_request::<lsp::request::Formatting, _, _>(move |_, _| async move {
+        panic!("the whole buffer should not be formatted");
+    });
+    let requested_ranges = Arc::new(Mutex::new(Vec::new()));
+    fake_server.handle_request::<lsp::request::RangeFormatting, _, _>({
+        let requested_ranges = requested_ranges.clone();
+        move |params, _| {
+            requested_ranges.lock().push(params.range);
+            // The first formatter grows the modified line, which the second one has to format.
+            let edits = if params.range
+                == lsp::Range::new(lsp::Position::new(1, 0), lsp::Position::new(1, 3))
+            {
+                vec![lsp::TextEdit::new(params.range, "TWO\nTWO_AGAIN".into())]
+            } else {
+                vec![]
+            };
+            async move { Ok(Some(edits)) }
+        }
+    });
+
+    let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+    let (editor, cx) = cx.add_window_view(|cx| build_editor(buffer, cx));
+    editor.update(cx, |editor, cx| editor.set_text("one\nTWO\nthree\n", cx));
+
+    let save = editor
+        .update(cx, |editor, cx| editor.save(true, project.clone(), cx))
+        .unwrap();
+    cx.executor().start_waiting();
+    save.await;
+    assert_eq!(
+        *requested_ranges.lock(),
+        [
+            // The deleted line is formatted where it used to be.
+            lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(0, 0)),
+            lsp::Range::new(lsp::Position::new(1, 0), lsp::Position::new(1, 3)),
+            lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(0, 0)),
+            lsp::Range::new(lsp::Position::new(1, 0), lsp::Position::new(2, 9)),
+        ]
+    );
+    assert_eq!(
+        editor.update(cx, |editor, cx| editor.text(cx)),
+        "one\nTWO\nTWO_AGAIN\nthree\n"
+    );
+}
+
 #[gpui::test]
 async fn test_document_format_manual_trigger(cx: &mut gpui::TestAppContext) {
     init_test(cx, |settings| {
@@ -13098,6 +13457,122 @@ async fn test_goto_definition_with_find_all_references_fallback(cx: &mut gpui::T
     });
 }
 
+#[gpui::test]
+async fn test_rename_without_language_server(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/a",
+        json!({
+            "lib.rs": "// count\npub fn count() -> usize {\n    0\n}\n",
+            "main.rs": "fn main() {\n    let count = lib::count();\n}\n",
+        }),
+    )
+    .await;
+    let project = Project::test(fs, ["/a".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(Arc::new(
+        Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        )
+        .with_highlights_query(
+            r#"
+            (identifier) @variable
+            (line_comment) @comment
+            "#,
+        )
+        .unwrap(),
+    ));
+
+    let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
+    let editor = workspace
+        .update(cx, |workspace, cx| {
+            workspace.open_path((worktree_id, "main.rs"), None, true, cx)
+        })
+        .unwrap()
+        .await
+        .unwrap()
+        .downcast::<Editor>()
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    editor.update(cx, |editor, cx| {
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(1, 9)..Point::new(1, 9)])
+        });
+    });
+    editor
+        .update(cx, |editor, cx| editor.rename(&Rename, cx))
+        .unwrap()
+        .await
+        .unwrap();
+    editor.update(cx, |editor, cx| {
+        let rename = editor.pending_rename().unwrap();
+        assert_eq!(rename.old_name.as_ref(), "count");
+        rename
+            .editor
+            .clone()
+            .update(cx, |rename_editor, cx| rename_editor.set_text("total", cx));
+    });
+    editor
+        .update(cx, |editor, cx| editor.confirm_rename(&ConfirmRename, cx))
+        .unwrap()
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    // The occurrences are previewed rather than renamed, and the one in the comment is skipped.
+    let preview = workspace
+        .update(cx, |workspace, cx| workspace.active_item_as::<Editor>(cx))
+        .unwrap()
+        .unwrap();
+    assert_ne!(preview, editor);
+    preview.update(cx, |preview, cx| {
+        assert_eq!(
+            preview.text(cx),
+            "// count\npub fn count() -> usize {\n    0\n}\nfn main() {\n    let count = lib::count();\n}\n"
+        );
+        let snapshot = preview.buffer().read(cx).snapshot(cx);
+        assert_eq!(
+            preview
+                .background_highlights
+                .values()
+                .flat_map(|(_, ranges)| ranges.iter())
+                .map(|range| range.to_point(&snapshot))
+                .collect::<Vec<_>>(),
+            [
+                Point::new(1, 7)..Point::new(1, 12),
+                Point::new(5, 8)..Point::new(5, 13),
+                Point::new(5, 21)..Point::new(5, 26),
+            ]
+        );
+
+        // Deselect the function definition in the library, then apply the rename.
+        preview.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(1, 9)..Point::new(1, 9)])
+        });
+        preview.toggle_rename_occurrence(&ToggleRenameOccurrence, cx);
+        preview.apply_rename_preview(&ApplyRenamePreview, cx);
+        assert_eq!(
+            preview.text(cx),
+            "// count\npub fn count() -> usize {\n    0\n}\nfn main() {\n    let total = lib::total();\n}\n"
+        );
+    });
+}
+
 fn empty_range(row: usize, column: usize) -> Range<DisplayPoint> {
     let point = DisplayPoint::new(DisplayRow(row as u32), column as u32);
     point..point
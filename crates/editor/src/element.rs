@@ -194,6 +194,15 @@ impl EditorElement {
         register_action(view, cx, Editor::sort_lines_case_insensitive);
         register_action(view, cx, Editor::reverse_lines);
         register_action(view, cx, Editor::shuffle_lines);
+        register_action(view, cx, Editor::align_selections);
+        register_action(view, cx, Editor::insert_sequence);
+        register_action(view, cx, Editor::sort_selections);
+        register_action(view, cx, Editor::reverse_selections);
+        register_action(view, cx, Editor::shuffle_selections);
+        register_action(view, cx, Editor::unique_selections);
+        register_action(view, cx, Editor::rotate_selections_forward);
+        register_action(view, cx, Editor::rotate_selections_backward);
+        register_action(view, cx, Editor::evaluate_selections);
         register_action(view, cx, Editor::convert_to_upper_case);
         register_action(view, cx, Editor::convert_to_lower_case);
         register_action(view, cx, Editor::convert_to_title_case);
@@ -219,6 +228,8 @@ impl EditorElement {
         register_action(view, cx, Editor::copy);
         register_action(view, cx, Editor::paste);
         register_action(view, cx, Editor::undo);
+        register_action(view, cx, Editor::undo_tree_earlier);
+        register_action(view, cx, Editor::undo_tree_later);
         register_action(view, cx, Editor::redo);
         register_action(view, cx, Editor::move_page_up);
         register_action(view, cx, Editor::move_page_down);
@@ -292,6 +303,18 @@ impl EditorElement {
         register_action(view, cx, Editor::toggle_comments);
         register_action(view, cx, Editor::select_larger_syntax_node);
         register_action(view, cx, Editor::select_smaller_syntax_node);
+        register_action(view, cx, Editor::select_next_sibling_syntax_node);
+        register_action(view, cx, Editor::select_previous_sibling_syntax_node);
+        register_action(view, cx, Editor::select_parent_syntax_node);
+        register_action(view, cx, Editor::select_first_child_syntax_node);
+        register_action(view, cx, Editor::swap_with_next_sibling_syntax_node);
+        register_action(view, cx, Editor::swap_with_previous_sibling_syntax_node);
+        register_action(view, cx, Editor::select_next_function);
+        register_action(view, cx, Editor::select_previous_function);
+        register_action(view, cx, Editor::select_next_class);
+        register_action(view, cx, Editor::select_previous_class);
+        register_action(view, cx, Editor::select_next_argument);
+        register_action(view, cx, Editor::select_previous_argument);
         register_action(view, cx, Editor::select_enclosing_symbol);
         register_action(view, cx, Editor::move_to_enclosing_bracket);
         register_action(view, cx, Editor::undo_selection);
@@ -423,6 +446,8 @@ impl EditorElement {
                 cx.propagate();
             }
         });
+        register_action(view, cx, Editor::toggle_rename_occurrence);
+        register_action(view, cx, Editor::apply_rename_preview);
         register_action(view, cx, |editor, action, cx| {
             if let Some(task) = editor.find_all_references(action, cx) {
                 task.detach_and_log_err(cx);
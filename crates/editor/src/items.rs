@@ -1,6 +1,7 @@
 use crate::{
     editor_settings::SeedQuerySetting,
     persistence::{SerializedEditor, DB},
+    persistent_undo,
     scroll::ScrollAnchor,
     Anchor, Autoscroll, Editor, EditorEvent, EditorSettings, ExcerptId, ExcerptRange, MultiBuffer,
     MultiBufferSnapshot, NavigationData, SearchWithinRange, ToPoint as _,
@@ -1174,6 +1175,7 @@ impl ProjectItem for Editor {
         buffer: Model<Buffer>,
         cx: &mut ViewContext<Self>,
     ) -> Self {
+        persistent_undo::track_buffer(&buffer, &project, cx);
         Self::for_buffer(buffer, Some(project), cx)
     }
 }
@@ -1298,9 +1300,10 @@ impl SearchableItem for Editor {
         query: &SearchQuery,
         cx: &mut ViewContext<Self>,
     ) {
-        let text = self.buffer.read(cx);
-        let text = text.snapshot(cx);
-        let text = text.text_for_range(identifier.clone()).collect::<Vec<_>>();
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let text = snapshot
+            .text_for_range(identifier.clone())
+            .collect::<Vec<_>>();
         let text: Cow<_> = if text.len() == 1 {
             text.first().cloned().unwrap().into()
         } else {
@@ -1308,7 +1311,7 @@ impl SearchableItem for Editor {
             joined_chunks.into()
         };
 
-        if let Some(replacement) = query.replacement_for(&text) {
+        if let Some(replacement) = replacement_for_match(&text, identifier, &snapshot, query) {
             self.transact(cx, |this, cx| {
                 this.edit([(identifier.clone(), Arc::from(&*replacement))], cx);
             });
@@ -1320,11 +1323,10 @@ impl SearchableItem for Editor {
         query: &SearchQuery,
         cx: &mut ViewContext<Self>,
     ) {
-        let text = self.buffer.read(cx);
-        let text = text.snapshot(cx);
+        let snapshot = self.buffer.read(cx).snapshot(cx);
         let mut edits = vec![];
         for m in matches {
-            let text = text.text_for_range(m.clone()).collect::<Vec<_>>();
+            let text = snapshot.text_for_range(m.clone()).collect::<Vec<_>>();
             let text: Cow<_> = if text.len() == 1 {
                 text.first().cloned().unwrap().into()
             } else {
@@ -1332,7 +1334,7 @@ impl SearchableItem for Editor {
                 joined_chunks.into()
             };
 
-            if let Some(replacement) = query.replacement_for(&text) {
+            if let Some(replacement) = replacement_for_match(&text, m, &snapshot, query) {
                 edits.push((m.clone(), Arc::from(&*replacement)));
             }
         }
@@ -1482,6 +1484,26 @@ impl SearchableItem for Editor {
     }
 }
 
+/// Structural replacements are filled in from the captures of the match, so they need to
+/// look at the buffer the match is in.
+fn replacement_for_match<'a>(
+    text: &'a str,
+    range: &Range<Anchor>,
+    snapshot: &MultiBufferSnapshot,
+    query: &SearchQuery,
+) -> Option<Cow<'a, str>> {
+    if query.is_structural() {
+        use language::ToOffset as _;
+
+        if let Some(buffer) = snapshot.buffer_for_excerpt(range.start.excerpt_id) {
+            let range =
+                range.start.text_anchor.to_offset(buffer)..range.end.text_anchor.to_offset(buffer);
+            return query.replacement_for_range(buffer, range, text);
+        }
+    }
+    query.replacement_for(text)
+}
+
 pub fn active_match_index(
     ranges: &[Range<Anchor>],
     cursor: &Anchor,
@@ -90,6 +90,12 @@ define_connection!(
     //   mtime_seconds: Option<i64>,
     //   mtime_nanos: Option<i32>,
     // )
+    //
+    // buffer_histories(
+    //   path: PathBuf,
+    //   content_hash: String,
+    //   history: String,
+    // )
     pub static ref DB: EditorDb<WorkspaceDb> =
         &[sql! (
             CREATE TABLE editors(
@@ -137,6 +143,13 @@ define_connection!(
             ALTER TABLE editors ADD COLUMN mtime_seconds INTEGER DEFAULT NULL;
             ALTER TABLE editors ADD COLUMN mtime_nanos INTEGER DEFAULT NULL;
         ),
+        sql! (
+            CREATE TABLE buffer_histories (
+                path BLOB NOT NULL PRIMARY KEY,
+                content_hash TEXT NOT NULL,
+                history TEXT NOT NULL
+            ) STRICT;
+        ),
         ];
 );
 
@@ -191,6 +204,34 @@ impl EditorDb {
         }
     }
 
+    // Returns the content hash and the serialized undo history
+    query! {
+        pub fn get_buffer_history(path: PathBuf) -> Result<Option<(String, String)>> {
+            SELECT content_hash, history
+            FROM buffer_histories
+            WHERE path = ?
+        }
+    }
+
+    query! {
+        pub async fn save_buffer_history(path: PathBuf, content_hash: String, history: String) -> Result<()> {
+            INSERT INTO buffer_histories
+                (path, content_hash, history)
+            VALUES
+                (?1, ?2, ?3)
+            ON CONFLICT DO UPDATE SET
+                content_hash = ?2,
+                history = ?3
+        }
+    }
+
+    query! {
+        pub async fn delete_buffer_history(path: PathBuf) -> Result<()> {
+            DELETE FROM buffer_histories
+            WHERE path = ?
+        }
+    }
+
     pub async fn delete_unloaded_items(
         &self,
         workspace: WorkspaceId,
@@ -298,4 +339,29 @@ mod tests {
             .unwrap();
         assert_eq!(have, serialized_editor);
     }
+
+    #[gpui::test]
+    async fn test_save_and_get_buffer_history() {
+        let path = PathBuf::from("/tmp/buffer-history-test.txt");
+        assert_eq!(DB.get_buffer_history(path.clone()).unwrap(), None);
+
+        DB.save_buffer_history(path.clone(), "hash-1".into(), "{}".into())
+            .await
+            .unwrap();
+        assert_eq!(
+            DB.get_buffer_history(path.clone()).unwrap(),
+            Some(("hash-1".into(), "{}".into()))
+        );
+
+        DB.save_buffer_history(path.clone(), "hash-2".into(), "[]".into())
+            .await
+            .unwrap();
+        assert_eq!(
+            DB.get_buffer_history(path.clone()).unwrap(),
+            Some(("hash-2".into(), "[]".into()))
+        );
+
+        DB.delete_buffer_history(path.clone()).await.unwrap();
+        assert_eq!(DB.get_buffer_history(path).unwrap(), None);
+    }
 }
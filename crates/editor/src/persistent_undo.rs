@@ -0,0 +1,242 @@
+use std::path::PathBuf;
+
+use collections::HashMap;
+use gpui::{AppContext, EntityId, Global, Model, Subscription, WeakModel};
+use language::{Buffer, BufferEvent, Rope, SerializedHistory};
+use project::Project;
+use settings::Settings;
+use sha2::{Digest, Sha256};
+
+use crate::{persistence::DB, EditorSettings};
+
+/// Buffers whose undo history is being persisted, along with the subscription
+/// that saves their history whenever they're saved.
+#[derive(Default)]
+struct PersistentUndoBuffers(HashMap<EntityId, Subscription>);
+
+impl Global for PersistentUndoBuffers {}
+
+/// Restores the undo history that was stored the last time the given buffer
+/// was saved, and starts storing its history on every subsequent save.
+pub(crate) fn track_buffer(buffer: &Model<Buffer>, project: &Model<Project>, cx: &mut AppContext) {
+    if !EditorSettings::get_global(cx).persistent_undo.enabled {
+        return;
+    }
+    let buffer_id = buffer.entity_id();
+    if cx
+        .default_global::<PersistentUndoBuffers>()
+        .0
+        .contains_key(&buffer_id)
+    {
+        return;
+    }
+    let Some(abs_path) = local_abs_path(buffer, cx) else {
+        return;
+    };
+
+    restore_history(buffer, project, abs_path, cx);
+
+    let subscription = cx.subscribe(buffer, |buffer, event, cx| {
+        if let BufferEvent::Saved = event {
+            save_history(buffer, cx);
+        }
+    });
+    cx.observe_release(buffer, move |_, cx| {
+        cx.default_global::<PersistentUndoBuffers>()
+            .0
+            .remove(&buffer_id);
+    })
+    .detach();
+    cx.default_global::<PersistentUndoBuffers>()
+        .0
+        .insert(buffer_id, subscription);
+}
+
+fn local_abs_path(buffer: &Model<Buffer>, cx: &AppContext) -> Option<PathBuf> {
+    let file = buffer.read(cx).file()?.as_local()?;
+    Some(file.abs_path(cx))
+}
+
+fn content_hash(text: &Rope) -> String {
+    let mut hasher = Sha256::new();
+    for chunk in text.chunks() {
+        hasher.update(chunk.as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+fn is_pristine(buffer: &Buffer) -> bool {
+    !buffer.is_dirty() && buffer.peek_undo_stack().is_none() && buffer.peek_redo_stack().is_none()
+}
+
+/// Whether the project's buffers are replicated to collaborators, in which case
+/// history can't be restored: it's rebuilt without broadcasting its operations.
+fn is_shared(project: &WeakModel<Project>, cx: &AppContext) -> bool {
+    project
+        .upgrade()
+        .map_or(true, |project| project.read(cx).is_shared())
+}
+
+/// Loads and verifies the stored history in the background, then rebuilds it
+/// on the buffer if the buffer hasn't been edited in the meantime.
+fn restore_history(
+    buffer: &Model<Buffer>,
+    project: &Model<Project>,
+    abs_path: PathBuf,
+    cx: &mut AppContext,
+) {
+    let project = project.downgrade();
+    let (text, version) = {
+        let buffer = buffer.read(cx);
+        if !is_pristine(buffer) || is_shared(&project, cx) {
+            return;
+        }
+        (buffer.as_rope().clone(), buffer.version())
+    };
+
+    let load_history = cx.background_executor().spawn(async move {
+        let Some((stored_hash, history)) = DB.get_buffer_history(abs_path.clone())? else {
+            return anyhow::Ok(None);
+        };
+        if content_hash(&text) != stored_hash {
+            // The file was changed outside of Zed since its history was stored.
+            DB.delete_buffer_history(abs_path).await?;
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str::<SerializedHistory>(&history)?))
+    });
+
+    let buffer = buffer.downgrade();
+    cx.spawn(|mut cx| async move {
+        let Some(history) = load_history.await? else {
+            return Ok(());
+        };
+        buffer.update(&mut cx, |buffer, cx| {
+            if buffer.version() != version || !is_pristine(buffer) || is_shared(&project, cx) {
+                return Ok(());
+            }
+            buffer.restore_history(&history)
+        })?
+    })
+    .detach_and_log_err(cx);
+}
+
+fn save_history(buffer: Model<Buffer>, cx: &mut AppContext) {
+    let settings = EditorSettings::get_global(cx).persistent_undo;
+    if !settings.enabled {
+        return;
+    }
+    let Some(abs_path) = local_abs_path(&buffer, cx) else {
+        return;
+    };
+
+    let text = buffer.read(cx).as_rope().clone();
+    let history = buffer.read(cx).serialize_history(cx);
+    let max_len = (settings.max_size_kb as usize).saturating_mul(1024);
+    cx.background_executor()
+        .spawn(async move {
+            let mut history = history.await;
+            truncate_history(&mut history, max_len);
+            if history.is_empty() {
+                return DB.delete_buffer_history(abs_path).await;
+            }
+
+            let content_hash = content_hash(&text);
+            let history = serde_json::to_string(&history)?;
+            DB.save_buffer_history(abs_path, content_hash, history)
+                .await
+        })
+        .detach_and_log_err(cx);
+}
+
+/// Drops the oldest undo entries, and then the furthest redo entries, until
+/// the history fits within the given number of bytes.
+fn truncate_history(history: &mut SerializedHistory, max_len: usize) {
+    let mut len = history.text_len();
+    while len > max_len {
+        let dropped = if history.undo_stack.is_empty() {
+            history.redo_stack.pop()
+        } else {
+            Some(history.undo_stack.remove(0))
+        };
+        let Some(dropped) = dropped else {
+            break;
+        };
+        len -= dropped.text_len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor_tests::init_test;
+    use gpui::TestAppContext;
+    use language::{SerializedEdit, SerializedTransaction};
+    use project::FakeFs;
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_restore_history(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/persistent-undo",
+            json!({ "local.txt": "one two", "shared.txt": "one two" }),
+        )
+        .await;
+        let history = SerializedHistory {
+            undo_stack: vec![SerializedTransaction {
+                edits: vec![SerializedEdit {
+                    range: 3..3,
+                    old_text: String::new(),
+                    new_text: " two".into(),
+                }],
+            }],
+            redo_stack: Vec::new(),
+        };
+        for path in ["/persistent-undo/local.txt", "/persistent-undo/shared.txt"] {
+            DB.save_buffer_history(
+                path.into(),
+                content_hash(&Rope::from("one two")),
+                serde_json::to_string(&history).unwrap(),
+            )
+            .await
+            .unwrap();
+        }
+
+        let project = Project::test(fs.clone(), ["/persistent-undo".as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/persistent-undo/local.txt", cx)
+            })
+            .await
+            .unwrap();
+        cx.update(|cx| track_buffer(&buffer, &project, cx));
+        cx.run_until_parked();
+        buffer.update(cx, |buffer, cx| {
+            assert!(!buffer.is_dirty());
+            buffer.undo(cx);
+            assert_eq!(buffer.text(), "one");
+        });
+
+        // The buffers of a shared project are replicated to collaborators, who would never
+        // receive the operations of the restored history.
+        let project = Project::test(fs, ["/persistent-undo".as_ref()], cx).await;
+        project
+            .update(cx, |project, cx| project.shared(1, cx))
+            .unwrap();
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/persistent-undo/shared.txt", cx)
+            })
+            .await
+            .unwrap();
+        let version = buffer.read_with(cx, |buffer, _| buffer.version());
+        cx.update(|cx| track_buffer(&buffer, &project, cx));
+        cx.run_until_parked();
+        buffer.read_with(cx, |buffer, _| {
+            assert_eq!(buffer.version(), version);
+            assert!(buffer.peek_undo_stack().is_none());
+        });
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{ApplyAllDiffHunks, Editor, EditorEvent, SemanticsProvider};
-use collections::HashSet;
+use collections::{HashMap, HashSet};
 use futures::{channel::mpsc, future::join_all};
 use gpui::{AppContext, EventEmitter, FocusableView, Model, Render, Subscription, Task, View};
 use language::{Buffer, BufferEvent, Capability};
@@ -464,4 +464,13 @@ impl SemanticsProvider for BranchBufferSemanticsProvider {
     ) -> Option<Task<gpui::Result<project::ProjectTransaction>>> {
         None
     }
+
+    fn identifier_occurrences(
+        &self,
+        _: &Model<Buffer>,
+        _: text::Anchor,
+        _: &mut AppContext,
+    ) -> Option<Task<gpui::Result<HashMap<Model<Buffer>, Vec<Range<text::Anchor>>>>>> {
+        None
+    }
 }
@@ -0,0 +1,150 @@
+use crate::{
+    Anchor, ApplyRenamePreview, Editor, ToOffset, ToggleRenameOccurrence,
+    DEFAULT_MULTIBUFFER_CONTEXT,
+};
+use anyhow::Result;
+use collections::{HashMap, HashSet};
+use gpui::{
+    px, AsyncWindowContext, Context as _, HighlightStyle, Model, StrikethroughStyle, ViewContext,
+    VisualContext as _, WeakView,
+};
+use language::{Buffer, Capability};
+use multi_buffer::MultiBuffer;
+use std::{ops::Range, sync::Arc};
+use workspace::Workspace;
+
+/// A rename of an identifier whose occurrences were found without a language server.
+///
+/// The occurrences are shown in a multibuffer, where the ones that refer to other symbols
+/// can be deselected before the rename is applied.
+pub(crate) struct RenamePreview {
+    new_name: String,
+    occurrences: Vec<Range<Anchor>>,
+    rejected: HashSet<usize>,
+}
+
+enum RenamePreviewOccurrence {}
+enum RejectedRenameOccurrence {}
+
+impl Editor {
+    pub(crate) async fn open_rename_preview(
+        workspace: WeakView<Workspace>,
+        occurrences: HashMap<Model<Buffer>, Vec<Range<text::Anchor>>>,
+        old_name: Arc<str>,
+        new_name: String,
+        mut cx: AsyncWindowContext,
+    ) -> Result<()> {
+        let mut entries = occurrences.into_iter().collect::<Vec<_>>();
+        cx.update(|cx| {
+            entries.sort_unstable_by_key(|(buffer, _)| {
+                buffer.read(cx).file().map(|file| file.path().clone())
+            });
+        })?;
+
+        let mut occurrences = Vec::new();
+        let title = format!("Rename: {} → {}", old_name, new_name);
+        let excerpt_buffer = cx.new_model(|cx| {
+            let mut multibuffer = MultiBuffer::new(Capability::ReadWrite).with_title(title);
+            for (buffer, ranges) in entries {
+                occurrences.extend(multibuffer.push_excerpts_with_context_lines(
+                    buffer,
+                    ranges,
+                    DEFAULT_MULTIBUFFER_CONTEXT,
+                    cx,
+                ));
+            }
+            multibuffer
+        })?;
+
+        workspace.update(&mut cx, |workspace, cx| {
+            let project = workspace.project().clone();
+            let editor =
+                cx.new_view(|cx| Editor::for_multibuffer(excerpt_buffer, Some(project), true, cx));
+            workspace.add_item_to_active_pane(Box::new(editor.clone()), None, true, cx);
+            editor.update(cx, |editor, cx| {
+                editor.rename_preview = Some(RenamePreview {
+                    new_name,
+                    occurrences,
+                    rejected: HashSet::default(),
+                });
+                editor.refresh_rename_preview(cx);
+            });
+        })
+    }
+
+    pub fn toggle_rename_occurrence(
+        &mut self,
+        _: &ToggleRenameOccurrence,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let selections = self.selections.all::<usize>(cx);
+        let Some(preview) = self.rename_preview.as_mut() else {
+            cx.propagate();
+            return;
+        };
+
+        for (ix, occurrence) in preview.occurrences.iter().enumerate() {
+            let occurrence =
+                occurrence.start.to_offset(&snapshot)..occurrence.end.to_offset(&snapshot);
+            let is_selected = selections.iter().any(|selection| {
+                selection.start <= occurrence.end && occurrence.start <= selection.end
+            });
+            if is_selected && !preview.rejected.remove(&ix) {
+                preview.rejected.insert(ix);
+            }
+        }
+        self.refresh_rename_preview(cx);
+    }
+
+    pub fn apply_rename_preview(&mut self, _: &ApplyRenamePreview, cx: &mut ViewContext<Self>) {
+        let Some(preview) = self.rename_preview.take() else {
+            cx.propagate();
+            return;
+        };
+
+        self.clear_background_highlights::<RenamePreviewOccurrence>(cx);
+        self.clear_highlights::<RejectedRenameOccurrence>(cx);
+        let edits = preview
+            .occurrences
+            .into_iter()
+            .enumerate()
+            .filter(|(ix, _)| !preview.rejected.contains(ix))
+            .map(|(_, occurrence)| (occurrence, preview.new_name.clone()))
+            .collect::<Vec<_>>();
+        self.transact(cx, |this, cx| this.edit(edits, cx));
+    }
+
+    fn refresh_rename_preview(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(preview) = self.rename_preview.as_ref() else {
+            return;
+        };
+
+        let mut accepted = Vec::new();
+        let mut rejected = Vec::new();
+        for (ix, occurrence) in preview.occurrences.iter().enumerate() {
+            if preview.rejected.contains(&ix) {
+                rejected.push(occurrence.clone());
+            } else {
+                accepted.push(occurrence.clone());
+            }
+        }
+        self.highlight_background::<RenamePreviewOccurrence>(
+            &accepted,
+            |colors| colors.editor_document_highlight_write_background,
+            cx,
+        );
+        self.highlight_text::<RejectedRenameOccurrence>(
+            rejected,
+            HighlightStyle {
+                strikethrough: Some(StrikethroughStyle {
+                    thickness: px(1.),
+                    color: None,
+                }),
+                fade_out: Some(0.6),
+                ..Default::default()
+            },
+            cx,
+        );
+    }
+}
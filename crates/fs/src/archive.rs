@@ -0,0 +1,468 @@
+//! Extracting archives and compressed files, so that the files stored in them can be searched
+//! and opened.
+
+use crate::{Fs, RemoveOptions};
+use anyhow::{anyhow, bail, Context as _, Result};
+use async_compression::futures::bufread::GzipDecoder;
+use async_tar::Archive;
+use async_zip::base::read::stream::ZipFileReader;
+use futures::{
+    io::{BufReader, Cursor},
+    AsyncRead, AsyncReadExt, StreamExt,
+};
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Component, Path, PathBuf},
+    pin::Pin,
+};
+
+/// The maximum number of bytes extracted from a single archive, to guard against archives
+/// that decompress to far more than they take up on disk.
+pub const MAX_EXTRACTED_SIZE: u64 = 256 * 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveKind {
+    /// A `.zip` or `.jar` archive.
+    Zip,
+    /// A `.tar` archive.
+    Tar,
+    /// A gzip-compressed tar archive, such as `.tar.gz` or `.tgz`.
+    TarGz,
+    /// A single gzip-compressed file, such as `app.log.gz`.
+    Gzip,
+}
+
+impl ArchiveKind {
+    /// Returns the kind of archive at `path`, based on its extension.
+    pub fn for_path(path: &Path) -> Option<Self> {
+        let file_name = path.file_name()?.to_str()?.to_lowercase();
+        if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if file_name.ends_with(".tar") {
+            Some(Self::Tar)
+        } else if file_name.ends_with(".zip") || file_name.ends_with(".jar") {
+            Some(Self::Zip)
+        } else if file_name.ends_with(".gz") {
+            Some(Self::Gzip)
+        } else {
+            None
+        }
+    }
+}
+
+/// Separates the path of an archive from the path of a file within it, as in
+/// `vendor/lib.jar!/src/main.rs`.
+pub const ARCHIVE_PATH_SEPARATOR: char = '!';
+
+/// Returns the path of a file stored in the archive at `archive_path`, or `None` if the path of
+/// the file within the archive could point outside of it.
+pub fn archive_entry_path(archive_path: &Path, inner_path: &Path) -> Option<PathBuf> {
+    if !is_normal_relative_path(inner_path) {
+        return None;
+    }
+    let mut path = archive_path.as_os_str().to_owned();
+    path.push(ARCHIVE_PATH_SEPARATOR.to_string());
+    let mut path = PathBuf::from(path);
+    path.push(inner_path);
+    Some(path)
+}
+
+/// Whether `path` is a non-empty relative path made of plain file names, without any `..`,
+/// root or prefix that could make it escape the directory it is joined onto.
+fn is_normal_relative_path(path: &Path) -> bool {
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Splits the path of a file stored in an archive into the path of the archive and the path
+/// of the file within it.
+pub fn split_archive_entry_path(path: &Path) -> Option<(PathBuf, PathBuf)> {
+    let mut archive_path = PathBuf::new();
+    let mut components = path.components();
+    while let Some(component) = components.next() {
+        let name = component.as_os_str().to_str()?;
+        match name.strip_suffix(ARCHIVE_PATH_SEPARATOR) {
+            Some(archive_name) if ArchiveKind::for_path(Path::new(archive_name)).is_some() => {
+                archive_path.push(archive_name);
+                let inner_path = components.as_path();
+                if !is_normal_relative_path(inner_path) {
+                    return None;
+                }
+                return Some((archive_path, inner_path.to_path_buf()));
+            }
+            _ => archive_path.push(component),
+        }
+    }
+    None
+}
+
+/// The files of an archive, extracted into a cache directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExtractedArchive {
+    /// The directory the files were extracted into.
+    pub dir: PathBuf,
+    /// The paths of the files within the archive.
+    pub files: Vec<PathBuf>,
+}
+
+impl ExtractedArchive {
+    /// Returns the path of the extracted copy of the file stored at `inner_path`.
+    pub fn file_path(&self, inner_path: &Path) -> Option<PathBuf> {
+        self.files
+            .iter()
+            .any(|path| path == inner_path)
+            .then(|| self.dir.join(inner_path))
+    }
+}
+
+/// Extracts the files stored in the archive at `path` into Zed's temp directory. A gzip-compressed
+/// file is treated as an archive containing a single file, named after it without its `.gz`
+/// extension.
+///
+/// Extractions are reused until the archive is modified. Archives that store anything other than
+/// plain files and directories, with paths that could point outside of the directory they are
+/// extracted into, or that are larger than [`MAX_EXTRACTED_SIZE`] are rejected.
+pub async fn extract_archive(fs: &dyn Fs, path: &Path) -> Result<ExtractedArchive> {
+    let kind = ArchiveKind::for_path(path)
+        .ok_or_else(|| anyhow!("{path:?} is not a supported archive"))?;
+    let metadata = fs
+        .metadata(path)
+        .await?
+        .ok_or_else(|| anyhow!("{path:?} does not exist"))?;
+
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    metadata.mtime.hash(&mut hasher);
+    metadata.len.hash(&mut hasher);
+    let key = format!("{:016x}", hasher.finish());
+    let cache_dir = paths::temp_dir().join("archives");
+    let dir = cache_dir.join(&key);
+    // Lists the extracted files, and is only written once the extraction is complete.
+    let manifest_path = cache_dir.join(format!("{key}.files"));
+
+    if fs.is_file(&manifest_path).await {
+        let manifest = fs.load(&manifest_path).await?;
+        return Ok(ExtractedArchive {
+            dir,
+            files: manifest.lines().map(PathBuf::from).collect(),
+        });
+    }
+
+    fs.remove_dir(
+        &dir,
+        RemoveOptions {
+            recursive: true,
+            ignore_if_not_exists: true,
+        },
+    )
+    .await?;
+    fs.create_dir(&dir).await?;
+    let bytes = fs.load_bytes(path).await?;
+    let files = match kind {
+        ArchiveKind::Tar => {
+            let files = check_tar(Cursor::new(&bytes)).await?;
+            extract_tar(fs, &dir, Cursor::new(&bytes)).await?;
+            files
+        }
+        ArchiveKind::TarGz => {
+            let files = check_tar(gzip_reader(&bytes)).await?;
+            extract_tar(fs, &dir, gzip_reader(&bytes)).await?;
+            files
+        }
+        ArchiveKind::Zip => extract_zip(fs, &dir, Cursor::new(&bytes)).await?,
+        ArchiveKind::Gzip => {
+            let name = path
+                .file_stem()
+                .map(PathBuf::from)
+                .ok_or_else(|| anyhow!("{path:?} has no file name"))?;
+            let content = read_with_limit(gzip_reader(&bytes), MAX_EXTRACTED_SIZE).await?;
+            write_file(fs, &dir.join(&name), content).await?;
+            vec![name]
+        }
+    };
+
+    let manifest = files
+        .iter()
+        .map(|path| {
+            path.to_str()
+                .ok_or_else(|| anyhow!("{path:?} is not a valid UTF-8 path"))
+        })
+        .collect::<Result<Vec<_>>>()?
+        .join("\n");
+    fs.atomic_write(manifest_path, manifest).await?;
+    Ok(ExtractedArchive { dir, files })
+}
+
+fn gzip_reader(bytes: &[u8]) -> GzipDecoder<BufReader<Cursor<&[u8]>>> {
+    GzipDecoder::new(BufReader::new(Cursor::new(bytes)))
+}
+
+async fn extract_tar(
+    fs: &dyn Fs,
+    dir: &Path,
+    mut reader: impl AsyncRead + Send + Unpin,
+) -> Result<()> {
+    fs.extract_tar_file(dir, Archive::new(Pin::new(&mut reader)))
+        .await
+        .with_context(|| format!("extracting into {dir:?}"))
+}
+
+/// Checks that a tar archive can be safely extracted, and returns the paths of its files.
+async fn check_tar(reader: impl AsyncRead + Unpin) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut size = 0;
+    let mut entries = Archive::new(reader).entries()?;
+    while let Some(entry) = entries.next().await {
+        let entry = entry?;
+        let path = entry.path()?.to_path_buf();
+        let entry_type = entry.header().entry_type();
+        if !entry_type.is_file() && !entry_type.is_dir() {
+            bail!("{path:?} is neither a file nor a directory");
+        }
+        if !is_normal_relative_path(&path) {
+            bail!("{path:?} is not a valid path within an archive");
+        }
+        size += entry.header().size()?;
+        if size > MAX_EXTRACTED_SIZE {
+            bail!("archive is larger than {MAX_EXTRACTED_SIZE} bytes");
+        }
+        if entry_type.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+async fn extract_zip(
+    fs: &dyn Fs,
+    dir: &Path,
+    reader: impl AsyncRead + Unpin,
+) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut remaining = MAX_EXTRACTED_SIZE;
+    let mut reader = ZipFileReader::new(BufReader::new(reader));
+    while let Some(mut item) = reader.next_with_entry().await? {
+        let entry = item.reader_mut().entry();
+        let path = PathBuf::from(entry.filename().as_str()?);
+        if !is_normal_relative_path(&path) {
+            bail!("{path:?} is not a valid path within an archive");
+        }
+        if !entry.dir()? {
+            let content = read_with_limit(item.reader_mut(), remaining).await?;
+            remaining -= content.len() as u64;
+            write_file(fs, &dir.join(&path), content).await?;
+            files.push(path);
+        }
+        reader = item.skip().await?;
+    }
+    Ok(files)
+}
+
+/// Reads `reader` to the end, failing once more than `limit` bytes have been read.
+async fn read_with_limit(reader: impl AsyncRead + Unpin, limit: u64) -> Result<Vec<u8>> {
+    let mut content = Vec::new();
+    reader.take(limit + 1).read_to_end(&mut content).await?;
+    if content.len() as u64 > limit {
+        bail!("archive is larger than {MAX_EXTRACTED_SIZE} bytes");
+    }
+    Ok(content)
+}
+
+async fn write_file(fs: &dyn Fs, path: &Path, content: Vec<u8>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs.create_dir(parent).await?;
+    }
+    let mut content = Cursor::new(content);
+    fs.create_file_with(path, Pin::new(&mut content))
+        .await
+        .with_context(|| format!("extracting {path:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FakeFs;
+    use async_compression::futures::write::GzipEncoder;
+    use async_zip::{base::write::ZipFileWriter, Compression, ZipEntryBuilder};
+    use futures::AsyncWriteExt;
+    use gpui::BackgroundExecutor;
+
+    #[gpui::test]
+    async fn test_extract_archives(executor: BackgroundExecutor) {
+        let fs = FakeFs::new(executor);
+        fs.insert_file(
+            "/root/lib.jar",
+            zip(&[("src/main.rs", "fn main() {}"), ("README.md", "# Readme")]).await,
+        )
+        .await;
+        fs.insert_file(
+            "/root/src.tar",
+            tar(&[("src/lib.rs", "pub fn f() {}")]).await,
+        )
+        .await;
+
+        let mut log = GzipEncoder::new(Vec::new());
+        log.write_all(b"ERROR: oops").await.unwrap();
+        log.close().await.unwrap();
+        fs.insert_file("/root/app.log.gz", log.into_inner()).await;
+        fs.insert_file("/root/notes.txt", b"notes".to_vec()).await;
+
+        let jar = extract_archive(fs.as_ref(), Path::new("/root/lib.jar"))
+            .await
+            .unwrap();
+        assert_eq!(
+            jar.files,
+            [PathBuf::from("src/main.rs"), PathBuf::from("README.md")]
+        );
+        assert_eq!(
+            fs.load(&jar.file_path(Path::new("src/main.rs")).unwrap())
+                .await
+                .unwrap(),
+            "fn main() {}"
+        );
+        assert_eq!(jar.file_path(Path::new("src/other.rs")), None);
+
+        let tar = extract_archive(fs.as_ref(), Path::new("/root/src.tar"))
+            .await
+            .unwrap();
+        assert_eq!(tar.files, [PathBuf::from("src/lib.rs")]);
+        assert_eq!(
+            fs.load(&tar.dir.join("src/lib.rs")).await.unwrap(),
+            "pub fn f() {}"
+        );
+
+        let log = extract_archive(fs.as_ref(), Path::new("/root/app.log.gz"))
+            .await
+            .unwrap();
+        assert_eq!(log.files, [PathBuf::from("app.log")]);
+        assert_eq!(
+            fs.load(&log.dir.join("app.log")).await.unwrap(),
+            "ERROR: oops"
+        );
+
+        assert!(extract_archive(fs.as_ref(), Path::new("/root/notes.txt"))
+            .await
+            .is_err());
+
+        // Extractions are reused until the archive changes.
+        assert_eq!(
+            extract_archive(fs.as_ref(), Path::new("/root/lib.jar"))
+                .await
+                .unwrap(),
+            jar
+        );
+        fs.insert_file(
+            "/root/lib.jar",
+            zip(&[("src/main.rs", "fn main() { }")]).await,
+        )
+        .await;
+        let changed_jar = extract_archive(fs.as_ref(), Path::new("/root/lib.jar"))
+            .await
+            .unwrap();
+        assert_ne!(changed_jar.dir, jar.dir);
+        assert_eq!(changed_jar.files, [PathBuf::from("src/main.rs")]);
+    }
+
+    #[gpui::test]
+    async fn test_extract_malicious_archives(executor: BackgroundExecutor) {
+        let fs = FakeFs::new(executor);
+        fs.insert_file(
+            "/root/evil.zip",
+            zip(&[("ok.txt", "ok"), ("../../evil.txt", "evil")]).await,
+        )
+        .await;
+        fs.insert_file(
+            "/root/evil.tar",
+            tar(&[("ok.txt", "ok"), ("../../evil.txt", "evil")]).await,
+        )
+        .await;
+        fs.insert_file("/root/absolute.tar", tar(&[("/evil.txt", "evil")]).await)
+            .await;
+
+        for path in ["/root/evil.zip", "/root/evil.tar", "/root/absolute.tar"] {
+            assert!(
+                extract_archive(fs.as_ref(), Path::new(path)).await.is_err(),
+                "{path} should not be extracted"
+            );
+        }
+        assert!(
+            fs.files().iter().all(|path| !path.ends_with("evil.txt")),
+            "files with paths outside of the archive should not be extracted"
+        );
+    }
+
+    async fn zip(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = Vec::new();
+        let mut writer = ZipFileWriter::new(&mut zip);
+        for (path, content) in files {
+            let builder = ZipEntryBuilder::new(path.to_string().into(), Compression::Deflate);
+            writer
+                .write_entry_whole(builder, content.as_bytes())
+                .await
+                .unwrap();
+        }
+        writer.close().await.unwrap();
+        zip
+    }
+
+    /// Builds a tar archive, writing the paths of its files as is, since the tar builder refuses
+    /// to write the paths of malicious archives.
+    async fn tar(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = async_tar::Builder::new(Vec::new());
+        for (path, content) in files {
+            let mut header = async_tar::Header::new_old();
+            header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+            header.set_entry_type(async_tar::EntryType::Regular);
+            header.set_mode(0o644);
+            header.set_size(content.len() as u64);
+            header.set_cksum();
+            builder.append(&header, content.as_bytes()).await.unwrap();
+        }
+        builder.into_inner().await.unwrap()
+    }
+
+    #[test]
+    fn test_archive_entry_paths() {
+        let path =
+            archive_entry_path(Path::new("vendor/lib.jar"), Path::new("src/main.rs")).unwrap();
+        assert_eq!(path, Path::new("vendor/lib.jar!/src/main.rs"));
+        for inner_path in ["../main.rs", "/main.rs", "src/../../main.rs", ""] {
+            assert_eq!(
+                archive_entry_path(Path::new("vendor/lib.jar"), Path::new(inner_path)),
+                None,
+                "{inner_path}"
+            );
+        }
+        assert_eq!(
+            split_archive_entry_path(&path),
+            Some((
+                PathBuf::from("vendor/lib.jar"),
+                PathBuf::from("src/main.rs")
+            ))
+        );
+        assert_eq!(split_archive_entry_path(Path::new("vendor/lib.jar")), None);
+        assert_eq!(split_archive_entry_path(Path::new("vendor/lib.jar!")), None);
+        assert_eq!(split_archive_entry_path(Path::new("wow!/main.rs")), None);
+        assert_eq!(
+            split_archive_entry_path(Path::new("vendor/lib.jar!/../secret.txt")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_archive_kind_for_path() {
+        for (path, kind) in [
+            ("a/b.tar.gz", Some(ArchiveKind::TarGz)),
+            ("b.TGZ", Some(ArchiveKind::TarGz)),
+            ("b.tar", Some(ArchiveKind::Tar)),
+            ("b.zip", Some(ArchiveKind::Zip)),
+            ("b.jar", Some(ArchiveKind::Zip)),
+            ("b.log.gz", Some(ArchiveKind::Gzip)),
+            ("b.rs", None),
+        ] {
+            assert_eq!(ArchiveKind::for_path(Path::new(path)), kind, "{path}");
+        }
+    }
+}
@@ -1,3 +1,5 @@
+pub mod archive;
+
 use anyhow::{anyhow, Result};
 use git::GitHostingProviderRegistry;
 
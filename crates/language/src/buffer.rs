@@ -14,7 +14,7 @@ use crate::{
         SyntaxMapMatches, SyntaxSnapshot, ToTreeSitterPoint,
     },
     task_context::RunnableRange,
-    LanguageScope, Outline, OutlineConfig, RunnableCapture, RunnableTag,
+    LanguageScope, Outline, OutlineConfig, RunnableCapture, RunnableTag, TextObject,
 };
 use anyhow::{anyhow, Context, Result};
 use async_watch as watch;
@@ -549,6 +549,109 @@ pub struct Diff {
     edits: Vec<(Range<usize>, Arc<str>)>,
 }
 
+/// The maximum number of undo entries, and of redo entries, kept by
+/// [`Buffer::serialize_history`].
+pub const MAX_SERIALIZED_HISTORY_ENTRIES: usize = 200;
+
+/// A buffer's undo and redo stacks, expressed as plain-text edits so that they
+/// can be persisted and replayed onto a buffer containing the same text.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializedHistory {
+    /// Transactions that can be undone, from oldest to newest.
+    pub undo_stack: Vec<SerializedTransaction>,
+    /// Transactions that can be redone, in the order in which they would be redone.
+    pub redo_stack: Vec<SerializedTransaction>,
+}
+
+/// A single entry in a [`SerializedHistory`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializedTransaction {
+    /// The edits performed by the transaction, sorted by position and expressed
+    /// in terms of offsets in the text that preceded the transaction.
+    pub edits: Vec<SerializedEdit>,
+}
+
+/// A single edit within a [`SerializedTransaction`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializedEdit {
+    pub range: Range<usize>,
+    pub old_text: String,
+    pub new_text: String,
+}
+
+impl SerializedHistory {
+    /// Returns whether there is nothing to undo or redo.
+    pub fn is_empty(&self) -> bool {
+        self.undo_stack.is_empty() && self.redo_stack.is_empty()
+    }
+
+    /// Returns the approximate number of bytes of text stored in this history.
+    pub fn text_len(&self) -> usize {
+        self.undo_stack
+            .iter()
+            .chain(&self.redo_stack)
+            .map(SerializedTransaction::text_len)
+            .sum()
+    }
+}
+
+impl SerializedTransaction {
+    fn between(old_text: &str, new_text: &str) -> Self {
+        Self {
+            edits: text_diff_edits(old_text, new_text)
+                .into_iter()
+                .map(|(range, text)| SerializedEdit {
+                    old_text: old_text[range.clone()].to_string(),
+                    range,
+                    new_text: text.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the number of bytes of text stored in this transaction.
+    pub fn text_len(&self) -> usize {
+        self.edits
+            .iter()
+            .map(|edit| edit.old_text.len() + edit.new_text.len())
+            .sum()
+    }
+
+    /// Returns the transaction that reverts this one.
+    fn inverse(&self) -> Self {
+        let mut delta = 0_isize;
+        let edits = self
+            .edits
+            .iter()
+            .map(|edit| {
+                let start = (edit.range.start as isize + delta) as usize;
+                delta += edit.new_text.len() as isize - edit.old_text.len() as isize;
+                SerializedEdit {
+                    range: start..start + edit.new_text.len(),
+                    old_text: edit.new_text.clone(),
+                    new_text: edit.old_text.clone(),
+                }
+            })
+            .collect();
+        Self { edits }
+    }
+
+    /// Applies this transaction to the given text, verifying that the text
+    /// it replaces matches the text it expects to replace.
+    fn apply_to(&self, text: &mut String) -> Result<()> {
+        for edit in self.edits.iter().rev() {
+            let replaced = text
+                .get(edit.range.clone())
+                .ok_or_else(|| anyhow!("invalid edit range {:?}", edit.range))?;
+            if replaced != edit.old_text {
+                return Err(anyhow!("edit does not match text at {:?}", edit.range));
+            }
+            text.replace_range(edit.range.clone(), &edit.new_text);
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, Copy)]
 pub(crate) struct DiagnosticEndpoint {
     offset: usize,
@@ -1306,6 +1409,18 @@ impl Buffer {
         self.parse_status.1.clone()
     }
 
+    /// Waits until the buffer is no longer parsing in the background.
+    pub fn parsing_idle(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut parse_status = self.parse_status();
+        async move {
+            while *parse_status.borrow() != ParseStatus::Idle {
+                if parse_status.changed().await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
     /// Assign to the buffer a set of diagnostics created by a given language server.
     pub fn update_diagnostics(
         &mut self,
@@ -1570,60 +1685,7 @@ impl Buffer {
                 let line_ending = LineEnding::detect(&new_text);
                 LineEnding::normalize(&mut new_text);
 
-                let diff = TextDiff::from_chars(old_text.as_str(), new_text.as_str());
-                let empty: Arc<str> = Arc::default();
-
-                let mut edits = Vec::new();
-                let mut old_offset = 0;
-                let mut new_offset = 0;
-                let mut last_edit: Option<(Range<usize>, Range<usize>)> = None;
-                for change in diff.iter_all_changes().map(Some).chain([None]) {
-                    if let Some(change) = &change {
-                        let len = change.value().len();
-                        match change.tag() {
-                            ChangeTag::Equal => {
-                                old_offset += len;
-                                new_offset += len;
-                            }
-                            ChangeTag::Delete => {
-                                let old_end_offset = old_offset + len;
-                                if let Some((last_old_range, _)) = &mut last_edit {
-                                    last_old_range.end = old_end_offset;
-                                } else {
-                                    last_edit =
-                                        Some((old_offset..old_end_offset, new_offset..new_offset));
-                                }
-                                old_offset = old_end_offset;
-                            }
-                            ChangeTag::Insert => {
-                                let new_end_offset = new_offset + len;
-                                if let Some((_, last_new_range)) = &mut last_edit {
-                                    last_new_range.end = new_end_offset;
-                                } else {
-                                    last_edit =
-                                        Some((old_offset..old_offset, new_offset..new_end_offset));
-                                }
-                                new_offset = new_end_offset;
-                            }
-                        }
-                    }
-
-                    if let Some((old_range, new_range)) = &last_edit {
-                        if old_offset > old_range.end
-                            || new_offset > new_range.end
-                            || change.is_none()
-                        {
-                            let text = if new_range.is_empty() {
-                                empty.clone()
-                            } else {
-                                new_text[new_range.clone()].into()
-                            };
-                            edits.push((old_range.clone(), text));
-                            last_edit.take();
-                        }
-                    }
-                }
-
+                let edits = text_diff_edits(&old_text, &new_text);
                 Diff {
                     base_version,
                     line_ending,
@@ -1712,6 +1774,129 @@ impl Buffer {
         self.end_transaction(cx)
     }
 
+    /// Spawns a background task that converts the buffer's undo and redo stacks
+    /// into a [`SerializedHistory`], which can be restored with [`Buffer::restore_history`].
+    ///
+    /// Only the newest [`MAX_SERIALIZED_HISTORY_ENTRIES`] undo entries, and the next
+    /// [`MAX_SERIALIZED_HISTORY_ENTRIES`] redo entries, are serialized.
+    pub fn serialize_history(&self, cx: &AppContext) -> Task<SerializedHistory> {
+        let snapshot = self.text.snapshot();
+        let undo_stack = self.text.undo_stack();
+        let undo_versions = undo_stack[undo_stack
+            .len()
+            .saturating_sub(MAX_SERIALIZED_HISTORY_ENTRIES)..]
+            .iter()
+            .map(|entry| entry.transaction().start.clone())
+            .collect::<Vec<_>>();
+        let redo_versions = self
+            .text
+            .redo_stack()
+            .iter()
+            .rev()
+            .take(MAX_SERIALIZED_HISTORY_ENTRIES)
+            .map(|entry| {
+                let transaction = entry.transaction();
+                let mut version = transaction.start.clone();
+                for edit_id in &transaction.edit_ids {
+                    version.observe(*edit_id);
+                }
+                version
+            })
+            .collect::<Vec<_>>();
+
+        cx.background_executor().spawn(async move {
+            let current_text = snapshot.text();
+            let undo_states = undo_versions
+                .iter()
+                .map(|version| snapshot.rope_for_version(version).to_string())
+                .chain([current_text.clone()])
+                .collect::<Vec<_>>();
+            let redo_states = [current_text]
+                .into_iter()
+                .chain(
+                    redo_versions
+                        .iter()
+                        .map(|version| snapshot.rope_for_version(version).to_string()),
+                )
+                .collect::<Vec<_>>();
+            let transactions = |states: &[String]| {
+                states
+                    .windows(2)
+                    .map(|states| SerializedTransaction::between(&states[0], &states[1]))
+                    .filter(|transaction| !transaction.edits.is_empty())
+                    .collect()
+            };
+            SerializedHistory {
+                undo_stack: transactions(&undo_states),
+                redo_stack: transactions(&redo_states),
+            }
+        })
+    }
+
+    /// Replays a previously-serialized history onto this buffer, so that its
+    /// transactions can be undone and redone. The buffer's text must match the
+    /// text it had when the history was serialized, and is left unchanged.
+    ///
+    /// The history is rebuilt without broadcasting operations or emitting
+    /// events, and the buffer is considered saved afterwards. Because of that,
+    /// it must not be restored on a buffer that is replicated to collaborators,
+    /// as they would never receive the versions every later edit depends on.
+    pub fn restore_history(&mut self, history: &SerializedHistory) -> Result<()> {
+        let current_text = self.text();
+        let mut text = current_text.clone();
+        for transaction in history.undo_stack.iter().rev() {
+            transaction.inverse().apply_to(&mut text)?;
+        }
+        for transaction in history.undo_stack.iter().chain(&history.redo_stack) {
+            transaction.apply_to(&mut text)?;
+        }
+        let mut expected_text = current_text.clone();
+        for transaction in &history.redo_stack {
+            transaction.apply_to(&mut expected_text)?;
+        }
+        if text != expected_text {
+            return Err(anyhow!("history does not match the buffer's text"));
+        }
+
+        let was_saved = !self.has_unsaved_edits();
+        self.text.finalize_last_transaction();
+        for transaction in history.undo_stack.iter().rev() {
+            self.text.start_transaction();
+            self.apply_serialized_transaction(&transaction.inverse());
+            if let Some((transaction_id, _)) = self.text.end_transaction() {
+                self.text.forget_transaction(transaction_id);
+            }
+        }
+        for transaction in history.undo_stack.iter().chain(&history.redo_stack) {
+            self.text.start_transaction();
+            self.apply_serialized_transaction(transaction);
+            self.text.end_transaction();
+            self.text.finalize_last_transaction();
+        }
+        for _ in &history.redo_stack {
+            self.text.undo();
+        }
+        // The inverse transactions were forgotten, so the undo tree is rebuilt to
+        // match the restored stacks.
+        self.text.reset_undo_tree();
+
+        if was_saved {
+            self.saved_version = self.version();
+            self.has_unsaved_edits
+                .set((self.saved_version.clone(), false));
+        }
+        Ok(())
+    }
+
+    fn apply_serialized_transaction(&mut self, transaction: &SerializedTransaction) {
+        self.text.edit(
+            transaction
+                .edits
+                .iter()
+                .map(|edit| (edit.range.clone(), edit.new_text.as_str())),
+        );
+    }
+
     fn has_unsaved_edits(&self) -> bool {
         let (last_version, has_unsaved_edits) = self.has_unsaved_edits.take();
 
@@ -2289,6 +2474,22 @@ impl Buffer {
         }
     }
 
+    /// Returns the buffer to the state described by the given node of its undo tree.
+    pub fn restore_undo_tree_node(&mut self, ix: usize, cx: &mut ModelContext<Self>) -> bool {
+        let was_dirty = self.is_dirty();
+        let old_version = self.version.clone();
+
+        let operations = self.text.restore_undo_tree_node(ix);
+        if operations.is_empty() {
+            return false;
+        }
+        for operation in operations {
+            self.send_operation(Operation::Buffer(operation), true, cx);
+        }
+        self.did_edit(&old_version, was_dirty, cx);
+        true
+    }
+
     /// Manually undoes a specific transaction in the buffer's undo history.
     pub fn undo_transaction(
         &mut self,
@@ -2991,6 +3192,165 @@ impl BufferSnapshot {
         result
     }
 
+    /// Returns the smallest named syntax node containing the given range. When
+    /// several nested nodes span that same range, the outermost one is returned.
+    fn syntax_node_for_range(&self, range: Range<usize>) -> Option<tree_sitter::Node<'_>> {
+        let layer = self.syntax_layer_at(range.start)?;
+        let mut node = layer
+            .node()
+            .named_descendant_for_byte_range(range.start, range.end)?;
+        while let Some(parent) = node.parent() {
+            if parent.byte_range() != node.byte_range() {
+                break;
+            }
+            node = parent;
+        }
+        Some(node)
+    }
+
+    /// Returns the range of the syntax node containing the given range, along
+    /// with the range of the next or previous named sibling of that node. If the
+    /// node has no such sibling, the closest ancestor that has one is used.
+    pub fn syntax_sibling_ranges<T: ToOffset>(
+        &self,
+        range: Range<T>,
+        forward: bool,
+    ) -> Option<(Range<usize>, Range<usize>)> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        let mut node = self.syntax_node_for_range(range)?;
+        loop {
+            let sibling = if forward {
+                node.next_named_sibling()
+            } else {
+                node.prev_named_sibling()
+            };
+            if let Some(sibling) = sibling {
+                return Some((node.byte_range(), sibling.byte_range()));
+            }
+            node = node.parent()?;
+        }
+    }
+
+    /// Returns the range of the syntax node that encloses the given range. If
+    /// the range already spans a node, the range of its parent is returned.
+    pub fn range_for_syntax_parent<T: ToOffset>(&self, range: Range<T>) -> Option<Range<usize>> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        let mut node = self.syntax_node_for_range(range.clone())?;
+        if node.byte_range() == range {
+            let node_range = node.byte_range();
+            while node.byte_range() == node_range {
+                node = node.parent()?;
+            }
+        }
+        Some(node.byte_range())
+    }
+
+    /// Returns the range of the first named child of the syntax node that
+    /// contains the given range.
+    pub fn range_for_syntax_first_child<T: ToOffset>(
+        &self,
+        range: Range<T>,
+    ) -> Option<Range<usize>> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        let node = self.syntax_node_for_range(range)?;
+        let child = node.named_child(0)?;
+        Some(child.byte_range())
+    }
+
+    /// Returns the ranges of the text objects captured by the language's
+    /// `textobjects` query that intersect the given range.
+    pub fn text_object_ranges<T: ToOffset>(
+        &self,
+        range: Range<T>,
+    ) -> impl Iterator<Item = (Range<usize>, TextObject)> + '_ {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        let mut matches = self.syntax.matches(range, &self.text, |grammar| {
+            grammar
+                .text_object_config
+                .as_ref()
+                .map(|config| &config.query)
+        });
+        let configs = matches
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.text_object_config.as_ref())
+            .collect::<Vec<_>>();
+
+        let mut captures = Vec::<(Range<usize>, TextObject)>::new();
+        iter::from_fn(move || loop {
+            if let Some(capture) = captures.pop() {
+                return Some(capture);
+            }
+
+            let mat = matches.peek()?;
+            if let Some(config) = configs[mat.grammar_index] {
+                // A query can capture several consecutive nodes as a single text
+                // object, such as each of the statements in a function's body.
+                for capture in mat.captures {
+                    let Some(Some(text_object)) = config.text_objects.get(capture.index as usize)
+                    else {
+                        continue;
+                    };
+                    let range = capture.node.byte_range();
+                    if let Some((existing_range, _)) = captures
+                        .iter_mut()
+                        .find(|(_, existing_object)| existing_object == text_object)
+                    {
+                        existing_range.start = existing_range.start.min(range.start);
+                        existing_range.end = existing_range.end.max(range.end);
+                    } else {
+                        captures.push((range, *text_object));
+                    }
+                }
+            }
+            matches.advance();
+        })
+    }
+
+    /// Returns the range of the smallest text object of the given kind that
+    /// contains the given range.
+    pub fn range_for_text_object<T: ToOffset>(
+        &self,
+        range: Range<T>,
+        text_object: TextObject,
+    ) -> Option<Range<usize>> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        self.text_object_ranges(range.clone())
+            .filter(|(object_range, object)| {
+                *object == text_object
+                    && object_range.start <= range.start
+                    && object_range.end >= range.end
+            })
+            .map(|(object_range, _)| object_range)
+            .min_by_key(|object_range| object_range.len())
+    }
+
+    /// Returns the range of the closest text object of the given kind that
+    /// starts after the start of the given range, or before it when searching
+    /// backward.
+    pub fn range_for_adjacent_text_object<T: ToOffset>(
+        &self,
+        range: Range<T>,
+        text_object: TextObject,
+        forward: bool,
+    ) -> Option<Range<usize>> {
+        let start = range.start.to_offset(self);
+        let search_range = if forward { start..self.len() } else { 0..start };
+        let candidates = self
+            .text_object_ranges(search_range)
+            .filter(|(_, object)| *object == text_object)
+            .map(|(object_range, _)| object_range);
+        if forward {
+            candidates
+                .filter(|object_range| object_range.start > start)
+                .min_by_key(|object_range| (object_range.start, Reverse(object_range.end)))
+        } else {
+            candidates
+                .filter(|object_range| object_range.start < start)
+                .max_by_key(|object_range| (object_range.start, object_range.end))
+        }
+    }
+
     /// Returns the outline for the buffer.
     ///
     /// This method allows passing an optional [`SyntaxTheme`] to
@@ -3385,6 +3745,80 @@ impl BufferSnapshot {
         })
     }
 
+    /// Returns the range of the identifier containing `position`, if there is one.
+    pub fn identifier_range_at<T: ToOffset>(&self, position: T) -> Option<Range<usize>> {
+        let (range, _) = self.surrounding_word(position);
+        let name = self.text_for_range(range.clone()).collect::<String>();
+        if name.is_empty()
+            || !self
+                .identifier_ranges(range.clone(), &name)
+                .contains(&range)
+        {
+            return None;
+        }
+        Some(range)
+    }
+
+    /// Returns the ranges of the identifiers named `name` within `range`, as captured by the
+    /// language's highlights and outline queries. Unlike a textual search, this skips
+    /// occurrences of the name within strings, comments and longer identifiers.
+    pub fn identifier_ranges<T: ToOffset>(&self, range: Range<T>, name: &str) -> Vec<Range<usize>> {
+        let offset_range = range.start.to_offset(self)..range.end.to_offset(self);
+        let mut ranges = Vec::new();
+
+        let mut syntax_matches = self.syntax.matches(offset_range.clone(), self, |grammar| {
+            grammar.highlights_query.as_ref()
+        });
+        let capture_names = syntax_matches
+            .grammars()
+            .iter()
+            .map(|grammar| {
+                grammar
+                    .highlights_query
+                    .as_ref()
+                    .map(|query| query.capture_names())
+            })
+            .collect::<Vec<_>>();
+        while let Some(mat) = syntax_matches.peek() {
+            ranges.extend(
+                mat.captures
+                    .iter()
+                    .filter(|capture| {
+                        capture_names[mat.grammar_index]
+                            .and_then(|names| names.get(capture.index as usize))
+                            .map_or(false, |name| is_identifier_capture(name))
+                    })
+                    .map(|capture| capture.node.byte_range()),
+            );
+            syntax_matches.advance();
+        }
+
+        let mut syntax_matches = self.syntax.matches(offset_range, self, |grammar| {
+            grammar.outline_config.as_ref().map(|config| &config.query)
+        });
+        let configs = syntax_matches
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.outline_config.as_ref())
+            .collect::<Vec<_>>();
+        while let Some(mat) = syntax_matches.peek() {
+            if let Some(config) = configs[mat.grammar_index] {
+                ranges.extend(
+                    mat.captures
+                        .iter()
+                        .filter(|capture| capture.index == config.name_capture_ix)
+                        .map(|capture| capture.node.byte_range()),
+                );
+            }
+            syntax_matches.advance();
+        }
+
+        ranges.retain(|range| range.len() == name.len() && self.contains_str_at(range.start, name));
+        ranges.sort_unstable_by_key(|range| range.start);
+        ranges.dedup();
+        ranges
+    }
+
     pub fn injections_intersecting_range<T: ToOffset>(
         &self,
         range: Range<T>,
@@ -3959,6 +4393,31 @@ impl BufferSnapshot {
     }
 }
 
+/// Returns whether a highlights query capture with the given name marks identifiers, as
+/// opposed to literals, comments, keywords or punctuation.
+fn is_identifier_capture(name: &str) -> bool {
+    let category = name.split('.').next().unwrap_or(name);
+    matches!(
+        category,
+        "variable"
+            | "function"
+            | "method"
+            | "type"
+            | "property"
+            | "field"
+            | "parameter"
+            | "constructor"
+            | "constant"
+            | "enum"
+            | "variant"
+            | "label"
+            | "attribute"
+            | "tag"
+            | "namespace"
+            | "module"
+    )
+}
+
 fn indent_size_for_line(text: &text::BufferSnapshot, row: u32) -> IndentSize {
     indent_size_for_text(text.chars_at(Point::new(row, 0)))
 }
@@ -4466,6 +4925,61 @@ impl CharClassifier {
     }
 }
 
+/// Computes the minimal set of edits that transform `old_text` into `new_text`,
+/// expressed in terms of offsets in `old_text`.
+fn text_diff_edits(old_text: &str, new_text: &str) -> Vec<(Range<usize>, Arc<str>)> {
+    let diff = TextDiff::from_chars(old_text, new_text);
+    let empty: Arc<str> = Arc::default();
+
+    let mut edits = Vec::new();
+    let mut old_offset = 0;
+    let mut new_offset = 0;
+    let mut last_edit: Option<(Range<usize>, Range<usize>)> = None;
+    for change in diff.iter_all_changes().map(Some).chain([None]) {
+        if let Some(change) = &change {
+            let len = change.value().len();
+            match change.tag() {
+                ChangeTag::Equal => {
+                    old_offset += len;
+                    new_offset += len;
+                }
+                ChangeTag::Delete => {
+                    let old_end_offset = old_offset + len;
+                    if let Some((last_old_range, _)) = &mut last_edit {
+                        last_old_range.end = old_end_offset;
+                    } else {
+                        last_edit = Some((old_offset..old_end_offset, new_offset..new_offset));
+                    }
+                    old_offset = old_end_offset;
+                }
+                ChangeTag::Insert => {
+                    let new_end_offset = new_offset + len;
+                    if let Some((_, last_new_range)) = &mut last_edit {
+                        last_new_range.end = new_end_offset;
+                    } else {
+                        last_edit = Some((old_offset..old_offset, new_offset..new_end_offset));
+                    }
+                    new_offset = new_end_offset;
+                }
+            }
+        }
+
+        if let Some((old_range, new_range)) = &last_edit {
+            if old_offset > old_range.end || new_offset > new_range.end || change.is_none() {
+                let text = if new_range.is_empty() {
+                    empty.clone()
+                } else {
+                    new_text[new_range.clone()].into()
+                };
+                edits.push((old_range.clone(), text));
+                last_edit.take();
+            }
+        }
+    }
+
+    edits
+}
+
 /// Find all of the ranges of whitespace that occur at the ends of lines
 /// in the given rope.
 ///
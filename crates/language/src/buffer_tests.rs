@@ -374,6 +374,80 @@ async fn test_apply_diff(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_serialize_and_restore_history(cx: &mut TestAppContext) {
+    let buffer = cx.new_model(|cx| Buffer::local("one two three", cx));
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit([(0..3, "ONE")], None, cx);
+        buffer.finalize_last_transaction();
+        buffer.edit([(8..13, "3"), (3..3, "!")], None, cx);
+        buffer.finalize_last_transaction();
+        buffer.edit([(0..0, "zero ")], None, cx);
+        buffer.finalize_last_transaction();
+        buffer.undo(cx);
+        assert_eq!(buffer.text(), "ONE! two 3");
+    });
+    let history = buffer
+        .update(cx, |buffer, cx| buffer.serialize_history(cx))
+        .await;
+    assert_eq!(history.undo_stack.len(), 2);
+    assert_eq!(history.redo_stack.len(), 1);
+
+    let restored = cx.new_model(|cx| Buffer::local("ONE! two 3", cx));
+    let events = Arc::new(Mutex::new(Vec::new()));
+    restored.update(cx, |_, cx| {
+        let events = events.clone();
+        cx.subscribe(&restored, move |_, _, event, _| {
+            events.lock().push(event.clone())
+        })
+        .detach();
+    });
+    restored.update(cx, |buffer, _| {
+        buffer.restore_history(&history).unwrap();
+        assert_eq!(buffer.text(), "ONE! two 3");
+        assert!(!buffer.is_dirty());
+
+        // The undo tree holds a single branch through the restored states.
+        let undo_tree = buffer.undo_tree();
+        assert_eq!(
+            undo_tree
+                .nodes()
+                .iter()
+                .map(|node| (node.text.to_string(), node.parent))
+                .collect::<Vec<_>>(),
+            [
+                ("one two three".to_string(), None),
+                ("ONE two three".to_string(), Some(0)),
+                ("ONE! two 3".to_string(), Some(1)),
+                ("zero ONE! two 3".to_string(), Some(2)),
+            ]
+        );
+        assert_eq!(undo_tree.current(), 2);
+    });
+    cx.run_until_parked();
+    assert!(events.lock().is_empty());
+
+    restored.update(cx, |buffer, cx| {
+        buffer.redo(cx);
+        assert_eq!(buffer.text(), "zero ONE! two 3");
+        buffer.undo(cx);
+        buffer.undo(cx);
+        assert_eq!(buffer.text(), "ONE two three");
+        buffer.undo(cx);
+        assert_eq!(buffer.text(), "one two three");
+        buffer.undo(cx);
+        assert_eq!(buffer.text(), "one two three");
+    });
+
+    // A history that doesn't match the buffer's text is rejected.
+    let mismatched = cx.new_model(|cx| Buffer::local("something else", cx));
+    mismatched.update(cx, |buffer, _| {
+        assert!(buffer.restore_history(&history).is_err());
+        assert_eq!(buffer.text(), "something else");
+        assert!(buffer.peek_undo_stack().is_none());
+    });
+}
+
 #[gpui::test(iterations = 10)]
 async fn test_normalize_whitespace(cx: &mut gpui::TestAppContext) {
     let text = [
@@ -597,6 +671,49 @@ async fn test_resetting_language(cx: &mut gpui::TestAppContext) {
     assert_eq!(get_tree_sexp(&buffer, cx), "(document (object))");
 }
 
+#[gpui::test]
+fn test_identifier_ranges(cx: &mut AppContext) {
+    let text = r#"
+        struct Foo;
+        fn foo(foo: Foo) -> Foo {
+            // foo
+            let foobar = "foo";
+            foo
+        }
+    "#
+    .unindent();
+
+    let language = rust_lang()
+        .with_highlights_query(
+            r#"
+            (identifier) @variable
+            (type_identifier) @type
+            (string_literal) @string
+            (line_comment) @comment
+            "#,
+        )
+        .unwrap();
+    let buffer = cx.new_model(|cx| Buffer::local(text, cx).with_language(Arc::new(language), cx));
+    let snapshot = buffer.read(cx).snapshot();
+    let identifiers = |name: &str| {
+        snapshot
+            .identifier_ranges(0..snapshot.len(), name)
+            .into_iter()
+            .map(|range| snapshot.offset_to_point(range.start))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        identifiers("foo"),
+        [Point::new(1, 3), Point::new(1, 7), Point::new(4, 4)]
+    );
+    assert_eq!(
+        identifiers("Foo"),
+        [Point::new(0, 7), Point::new(1, 12), Point::new(1, 20)]
+    );
+    assert_eq!(identifiers("bar"), []);
+}
+
 #[gpui::test]
 async fn test_outline(cx: &mut gpui::TestAppContext) {
     let text = r#"
@@ -1101,6 +1218,110 @@ fn test_range_for_syntax_ancestor(cx: &mut AppContext) {
     }
 }
 
+#[gpui::test]
+fn test_syntax_node_navigation(cx: &mut AppContext) {
+    cx.new_model(|cx| {
+        let text = "fn a(x: u8, y: u8) { b(x, y) }\nfn c() {}";
+        let buffer = Buffer::local(text, cx).with_language(Arc::new(rust_lang()), cx);
+        let snapshot = buffer.snapshot();
+
+        assert_eq!(
+            snapshot.syntax_sibling_ranges(range_of(text, "x: u8"), true),
+            Some((range_of(text, "x: u8"), range_of(text, "y: u8")))
+        );
+        assert_eq!(
+            snapshot.syntax_sibling_ranges(range_of(text, "y: u8"), false),
+            Some((range_of(text, "y: u8"), range_of(text, "x: u8")))
+        );
+        // When the node has no sibling in that direction, its parent's sibling is used.
+        assert_eq!(
+            snapshot.syntax_sibling_ranges(range_of(text, "y: u8"), true),
+            Some((
+                range_of(text, "(x: u8, y: u8)"),
+                range_of(text, "{ b(x, y) }")
+            ))
+        );
+        assert_eq!(
+            snapshot.range_for_syntax_parent(range_of(text, "x: u8")),
+            Some(range_of(text, "(x: u8, y: u8)"))
+        );
+        assert_eq!(
+            snapshot.range_for_syntax_first_child(range_of(text, "(x: u8, y: u8)")),
+            Some(range_of(text, "x: u8"))
+        );
+
+        buffer
+    });
+
+    fn range_of(text: &str, part: &str) -> Range<usize> {
+        let start = text.find(part).unwrap();
+        start..start + part.len()
+    }
+}
+
+#[gpui::test]
+fn test_text_object_ranges(cx: &mut AppContext) {
+    cx.new_model(|cx| {
+        let language = rust_lang()
+            .with_text_object_query(
+                r#"
+                (function_item body: (_) @function.inside) @function.around
+                (parameter) @parameter.inside
+                "#,
+            )
+            .unwrap();
+        let text = "fn a(x: u8, y: u8) { b(x, y) }\nfn c() {}";
+        let buffer = Buffer::local(text, cx).with_language(Arc::new(language), cx);
+        let snapshot = buffer.snapshot();
+
+        let call = range_of(text, "b(x, y)");
+        assert_eq!(
+            snapshot.range_for_text_object(call.clone(), TextObject::AroundFunction),
+            Some(range_of(text, "fn a(x: u8, y: u8) { b(x, y) }"))
+        );
+        assert_eq!(
+            snapshot.range_for_text_object(call.clone(), TextObject::InsideFunction),
+            Some(range_of(text, "{ b(x, y) }"))
+        );
+        assert_eq!(
+            snapshot.range_for_text_object(call, TextObject::Argument),
+            None
+        );
+
+        assert_eq!(
+            snapshot.range_for_adjacent_text_object(
+                range_of(text, "x: u8"),
+                TextObject::Argument,
+                true
+            ),
+            Some(range_of(text, "y: u8"))
+        );
+        assert_eq!(
+            snapshot.range_for_adjacent_text_object(
+                range_of(text, "x: u8"),
+                TextObject::AroundFunction,
+                true
+            ),
+            Some(range_of(text, "fn c() {}"))
+        );
+        assert_eq!(
+            snapshot.range_for_adjacent_text_object(
+                range_of(text, "fn c() {}"),
+                TextObject::AroundFunction,
+                false
+            ),
+            Some(range_of(text, "fn a(x: u8, y: u8) { b(x, y) }"))
+        );
+
+        buffer
+    });
+
+    fn range_of(text: &str, part: &str) -> Range<usize> {
+        let start = text.find(part).unwrap();
+        start..start + part.len()
+    }
+}
+
 #[gpui::test]
 fn test_autoindent_with_soft_tabs(cx: &mut AppContext) {
     init_settings(cx, |_| {});
@@ -3007,6 +3228,89 @@ fn test_random_collaboration(cx: &mut AppContext, mut rng: StdRng) {
     }
 }
 
+#[gpui::test]
+async fn test_structural_search_code_pattern(cx: &mut TestAppContext) {
+    let text = r#"
+        fn main() {
+            foo(1, bar(2));
+            foo(x, x);
+            baz(1, 2);
+        }
+    "#
+    .unindent();
+    let buffer =
+        cx.new_model(|cx| Buffer::local(text, cx).with_language(Arc::new(rust_lang()), cx));
+    buffer.update(cx, |buffer, _| buffer.parsing_idle()).await;
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
+    let matched_text = |matches: &[StructuralMatch]| {
+        matches
+            .iter()
+            .map(|mat| {
+                snapshot
+                    .text_for_range(mat.range.clone())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let pattern = StructuralPattern::new("foo($A, $B)");
+    assert_eq!(pattern.required_word(), Some("foo"));
+    let matches = pattern.matches(&snapshot, 0..snapshot.len());
+    assert_eq!(matched_text(&matches), ["foo(1, bar(2))", "foo(x, x)"]);
+    assert_eq!(
+        matches[0].expand_template("baz($B, $A, $C, $$A)", &snapshot),
+        "baz(bar(2), 1, $C, $A)"
+    );
+
+    // A repeated metavariable only matches identical text.
+    let pattern = StructuralPattern::new("foo($A, $A)");
+    let matches = pattern.matches(&snapshot, 0..snapshot.len());
+    assert_eq!(matched_text(&matches), ["foo(x, x)"]);
+
+    // Matches outside of the searched range are ignored.
+    let start = snapshot.text().find("foo(x").unwrap();
+    let pattern = StructuralPattern::new("$_($_, $_)");
+    let matches = pattern.matches(&snapshot, start..snapshot.len());
+    assert_eq!(matched_text(&matches), ["foo(x, x)", "baz(1, 2)"]);
+}
+
+#[gpui::test]
+async fn test_structural_search_query(cx: &mut TestAppContext) {
+    let text = r#"
+        fn main() {
+            foo(1);
+            bar(2);
+        }
+    "#
+    .unindent();
+    let buffer =
+        cx.new_model(|cx| Buffer::local(text, cx).with_language(Arc::new(rust_lang()), cx));
+    buffer.update(cx, |buffer, _| buffer.parsing_idle()).await;
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
+
+    let pattern = StructuralPattern::new(
+        r#"(call_expression function: (identifier) @name arguments: (_) @args (#eq? @name "bar")) @match"#,
+    );
+    assert_eq!(pattern.required_word(), None);
+    let matches = pattern.matches(&snapshot, 0..snapshot.len());
+    assert_eq!(matches.len(), 1);
+    assert_eq!(
+        snapshot
+            .text_for_range(matches[0].range.clone())
+            .collect::<String>(),
+        "bar(2)"
+    );
+    assert_eq!(
+        matches[0]
+            .captures
+            .iter()
+            .map(|capture| capture.name.as_str())
+            .collect::<Vec<_>>(),
+        ["name", "args"]
+    );
+    assert_eq!(matches[0].expand_template("baz$args", &snapshot), "baz(2)");
+}
+
 #[test]
 fn test_contiguous_ranges() {
     assert_eq!(
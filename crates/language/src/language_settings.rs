@@ -5,7 +5,9 @@ use anyhow::Result;
 use collections::{HashMap, HashSet};
 use core::slice;
 use ec4rs::{
-    property::{FinalNewline, IndentSize, IndentStyle, MaxLineLen, TabWidth, TrimTrailingWs},
+    property::{
+        EndOfLine, FinalNewline, IndentSize, IndentStyle, MaxLineLen, TabWidth, TrimTrailingWs,
+    },
     Properties as EditorconfigProperties,
 };
 use globset::{Glob, GlobMatcher, GlobSet, GlobSetBuilder};
@@ -96,6 +98,8 @@ pub struct LanguageSettings {
     /// Whether or not to ensure there's a single newline at the end of a buffer
     /// when saving it.
     pub ensure_final_newline_on_save: bool,
+    /// Which line endings to use when saving a buffer.
+    pub line_ending: LineEndingSetting,
     /// How to perform a buffer format.
     pub formatter: SelectedFormatter,
     /// Zed's Prettier integration settings.
@@ -283,6 +287,11 @@ pub struct LanguageSettingsContent {
     /// Default: true
     #[serde(default)]
     pub ensure_final_newline_on_save: Option<bool>,
+    /// Which line endings to use when saving a buffer.
+    ///
+    /// Default: detect
+    #[serde(default)]
+    pub line_ending: Option<LineEndingSetting>,
     /// How to perform a buffer format.
     ///
     /// Default: auto
@@ -401,6 +410,19 @@ pub enum SoftWrap {
     Bounded,
 }
 
+/// Controls which line endings buffers are saved with.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LineEndingSetting {
+    /// Keep the line endings that the file was loaded with.
+    #[default]
+    Detect,
+    /// Use `\n` line endings.
+    Lf,
+    /// Use `\r\n` line endings.
+    Crlf,
+}
+
 /// Controls the behavior of formatting files when they are saved.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FormatOnSave {
@@ -408,6 +430,9 @@ pub enum FormatOnSave {
     On,
     /// Files should not be formatted on save.
     Off,
+    /// Only the lines that were modified since the last Git commit should be
+    /// formatted on save, using the selected formatter.
+    ModifiedLines,
     List(FormatterList),
 }
 
@@ -432,6 +457,7 @@ impl JsonSchema for FormatOnSave {
             enum_values: Some(vec![
                 Value::String("on".into()),
                 Value::String("off".into()),
+                Value::String("modified_lines".into()),
                 Value::String("prettier".into()),
                 Value::String("language_server".into()),
             ]),
@@ -458,6 +484,7 @@ impl Serialize for FormatOnSave {
         match self {
             Self::On => serializer.serialize_str("on"),
             Self::Off => serializer.serialize_str("off"),
+            Self::ModifiedLines => serializer.serialize_str("modified_lines"),
             Self::List(list) => list.serialize(serializer),
         }
     }
@@ -484,6 +511,8 @@ impl<'de> Deserialize<'de> for FormatOnSave {
                     Ok(Self::Value::On)
                 } else if v == "off" {
                     Ok(Self::Value::Off)
+                } else if v == "modified_lines" {
+                    Ok(Self::Value::ModifiedLines)
                 } else if v == "language_server" {
                     Ok(Self::Value::List(FormatterList(
                         Formatter::LanguageServer { name: None }.into(),
@@ -896,6 +925,11 @@ fn merge_with_editorconfig(settings: &mut LanguageSettings, cfg: &EditorconfigPr
             TrimTrailingWs::Value(b) => b,
         })
         .ok();
+    let line_ending = cfg.get::<EndOfLine>().ok().and_then(|v| match v {
+        EndOfLine::Lf => Some(LineEndingSetting::Lf),
+        EndOfLine::CrLf => Some(LineEndingSetting::Crlf),
+        EndOfLine::Cr => None,
+    });
     let preferred_line_length = max_line_length;
     let soft_wrap = if max_line_length.is_some() {
         Some(SoftWrap::PreferredLineLength)
@@ -918,6 +952,7 @@ fn merge_with_editorconfig(settings: &mut LanguageSettings, cfg: &EditorconfigPr
         &mut settings.ensure_final_newline_on_save,
         ensure_final_newline_on_save,
     );
+    merge(&mut settings.line_ending, line_ending);
     merge(&mut settings.preferred_line_length, preferred_line_length);
     merge(&mut settings.soft_wrap, soft_wrap);
 }
@@ -1161,6 +1196,7 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         &mut settings.ensure_final_newline_on_save,
         src.ensure_final_newline_on_save,
     );
+    merge(&mut settings.line_ending, src.line_ending);
     merge(
         &mut settings.enable_language_server,
         src.enable_language_server,
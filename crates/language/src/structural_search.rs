@@ -0,0 +1,397 @@
+//! Structural search matches syntax trees rather than text. A pattern is either a
+//! Tree-sitter query, such as `(call_expression function: (identifier) @name)`, or a
+//! snippet of code with metavariables, such as `foo($A, $B)`.
+
+use crate::{syntax_map::TextProvider, with_parser, with_query_cursor, BufferSnapshot, Grammar};
+use crate::{GrammarId, Node, Tree};
+use collections::HashMap;
+use parking_lot::Mutex;
+use std::{fmt, ops::Range, sync::Arc};
+use tree_sitter::Query;
+
+/// The capture name marking the range of a whole match in a Tree-sitter query pattern.
+const MATCH_CAPTURE_NAME: &str = "match";
+const PLACEHOLDER_PREFIX: &str = "zed_metavar_";
+const PATTERN_SUFFIXES: &[&str] = &["", ";"];
+/// A metavariable that matches any node without binding it.
+const WILDCARD_METAVARIABLE: &str = "_";
+
+/// A structural search pattern, compiled lazily for every grammar it is matched against.
+pub struct StructuralPattern {
+    source: String,
+    /// The source with every metavariable replaced by an identifier, so that it can be parsed.
+    placeholder_source: String,
+    /// Pairs of placeholder identifiers and the metavariable names they stand for.
+    metavariables: Vec<(String, String)>,
+    compiled: Mutex<HashMap<GrammarId, Option<Arc<CompiledPattern>>>>,
+}
+
+enum CompiledPattern {
+    Query(Query),
+    Code {
+        tree: Tree,
+        /// The placeholder source the tree was parsed from.
+        source: String,
+    },
+}
+
+/// A range of a buffer matched by a [`StructuralPattern`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructuralMatch {
+    pub range: Range<usize>,
+    pub captures: Vec<StructuralCapture>,
+}
+
+/// A metavariable or query capture within a [`StructuralMatch`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructuralCapture {
+    pub name: String,
+    pub range: Range<usize>,
+}
+
+impl StructuralPattern {
+    pub fn new(source: &str) -> Self {
+        let source = source.trim().to_string();
+        let mut placeholder_source = String::with_capacity(source.len());
+        let mut metavariables = Vec::<(String, String)>::new();
+        let mut chars = source.char_indices();
+        while let Some((ix, ch)) = chars.next() {
+            if ch == '$' {
+                let name_len = metavariable_name_len(&source[ix + 1..]);
+                if name_len > 0 {
+                    let name = &source[ix + 1..ix + 1 + name_len];
+                    let existing = metavariables
+                        .iter()
+                        .find(|(_, existing_name)| {
+                            existing_name == name && name != WILDCARD_METAVARIABLE
+                        })
+                        .map(|(placeholder, _)| placeholder.clone());
+                    let placeholder = existing.unwrap_or_else(|| {
+                        let placeholder = format!("{PLACEHOLDER_PREFIX}{}", metavariables.len());
+                        metavariables.push((placeholder.clone(), name.to_string()));
+                        placeholder
+                    });
+                    placeholder_source.push_str(&placeholder);
+                    chars.nth(name_len - 1);
+                    continue;
+                }
+            }
+            placeholder_source.push(ch);
+        }
+
+        Self {
+            source,
+            placeholder_source,
+            metavariables,
+            compiled: Default::default(),
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether the pattern reads as a Tree-sitter query rather than as a code pattern.
+    pub fn looks_like_query(&self) -> bool {
+        self.source.starts_with('(') || self.source.starts_with('[')
+    }
+
+    /// The longest word that every match of this pattern must contain. Used to skip files
+    /// that cannot match without parsing them.
+    pub fn required_word(&self) -> Option<&str> {
+        if self.looks_like_query() {
+            return None;
+        }
+        let mut longest: Option<&str> = None;
+        let mut word_start = None;
+        for (ix, ch) in self.source.char_indices().chain([(self.source.len(), ' ')]) {
+            if ch.is_alphanumeric() || ch == '_' {
+                word_start.get_or_insert(ix);
+            } else if let Some(start) = word_start.take() {
+                let is_metavariable = self.source[..start].ends_with('$')
+                    && metavariable_name_len(&self.source[start..]) == ix - start;
+                let word = &self.source[start..ix];
+                if !is_metavariable && longest.map_or(true, |longest| word.len() > longest.len()) {
+                    longest = Some(word);
+                }
+            }
+        }
+        longest
+    }
+
+    /// Finds the non-overlapping matches of this pattern that lie within the given range,
+    /// across every syntax layer of the buffer.
+    pub fn matches(&self, buffer: &BufferSnapshot, range: Range<usize>) -> Vec<StructuralMatch> {
+        let mut matches = Vec::new();
+        for layer in buffer.syntax_layers() {
+            let Some(grammar) = layer.language.grammar() else {
+                continue;
+            };
+            let Some(compiled) = self.compile(grammar) else {
+                continue;
+            };
+            match compiled.as_ref() {
+                CompiledPattern::Query(query) => {
+                    query_matches(query, layer.node(), buffer, range.clone(), &mut matches)
+                }
+                CompiledPattern::Code { tree, source } => {
+                    let pattern_root = tree
+                        .root_node()
+                        .descendant_for_byte_range(0, self.placeholder_source.len())
+                        .unwrap_or_else(|| tree.root_node());
+                    self.code_matches(
+                        pattern_root,
+                        source,
+                        layer.node(),
+                        buffer,
+                        range.clone(),
+                        &mut matches,
+                    )
+                }
+            }
+        }
+
+        matches.sort_by_key(|mat| (mat.range.start, std::cmp::Reverse(mat.range.end)));
+        let mut last_end = None;
+        matches.retain(|mat| {
+            if last_end.map_or(true, |last_end| mat.range.start >= last_end) {
+                last_end = Some(mat.range.end);
+                true
+            } else {
+                false
+            }
+        });
+        matches
+    }
+
+    fn compile(&self, grammar: &Grammar) -> Option<Arc<CompiledPattern>> {
+        self.compiled
+            .lock()
+            .entry(grammar.id())
+            .or_insert_with(|| {
+                if self.looks_like_query() {
+                    if let Ok(query) = Query::new(&grammar.ts_language, &self.source) {
+                        return Some(Arc::new(CompiledPattern::Query(query)));
+                    }
+                }
+
+                // Many grammars only accept statements at the top level, so that a bare
+                // expression such as `foo($A)` has to be terminated to parse.
+                with_parser(|parser| {
+                    parser.set_language(&grammar.ts_language).ok()?;
+                    PATTERN_SUFFIXES.iter().find_map(|suffix| {
+                        let source = format!("{}{suffix}", self.placeholder_source);
+                        let tree = parser.parse(&source, None)?;
+                        if tree.root_node().has_error() {
+                            None
+                        } else {
+                            Some(Arc::new(CompiledPattern::Code { tree, source }))
+                        }
+                    })
+                })
+            })
+            .clone()
+    }
+
+    fn code_matches(
+        &self,
+        pattern: Node,
+        pattern_source: &str,
+        root: Node,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+        matches: &mut Vec<StructuralMatch>,
+    ) {
+        let mut cursor = root.walk();
+        loop {
+            let node = cursor.node();
+            let node_range = node.byte_range();
+            let mut visit_children = node_range.start < range.end && node_range.end > range.start;
+            if visit_children
+                && !node_range.is_empty()
+                && node_range.start >= range.start
+                && node_range.end <= range.end
+            {
+                let mut captures = Vec::new();
+                if self.match_node(pattern, pattern_source, node, buffer, &mut captures) {
+                    matches.push(StructuralMatch {
+                        range: node_range,
+                        captures,
+                    });
+                    visit_children = false;
+                }
+            }
+
+            if visit_children && cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn match_node(
+        &self,
+        pattern: Node,
+        pattern_source: &str,
+        candidate: Node,
+        buffer: &BufferSnapshot,
+        captures: &mut Vec<StructuralCapture>,
+    ) -> bool {
+        let pattern_text = &pattern_source[pattern.byte_range()];
+        if let Some((_, name)) = self
+            .metavariables
+            .iter()
+            .find(|(placeholder, _)| placeholder == pattern_text)
+        {
+            if name == WILDCARD_METAVARIABLE {
+                return true;
+            }
+            if let Some(capture) = captures.iter().find(|capture| capture.name == *name) {
+                return text_eq(buffer, capture.range.clone(), candidate.byte_range());
+            }
+            captures.push(StructuralCapture {
+                name: name.clone(),
+                range: candidate.byte_range(),
+            });
+            return true;
+        }
+
+        if pattern.kind_id() != candidate.kind_id() {
+            return false;
+        }
+        if pattern.child_count() == 0 {
+            return candidate.byte_range().len() == pattern_text.len()
+                && buffer
+                    .text_for_range(candidate.byte_range())
+                    .flat_map(str::chars)
+                    .eq(pattern_text.chars());
+        }
+
+        let pattern_children = significant_children(pattern);
+        let candidate_children = significant_children(candidate);
+        pattern_children.len() == candidate_children.len()
+            && pattern_children
+                .into_iter()
+                .zip(candidate_children)
+                .all(|(pattern, candidate)| {
+                    self.match_node(pattern, pattern_source, candidate, buffer, captures)
+                })
+    }
+}
+
+impl fmt::Debug for StructuralPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StructuralPattern")
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+impl StructuralMatch {
+    /// Expands a replacement template, substituting `$NAME` with the text captured by the
+    /// metavariable or query capture of that name. `$$` inserts a literal `$`, and names
+    /// that were not captured are left untouched.
+    pub fn expand_template(&self, template: &str, buffer: &BufferSnapshot) -> String {
+        let mut expanded = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(dollar_ix) = rest.find('$') {
+            expanded.push_str(&rest[..dollar_ix]);
+            rest = &rest[dollar_ix + 1..];
+            if let Some(after_dollar) = rest.strip_prefix('$') {
+                expanded.push('$');
+                rest = after_dollar;
+                continue;
+            }
+
+            let name_len = rest
+                .find(|ch: char| !(ch.is_alphanumeric() || ch == '_'))
+                .unwrap_or(rest.len());
+            let name = &rest[..name_len];
+            if let Some(capture) = self.captures.iter().find(|capture| capture.name == name) {
+                expanded.extend(buffer.text_for_range(capture.range.clone()));
+            } else {
+                expanded.push('$');
+                expanded.push_str(name);
+            }
+            rest = &rest[name_len..];
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+}
+
+fn query_matches(
+    query: &Query,
+    root: Node,
+    buffer: &BufferSnapshot,
+    range: Range<usize>,
+    matches: &mut Vec<StructuralMatch>,
+) {
+    with_query_cursor(|cursor| {
+        cursor.set_byte_range(range.clone());
+        let capture_names = query.capture_names();
+        for mat in cursor.matches(query, root, TextProvider(buffer.as_rope())) {
+            let mut match_range = None;
+            let mut captures = Vec::new();
+            for capture in mat.captures {
+                let name = capture_names[capture.index as usize];
+                let capture_range = capture.node.byte_range();
+                if name == MATCH_CAPTURE_NAME {
+                    match_range = Some(capture_range);
+                } else if !name.starts_with('_') {
+                    captures.push(StructuralCapture {
+                        name: name.to_string(),
+                        range: capture_range,
+                    });
+                }
+            }
+
+            let match_range = match_range.or_else(|| {
+                let start = captures.iter().map(|capture| capture.range.start).min()?;
+                let end = captures.iter().map(|capture| capture.range.end).max()?;
+                Some(start..end)
+            });
+            if let Some(match_range) = match_range {
+                if match_range.start >= range.start && match_range.end <= range.end {
+                    matches.push(StructuralMatch {
+                        range: match_range,
+                        captures,
+                    });
+                }
+            }
+        }
+    });
+}
+
+fn significant_children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| !child.is_extra())
+        .collect()
+}
+
+fn metavariable_name_len(text: &str) -> usize {
+    let mut chars = text.char_indices();
+    match chars.next() {
+        Some((_, ch)) if ch.is_ascii_uppercase() || ch == '_' => {}
+        _ => return 0,
+    }
+    match chars.find(|(_, ch)| !(ch.is_ascii_uppercase() || ch.is_ascii_digit() || *ch == '_')) {
+        // `$Foo` is not a metavariable followed by `oo`.
+        Some((_, ch)) if ch.is_alphanumeric() => 0,
+        Some((ix, _)) => ix,
+        None => text.len(),
+    }
+}
+
+fn text_eq(buffer: &BufferSnapshot, a: Range<usize>, b: Range<usize>) -> bool {
+    a.len() == b.len()
+        && buffer
+            .text_for_range(a)
+            .flat_map(str::chars)
+            .eq(buffer.text_for_range(b).flat_map(str::chars))
+}
@@ -14,6 +14,7 @@ use gpui::{
     AnyElement, AnyView, AppContext, AsyncAppContext, Model, SharedString, Task, WindowContext,
 };
 pub use model::*;
+use parking_lot::Mutex;
 use project::Fs;
 use proto::Plan;
 pub(crate) use rate_limiter::*;
@@ -23,6 +24,7 @@ pub use role::*;
 use schemars::JsonSchema;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::fmt;
+use std::ops::{Add, AddAssign};
 use std::{future::Future, sync::Arc};
 use ui::IconName;
 
@@ -59,6 +61,54 @@ pub enum LanguageModelCompletionEvent {
     Stop(StopReason),
     Text(String),
     ToolUse(LanguageModelToolUse),
+    /// The tokens consumed by the request so far.
+    UsageUpdate(TokenUsage),
+}
+
+/// The number of tokens a completion request consumed, as reported by the provider.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct TokenUsage {
+    #[serde(default)]
+    pub input_tokens: u32,
+    #[serde(default)]
+    pub output_tokens: u32,
+    #[serde(default)]
+    pub cache_creation_input_tokens: u32,
+    #[serde(default)]
+    pub cache_read_input_tokens: u32,
+}
+
+impl TokenUsage {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn total_tokens(&self) -> u32 {
+        self.input_tokens
+            + self.output_tokens
+            + self.cache_creation_input_tokens
+            + self.cache_read_input_tokens
+    }
+}
+
+impl Add<TokenUsage> for TokenUsage {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            input_tokens: self.input_tokens + other.input_tokens,
+            output_tokens: self.output_tokens + other.output_tokens,
+            cache_creation_input_tokens: self.cache_creation_input_tokens
+                + other.cache_creation_input_tokens,
+            cache_read_input_tokens: self.cache_read_input_tokens + other.cache_read_input_tokens,
+        }
+    }
+}
+
+impl AddAssign<TokenUsage> for TokenUsage {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
@@ -69,6 +119,13 @@ pub enum StopReason {
     ToolUse,
 }
 
+/// The text of a streamed completion, along with the tokens the request consumed.
+pub struct LanguageModelTextStream {
+    pub stream: BoxStream<'static, Result<String>>,
+    /// The latest usage reported by the provider, updated as the stream is consumed.
+    pub last_token_usage: Arc<Mutex<TokenUsage>>,
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
 pub struct LanguageModelToolUse {
     pub id: String,
@@ -113,21 +170,36 @@ pub trait LanguageModel: Send + Sync {
         &self,
         request: LanguageModelRequest,
         cx: &AsyncAppContext,
-    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
+    ) -> BoxFuture<'static, Result<LanguageModelTextStream>> {
         let events = self.stream_completion(request, cx);
 
         async move {
-            Ok(events
+            let last_token_usage = Arc::new(Mutex::new(TokenUsage::default()));
+            let stream = events
                 .await?
-                .filter_map(|result| async move {
-                    match result {
-                        Ok(LanguageModelCompletionEvent::Text(text)) => Some(Ok(text)),
-                        Ok(LanguageModelCompletionEvent::Stop(_)) => None,
-                        Ok(LanguageModelCompletionEvent::ToolUse(_)) => None,
-                        Err(err) => Some(Err(err)),
+                .filter_map({
+                    let last_token_usage = last_token_usage.clone();
+                    move |result| {
+                        let last_token_usage = last_token_usage.clone();
+                        async move {
+                            match result {
+                                Ok(LanguageModelCompletionEvent::Text(text)) => Some(Ok(text)),
+                                Ok(LanguageModelCompletionEvent::Stop(_)) => None,
+                                Ok(LanguageModelCompletionEvent::ToolUse(_)) => None,
+                                Ok(LanguageModelCompletionEvent::UsageUpdate(usage)) => {
+                                    *last_token_usage.lock() = usage;
+                                    None
+                                }
+                                Err(err) => Some(Err(err)),
+                            }
+                        }
                     }
                 })
-                .boxed())
+                .boxed();
+            Ok(LanguageModelTextStream {
+                stream,
+                last_token_usage,
+            })
         }
         .boxed()
     }
@@ -261,3 +333,33 @@ impl From<String> for LanguageModelProviderName {
         Self(SharedString::from(value))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use gpui::TestAppContext;
+
+    use super::*;
+    use crate::provider::fake::FakeLanguageModel;
+
+    #[gpui::test]
+    async fn test_stream_completion_text_reports_usage(cx: &mut TestAppContext) {
+        let model = FakeLanguageModel::default();
+        let text_stream =
+            model.stream_completion_text(LanguageModelRequest::default(), &cx.to_async());
+
+        let usage = TokenUsage {
+            input_tokens: 10,
+            output_tokens: 2,
+            ..Default::default()
+        };
+        model.stream_last_completion_response("Hello".into());
+        model.stream_last_completion_usage(usage);
+        model.stream_last_completion_response(" world".into());
+        model.end_last_completion_stream();
+
+        let text_stream = text_stream.await.unwrap();
+        let text = text_stream.stream.try_collect::<String>().await.unwrap();
+        assert_eq!(text, "Hello world");
+        assert_eq!(*text_stream.last_token_usage.lock(), usage);
+    }
+}
@@ -3,8 +3,8 @@ use crate::{
     LanguageModelId, LanguageModelName, LanguageModelProvider, LanguageModelProviderId,
     LanguageModelProviderName, LanguageModelProviderState, LanguageModelRequest, RateLimiter, Role,
 };
-use crate::{LanguageModelCompletionEvent, LanguageModelToolUse, StopReason};
-use anthropic::{AnthropicError, ContentDelta, Event, ResponseContent};
+use crate::{LanguageModelCompletionEvent, LanguageModelToolUse, StopReason, TokenUsage};
+use anthropic::{AnthropicError, ContentDelta, Event, ResponseContent, Usage};
 use anyhow::{anyhow, Context as _, Result};
 use collections::{BTreeMap, HashMap};
 use editor::{Editor, EditorElement, EditorStyle};
@@ -449,17 +449,35 @@ pub fn map_to_language_model_completion_events(
     struct State {
         events: Pin<Box<dyn Send + Stream<Item = Result<Event, AnthropicError>>>>,
         tool_uses_by_index: HashMap<usize, RawToolUse>,
+        usage: TokenUsage,
+        pending_stop_reason: Option<StopReason>,
     }
 
     futures::stream::unfold(
         State {
             events,
             tool_uses_by_index: HashMap::default(),
+            usage: TokenUsage::default(),
+            pending_stop_reason: None,
         },
         |mut state| async move {
+            if let Some(stop_reason) = state.pending_stop_reason.take() {
+                return Some((
+                    Some(Ok(LanguageModelCompletionEvent::Stop(stop_reason))),
+                    state,
+                ));
+            }
+
             while let Some(event) = state.events.next().await {
                 match event {
                     Ok(event) => match event {
+                        Event::MessageStart { message } => {
+                            update_usage(&mut state.usage, &message.usage);
+                            return Some((
+                                Some(Ok(LanguageModelCompletionEvent::UsageUpdate(state.usage))),
+                                state,
+                            ));
+                        }
                         Event::ContentBlockStart {
                             index,
                             content_block,
@@ -516,20 +534,22 @@ pub fn map_to_language_model_completion_events(
                                 ));
                             }
                         }
-                        Event::MessageDelta { delta, .. } => {
+                        Event::MessageDelta { delta, usage } => {
+                            update_usage(&mut state.usage, &usage);
                             if let Some(stop_reason) = delta.stop_reason.as_deref() {
-                                let stop_reason = match stop_reason {
+                                // Report the final usage before stopping.
+                                state.pending_stop_reason = Some(match stop_reason {
                                     "end_turn" => StopReason::EndTurn,
                                     "max_tokens" => StopReason::MaxTokens,
                                     "tool_use" => StopReason::ToolUse,
                                     _ => StopReason::EndTurn,
-                                };
-
-                                return Some((
-                                    Some(Ok(LanguageModelCompletionEvent::Stop(stop_reason))),
-                                    state,
-                                ));
+                                });
                             }
+
+                            return Some((
+                                Some(Ok(LanguageModelCompletionEvent::UsageUpdate(state.usage))),
+                                state,
+                            ));
                         }
                         Event::Error { error } => {
                             return Some((
@@ -551,6 +571,22 @@ pub fn map_to_language_model_completion_events(
     .filter_map(|event| async move { event })
 }
 
+/// Anthropic reports cumulative counts, and omits the ones that haven't changed.
+fn update_usage(usage: &mut TokenUsage, new: &Usage) {
+    if let Some(input_tokens) = new.input_tokens {
+        usage.input_tokens = input_tokens;
+    }
+    if let Some(output_tokens) = new.output_tokens {
+        usage.output_tokens = output_tokens;
+    }
+    if let Some(cache_creation_input_tokens) = new.cache_creation_input_tokens {
+        usage.cache_creation_input_tokens = cache_creation_input_tokens;
+    }
+    if let Some(cache_read_input_tokens) = new.cache_read_input_tokens {
+        usage.cache_read_input_tokens = cache_read_input_tokens;
+    }
+}
+
 struct ConfigurationView {
     api_key_editor: View<Editor>,
     state: gpui::Model<State>,
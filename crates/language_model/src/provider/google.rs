@@ -1,8 +1,8 @@
 use anyhow::{anyhow, Result};
 use collections::BTreeMap;
 use editor::{Editor, EditorElement, EditorStyle};
-use futures::{future::BoxFuture, FutureExt, StreamExt};
-use google_ai::stream_generate_content;
+use futures::{future::BoxFuture, FutureExt, Stream, StreamExt};
+use google_ai::{stream_generate_content, GenerateContentResponse, Part, TextPart};
 use gpui::{
     AnyView, AppContext, AsyncAppContext, FontStyle, ModelContext, Subscription, Task, TextStyle,
     View, WhiteSpace,
@@ -17,12 +17,12 @@ use theme::ThemeSettings;
 use ui::{prelude::*, Icon, IconName, Tooltip};
 use util::ResultExt;
 
-use crate::LanguageModelCompletionEvent;
 use crate::{
     settings::AllLanguageModelSettings, LanguageModel, LanguageModelId, LanguageModelName,
     LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
     LanguageModelProviderState, LanguageModelRequest, RateLimiter,
 };
+use crate::{LanguageModelCompletionEvent, TokenUsage};
 
 const PROVIDER_ID: &str = "google";
 const PROVIDER_NAME: &str = "Google AI";
@@ -313,15 +313,9 @@ impl LanguageModel for GoogleLanguageModel {
                 low_speed_timeout,
             );
             let events = response.await?;
-            Ok(google_ai::extract_text_from_events(events).boxed())
+            Ok(map_to_language_model_completion_events(events).boxed())
         });
-        async move {
-            Ok(future
-                .await?
-                .map(|result| result.map(LanguageModelCompletionEvent::Text))
-                .boxed())
-        }
-        .boxed()
+        async move { Ok(future.await?.boxed()) }.boxed()
     }
 
     fn use_any_tool(
@@ -336,6 +330,44 @@ impl LanguageModel for GoogleLanguageModel {
     }
 }
 
+pub fn map_to_language_model_completion_events(
+    events: impl Stream<Item = Result<GenerateContentResponse>>,
+) -> impl Stream<Item = Result<LanguageModelCompletionEvent>> {
+    events.flat_map(|event| {
+        let mut completion_events = Vec::new();
+        match event {
+            Ok(event) => {
+                let text = event
+                    .candidates
+                    .and_then(|candidates| candidates.into_iter().next())
+                    .and_then(|candidate| candidate.content.parts.into_iter().next())
+                    .and_then(|part| match part {
+                        Part::TextPart(TextPart { text }) => Some(text),
+                        _ => None,
+                    });
+                if let Some(text) = text {
+                    completion_events.push(Ok(LanguageModelCompletionEvent::Text(text)));
+                }
+                if let Some(usage) = event.usage_metadata {
+                    // Gemini counts cached tokens as part of the prompt.
+                    completion_events.push(Ok(LanguageModelCompletionEvent::UsageUpdate(
+                        TokenUsage {
+                            input_tokens: usage
+                                .prompt_token_count
+                                .saturating_sub(usage.cached_content_token_count),
+                            output_tokens: usage.candidates_token_count,
+                            cache_creation_input_tokens: 0,
+                            cache_read_input_tokens: usage.cached_content_token_count,
+                        },
+                    )));
+                }
+            }
+            Err(error) => completion_events.push(Err(error)),
+        }
+        futures::stream::iter(completion_events)
+    })
+}
+
 struct ConfigurationView {
     api_key_editor: View<Editor>,
     state: gpui::Model<State>,
@@ -13,12 +13,12 @@ use std::{collections::BTreeMap, sync::Arc, time::Duration};
 use ui::{prelude::*, ButtonLike, Indicator};
 use util::ResultExt;
 
-use crate::LanguageModelCompletionEvent;
 use crate::{
     settings::AllLanguageModelSettings, LanguageModel, LanguageModelId, LanguageModelName,
     LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
     LanguageModelProviderState, LanguageModelRequest, RateLimiter, Role,
 };
+use crate::{LanguageModelCompletionEvent, TokenUsage};
 
 const OLLAMA_DOWNLOAD_URL: &str = "https://ollama.com/download";
 const OLLAMA_LIBRARY_URL: &str = "https://ollama.com/library";
@@ -234,6 +234,7 @@ impl OllamaLanguageModel {
             stream: true,
             options: Some(ChatOptions {
                 num_ctx: Some(self.model.max_tokens),
+                num_predict: request.max_tokens.map(|tokens| tokens as isize),
                 stop: Some(request.stop),
                 temperature: request.temperature.or(Some(1.0)),
                 ..Default::default()
@@ -321,7 +322,8 @@ impl LanguageModel for OllamaLanguageModel {
                 stream_chat_completion(http_client.as_ref(), &api_url, request, low_speed_timeout)
                     .await?;
             let stream = response
-                .filter_map(|response| async move {
+                .flat_map(|response| {
+                    let mut events = Vec::new();
                     match response {
                         Ok(delta) => {
                             let content = match delta.message {
@@ -329,22 +331,26 @@ impl LanguageModel for OllamaLanguageModel {
                                 ChatMessage::Assistant { content, .. } => content,
                                 ChatMessage::System { content } => content,
                             };
-                            Some(Ok(content))
+                            events.push(Ok(LanguageModelCompletionEvent::Text(content)));
+                            if delta.prompt_eval_count.is_some() || delta.eval_count.is_some() {
+                                events.push(Ok(LanguageModelCompletionEvent::UsageUpdate(
+                                    TokenUsage {
+                                        input_tokens: delta.prompt_eval_count.unwrap_or(0),
+                                        output_tokens: delta.eval_count.unwrap_or(0),
+                                        ..Default::default()
+                                    },
+                                )));
+                            }
                         }
-                        Err(error) => Some(Err(error)),
+                        Err(error) => events.push(Err(error)),
                     }
+                    futures::stream::iter(events)
                 })
                 .boxed();
             Ok(stream)
         });
 
-        async move {
-            Ok(future
-                .await?
-                .map(|result| result.map(LanguageModelCompletionEvent::Text))
-                .boxed())
-        }
-        .boxed()
+        async move { Ok(future.await?.boxed()) }.boxed()
     }
 
     fn use_any_tool(
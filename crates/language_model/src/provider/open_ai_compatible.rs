@@ -0,0 +1,797 @@
+use anyhow::{anyhow, Result};
+use collections::BTreeMap;
+use futures::{future::BoxFuture, stream::BoxStream, FutureExt, Stream, StreamExt};
+use gpui::{AnyView, AppContext, AsyncAppContext, ModelContext, Subscription, Task};
+use http_client::HttpClient;
+use open_ai::{
+    list_models, stream_completion, FunctionDefinition, ResponseStreamEvent, ToolChoice,
+    ToolDefinition,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsStore};
+use std::{sync::Arc, time::Duration};
+use ui::{prelude::*, ButtonLike, Indicator};
+use util::ResultExt;
+
+use crate::{
+    provider::open_ai::count_open_ai_tokens, settings::AllLanguageModelSettings, LanguageModel,
+    LanguageModelCompletionEvent, LanguageModelId, LanguageModelName, LanguageModelProvider,
+    LanguageModelProviderId, LanguageModelProviderName, LanguageModelProviderState,
+    LanguageModelRequest, LanguageModelToolUse, RateLimiter, StopReason,
+};
+
+/// The prefix of the IDs of OpenAI-compatible providers, which are followed by the
+/// name the provider was given in the settings.
+pub const PROVIDER_ID_PREFIX: &str = "openai_compatible/";
+
+/// The context length assumed for discovered models whose server doesn't report one.
+const DEFAULT_MAX_TOKENS: usize = 8192;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OpenAiCompatibleSettings {
+    pub api_url: String,
+    pub api_key_env_var: Option<String>,
+    pub low_speed_timeout: Option<Duration>,
+    pub discover_models: bool,
+    pub available_models: Vec<AvailableModel>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AvailableModel {
+    /// The model's ID in the server's API, e.g. "meta-llama/Llama-3.1-8B-Instruct".
+    pub name: String,
+    /// The model's name in Zed's UI, such as in the model selector dropdown menu in the assistant panel.
+    pub display_name: Option<String>,
+    /// The model's context window size.
+    pub max_tokens: usize,
+    /// The maximum number of tokens the model can generate.
+    pub max_output_tokens: Option<u32>,
+    /// Whether the server supports calling tools with this model.
+    #[serde(default)]
+    pub supports_tools: bool,
+}
+
+pub fn provider_id(name: &str) -> LanguageModelProviderId {
+    LanguageModelProviderId::from(format!("{PROVIDER_ID_PREFIX}{name}"))
+}
+
+pub struct OpenAiCompatibleLanguageModelProvider {
+    name: Arc<str>,
+    http_client: Arc<dyn HttpClient>,
+    state: gpui::Model<State>,
+}
+
+pub struct State {
+    name: Arc<str>,
+    http_client: Arc<dyn HttpClient>,
+    api_key: Option<String>,
+    discovered_models: Vec<AvailableModel>,
+    _subscription: Subscription,
+}
+
+impl State {
+    fn settings<'a>(&self, cx: &'a AppContext) -> Option<&'a OpenAiCompatibleSettings> {
+        AllLanguageModelSettings::get_global(cx)
+            .openai_compatible
+            .get(self.name.as_ref())
+    }
+
+    fn is_authenticated(&self, cx: &AppContext) -> bool {
+        let Some(settings) = self.settings(cx) else {
+            return false;
+        };
+        let has_api_key = settings.api_key_env_var.is_none() || self.api_key.is_some();
+        let has_models =
+            !settings.available_models.is_empty() || !self.discovered_models.is_empty();
+        has_api_key && has_models
+    }
+
+    fn fetch_models(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        let Some(settings) = self.settings(cx).cloned() else {
+            return Task::ready(Err(anyhow!("provider {} is not configured", self.name)));
+        };
+
+        let api_key = match &settings.api_key_env_var {
+            Some(var) => match std::env::var(var) {
+                Ok(api_key) => Some(api_key),
+                Err(_) => {
+                    self.api_key = None;
+                    cx.notify();
+                    return Task::ready(Err(anyhow!("{var} environment variable is not set")));
+                }
+            },
+            None => None,
+        };
+        if !settings.discover_models {
+            self.api_key = api_key;
+            self.discovered_models.clear();
+            cx.notify();
+            return Task::ready(Ok(()));
+        }
+
+        let http_client = self.http_client.clone();
+        let api_url = settings.api_url;
+        let low_speed_timeout = settings.low_speed_timeout;
+        cx.spawn(|this, mut cx| async move {
+            let models = list_models(
+                http_client.as_ref(),
+                &api_url,
+                api_key.as_deref(),
+                low_speed_timeout,
+            )
+            .await;
+
+            this.update(&mut cx, |this, cx| {
+                this.api_key = api_key;
+                this.discovered_models = match &models {
+                    Ok(models) => models
+                        .iter()
+                        .map(|model| AvailableModel {
+                            name: model.id.clone(),
+                            display_name: None,
+                            max_tokens: model.max_token_count().unwrap_or(DEFAULT_MAX_TOKENS),
+                            max_output_tokens: None,
+                            supports_tools: false,
+                        })
+                        .collect(),
+                    Err(_) => Vec::new(),
+                };
+                cx.notify();
+            })?;
+            models.map(|_| ())
+        })
+    }
+
+    fn authenticate(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        if self.is_authenticated(cx) {
+            Task::ready(Ok(()))
+        } else {
+            self.fetch_models(cx)
+        }
+    }
+}
+
+impl OpenAiCompatibleLanguageModelProvider {
+    pub fn new(name: Arc<str>, http_client: Arc<dyn HttpClient>, cx: &mut AppContext) -> Self {
+        let state = cx.new_model(|cx| {
+            let mut settings = AllLanguageModelSettings::get_global(cx)
+                .openai_compatible
+                .get(name.as_ref())
+                .cloned();
+            State {
+                name: name.clone(),
+                http_client: http_client.clone(),
+                api_key: None,
+                discovered_models: Vec::new(),
+                _subscription: cx.observe_global::<SettingsStore>(move |this: &mut State, cx| {
+                    let new_settings = this.settings(cx).cloned();
+                    if new_settings != settings {
+                        settings = new_settings;
+                        this.fetch_models(cx).detach();
+                    }
+                    cx.notify();
+                }),
+            }
+        });
+        state.update(cx, |state, cx| state.fetch_models(cx).detach());
+
+        Self {
+            name,
+            http_client,
+            state,
+        }
+    }
+}
+
+impl LanguageModelProviderState for OpenAiCompatibleLanguageModelProvider {
+    type ObservableEntity = State;
+
+    fn observable_entity(&self) -> Option<gpui::Model<Self::ObservableEntity>> {
+        Some(self.state.clone())
+    }
+}
+
+impl LanguageModelProvider for OpenAiCompatibleLanguageModelProvider {
+    fn id(&self) -> LanguageModelProviderId {
+        provider_id(&self.name)
+    }
+
+    fn name(&self) -> LanguageModelProviderName {
+        LanguageModelProviderName::from(self.name.to_string())
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::AiOpenAi
+    }
+
+    fn provided_models(&self, cx: &AppContext) -> Vec<Arc<dyn LanguageModel>> {
+        let state = self.state.read(cx);
+        let mut models = BTreeMap::default();
+
+        // Add models from the server's model listing
+        for model in &state.discovered_models {
+            models.insert(model.name.clone(), model.clone());
+        }
+
+        // Override with available models from settings
+        if let Some(settings) = state.settings(cx) {
+            for model in &settings.available_models {
+                models.insert(model.name.clone(), model.clone());
+            }
+        }
+
+        models
+            .into_values()
+            .map(|model| {
+                Arc::new(OpenAiCompatibleLanguageModel {
+                    id: LanguageModelId::from(model.name.clone()),
+                    provider_name: self.name.clone(),
+                    model,
+                    state: self.state.clone(),
+                    http_client: self.http_client.clone(),
+                    request_limiter: RateLimiter::new(4),
+                }) as Arc<dyn LanguageModel>
+            })
+            .collect()
+    }
+
+    fn is_authenticated(&self, cx: &AppContext) -> bool {
+        self.state.read(cx).is_authenticated(cx)
+    }
+
+    fn authenticate(&self, cx: &mut AppContext) -> Task<Result<()>> {
+        self.state.update(cx, |state, cx| state.authenticate(cx))
+    }
+
+    fn configuration_view(&self, cx: &mut WindowContext) -> AnyView {
+        let state = self.state.clone();
+        cx.new_view(|cx| ConfigurationView::new(state, cx)).into()
+    }
+
+    fn reset_credentials(&self, cx: &mut AppContext) -> Task<Result<()>> {
+        self.state.update(cx, |state, cx| state.fetch_models(cx))
+    }
+}
+
+pub struct OpenAiCompatibleLanguageModel {
+    id: LanguageModelId,
+    provider_name: Arc<str>,
+    model: AvailableModel,
+    state: gpui::Model<State>,
+    http_client: Arc<dyn HttpClient>,
+    request_limiter: RateLimiter,
+}
+
+impl OpenAiCompatibleLanguageModel {
+    fn to_open_ai_request(&self, request: LanguageModelRequest) -> open_ai::Request {
+        let tools = if self.model.supports_tools {
+            request
+                .tools
+                .iter()
+                .map(|tool| ToolDefinition::Function {
+                    function: FunctionDefinition {
+                        name: tool.name.clone(),
+                        description: Some(tool.description.clone()),
+                        parameters: Some(tool.input_schema.clone()),
+                    },
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let mut request = request.into_open_ai(self.model.name.clone(), self.max_output_tokens());
+        // Unlike OpenAI's own API, compatible servers stream every model.
+        request.stream = true;
+        // Not every server accepts stream options, so only use the usage they
+        // report on their own.
+        request.stream_options = None;
+        request.tools = tools;
+        request
+    }
+
+    fn stream_completion(
+        &self,
+        request: open_ai::Request,
+        cx: &AsyncAppContext,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<ResponseStreamEvent>>>> {
+        let http_client = self.http_client.clone();
+        let Ok(settings) = cx.read_model(&self.state, |state, cx| {
+            state.settings(cx).map(|settings| {
+                (
+                    state.api_key.clone(),
+                    settings.api_url.clone(),
+                    settings.low_speed_timeout,
+                )
+            })
+        }) else {
+            return futures::future::ready(Err(anyhow!("App state dropped"))).boxed();
+        };
+        let Some((api_key, api_url, low_speed_timeout)) = settings else {
+            return futures::future::ready(Err(anyhow!(
+                "provider {} is not configured",
+                self.provider_name
+            )))
+            .boxed();
+        };
+
+        let future = self.request_limiter.stream(async move {
+            let request = stream_completion(
+                http_client.as_ref(),
+                &api_url,
+                api_key.as_deref().unwrap_or_default(),
+                request,
+                low_speed_timeout,
+            );
+            let response = request.await?;
+            Ok(response)
+        });
+
+        async move { Ok(future.await?.boxed()) }.boxed()
+    }
+}
+
+impl LanguageModel for OpenAiCompatibleLanguageModel {
+    fn id(&self) -> LanguageModelId {
+        self.id.clone()
+    }
+
+    fn name(&self) -> LanguageModelName {
+        LanguageModelName::from(
+            self.model
+                .display_name
+                .clone()
+                .unwrap_or_else(|| self.model.name.clone()),
+        )
+    }
+
+    fn provider_id(&self) -> LanguageModelProviderId {
+        provider_id(&self.provider_name)
+    }
+
+    fn provider_name(&self) -> LanguageModelProviderName {
+        LanguageModelProviderName::from(self.provider_name.to_string())
+    }
+
+    fn telemetry_id(&self) -> String {
+        format!("openai_compatible/{}", self.model.name)
+    }
+
+    fn max_token_count(&self) -> usize {
+        self.model.max_tokens
+    }
+
+    fn max_output_tokens(&self) -> Option<u32> {
+        self.model.max_output_tokens
+    }
+
+    fn count_tokens(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AppContext,
+    ) -> BoxFuture<'static, Result<usize>> {
+        // The served model's tokenizer is unknown, so estimate with OpenAI's.
+        let model = open_ai::Model::Custom {
+            name: self.model.name.clone(),
+            display_name: None,
+            max_tokens: self.model.max_tokens,
+            max_output_tokens: None,
+            max_completion_tokens: None,
+        };
+        count_open_ai_tokens(request, model, cx)
+    }
+
+    fn stream_completion(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AsyncAppContext,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<LanguageModelCompletionEvent>>>> {
+        let request = self.to_open_ai_request(request);
+        let completions = self.stream_completion(request, cx);
+        async move { Ok(map_to_completion_events(completions.await?).boxed()) }.boxed()
+    }
+
+    fn use_any_tool(
+        &self,
+        request: LanguageModelRequest,
+        tool_name: String,
+        tool_description: String,
+        schema: serde_json::Value,
+        cx: &AsyncAppContext,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
+        if !self.model.supports_tools {
+            return futures::future::ready(Err(anyhow!(
+                "tool calling is not enabled for {}; set `supports_tools` in its settings",
+                self.model.name
+            )))
+            .boxed();
+        }
+
+        let mut request = self.to_open_ai_request(request);
+        request.tool_choice = Some(ToolChoice::Other(ToolDefinition::Function {
+            function: FunctionDefinition {
+                name: tool_name.clone(),
+                description: None,
+                parameters: None,
+            },
+        }));
+        request.tools = vec![ToolDefinition::Function {
+            function: FunctionDefinition {
+                name: tool_name.clone(),
+                description: Some(tool_description),
+                parameters: Some(schema),
+            },
+        }];
+
+        let response = self.stream_completion(request, cx);
+        self.request_limiter
+            .run(async move {
+                let response = response.await?;
+                Ok(
+                    open_ai::extract_tool_args_from_events(tool_name, Box::pin(response))
+                        .await?
+                        .boxed(),
+                )
+            })
+            .boxed()
+    }
+}
+
+#[derive(Default)]
+struct PendingToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+/// Converts a stream of chat completion chunks into completion events, assembling the
+/// tool calls that are streamed in pieces and emitting them once the choice finishes.
+fn map_to_completion_events(
+    events: impl Stream<Item = Result<ResponseStreamEvent>>,
+) -> impl Stream<Item = Result<LanguageModelCompletionEvent>> {
+    let mut tool_calls = BTreeMap::<usize, PendingToolCall>::default();
+    events.flat_map(move |event| {
+        let mut completion_events = Vec::new();
+        match event {
+            Ok(event) => {
+                if let Some(usage) = event.usage {
+                    completion_events.push(Ok(LanguageModelCompletionEvent::UsageUpdate(
+                        super::open_ai::token_usage(&usage),
+                    )));
+                }
+
+                for choice in event.choices {
+                    if let Some(content) = choice.delta.content.filter(|text| !text.is_empty()) {
+                        completion_events.push(Ok(LanguageModelCompletionEvent::Text(content)));
+                    }
+
+                    for chunk in choice.delta.tool_calls.unwrap_or_default() {
+                        let call = tool_calls.entry(chunk.index).or_default();
+                        if let Some(id) = chunk.id {
+                            call.id = id;
+                        }
+                        if let Some(function) = chunk.function {
+                            if let Some(name) = function.name {
+                                call.name = name;
+                            }
+                            if let Some(arguments) = function.arguments {
+                                call.arguments.push_str(&arguments);
+                            }
+                        }
+                    }
+
+                    let Some(finish_reason) = choice.finish_reason else {
+                        continue;
+                    };
+                    let has_tool_calls = !tool_calls.is_empty();
+                    for (_, call) in std::mem::take(&mut tool_calls) {
+                        let arguments = if call.arguments.trim().is_empty() {
+                            "{}"
+                        } else {
+                            call.arguments.as_str()
+                        };
+                        completion_events.push(
+                            serde_json::from_str(arguments)
+                                .map(|input| {
+                                    LanguageModelCompletionEvent::ToolUse(LanguageModelToolUse {
+                                        id: call.id,
+                                        name: call.name,
+                                        input,
+                                    })
+                                })
+                                .map_err(|error| anyhow!("invalid tool call arguments: {error}")),
+                        );
+                    }
+                    let stop_reason = match finish_reason.as_str() {
+                        "length" => StopReason::MaxTokens,
+                        // Some servers finish with "stop" even after calling tools.
+                        _ if has_tool_calls => StopReason::ToolUse,
+                        _ => StopReason::EndTurn,
+                    };
+                    completion_events.push(Ok(LanguageModelCompletionEvent::Stop(stop_reason)));
+                }
+            }
+            Err(error) => completion_events.push(Err(error)),
+        }
+        futures::stream::iter(completion_events)
+    })
+}
+
+struct ConfigurationView {
+    state: gpui::Model<State>,
+    loading_models_task: Option<Task<()>>,
+}
+
+impl ConfigurationView {
+    pub fn new(state: gpui::Model<State>, cx: &mut ViewContext<Self>) -> Self {
+        cx.observe(&state, |_, _, cx| cx.notify()).detach();
+
+        let loading_models_task = Some(cx.spawn({
+            let state = state.clone();
+            |this, mut cx| async move {
+                if let Some(task) = state
+                    .update(&mut cx, |state, cx| state.authenticate(cx))
+                    .log_err()
+                {
+                    // We don't log an error, because an unreachable server is also an error.
+                    let _ = task.await;
+                }
+                this.update(&mut cx, |this, cx| {
+                    this.loading_models_task = None;
+                    cx.notify();
+                })
+                .log_err();
+            }
+        }));
+
+        Self {
+            state,
+            loading_models_task,
+        }
+    }
+
+    fn retry_connection(&mut self, cx: &mut ViewContext<Self>) {
+        self.state
+            .update(cx, |state, cx| state.fetch_models(cx))
+            .detach_and_log_err(cx);
+    }
+}
+
+impl Render for ConfigurationView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        if self.loading_models_task.is_some() {
+            return div().child(Label::new("Loading models...")).into_any();
+        }
+
+        let state = self.state.read(cx);
+        let is_authenticated = state.is_authenticated(cx);
+        let Some(settings) = state.settings(cx) else {
+            let message = format!("The \"{}\" provider is no longer configured.", state.name);
+            return div().child(Label::new(message)).into_any();
+        };
+
+        let server_description = format!(
+            "Uses the OpenAI-compatible server at {}, as configured in the \"{}\" entry of `language_models.openai_compatible` in your settings.",
+            settings.api_url, state.name
+        );
+        let api_key_description = match &settings.api_key_env_var {
+            Some(var) if state.api_key.is_some() => {
+                format!("API key set in {var} environment variable.")
+            }
+            Some(var) => {
+                format!("Assign the {var} environment variable and restart Zed to authenticate.")
+            }
+            None => "The server doesn't require an API key.".to_string(),
+        };
+
+        v_flex()
+            .size_full()
+            .gap_2()
+            .child(Label::new(server_description))
+            .child(Label::new(api_key_description).size(LabelSize::Small))
+            .child(
+                h_flex()
+                    .w_full()
+                    .pt_2()
+                    .justify_end()
+                    .child(if is_authenticated {
+                        // This is only a button to ensure the spacing is correct
+                        // it should stay disabled
+                        ButtonLike::new("connected")
+                            .disabled(true)
+                            // Since this won't ever be clickable, we can use the arrow cursor
+                            .cursor_style(gpui::CursorStyle::Arrow)
+                            .child(
+                                h_flex()
+                                    .gap_2()
+                                    .child(Indicator::dot().color(Color::Success))
+                                    .child(Label::new("Connected"))
+                                    .into_any_element(),
+                            )
+                            .into_any_element()
+                    } else {
+                        Button::new("retry_openai_compatible_models", "Connect")
+                            .icon_position(IconPosition::Start)
+                            .icon(IconName::ArrowCircle)
+                            .on_click(cx.listener(|this, _, cx| this.retry_connection(cx)))
+                            .into_any_element()
+                    }),
+            )
+            .into_any()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use gpui::TestAppContext;
+    use http_client::{FakeHttpClient, Response};
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_map_to_completion_events() {
+        let chunks = [
+            json!({ "delta": { "role": "assistant", "content": "Let me check." } }),
+            json!({ "delta": { "tool_calls": [{ "index": 0, "id": "call_1", "function": { "name": "read_file", "arguments": "{\"path\":" } }] } }),
+            json!({ "delta": { "tool_calls": [{ "index": 0, "function": { "arguments": " \"a.rs\"}" } }] } }),
+            json!({ "delta": {}, "finish_reason": "stop" }),
+        ];
+        let events = chunks.into_iter().map(|choice| {
+            let mut choice = choice;
+            choice["index"] = json!(0);
+            Ok(serde_json::from_value(json!({
+                "created": 0,
+                "model": "llama",
+                "choices": [choice],
+                "usage": null,
+            }))
+            .unwrap())
+        });
+
+        let events = block_on(
+            map_to_completion_events(futures::stream::iter(events))
+                .map(|event| event.unwrap())
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(
+            events,
+            [
+                LanguageModelCompletionEvent::Text("Let me check.".into()),
+                LanguageModelCompletionEvent::ToolUse(LanguageModelToolUse {
+                    id: "call_1".into(),
+                    name: "read_file".into(),
+                    input: json!({ "path": "a.rs" }),
+                }),
+                LanguageModelCompletionEvent::Stop(StopReason::ToolUse),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_discover_models(cx: &mut TestAppContext) {
+        let http_client = FakeHttpClient::create(|request| async move {
+            assert_eq!(request.uri(), "http://localhost:8000/v1/models");
+            let models = json!({
+                "data": [
+                    { "id": "llama", "max_model_len": 32768 },
+                    { "id": "qwen", "meta": { "n_ctx_train": 4096 } },
+                    { "id": "phi" },
+                ]
+            });
+            Ok(Response::new(models.to_string().into()))
+        });
+        cx.update(|cx| {
+            init_test(cx);
+            set_provider_settings(json!({ "api_url": "http://localhost:8000/v1" }), cx);
+        });
+        let provider = cx.update(|cx| {
+            OpenAiCompatibleLanguageModelProvider::new("local".into(), http_client, cx)
+        });
+        cx.run_until_parked();
+
+        cx.update(|cx| {
+            assert!(provider.is_authenticated(cx));
+            assert_eq!(
+                provided_models(&provider, cx),
+                [
+                    ("llama".into(), "llama".into(), 32768),
+                    ("phi".into(), "phi".into(), DEFAULT_MAX_TOKENS),
+                    ("qwen".into(), "qwen".into(), 4096),
+                ]
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_settings_override_discovered_models(cx: &mut TestAppContext) {
+        let http_client = FakeHttpClient::create(|_| async move {
+            let models = json!({ "data": [{ "id": "llama" }, { "id": "qwen" }] });
+            Ok(Response::new(models.to_string().into()))
+        });
+        let available_models = json!([
+            { "name": "llama", "display_name": "Llama 3.1", "max_tokens": 128000 },
+            { "name": "custom", "max_tokens": 2048 },
+        ]);
+        cx.update(|cx| {
+            init_test(cx);
+            set_provider_settings(
+                json!({
+                    "api_url": "http://localhost:8000/v1",
+                    "available_models": available_models,
+                }),
+                cx,
+            );
+        });
+        let provider = cx.update(|cx| {
+            OpenAiCompatibleLanguageModelProvider::new("local".into(), http_client, cx)
+        });
+        cx.run_until_parked();
+
+        cx.update(|cx| {
+            assert_eq!(
+                provided_models(&provider, cx),
+                [
+                    ("custom".into(), "custom".into(), 2048),
+                    ("llama".into(), "Llama 3.1".into(), 128000),
+                    ("qwen".into(), "qwen".into(), DEFAULT_MAX_TOKENS),
+                ]
+            );
+        });
+
+        // Without discovery, only the models from the settings are provided.
+        cx.update(|cx| {
+            set_provider_settings(
+                json!({
+                    "api_url": "http://localhost:8000/v1",
+                    "discover_models": false,
+                    "available_models": available_models,
+                }),
+                cx,
+            );
+        });
+        cx.run_until_parked();
+
+        cx.update(|cx| {
+            assert_eq!(
+                provided_models(&provider, cx),
+                [
+                    ("custom".into(), "custom".into(), 2048),
+                    ("llama".into(), "Llama 3.1".into(), 128000),
+                ]
+            );
+        });
+    }
+
+    fn init_test(cx: &mut AppContext) {
+        let store = SettingsStore::test(cx);
+        cx.set_global(store);
+        AllLanguageModelSettings::register(cx);
+    }
+
+    fn set_provider_settings(settings: Value, cx: &mut AppContext) {
+        let settings = json!({ "language_models": { "openai_compatible": { "local": settings } } });
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store.set_user_settings(&settings.to_string(), cx).unwrap();
+        });
+    }
+
+    /// The ID, name and context length of each model of the provider.
+    fn provided_models(
+        provider: &OpenAiCompatibleLanguageModelProvider,
+        cx: &AppContext,
+    ) -> Vec<(String, String, usize)> {
+        provider
+            .provided_models(cx)
+            .iter()
+            .map(|model| {
+                (
+                    model.id().0.to_string(),
+                    model.name().0.to_string(),
+                    model.max_token_count(),
+                )
+            })
+            .collect()
+    }
+}
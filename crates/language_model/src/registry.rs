@@ -1,16 +1,23 @@
 use crate::provider::cloud::RefreshLlmTokenListener;
 use crate::{
     provider::{
-        anthropic::AnthropicLanguageModelProvider, cloud::CloudLanguageModelProvider,
-        copilot_chat::CopilotChatLanguageModelProvider, google::GoogleLanguageModelProvider,
-        ollama::OllamaLanguageModelProvider, open_ai::OpenAiLanguageModelProvider,
+        anthropic::AnthropicLanguageModelProvider,
+        cloud::CloudLanguageModelProvider,
+        copilot_chat::CopilotChatLanguageModelProvider,
+        google::GoogleLanguageModelProvider,
+        ollama::OllamaLanguageModelProvider,
+        open_ai::OpenAiLanguageModelProvider,
+        open_ai_compatible::{self, OpenAiCompatibleLanguageModelProvider},
     },
+    settings::AllLanguageModelSettings,
     LanguageModel, LanguageModelId, LanguageModelProvider, LanguageModelProviderId,
     LanguageModelProviderState,
 };
 use client::{Client, UserStore};
 use collections::BTreeMap;
 use gpui::{AppContext, EventEmitter, Global, Model, ModelContext};
+use http_client::HttpClient;
+use settings::{Settings, SettingsStore};
 use std::sync::Arc;
 use ui::Context;
 
@@ -51,6 +58,13 @@ fn register_language_model_providers(
     );
     registry.register_provider(CopilotChatLanguageModelProvider::new(cx), cx);
 
+    let http_client = client.http_client();
+    registry.sync_open_ai_compatible_providers(http_client.clone(), cx);
+    cx.observe_global::<SettingsStore>(move |registry, cx| {
+        registry.sync_open_ai_compatible_providers(http_client.clone(), cx);
+    })
+    .detach();
+
     cx.observe_flag::<feature_flags::LanguageModels, _>(move |enabled, cx| {
         let user_store = user_store.clone();
         let client = client.clone();
@@ -147,6 +161,48 @@ impl LanguageModelRegistry {
         }
     }
 
+    /// Registers a provider for each OpenAI-compatible server in the settings, and
+    /// unregisters the providers of servers that were removed from them.
+    fn sync_open_ai_compatible_providers(
+        &mut self,
+        http_client: Arc<dyn HttpClient>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let configured = AllLanguageModelSettings::get_global(cx)
+            .openai_compatible
+            .iter()
+            .filter(|(_, settings)| !settings.api_url.is_empty())
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+
+        let removed = self
+            .providers
+            .keys()
+            .filter(|id| {
+                id.0.strip_prefix(open_ai_compatible::PROVIDER_ID_PREFIX)
+                    .map_or(false, |name| !configured.iter().any(|n| n == name))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        for id in removed {
+            self.unregister_provider(id, cx);
+        }
+
+        for name in configured {
+            if !self
+                .providers
+                .contains_key(&open_ai_compatible::provider_id(&name))
+            {
+                let provider = OpenAiCompatibleLanguageModelProvider::new(
+                    name.into(),
+                    http_client.clone(),
+                    cx,
+                );
+                self.register_provider(provider, cx);
+            }
+        }
+    }
+
     pub fn providers(&self) -> Vec<Arc<dyn LanguageModelProvider>> {
         let zed_provider_id = LanguageModelProviderId(crate::provider::cloud::PROVIDER_ID.into());
         let mut providers = Vec::with_capacity(self.providers.len());
@@ -270,6 +326,8 @@ impl LanguageModelRegistry {
 mod tests {
     use super::*;
     use crate::provider::fake::FakeLanguageModelProvider;
+    use http_client::FakeHttpClient;
+    use serde_json::json;
 
     #[gpui::test]
     fn test_register_providers(cx: &mut AppContext) {
@@ -290,4 +348,54 @@ mod tests {
         let providers = registry.read(cx).providers();
         assert!(providers.is_empty());
     }
+
+    #[gpui::test]
+    fn test_sync_open_ai_compatible_providers(cx: &mut AppContext) {
+        let store = SettingsStore::test(cx);
+        cx.set_global(store);
+        AllLanguageModelSettings::register(cx);
+        let http_client = FakeHttpClient::with_404_response();
+        let registry = cx.new_model(|_| LanguageModelRegistry::default());
+
+        let set_providers = |providers: serde_json::Value, cx: &mut AppContext| {
+            let settings = json!({ "language_models": { "openai_compatible": providers } });
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.set_user_settings(&settings.to_string(), cx).unwrap();
+            });
+            registry.update(cx, |registry, cx| {
+                registry.sync_open_ai_compatible_providers(http_client.clone(), cx);
+            });
+        };
+        let provider_ids = |cx: &AppContext| {
+            registry
+                .read(cx)
+                .providers()
+                .iter()
+                .map(|provider| provider.id().0.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        // Servers without a URL get no provider.
+        set_providers(
+            json!({
+                "local": { "api_url": "http://localhost:8000/v1" },
+                "remote": { "api_url": "https://llm.example.com/v1" },
+                "unset": { "discover_models": false },
+            }),
+            cx,
+        );
+        assert_eq!(
+            provider_ids(cx),
+            ["openai_compatible/local", "openai_compatible/remote"]
+        );
+
+        set_providers(
+            json!({ "remote": { "api_url": "https://llm.example.com/v1" } }),
+            cx,
+        );
+        assert_eq!(provider_ids(cx), ["openai_compatible/remote"]);
+
+        set_providers(json!({}), cx);
+        assert!(provider_ids(cx).is_empty());
+    }
 }
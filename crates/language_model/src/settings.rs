@@ -1,6 +1,7 @@
 use std::{sync::Arc, time::Duration};
 
 use anyhow::Result;
+use collections::{BTreeMap, HashMap};
 use gpui::AppContext;
 use project::Fs;
 use schemars::JsonSchema;
@@ -16,6 +17,7 @@ use crate::{
         google::GoogleSettings,
         ollama::OllamaSettings,
         open_ai::OpenAiSettings,
+        open_ai_compatible::OpenAiCompatibleSettings,
     },
     LanguageModelCacheConfiguration,
 };
@@ -61,6 +63,7 @@ pub struct AllLanguageModelSettings {
     pub zed_dot_dev: ZedDotDevSettings,
     pub google: GoogleSettings,
     pub copilot_chat: CopilotChatSettings,
+    pub openai_compatible: BTreeMap<String, OpenAiCompatibleSettings>,
 }
 
 #[derive(Default, Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
@@ -72,6 +75,7 @@ pub struct AllLanguageModelSettingsContent {
     pub zed_dot_dev: Option<ZedDotDevSettingsContent>,
     pub google: Option<GoogleSettingsContent>,
     pub copilot_chat: Option<CopilotChatSettingsContent>,
+    pub openai_compatible: Option<HashMap<String, OpenAiCompatibleSettingsContent>>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
@@ -241,6 +245,22 @@ pub struct CopilotChatSettingsContent {
     low_speed_timeout_in_seconds: Option<u64>,
 }
 
+#[derive(Default, Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct OpenAiCompatibleSettingsContent {
+    /// The base URL of the server's API, e.g. "http://localhost:8000/v1".
+    pub api_url: Option<String>,
+    /// The environment variable to read the API key from. When unset, requests are sent
+    /// without an API key.
+    pub api_key_env_var: Option<String>,
+    pub low_speed_timeout_in_seconds: Option<u64>,
+    /// Whether to list the models the server provides using its `/models` endpoint.
+    ///
+    /// Default: true
+    pub discover_models: Option<bool>,
+    /// Models to add to, or override, the ones listed by the server.
+    pub available_models: Option<Vec<provider::open_ai_compatible::AvailableModel>>,
+}
+
 impl settings::Settings for AllLanguageModelSettings {
     const KEY: Option<&'static str> = Some("language_models");
 
@@ -373,6 +393,33 @@ impl settings::Settings for AllLanguageModelSettings {
                 settings.copilot_chat.low_speed_timeout =
                     Some(Duration::from_secs(low_speed_timeout));
             }
+
+            // OpenAI-compatible
+            for (name, content) in value.openai_compatible.iter().flatten() {
+                let settings = settings
+                    .openai_compatible
+                    .entry(name.clone())
+                    .or_insert_with(|| OpenAiCompatibleSettings {
+                        api_url: String::new(),
+                        api_key_env_var: None,
+                        low_speed_timeout: None,
+                        discover_models: true,
+                        available_models: Vec::new(),
+                    });
+                merge(&mut settings.api_url, content.api_url.clone());
+                if content.api_key_env_var.is_some() {
+                    settings.api_key_env_var = content.api_key_env_var.clone();
+                }
+                if let Some(low_speed_timeout_in_seconds) = content.low_speed_timeout_in_seconds {
+                    settings.low_speed_timeout =
+                        Some(Duration::from_secs(low_speed_timeout_in_seconds));
+                }
+                merge(&mut settings.discover_models, content.discover_models);
+                merge(
+                    &mut settings.available_models,
+                    content.available_models.clone(),
+                );
+            }
         }
 
         Ok(settings)
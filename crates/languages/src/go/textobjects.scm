@@ -0,0 +1,41 @@
+; functions
+(function_declaration
+    body: (block
+        "{"
+        (_)* @function.inside
+        "}")) @function.around
+
+(method_declaration
+    body: (block
+        "{"
+        (_)* @function.inside
+        "}")) @function.around
+
+(func_literal
+    body: (block
+        "{"
+        (_)* @function.inside
+        "}")) @function.around
+
+; classes
+(type_declaration
+    (type_spec
+        type: (struct_type
+            (field_declaration_list
+                "{"
+                (_)* @class.inside
+                "}")))) @class.around
+
+(type_declaration
+    (type_spec
+        type: (interface_type
+            "{"
+            (_)* @class.inside
+            "}"))) @class.around
+
+; arguments
+(parameter_list
+    (_) @parameter.inside)
+
+(argument_list
+    (_) @parameter.inside)
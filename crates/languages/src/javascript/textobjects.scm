@@ -0,0 +1,29 @@
+; functions
+(function_declaration
+    body: (statement_block
+        "{"
+        (_)* @function.inside
+        "}")) @function.around
+
+(method_definition
+    body: (statement_block
+        "{"
+        (_)* @function.inside
+        "}")) @function.around
+
+(arrow_function
+    body: (_) @function.inside) @function.around
+
+; classes
+(class_declaration
+    body: (class_body
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+; arguments
+(formal_parameters
+    (_) @parameter.inside)
+
+(arguments
+    (_) @parameter.inside)
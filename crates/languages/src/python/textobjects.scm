@@ -0,0 +1,20 @@
+; functions
+(function_definition
+    body: (_) @function.inside) @function.around
+
+(lambda
+    body: (_) @function.inside) @function.around
+
+; classes
+(class_definition
+    body: (_) @class.inside) @class.around
+
+; arguments
+(parameters
+    (_) @parameter.inside)
+
+(lambda_parameters
+    (_) @parameter.inside)
+
+(argument_list
+    (_) @parameter.inside)
@@ -0,0 +1,52 @@
+; functions
+(function_signature_item) @function.around
+
+(function_item
+    body: (_
+        "{"
+        (_)* @function.inside
+        "}")) @function.around
+
+(closure_expression
+    body: (_) @function.inside) @function.around
+
+; classes
+(struct_item
+    body: (field_declaration_list
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(enum_item
+    body: (enum_variant_list
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(union_item
+    body: (field_declaration_list
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(trait_item
+    body: (declaration_list
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(impl_item
+    body: (declaration_list
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+; arguments
+(parameters
+    (_) @parameter.inside)
+
+(closure_parameters
+    (_) @parameter.inside)
+
+(arguments
+    (_) @parameter.inside)
@@ -12,7 +12,7 @@ use language::{
     AutoindentMode, Buffer, BufferChunks, BufferRow, BufferSnapshot, Capability, CharClassifier,
     CharKind, Chunk, CursorShape, DiagnosticEntry, File, IndentGuide, IndentSize, Language,
     LanguageScope, OffsetRangeExt, OffsetUtf16, Outline, OutlineItem, Point, PointUtf16, Selection,
-    TextDimension, ToOffset as _, ToOffsetUtf16 as _, ToPoint as _, ToPointUtf16 as _,
+    TextDimension, TextObject, ToOffset as _, ToOffsetUtf16 as _, ToPoint as _, ToPointUtf16 as _,
     TransactionId, Unclipped,
 };
 use smallvec::SmallVec;
@@ -3836,6 +3836,75 @@ impl MultiBufferSnapshot {
         Some(excerpt.map_range_from_buffer(ancestor_buffer_range))
     }
 
+    pub fn syntax_sibling_ranges<T: ToOffset>(
+        &self,
+        range: Range<T>,
+        forward: bool,
+    ) -> Option<(Range<usize>, Range<usize>)> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        let excerpt = self.excerpt_containing(range.clone())?;
+
+        let (node_range, sibling_range) = excerpt
+            .buffer()
+            .syntax_sibling_ranges(excerpt.map_range_to_buffer(range), forward)?;
+        let covering_range =
+            node_range.start.min(sibling_range.start)..node_range.end.max(sibling_range.end);
+        if !excerpt.contains_buffer_range(covering_range) {
+            return None;
+        }
+
+        Some((
+            excerpt.map_range_from_buffer(node_range),
+            excerpt.map_range_from_buffer(sibling_range),
+        ))
+    }
+
+    pub fn range_for_syntax_parent<T: ToOffset>(&self, range: Range<T>) -> Option<Range<usize>> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        let excerpt = self.excerpt_containing(range.clone())?;
+
+        let parent_buffer_range = excerpt
+            .buffer()
+            .range_for_syntax_parent(excerpt.map_range_to_buffer(range))?;
+
+        Some(excerpt.map_range_from_buffer(parent_buffer_range))
+    }
+
+    pub fn range_for_syntax_first_child<T: ToOffset>(
+        &self,
+        range: Range<T>,
+    ) -> Option<Range<usize>> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        let excerpt = self.excerpt_containing(range.clone())?;
+
+        let child_buffer_range = excerpt
+            .buffer()
+            .range_for_syntax_first_child(excerpt.map_range_to_buffer(range))?;
+
+        Some(excerpt.map_range_from_buffer(child_buffer_range))
+    }
+
+    pub fn range_for_adjacent_text_object<T: ToOffset>(
+        &self,
+        range: Range<T>,
+        text_object: TextObject,
+        forward: bool,
+    ) -> Option<Range<usize>> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        let excerpt = self.excerpt_containing(range.clone())?;
+
+        let object_buffer_range = excerpt.buffer().range_for_adjacent_text_object(
+            excerpt.map_range_to_buffer(range),
+            text_object,
+            forward,
+        )?;
+        if !excerpt.contains_buffer_range(object_buffer_range.clone()) {
+            return None;
+        }
+
+        Some(excerpt.map_range_from_buffer(object_buffer_range))
+    }
+
     pub fn outline(&self, theme: Option<&SyntaxTheme>) -> Option<Outline<Anchor>> {
         let (excerpt_id, _, buffer) = self.as_singleton()?;
         let outline = buffer.outline(theme)?;
@@ -168,6 +168,14 @@ pub struct Request {
     pub tool_choice: Option<ToolChoice>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tools: Vec<ToolDefinition>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<StreamOptions>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StreamOptions {
+    /// Whether to send a final chunk reporting the tokens used by the request.
+    pub include_usage: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -470,6 +478,73 @@ pub async fn stream_completion(
     }
 }
 
+#[derive(Deserialize, Debug)]
+pub struct ModelListing {
+    pub id: String,
+    /// The context length of the model, as reported by vLLM.
+    pub max_model_len: Option<usize>,
+    /// Metadata about the model, as reported by llama.cpp.
+    pub meta: Option<ModelListingMeta>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ModelListingMeta {
+    /// The context length the model was trained with.
+    pub n_ctx_train: Option<usize>,
+}
+
+impl ModelListing {
+    /// The context length of the model, if the server reported one.
+    pub fn max_token_count(&self) -> Option<usize> {
+        self.max_model_len
+            .or_else(|| self.meta.as_ref()?.n_ctx_train)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct ListModelsResponse {
+    data: Vec<ModelListing>,
+}
+
+/// Lists the models served at the given URL, using the `/models` endpoint that
+/// OpenAI-compatible servers implement.
+pub async fn list_models(
+    client: &dyn HttpClient,
+    api_url: &str,
+    api_key: Option<&str>,
+    low_speed_timeout: Option<Duration>,
+) -> Result<Vec<ModelListing>> {
+    let uri = format!("{api_url}/models");
+    let mut request_builder = HttpRequest::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .header("Accept", "application/json");
+    if let Some(api_key) = api_key {
+        request_builder = request_builder.header("Authorization", format!("Bearer {}", api_key));
+    }
+    if let Some(low_speed_timeout) = low_speed_timeout {
+        request_builder = request_builder.read_timeout(low_speed_timeout);
+    };
+
+    let request = request_builder.body(AsyncBody::default())?;
+    let mut response = client.send(request).await?;
+
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+
+    if response.status().is_success() {
+        let response: ListModelsResponse =
+            serde_json::from_str(&body).context("Unable to parse model listing")?;
+        Ok(response.data)
+    } else {
+        Err(anyhow!(
+            "Failed to list models: {} {}",
+            response.status(),
+            body,
+        ))
+    }
+}
+
 #[derive(Copy, Clone, Serialize, Deserialize)]
 pub enum OpenAiEmbeddingModel {
     #[serde(rename = "text-embedding-3-small")]
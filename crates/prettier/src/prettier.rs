@@ -2,13 +2,13 @@ use anyhow::{anyhow, Context};
 use collections::{HashMap, HashSet};
 use fs::Fs;
 use gpui::{AsyncAppContext, Model};
-use language::{language_settings::language_settings, Buffer, Diff};
+use language::{language_settings::language_settings, Buffer, Diff, ToOffsetUtf16};
 use lsp::{LanguageServer, LanguageServerId};
 use node_runtime::NodeRuntime;
 use paths::default_prettier_dir;
 use serde::{Deserialize, Serialize};
 use std::{
-    ops::ControlFlow,
+    ops::{ControlFlow, Range},
     path::{Path, PathBuf},
     sync::Arc,
 };
@@ -194,10 +194,12 @@ impl Prettier {
         }))
     }
 
+    /// Formats the buffer, or only the given byte ranges of it when `ranges` is provided.
     pub async fn format(
         &self,
         buffer: &Model<Buffer>,
         buffer_path: Option<PathBuf>,
+        ranges: Option<Vec<Range<usize>>>,
         cx: &mut AsyncAppContext,
     ) -> anyhow::Result<Diff> {
         match self {
@@ -317,6 +319,17 @@ impl Prettier {
                             prettier_options,
                         );
 
+                        // Prettier expects the ranges in UTF-16 code units, like JavaScript strings.
+                        let ranges = ranges.map(|ranges| {
+                            ranges
+                                .into_iter()
+                                .map(|range| {
+                                    range.start.to_offset_utf16(buffer).0
+                                        ..range.end.to_offset_utf16(buffer).0
+                                })
+                                .collect()
+                        });
+
                         anyhow::Ok(FormatParams {
                             text: buffer.text(),
                             options: FormatOptions {
@@ -324,6 +337,7 @@ impl Prettier {
                                 plugins,
                                 path: buffer_path,
                                 prettier_options,
+                                ranges,
                             },
                         })
                     })?
@@ -448,6 +462,7 @@ struct FormatOptions {
     #[serde(rename = "filepath")]
     path: Option<PathBuf>,
     prettier_options: Option<HashMap<String, serde_json::Value>>,
+    ranges: Option<Vec<Range<usize>>>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
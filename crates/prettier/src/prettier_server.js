@@ -215,7 +215,20 @@ async function handleMessage(message, prettier) {
         params.options.filepath || ""
       }' with options: ${JSON.stringify(options)}\n`,
     );
-    const formattedText = await prettier.prettier.format(params.text, options);
+    let formattedText = params.text;
+    if (params.options.ranges) {
+      // Format the ranges from last to first, so that formatting a range does
+      // not move the ranges that are still to be formatted.
+      for (const range of [...params.options.ranges].reverse()) {
+        formattedText = await prettier.prettier.format(formattedText, {
+          ...options,
+          rangeStart: range.start,
+          rangeEnd: range.end,
+        });
+      }
+    } else {
+      formattedText = await prettier.prettier.format(params.text, options);
+    }
     sendResponse({ id, result: { text: formattedText } });
   } else if (method === "prettier/clear_cache") {
     prettier.prettier.clearConfigCache();
@@ -7,7 +7,7 @@ use ::git::{parse_git_remote_url, BuildPermalinkParams, GitHostingProviderRegist
 use anyhow::{anyhow, Context as _, Result};
 use client::Client;
 use collections::{hash_map, HashMap, HashSet};
-use fs::Fs;
+use fs::{archive, Fs};
 use futures::{channel::oneshot, stream::FuturesUnordered, StreamExt};
 use git::blame::Blame;
 use gpui::{
@@ -16,11 +16,12 @@ use gpui::{
 };
 use http_client::Url;
 use language::{
+    language_settings::{language_settings, LineEndingSetting},
     proto::{
         deserialize_line_ending, deserialize_version, serialize_line_ending, serialize_version,
         split_operations,
     },
-    Buffer, BufferEvent, Capability, File as _, Language, Operation,
+    Buffer, BufferEvent, Capability, File as _, Language, LineEnding, Operation,
 };
 use rpc::{proto, AnyProtoClient, ErrorExt as _, TypedEnvelope};
 use smol::channel::Receiver;
@@ -431,7 +432,14 @@ impl LocalBufferStore {
         let buffer = buffer_handle.read(cx);
 
         let text = buffer.as_rope().clone();
-        let line_ending = buffer.line_ending();
+        let line_ending =
+            match language_settings(buffer.language().map(|l| l.name()), buffer.file(), cx)
+                .line_ending
+            {
+                LineEndingSetting::Detect => buffer.line_ending(),
+                LineEndingSetting::Lf => LineEnding::Unix,
+                LineEndingSetting::Crlf => LineEnding::Windows,
+            };
         let version = buffer.version();
         let buffer_id = buffer.remote_id();
         if buffer.file().is_some_and(|file| !file.is_created()) {
@@ -833,6 +841,8 @@ impl BufferStoreImpl for Model<LocalBufferStore> {
         cx: &mut ModelContext<BufferStore>,
     ) -> Task<Result<Model<Buffer>>> {
         let buffer_store = cx.weak_model();
+        // Files stored in archives can't be written back to them.
+        let is_archive_entry = archive::split_archive_entry_path(&path).is_some();
         self.update(cx, |_, cx| {
             let load_buffer = worktree.update(cx, |worktree, cx| {
                 let load_file = worktree.load_file(path.as_ref(), cx);
@@ -849,7 +859,11 @@ impl BufferStoreImpl for Model<LocalBufferStore> {
                             text_buffer,
                             loaded.diff_base,
                             Some(loaded.file),
-                            Capability::ReadWrite,
+                            if is_archive_entry {
+                                Capability::ReadOnly
+                            } else {
+                                Capability::ReadWrite
+                            },
                         )
                     })
                 })
@@ -858,24 +872,26 @@ impl BufferStoreImpl for Model<LocalBufferStore> {
             cx.spawn(move |this, mut cx| async move {
                 let buffer = match load_buffer.await {
                     Ok(buffer) => Ok(buffer),
-                    Err(error) if is_not_found_error(&error) => cx.new_model(|cx| {
-                        let buffer_id = BufferId::from(cx.entity_id().as_non_zero_u64());
-                        let text_buffer = text::Buffer::new(0, buffer_id, "".into());
-                        Buffer::build(
-                            text_buffer,
-                            None,
-                            Some(Arc::new(File {
-                                worktree,
-                                path,
-                                mtime: None,
-                                entry_id: None,
-                                is_local: true,
-                                is_deleted: false,
-                                is_private: false,
-                            })),
-                            Capability::ReadWrite,
-                        )
-                    }),
+                    Err(error) if is_not_found_error(&error) && !is_archive_entry => {
+                        cx.new_model(|cx| {
+                            let buffer_id = BufferId::from(cx.entity_id().as_non_zero_u64());
+                            let text_buffer = text::Buffer::new(0, buffer_id, "".into());
+                            Buffer::build(
+                                text_buffer,
+                                None,
+                                Some(Arc::new(File {
+                                    worktree,
+                                    path,
+                                    mtime: None,
+                                    entry_id: None,
+                                    is_local: true,
+                                    is_deleted: false,
+                                    is_private: false,
+                                })),
+                                Capability::ReadWrite,
+                            )
+                        })
+                    }
                     Err(e) => Err(e),
                 }?;
                 this.update(&mut cx, |this, cx| {
@@ -1419,6 +1435,12 @@ impl BufferStore {
             let buffer = handle.read(cx);
             if let Some(entry_id) = buffer.entry_id(cx) {
                 open_buffers.insert(entry_id);
+            } else if buffer
+                .file()
+                .is_some_and(|file| archive::split_archive_entry_path(file.path()).is_some())
+            {
+                // Files stored in archives are searched along with their archive.
+                continue;
             } else {
                 limit = limit.saturating_sub(1);
                 unnamed_buffers.push(handle)
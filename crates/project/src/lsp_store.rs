@@ -71,7 +71,7 @@ use std::{
     sync::Arc,
     time::{Duration, Instant},
 };
-use text::{Anchor, BufferId, LineEnding, Point, Selection};
+use text::{Anchor, BufferId, LineEnding, Point, Selection, SelectionGoal, TransactionId};
 use util::{
     debug_panic, defer, maybe, merge_json_value_into, post_inc, ResultExt, TryFutureExt as _,
 };
@@ -357,6 +357,90 @@ impl LocalLspStore {
                                     }
                                 }
                             }
+                            FormatOnSave::ModifiedLines => {
+                                let formatters = match &settings.formatter {
+                                    SelectedFormatter::Auto if prettier_settings.allowed => {
+                                        vec![Formatter::Prettier]
+                                    }
+                                    SelectedFormatter::Auto => {
+                                        vec![Formatter::LanguageServer { name: None }]
+                                    }
+                                    SelectedFormatter::List(formatters) => {
+                                        formatters.as_ref().to_vec()
+                                    }
+                                };
+                                // The transaction of the first formatter's edits, when there
+                                // is no earlier formatting to group them with.
+                                let mut format_transaction_id = None;
+                                for formatter in &formatters {
+                                    // The modified lines are found again for each formatter, and
+                                    // its edits applied right away, as they can move or grow the
+                                    // lines the next formatter has to format.
+                                    let Some(modified_lines) =
+                                        Self::modified_lines_format_target(&buffer.handle, &mut cx)
+                                            .await?
+                                    else {
+                                        break;
+                                    };
+                                    if matches!(modified_lines, FormatTarget::Ranges(_))
+                                        && !Self::supports_range_formatting(
+                                            formatter,
+                                            primary_language_server.as_ref(),
+                                            &adapters_and_servers,
+                                        )
+                                    {
+                                        log::info!(
+                                            "skipping {formatter:?} on save, as it cannot format only the modified lines"
+                                        );
+                                        continue;
+                                    }
+                                    let Some(operation) = Self::perform_format(
+                                        formatter,
+                                        &modified_lines,
+                                        server_and_buffer,
+                                        lsp_store.clone(),
+                                        buffer,
+                                        &settings,
+                                        &adapters_and_servers,
+                                        push_to_history,
+                                        &mut project_transaction,
+                                        &mut cx,
+                                    )
+                                    .await?
+                                    else {
+                                        continue;
+                                    };
+                                    let applied = buffer.handle.update(&mut cx, |b, cx| {
+                                        // Stop if the buffer was edited since it was formatted,
+                                        // as those edits can't be grouped with the formatting.
+                                        let last_formatting =
+                                            whitespace_transaction_id.or(format_transaction_id);
+                                        if last_formatting.is_some_and(|transaction_id| {
+                                            b.peek_undo_stack().map_or(true, |entry| {
+                                                entry.transaction_id() != transaction_id
+                                            })
+                                        }) {
+                                            return false;
+                                        }
+                                        let group_with = last_formatting.or_else(|| {
+                                            project_transaction
+                                                .0
+                                                .get(&buffer.handle)
+                                                .map(|transaction| transaction.id)
+                                        });
+                                        Self::apply_format_operation(b, operation, group_with, cx);
+                                        if last_formatting.is_none() {
+                                            format_transaction_id = b
+                                                .peek_undo_stack()
+                                                .map(|entry| entry.transaction_id());
+                                        }
+                                        true
+                                    })?;
+                                    if !applied {
+                                        break;
+                                    }
+                                }
+                            }
                             FormatOnSave::List(formatters) => {
                                 for formatter in formatters.as_ref() {
                                     let diff = Self::perform_format(
@@ -465,23 +549,13 @@ impl LocalLspStore {
                 // Apply any language-specific formatting, and group the two formatting operations
                 // in the buffer's undo history.
                 for operation in format_operations {
-                    match operation {
-                        FormatOperation::Lsp(edits) => {
-                            b.edit(edits, None, cx);
-                        }
-                        FormatOperation::External(diff) => {
-                            b.apply_diff(diff, cx);
-                        }
-                        FormatOperation::Prettier(diff) => {
-                            b.apply_diff(diff, cx);
-                        }
-                    }
-
-                    if let Some(transaction_id) = whitespace_transaction_id {
-                        b.group_until_transaction(transaction_id);
-                    } else if let Some(transaction) = project_transaction.0.get(&buffer.handle) {
-                        b.group_until_transaction(transaction.id)
-                    }
+                    let group_with = whitespace_transaction_id.or_else(|| {
+                        project_transaction
+                            .0
+                            .get(&buffer.handle)
+                            .map(|transaction| transaction.id)
+                    });
+                    Self::apply_format_operation(b, operation, group_with, cx);
                 }
 
                 if let Some(transaction) = b.finalize_last_transaction().cloned() {
@@ -498,6 +572,108 @@ impl LocalLspStore {
         Ok(project_transaction)
     }
 
+    /// Applies the edits of a formatter, grouping them in the undo history with the transaction
+    /// of the formatting that came before, if any.
+    fn apply_format_operation(
+        buffer: &mut Buffer,
+        operation: FormatOperation,
+        group_with: Option<TransactionId>,
+        cx: &mut ModelContext<Buffer>,
+    ) {
+        match operation {
+            FormatOperation::Lsp(edits) => {
+                buffer.edit(edits, None, cx);
+            }
+            FormatOperation::External(diff) => {
+                buffer.apply_diff(diff, cx);
+            }
+            FormatOperation::Prettier(diff) => {
+                buffer.apply_diff(diff, cx);
+            }
+        }
+
+        if let Some(transaction_id) = group_with {
+            buffer.group_until_transaction(transaction_id);
+        }
+    }
+
+    /// Returns the lines of the buffer that were modified since the last Git commit, or the
+    /// whole buffer if it is not tracked by Git. Lines that were only deleted are returned as an
+    /// empty range where they used to be. Returns `None` if nothing was modified.
+    async fn modified_lines_format_target(
+        buffer: &Model<Buffer>,
+        cx: &mut AsyncAppContext,
+    ) -> Result<Option<FormatTarget>> {
+        // The diff is recalculated in the background after edits, so make sure it is current.
+        if let Some(recalculate_diff) =
+            buffer.update(cx, |buffer, cx| buffer.recalculate_diff(cx))?
+        {
+            recalculate_diff.await;
+        }
+
+        buffer.update(cx, |buffer, _| {
+            if buffer.diff_base().is_none() {
+                return Some(FormatTarget::Buffer);
+            }
+
+            let snapshot = buffer.snapshot();
+            let selections = snapshot
+                .git_diff_hunks_in_row_range(0..snapshot.max_point().row + 1)
+                .map(|hunk| {
+                    let start =
+                        snapshot.clip_point(Point::new(hunk.row_range.start, 0), Bias::Left);
+                    let end = if hunk.row_range.is_empty() {
+                        start
+                    } else {
+                        let end_row = hunk.row_range.end - 1;
+                        Point::new(end_row, snapshot.line_len(end_row))
+                    };
+                    Selection {
+                        id: 0,
+                        start,
+                        end,
+                        reversed: false,
+                        goal: SelectionGoal::None,
+                    }
+                })
+                .collect::<Vec<_>>();
+            if selections.is_empty() {
+                None
+            } else {
+                Some(FormatTarget::Ranges(selections))
+            }
+        })
+    }
+
+    /// Returns whether the formatter can format parts of a buffer, rather than all of it.
+    fn supports_range_formatting(
+        formatter: &Formatter,
+        primary_language_server: Option<&Arc<LanguageServer>>,
+        adapters_and_servers: &[(Arc<CachedLspAdapter>, Arc<LanguageServer>)],
+    ) -> bool {
+        match formatter {
+            Formatter::Prettier => true,
+            Formatter::LanguageServer { name } => {
+                let language_server = name
+                    .as_ref()
+                    .and_then(|name| {
+                        adapters_and_servers.iter().find_map(|(adapter, server)| {
+                            adapter.name.0.as_ref().eq(name.as_str()).then_some(server)
+                        })
+                    })
+                    .or(primary_language_server);
+                language_server.map_or(false, |server| {
+                    match server.capabilities().document_range_formatting_provider {
+                        Some(OneOf::Left(supported)) => supported,
+                        Some(OneOf::Right(_)) => true,
+                        None => false,
+                    }
+                })
+            }
+            Formatter::External { .. } | Formatter::CodeActions(_) => false,
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     async fn perform_format(
         formatter: &Formatter,
@@ -560,7 +736,21 @@ impl LocalLspStore {
                 let prettier = lsp_store.update(cx, |lsp_store, _cx| {
                     lsp_store.prettier_store().unwrap().downgrade()
                 })?;
-                prettier_store::format_with_prettier(&prettier, &buffer.handle, cx)
+                let ranges = match format_target {
+                    FormatTarget::Buffer => None,
+                    FormatTarget::Ranges(selections) => {
+                        Some(buffer.handle.read_with(cx, |buffer, _| {
+                            selections
+                                .iter()
+                                .map(|selection| {
+                                    selection.start.to_offset(buffer)
+                                        ..selection.end.to_offset(buffer)
+                                })
+                                .collect::<Vec<_>>()
+                        })?)
+                    }
+                };
+                prettier_store::format_with_prettier(&prettier, &buffer.handle, ranges, cx)
                     .await
                     .transpose()
                     .ok()
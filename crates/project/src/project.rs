@@ -2792,6 +2792,72 @@ impl Project {
         self.perform_rename_impl(buffer, position, new_name, true, cx)
     }
 
+    /// Finds the occurrences across the project of the identifier at `position`, using the
+    /// Tree-sitter queries of each buffer's language. This lets symbols be renamed when no
+    /// language server can rename them, so some occurrences may refer to other symbols.
+    pub fn identifier_occurrences<T: ToOffset>(
+        &mut self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<HashMap<Model<Buffer>, Vec<Range<Anchor>>>>> {
+        let snapshot = buffer.read(cx).snapshot();
+        let Some(name_range) = snapshot.identifier_range_at(position) else {
+            return Task::ready(Ok(HashMap::default()));
+        };
+        let name = snapshot.text_for_range(name_range).collect::<String>();
+
+        let query = match SearchQuery::text(
+            &name,
+            true,
+            true,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        ) {
+            Ok(query) => query,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        let mut search_results = self.search(query, cx);
+        cx.spawn(|_, mut cx| async move {
+            let mut occurrences = HashMap::default();
+            while let Some(search_result) = search_results.next().await {
+                let buffer = match search_result {
+                    SearchResult::Buffer { buffer, .. } => buffer,
+                    // Renaming only some of the occurrences would break the code.
+                    SearchResult::LimitReached => {
+                        return Err(anyhow!(
+                            "{name:?} occurs too many times to be renamed without a language server"
+                        ));
+                    }
+                };
+                buffer
+                    .update(&mut cx, |buffer, _| buffer.parsing_idle())?
+                    .await;
+                let snapshot = buffer.update(&mut cx, |buffer, _| buffer.snapshot())?;
+                let name = name.clone();
+                let ranges = cx
+                    .background_executor()
+                    .spawn(async move {
+                        snapshot
+                            .identifier_ranges(0..snapshot.len(), &name)
+                            .into_iter()
+                            .map(|range| {
+                                snapshot.anchor_after(range.start)
+                                    ..snapshot.anchor_before(range.end)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .await;
+                if !ranges.is_empty() {
+                    occurrences.insert(buffer, ranges);
+                }
+            }
+            Ok(occurrences)
+        })
+    }
+
     pub fn on_type_format<T: ToPointUtf16>(
         &mut self,
         buffer: Model<Buffer>,
@@ -2859,6 +2925,12 @@ impl Project {
                 for buffer in matching_buffer_chunk {
                     let buffer = buffer.clone();
                     let query = query.clone();
+                    // Structural queries match syntax trees, so they need the buffer to be parsed.
+                    if query.is_structural() {
+                        buffer
+                            .read_with(&cx, |buffer, _| buffer.parsing_idle())?
+                            .await;
+                    }
                     let snapshot = buffer.read_with(&cx, |buffer, _| buffer.snapshot())?;
                     chunk_results.push(cx.background_executor().spawn(async move {
                         let ranges = query
@@ -56,6 +56,60 @@ pub struct ProjectSettings {
     /// Configuration for session-related features
     #[serde(default)]
     pub session: SessionSettings,
+
+    /// Named project searches that can be rerun with `project_search: run saved search`.
+    ///
+    /// Default: []
+    #[serde(default)]
+    pub saved_searches: Vec<SavedSearch>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SavedSearch {
+    /// The name to pick the search by.
+    pub name: String,
+    /// The text, regular expression or structural pattern to search for.
+    pub query: String,
+    /// Globs of the paths to search in, such as `crates/**/*.rs`.
+    ///
+    /// Default: []
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Globs of the paths to skip, such as `vendor/*`.
+    ///
+    /// Default: []
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Whether to only match whole words.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub whole_word: bool,
+    /// Whether to match case sensitively.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Whether to also search files ignored by configuration.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub include_ignored: bool,
+    /// Whether to also search inside archives and compressed files.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub include_archives: bool,
+    /// Whether the query is a regular expression.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub regex: bool,
+    /// Whether the query is a structural pattern or Tree-sitter query.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub structural: bool,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -5,7 +5,8 @@ use gpui::{AppContext, SemanticVersion, UpdateGlobal};
 use http_client::Url;
 use language::{
     language_settings::{
-        language_settings, AllLanguageSettings, LanguageSettingsContent, SoftWrap,
+        language_settings, AllLanguageSettings, LanguageSettingsContent, LineEndingSetting,
+        SoftWrap,
     },
     tree_sitter_rust, tree_sitter_typescript, Diagnostic, DiagnosticSet, FakeLspAdapter,
     LanguageConfig, LanguageMatcher, LanguageName, LineEnding, OffsetRangeExt, Point, ToPoint,
@@ -175,6 +176,7 @@ async fn test_editorconfig_support(cx: &mut gpui::TestAppContext) {
         assert_eq!(settings_a.ensure_final_newline_on_save, true);
         assert_eq!(settings_a.remove_trailing_whitespace_on_save, true);
         assert_eq!(settings_a.preferred_line_length, 80);
+        assert_eq!(settings_a.line_ending, LineEndingSetting::Lf);
 
         // "max_line_length" also sets "soft_wrap"
         assert_eq!(settings_a.soft_wrap, SoftWrap::PreferredLineLength);
@@ -191,9 +193,52 @@ async fn test_editorconfig_support(cx: &mut gpui::TestAppContext) {
 
         // README.md should not be affected by .editorconfig's globe "*.rs"
         assert_eq!(Some(settings_readme.tab_size), NonZeroU32::new(8));
+        assert_eq!(settings_readme.line_ending, LineEndingSetting::Detect);
     });
 }
 
+#[gpui::test]
+async fn test_editorconfig_line_endings_on_save(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".editorconfig": "root = true\n[*.txt]\nend_of_line = lf\n",
+            "unix.txt": "one\r\ntwo\r\n",
+            "windows.md": "one\r\ntwo\r\n",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    cx.executor().run_until_parked();
+
+    for path in ["/dir/unix.txt", "/dir/windows.md"] {
+        let buffer = project
+            .update(cx, |project, cx| project.open_local_buffer(path, cx))
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, cx| {
+            assert_eq!(buffer.line_ending(), LineEnding::Windows);
+            buffer.edit([(0..0, "zero\n")], None, cx);
+        });
+        project
+            .update(cx, |project, cx| project.save_buffer(buffer, cx))
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(
+        fs.load(Path::new("/dir/unix.txt")).await.unwrap(),
+        "zero\none\ntwo\n"
+    );
+    assert_eq!(
+        fs.load(Path::new("/dir/windows.md")).await.unwrap(),
+        "zero\r\none\r\ntwo\r\n"
+    );
+}
+
 #[gpui::test]
 async fn test_managing_project_specific_settings(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -4652,6 +4697,66 @@ async fn test_search_in_gitignored_dirs(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_search_in_archives(cx: &mut gpui::TestAppContext) {
+    use async_compression::futures::write::GzipEncoder;
+    use futures::AsyncWriteExt as _;
+
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "main.rs": "// the needle",
+        }),
+    )
+    .await;
+    let mut log = GzipEncoder::new(Vec::new());
+    log.write_all(b"INFO: start\nERROR: needle\n")
+        .await
+        .unwrap();
+    log.close().await.unwrap();
+    fs.insert_file("/dir/app.log.gz", log.into_inner()).await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+    let query = SearchQuery::text(
+        "needle",
+        false,
+        false,
+        false,
+        Default::default(),
+        Default::default(),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        search(&project, query.clone(), cx).await.unwrap(),
+        HashMap::from_iter([("dir/main.rs".to_string(), vec![7..13])]),
+        "Archives should only be searched when opted in"
+    );
+    assert_eq!(
+        search(&project, query.with_archives(true), cx)
+            .await
+            .unwrap(),
+        HashMap::from_iter([
+            ("dir/main.rs".to_string(), vec![7..13]),
+            ("dir/app.log.gz!/app.log".to_string(), vec![19..25]),
+        ]),
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/app.log.gz!/app.log", cx)
+        })
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "INFO: start\nERROR: needle\n");
+        assert!(buffer.read_only());
+    });
+}
+
 #[gpui::test]
 async fn test_search_ordering(cx: &mut gpui::TestAppContext) {
     init_test(cx);
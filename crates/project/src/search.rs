@@ -2,7 +2,7 @@ use aho_corasick::{AhoCorasick, AhoCorasickBuilder};
 use anyhow::Result;
 use client::proto;
 use gpui::Model;
-use language::{Buffer, BufferSnapshot};
+use language::{Buffer, BufferSnapshot, StructuralMatch, StructuralPattern};
 use regex::{Captures, Regex, RegexBuilder};
 use smol::future::yield_now;
 use std::{
@@ -38,6 +38,8 @@ pub struct SearchInputs {
     files_to_include: PathMatcher,
     files_to_exclude: PathMatcher,
     buffers: Option<Vec<Model<Buffer>>>,
+    preserve_case: bool,
+    include_archives: bool,
 }
 
 impl SearchInputs {
@@ -53,6 +55,12 @@ impl SearchInputs {
     pub fn buffers(&self) -> &Option<Vec<Model<Buffer>>> {
         &self.buffers
     }
+    pub fn preserve_case(&self) -> bool {
+        self.preserve_case
+    }
+    pub fn include_archives(&self) -> bool {
+        self.include_archives
+    }
 }
 #[derive(Clone, Debug)]
 pub enum SearchQuery {
@@ -74,6 +82,15 @@ pub enum SearchQuery {
         include_ignored: bool,
         inner: SearchInputs,
     },
+
+    Structural {
+        pattern: Arc<StructuralPattern>,
+        /// Searches files for a word every match has to contain, before they are parsed.
+        prefilter: Option<Arc<AhoCorasick>>,
+        replacement: Option<String>,
+        include_ignored: bool,
+        inner: SearchInputs,
+    },
 }
 
 impl SearchQuery {
@@ -95,6 +112,8 @@ impl SearchQuery {
             files_to_exclude,
             files_to_include,
             buffers,
+            preserve_case: false,
+            include_archives: false,
         };
         Ok(Self::Text {
             search: Arc::new(search),
@@ -135,6 +154,8 @@ impl SearchQuery {
             files_to_exclude,
             files_to_include,
             buffers,
+            preserve_case: false,
+            include_archives: false,
         };
         Ok(Self::Regex {
             regex,
@@ -147,8 +168,50 @@ impl SearchQuery {
         })
     }
 
+    /// Creates a query matching syntax trees, from either a Tree-sitter query or a code
+    /// pattern with metavariables such as `foo($A, $B)`.
+    pub fn structural(
+        query: impl ToString,
+        include_ignored: bool,
+        files_to_include: PathMatcher,
+        files_to_exclude: PathMatcher,
+        buffers: Option<Vec<Model<Buffer>>>,
+    ) -> Result<Self> {
+        let query = query.to_string();
+        let pattern = StructuralPattern::new(&query);
+        let prefilter = pattern
+            .required_word()
+            .map(|word| AhoCorasickBuilder::new().build([word]))
+            .transpose()?
+            .map(Arc::new);
+        let inner = SearchInputs {
+            query: query.into(),
+            files_to_exclude,
+            files_to_include,
+            buffers,
+            preserve_case: false,
+            include_archives: false,
+        };
+        Ok(Self::Structural {
+            pattern: Arc::new(pattern),
+            prefilter,
+            replacement: None,
+            include_ignored,
+            inner,
+        })
+    }
+
     pub fn from_proto(message: proto::SearchQuery) -> Result<Self> {
-        if message.regex {
+        let include_archives = message.include_archives;
+        let query = if message.structural {
+            Self::structural(
+                message.query,
+                message.include_ignored,
+                deserialize_path_matches(&message.files_to_include)?,
+                deserialize_path_matches(&message.files_to_exclude)?,
+                None, // search opened only don't need search remote
+            )
+        } else if message.regex {
             Self::regex(
                 message.query,
                 message.whole_word,
@@ -168,7 +231,8 @@ impl SearchQuery {
                 deserialize_path_matches(&message.files_to_exclude)?,
                 None, // search opened only don't need search remote
             )
-        }
+        }?;
+        Ok(query.with_archives(include_archives))
     }
 
     pub fn with_replacement(mut self, new_replacement: String) -> Self {
@@ -180,6 +244,10 @@ impl SearchQuery {
             | Self::Regex {
                 ref mut replacement,
                 ..
+            }
+            | Self::Structural {
+                ref mut replacement,
+                ..
             } => {
                 *replacement = Some(new_replacement);
                 self
@@ -187,13 +255,41 @@ impl SearchQuery {
         }
     }
 
+    /// Makes replacements follow the case of the text they replace, so that replacing `foo`
+    /// with `bar` turns `Foo` into `Bar` and `FOO` into `BAR`.
+    pub fn with_preserved_case(mut self, preserve_case: bool) -> Self {
+        match self {
+            Self::Text { ref mut inner, .. }
+            | Self::Regex { ref mut inner, .. }
+            | Self::Structural { ref mut inner, .. } => {
+                inner.preserve_case = preserve_case;
+                self
+            }
+        }
+    }
+
+    /// Also searches the files stored in the `.zip`, `.jar` and `.tar.gz` archives of the
+    /// project, as well as its gzip-compressed files.
+    pub fn with_archives(mut self, include_archives: bool) -> Self {
+        match self {
+            Self::Text { ref mut inner, .. }
+            | Self::Regex { ref mut inner, .. }
+            | Self::Structural { ref mut inner, .. } => {
+                inner.include_archives = include_archives;
+                self
+            }
+        }
+    }
+
     pub fn to_proto(&self) -> proto::SearchQuery {
         proto::SearchQuery {
             query: self.as_str().to_string(),
             regex: self.is_regex(),
+            structural: self.is_structural(),
             whole_word: self.whole_word(),
             case_sensitive: self.case_sensitive(),
             include_ignored: self.include_ignored(),
+            include_archives: self.include_archives(),
             files_to_include: self.files_to_include().sources().join(","),
             files_to_exclude: self.files_to_exclude().sources().join(","),
         }
@@ -234,18 +330,31 @@ impl SearchQuery {
                     Ok(false)
                 }
             }
+            Self::Structural { prefilter, .. } => match prefilter {
+                Some(prefilter) => match prefilter.stream_find_iter(stream).next() {
+                    Some(Ok(_)) => Ok(true),
+                    Some(Err(err)) => Err(err.into()),
+                    None => Ok(false),
+                },
+                None => Ok(true),
+            },
         }
     }
     /// Returns the replacement text for this `SearchQuery`.
     pub fn replacement(&self) -> Option<&str> {
         match self {
-            SearchQuery::Text { replacement, .. } | SearchQuery::Regex { replacement, .. } => {
-                replacement.as_deref()
-            }
+            SearchQuery::Text { replacement, .. }
+            | SearchQuery::Regex { replacement, .. }
+            | SearchQuery::Structural { replacement, .. } => replacement.as_deref(),
         }
     }
     /// Replaces search hits if replacement is set. `text` is assumed to be a string that matches this `SearchQuery` exactly, without any leftovers on either side.
     pub fn replacement_for<'a>(&self, text: &'a str) -> Option<Cow<'a, str>> {
+        let replacement = self.raw_replacement_for(text)?;
+        Some(self.adjust_case(text, replacement))
+    }
+
+    fn raw_replacement_for<'a>(&self, text: &'a str) -> Option<Cow<'a, str>> {
         match self {
             SearchQuery::Text { replacement, .. } => replacement.clone().map(Cow::from),
             SearchQuery::Regex {
@@ -267,6 +376,52 @@ impl SearchQuery {
                     None
                 }
             }
+            // Structural replacements depend on the captures of a match, see `replacement_for_range`.
+            SearchQuery::Structural { .. } => None,
+        }
+    }
+
+    /// Replaces the search hit at `range` in `buffer` if replacement is set. Unlike
+    /// `replacement_for`, this fills in the metavariables of structural replacements.
+    pub fn replacement_for_range<'a>(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+        text: &'a str,
+    ) -> Option<Cow<'a, str>> {
+        match self {
+            SearchQuery::Structural {
+                replacement: Some(replacement),
+                ..
+            } => {
+                let mat = self.structural_match(buffer, range)?;
+                let replacement = mat.expand_template(replacement, buffer);
+                Some(self.adjust_case(text, Cow::Owned(replacement)))
+            }
+            _ => self.replacement_for(text),
+        }
+    }
+
+    fn adjust_case<'a>(&self, text: &str, replacement: Cow<'a, str>) -> Cow<'a, str> {
+        if self.preserve_case() {
+            Cow::Owned(preserve_case(text, &replacement))
+        } else {
+            replacement
+        }
+    }
+
+    /// Returns the structural match, with its captures, found at `range` in `buffer`.
+    pub fn structural_match(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Option<StructuralMatch> {
+        match self {
+            SearchQuery::Structural { pattern, .. } => pattern
+                .matches(buffer, range.clone())
+                .into_iter()
+                .find(|mat| mat.range == range),
+            _ => None,
         }
     }
 
@@ -358,6 +513,13 @@ impl SearchQuery {
                     }
                 }
             }
+
+            Self::Structural { pattern, .. } => {
+                let range = range_offset..range_offset + rope.len();
+                for mat in pattern.matches(buffer, range) {
+                    matches.push(mat.range.start - range_offset..mat.range.end - range_offset);
+                }
+            }
         }
 
         matches
@@ -375,6 +537,7 @@ impl SearchQuery {
         match self {
             Self::Text { whole_word, .. } => *whole_word,
             Self::Regex { whole_word, .. } => *whole_word,
+            Self::Structural { .. } => false,
         }
     }
 
@@ -382,6 +545,7 @@ impl SearchQuery {
         match self {
             Self::Text { case_sensitive, .. } => *case_sensitive,
             Self::Regex { case_sensitive, .. } => *case_sensitive,
+            Self::Structural { .. } => false,
         }
     }
 
@@ -393,13 +557,28 @@ impl SearchQuery {
             Self::Regex {
                 include_ignored, ..
             } => *include_ignored,
+            Self::Structural {
+                include_ignored, ..
+            } => *include_ignored,
         }
     }
 
+    pub fn preserve_case(&self) -> bool {
+        self.as_inner().preserve_case()
+    }
+
+    pub fn include_archives(&self) -> bool {
+        self.as_inner().include_archives()
+    }
+
     pub fn is_regex(&self) -> bool {
         matches!(self, Self::Regex { .. })
     }
 
+    pub fn is_structural(&self) -> bool {
+        matches!(self, Self::Structural { .. })
+    }
+
     pub fn files_to_include(&self) -> &PathMatcher {
         self.as_inner().files_to_include()
     }
@@ -437,8 +616,35 @@ impl SearchQuery {
     }
     pub fn as_inner(&self) -> &SearchInputs {
         match self {
-            Self::Regex { inner, .. } | Self::Text { inner, .. } => inner,
+            Self::Regex { inner, .. }
+            | Self::Text { inner, .. }
+            | Self::Structural { inner, .. } => inner,
+        }
+    }
+}
+
+/// Changes the case of `replacement` to match the one of `text`: all uppercase or capitalized,
+/// where a single uppercase letter counts as capitalized. Replacements for lowercase text or
+/// text with mixed case are returned as typed.
+fn preserve_case(text: &str, replacement: &str) -> String {
+    let mut letters = text.chars().filter(|c| c.is_alphabetic());
+    let Some(first) = letters.next() else {
+        return replacement.to_string();
+    };
+    let (has_upper, has_lower) = letters.fold((false, false), |(upper, lower), c| {
+        (upper || c.is_uppercase(), lower || c.is_lowercase())
+    });
+
+    if first.is_uppercase() && has_upper && !has_lower {
+        replacement.to_uppercase()
+    } else if first.is_uppercase() && !has_upper {
+        let mut chars = replacement.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
         }
+    } else {
+        replacement.to_string()
     }
 }
 
@@ -499,4 +705,105 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn case_preserving_replacements() {
+        let query = SearchQuery::text(
+            "foo",
+            false,
+            false,
+            false,
+            PathMatcher::default(),
+            PathMatcher::default(),
+            None,
+        )
+        .unwrap()
+        .with_replacement("bar".to_string());
+        assert_eq!(query.replacement_for("Foo").as_deref(), Some("bar"));
+
+        let query = query.with_preserved_case(true);
+        assert!(query.preserve_case());
+        assert_eq!(query.replacement_for("foo").as_deref(), Some("bar"));
+        assert_eq!(query.replacement_for("Foo").as_deref(), Some("Bar"));
+        assert_eq!(query.replacement_for("FOO").as_deref(), Some("BAR"));
+        assert_eq!(query.replacement_for("fOo").as_deref(), Some("bar"));
+
+        // Lowercase matches keep the replacement as typed.
+        let query = query.with_replacement("fooBar".to_string());
+        assert_eq!(query.replacement_for("foo").as_deref(), Some("fooBar"));
+        assert_eq!(query.replacement_for("FOO").as_deref(), Some("FOOBAR"));
+
+        // A single uppercase letter is capitalized, not uppercase.
+        let query = SearchQuery::text(
+            "a",
+            true,
+            false,
+            false,
+            PathMatcher::default(),
+            PathMatcher::default(),
+            None,
+        )
+        .unwrap()
+        .with_replacement("the".to_string())
+        .with_preserved_case(true);
+        assert_eq!(query.replacement_for("A").as_deref(), Some("The"));
+        assert_eq!(query.replacement_for("a").as_deref(), Some("the"));
+
+        let query = SearchQuery::regex(
+            r"get_(\w+)",
+            false,
+            false,
+            false,
+            PathMatcher::default(),
+            PathMatcher::default(),
+            None,
+        )
+        .unwrap()
+        .with_replacement("fetch_$1".to_string())
+        .with_preserved_case(true);
+        assert_eq!(
+            query.replacement_for("get_value").as_deref(),
+            Some("fetch_value")
+        );
+        assert_eq!(
+            query.replacement_for("GET_VALUE").as_deref(),
+            Some("FETCH_VALUE")
+        );
+        assert_eq!(
+            query.replacement_for("Get_value").as_deref(),
+            Some("Fetch_value")
+        );
+    }
+
+    #[test]
+    fn structural_query_detection_and_proto() {
+        let query = SearchQuery::structural(
+            "foo($A, $B)",
+            false,
+            PathMatcher::default(),
+            PathMatcher::default(),
+            None,
+        )
+        .unwrap();
+        assert!(query.detect("fn main() { foo(1, 2); }".as_bytes()).unwrap());
+        assert!(!query.detect("fn main() { bar(1, 2); }".as_bytes()).unwrap());
+        assert!(!query.whole_word());
+        assert!(!query.case_sensitive());
+
+        let query = SearchQuery::from_proto(query.to_proto()).unwrap();
+        assert!(query.is_structural());
+        assert!(!query.is_regex());
+        assert_eq!(query.as_str(), "foo($A, $B)");
+
+        // Tree-sitter queries can't be prefiltered by their text.
+        let query = SearchQuery::structural(
+            "(call_expression) @match",
+            false,
+            PathMatcher::default(),
+            PathMatcher::default(),
+            None,
+        )
+        .unwrap();
+        assert!(query.detect("fn main() {}".as_bytes()).unwrap());
+    }
 }
@@ -83,6 +83,18 @@ impl Project {
         kind: TerminalKind,
         window: AnyWindowHandle,
         cx: &mut ModelContext<Self>,
+    ) -> anyhow::Result<Model<Terminal>> {
+        self.create_terminal_with_scrollback(kind, None, window, cx)
+    }
+
+    /// Creates a terminal that shows the given scrollback of a previous session above the
+    /// output of its shell.
+    pub fn create_terminal_with_scrollback(
+        &mut self,
+        kind: TerminalKind,
+        restored_scrollback: Option<&str>,
+        window: AnyWindowHandle,
+        cx: &mut ModelContext<Self>,
     ) -> anyhow::Result<Model<Terminal>> {
         let path = match &kind {
             TerminalKind::Shell(path) => path.as_ref().map(|path| path.to_path_buf()),
@@ -230,7 +242,9 @@ impl Project {
             settings.cursor_shape.unwrap_or_default(),
             settings.alternate_scroll,
             settings.max_scroll_history_lines,
+            settings.shell_integration,
             ssh_details.is_some(),
+            restored_scrollback,
             window,
             completion_tx,
             cx,
@@ -5,13 +5,18 @@ use std::{
 
 use anyhow::{anyhow, Context as _, Result};
 use collections::{HashMap, HashSet};
-use fs::Fs;
+use fs::{
+    archive::{self, ArchiveKind},
+    Fs,
+};
 use futures::{
     future::{BoxFuture, Shared},
+    stream::BoxStream,
     FutureExt, SinkExt,
 };
 use gpui::{
-    AppContext, AsyncAppContext, EntityId, EventEmitter, Model, ModelContext, Task, WeakModel,
+    AppContext, AsyncAppContext, BackgroundExecutor, EntityId, EventEmitter, Model, ModelContext,
+    Task, WeakModel,
 };
 use postage::oneshot;
 use rpc::{
@@ -28,6 +33,10 @@ use worktree::{Entry, ProjectEntryId, Worktree, WorktreeId, WorktreeSettings};
 
 use crate::{search::SearchQuery, ProjectPath};
 
+/// The search candidates found for an entry of a worktree: the path of a file, or the paths of the
+/// matching files stored in an archive.
+type SearchCandidates = BoxStream<'static, ProjectPath>;
+
 struct MatchingEntry {
     worktree_path: Arc<Path>,
     path: ProjectPath,
@@ -625,10 +634,12 @@ impl WorktreeStore {
 
         let input = cx.background_executor().spawn({
             let fs = fs.clone();
+            let executor = executor.clone();
             let query = query.clone();
             async move {
                 Self::find_candidate_paths(
                     fs,
+                    executor,
                     snapshots,
                     open_entries,
                     query,
@@ -657,16 +668,15 @@ impl WorktreeStore {
         cx.background_executor()
             .spawn(async move {
                 let mut matched = 0;
-                while let Some(mut receiver) = output_rx.next().await {
-                    let Some(path) = receiver.next().await else {
-                        continue;
-                    };
-                    let Ok(_) = matching_paths_tx.send(path).await else {
-                        break;
-                    };
-                    matched += 1;
-                    if matched == limit {
-                        break;
+                'candidates: while let Some(mut candidates) = output_rx.next().await {
+                    while let Some(path) = candidates.next().await {
+                        let Ok(_) = matching_paths_tx.send(path).await else {
+                            break 'candidates;
+                        };
+                        matched += 1;
+                        if matched == limit {
+                            break 'candidates;
+                        }
                     }
                 }
                 drop(input);
@@ -683,7 +693,7 @@ impl WorktreeStore {
         query: &'a SearchQuery,
         include_root: bool,
         filter_tx: &'a Sender<MatchingEntry>,
-        output_tx: &'a Sender<oneshot::Receiver<ProjectPath>>,
+        output_tx: &'a Sender<SearchCandidates>,
     ) -> BoxFuture<'a, Result<()>> {
         async move {
             let abs_path = snapshot.abs_path().join(path);
@@ -734,7 +744,7 @@ impl WorktreeStore {
                         }
                     }
                     let (tx, rx) = oneshot::channel();
-                    output_tx.send(rx).await?;
+                    output_tx.send(Box::pin(rx)).await?;
                     filter_tx
                         .send(MatchingEntry {
                             respond: tx,
@@ -765,11 +775,12 @@ impl WorktreeStore {
 
     async fn find_candidate_paths(
         fs: Arc<dyn Fs>,
+        executor: BackgroundExecutor,
         snapshots: Vec<(worktree::Snapshot, WorktreeSettings)>,
         open_entries: HashSet<ProjectEntryId>,
         query: SearchQuery,
         filter_tx: Sender<MatchingEntry>,
-        output_tx: Sender<oneshot::Receiver<ProjectPath>>,
+        output_tx: Sender<SearchCandidates>,
     ) -> Result<()> {
         let include_root = snapshots.len() > 1;
         for (snapshot, settings) in snapshots {
@@ -796,6 +807,25 @@ impl WorktreeStore {
                     continue;
                 }
 
+                if query.include_archives() && ArchiveKind::for_path(&entry.path).is_some() {
+                    // Archives are scanned in the background, so that extracting them doesn't
+                    // hold up the search of the entries that come after them.
+                    let (archive_tx, archive_rx) = smol::channel::unbounded();
+                    output_tx.send(Box::pin(archive_rx)).await?;
+                    executor
+                        .spawn(Self::find_candidate_paths_in_archive(
+                            fs.clone(),
+                            snapshot.id(),
+                            snapshot.abs_path().join(&entry.path),
+                            entry.path.clone(),
+                            include_root.then(|| snapshot.root_name().to_string()),
+                            query.clone(),
+                            archive_tx,
+                        ))
+                        .detach();
+                    continue;
+                }
+
                 if query.filters_path() {
                     let matched_path = if include_root {
                         let mut full_path = PathBuf::from(snapshot.root_name());
@@ -830,12 +860,71 @@ impl WorktreeStore {
                         .await?;
                 }
 
-                output_tx.send(rx).await?;
+                output_tx.send(Box::pin(rx)).await?;
             }
         }
         Ok(())
     }
 
+    /// Searches the files stored in an archive, which are matched against the query as they are
+    /// extracted, since they can't be opened on their own.
+    async fn find_candidate_paths_in_archive(
+        fs: Arc<dyn Fs>,
+        worktree_id: WorktreeId,
+        abs_path: PathBuf,
+        archive_path: Arc<Path>,
+        root_name: Option<String>,
+        query: SearchQuery,
+        output_tx: Sender<ProjectPath>,
+    ) {
+        let Some(extracted) = archive::extract_archive(fs.as_ref(), &abs_path)
+            .await
+            .with_context(|| format!("extracting archive {abs_path:?}"))
+            .log_err()
+        else {
+            return;
+        };
+
+        for inner_path in &extracted.files {
+            let Some(path) = archive::archive_entry_path(&archive_path, inner_path) else {
+                continue;
+            };
+            if query.filters_path() {
+                let matched_path = if let Some(root_name) = &root_name {
+                    query.file_matches(&Path::new(root_name).join(&path))
+                } else {
+                    query.file_matches(&path)
+                };
+                if !matched_path {
+                    continue;
+                }
+            }
+
+            let Some(content) = fs
+                .load_bytes(&extracted.dir.join(inner_path))
+                .await
+                .log_err()
+            else {
+                continue;
+            };
+            // Skip binary files.
+            if content.contains(&0) || std::str::from_utf8(&content).is_err() {
+                continue;
+            }
+            if !query.detect(content.as_slice()).unwrap_or(false) {
+                continue;
+            }
+
+            let project_path = ProjectPath {
+                worktree_id,
+                path: Arc::from(path),
+            };
+            if output_tx.send(project_path).await.is_err() {
+                break;
+            }
+        }
+    }
+
     async fn filter_paths(
         fs: &Arc<dyn Fs>,
         mut input: Receiver<MatchingEntry>,
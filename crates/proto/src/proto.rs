@@ -333,6 +333,8 @@ messages!(
     (OpenContextResponse, Foreground),
     (CreateContext, Foreground),
     (CreateContextResponse, Foreground),
+    (ForkContext, Foreground),
+    (ForkContextResponse, Foreground),
     (UpdateContext, Foreground),
     (SynchronizeContexts, Foreground),
     (SynchronizeContextsResponse, Foreground),
@@ -463,6 +465,7 @@ request_messages!(
     (RestartLanguageServers, Ack),
     (OpenContext, OpenContextResponse),
     (CreateContext, CreateContextResponse),
+    (ForkContext, ForkContextResponse),
     (SynchronizeContexts, SynchronizeContextsResponse),
     (LspExtSwitchSourceHeader, LspExtSwitchSourceHeaderResponse),
     (AddWorktree, AddWorktreeResponse),
@@ -540,6 +543,7 @@ entity_messages!(
     AdvertiseContexts,
     OpenContext,
     CreateContext,
+    ForkContext,
     UpdateContext,
     SynchronizeContexts,
     LspExtSwitchSourceHeader,
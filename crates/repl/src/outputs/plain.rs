@@ -127,7 +127,7 @@ impl TerminalOutput {
         let term = alacritty_terminal::Term::new(
             Config::default(),
             &terminal_size(cx),
-            terminal::ZedListener(events_tx.clone()),
+            terminal::ZedListener::new(events_tx.clone()),
         );
 
         mem::forget(events_rx);
@@ -1,30 +1,35 @@
 use crate::{
+    saved_searches::{self, SavedSearchesModal},
     FocusSearch, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll, ReplaceNext, SearchOptions,
-    SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive, ToggleIncludeIgnored, ToggleRegex,
-    ToggleReplace, ToggleWholeWord,
+    SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive, ToggleIncludeArchives,
+    ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleStructural, ToggleWholeWord,
 };
 use collections::{HashMap, HashSet};
 use editor::{
     actions::SelectAll,
+    display_map::{BlockPlacement, BlockProperties, BlockStyle, CustomBlockId},
     items::active_match_index,
     scroll::{Autoscroll, Axis},
-    Anchor, Editor, EditorElement, EditorEvent, EditorSettings, EditorStyle, MultiBuffer,
-    MAX_TAB_TITLE_LEN,
+    Anchor, Bias, Editor, EditorElement, EditorEvent, EditorSettings, EditorStyle, ExcerptId,
+    MultiBuffer, MultiBufferSnapshot, ToOffset as _, ToPoint as _, MAX_TAB_TITLE_LEN,
 };
 use futures::StreamExt;
 use gpui::{
-    actions, div, Action, AnyElement, AnyView, AppContext, Context as _, EntityId, EventEmitter,
-    FocusHandle, FocusableView, Global, Hsla, InteractiveElement, IntoElement, KeyContext, Model,
-    ModelContext, ParentElement, Point, Render, SharedString, Styled, Subscription, Task,
-    TextStyle, UpdateGlobal, View, ViewContext, VisualContext, WeakModel, WeakView, WindowContext,
+    actions, div, impl_actions, Action, AnyElement, AnyView, AppContext, Context as _, EntityId,
+    EventEmitter, FocusHandle, FocusableView, FontWeight, Global, HighlightStyle, Hsla,
+    InteractiveElement, IntoElement, KeyContext, Model, ModelContext, ParentElement, Point, Render,
+    SharedString, StrikethroughStyle, Styled, StyledText, Subscription, Task, TextStyle,
+    UpdateGlobal, View, ViewContext, VisualContext, WeakModel, WeakView, WindowContext,
 };
-use language::Buffer;
+use language::{Buffer, BufferId, ToOffset as _};
 use menu::Confirm;
 use project::{
+    project_settings::{ProjectSettings, SavedSearch},
     search::{SearchInputKind, SearchQuery},
     search_history::SearchHistoryCursor,
     Project, ProjectPath,
 };
+use serde::Deserialize;
 use settings::Settings;
 use std::{
     any::{Any, TypeId},
@@ -40,8 +45,9 @@ use ui::{
 use util::paths::PathMatcher;
 use workspace::{
     item::{BreadcrumbText, Item, ItemEvent, ItemHandle},
+    notifications::NotificationId,
     searchable::{Direction, SearchableItem, SearchableItemHandle},
-    DeploySearch, ItemNavHistory, NewSearch, ToolbarItemEvent, ToolbarItemLocation,
+    DeploySearch, ItemNavHistory, NewSearch, Toast, ToolbarItemEvent, ToolbarItemLocation,
     ToolbarItemView, Workspace, WorkspaceId,
 };
 
@@ -50,14 +56,38 @@ const MAX_INPUT_WIDTH_REMS: f32 = 30.;
 
 actions!(
     project_search,
-    [SearchInNew, ToggleFocus, NextField, ToggleFilters]
+    [
+        SearchInNew,
+        ToggleFocus,
+        NextField,
+        ToggleFilters,
+        SearchWithinResults,
+        ExcludeMatch,
+        ExcludeFile,
+        SaveSearch,
+        ToggleReplacePreview,
+        TogglePreserveCase,
+        ToggleMatchReplacement
+    ]
 );
 
+/// Reruns a search from the `saved_searches` project setting, picking one when no name is given.
+#[derive(Clone, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RunSavedSearch {
+    pub name: Option<String>,
+}
+
+impl_actions!(project_search, [RunSavedSearch]);
+
 #[derive(Default)]
 struct ActiveSettings(HashMap<WeakModel<Project>, ProjectSearchSettings>);
 
 impl Global for ActiveSettings {}
 
+/// Highlights the metavariables and captures within the matches of a structural search.
+enum StructuralCaptures {}
+
 pub fn init(cx: &mut AppContext) {
     cx.set_global(ActiveSettings::default());
     cx.observe_new_views(|workspace: &mut Workspace, _cx| {
@@ -76,6 +106,36 @@ pub fn init(cx: &mut AppContext) {
         register_workspace_action(workspace, move |search_bar, _: &ToggleRegex, cx| {
             search_bar.toggle_search_option(SearchOptions::REGEX, cx);
         });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleStructural, cx| {
+            search_bar.toggle_search_option(SearchOptions::STRUCTURAL, cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &SearchWithinResults, cx| {
+            search_bar.search_within_results(cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &ExcludeMatch, cx| {
+            search_bar.exclude_match(cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &ExcludeFile, cx| {
+            search_bar.exclude_file(cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &SaveSearch, cx| {
+            search_bar.save_search(cx);
+        });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleReplacePreview, cx| {
+                search_bar.toggle_replace_preview(cx);
+            },
+        );
+        register_workspace_action(workspace, move |search_bar, _: &TogglePreserveCase, cx| {
+            search_bar.toggle_preserve_case(cx);
+        });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleMatchReplacement, cx| {
+                search_bar.toggle_match_replacement(cx);
+            },
+        );
         register_workspace_action(workspace, move |search_bar, action: &ToggleReplace, cx| {
             search_bar.toggle_replace(action, cx)
         });
@@ -106,6 +166,13 @@ pub fn init(cx: &mut AppContext) {
             ProjectSearchView::deploy_search(workspace, action, cx);
             cx.notify();
         });
+        workspace.register_action(move |workspace, action: &RunSavedSearch, cx| {
+            if workspace.has_active_modal(cx) {
+                cx.propagate();
+                return;
+            }
+            ProjectSearchView::run_saved_search(workspace, action, cx);
+        });
         workspace.register_action(move |workspace, action: &NewSearch, cx| {
             if workspace.has_active_modal(cx) {
                 cx.propagate();
@@ -132,6 +199,8 @@ pub struct ProjectSearch {
     search_id: usize,
     no_results: Option<bool>,
     limit_reached: bool,
+    /// The queries the results were narrowed down with, after the initial search.
+    refinements: Vec<String>,
     search_history_cursor: SearchHistoryCursor,
     search_included_history_cursor: SearchHistoryCursor,
     search_excluded_history_cursor: SearchHistoryCursor,
@@ -159,6 +228,12 @@ pub struct ProjectSearchView {
     excluded_files_editor: View<Editor>,
     filters_enabled: bool,
     replace_enabled: bool,
+    /// Whether the replacements are previewed below the lines of their matches.
+    replace_preview: bool,
+    preserve_case: bool,
+    /// The matches excluded from the replacements while previewing them.
+    rejected_replacements: Vec<Range<Anchor>>,
+    replace_preview_blocks: HashSet<CustomBlockId>,
     included_opened_only: bool,
     _subscriptions: Vec<Subscription>,
 }
@@ -188,6 +263,7 @@ impl ProjectSearch {
             search_id: 0,
             no_results: None,
             limit_reached: false,
+            refinements: Vec::new(),
             search_history_cursor: Default::default(),
             search_included_history_cursor: Default::default(),
             search_excluded_history_cursor: Default::default(),
@@ -207,6 +283,7 @@ impl ProjectSearch {
             search_id: self.search_id,
             no_results: self.no_results,
             limit_reached: self.limit_reached,
+            refinements: self.refinements.clone(),
             search_history_cursor: self.search_history_cursor.clone(),
             search_included_history_cursor: self.search_included_history_cursor.clone(),
             search_excluded_history_cursor: self.search_excluded_history_cursor.clone(),
@@ -250,6 +327,8 @@ impl ProjectSearch {
         self.search_id += 1;
         self.active_query = Some(query);
         self.match_ranges.clear();
+        self.refinements.clear();
+        let search_id = self.search_id;
         self.pending_search = Some(cx.spawn(|this, mut cx| async move {
             let mut matches = search.ready_chunks(1024);
             let this = this.upgrade()?;
@@ -300,7 +379,10 @@ impl ProjectSearch {
                     this.no_results = Some(false);
                 }
                 this.limit_reached = limit_reached;
-                this.pending_search.take();
+                // A refinement chained onto this search is still pending.
+                if this.search_id == search_id {
+                    this.pending_search.take();
+                }
                 cx.notify();
             })
             .ok()?;
@@ -309,6 +391,113 @@ impl ProjectSearch {
         }));
         cx.notify();
     }
+
+    /// Narrows down the results to the excerpts that also match the given query. The matches
+    /// of the original query are kept, so that they can still be replaced.
+    ///
+    /// A search or refinement that is still running is finished first, so that the new query
+    /// applies to all of its results.
+    fn search_within_results(&mut self, query: SearchQuery, cx: &mut ModelContext<Self>) {
+        self.refinements.push(query.as_str().to_string());
+        self.search_id += 1;
+        let search_id = self.search_id;
+        let previous_search = self.pending_search.take();
+        self.pending_search = Some(cx.spawn(|this, mut cx| async move {
+            if let Some(previous_search) = previous_search {
+                previous_search.await;
+            }
+            let excerpts = this
+                .update(&mut cx, |this, cx| {
+                    this.excerpts
+                        .read(cx)
+                        .snapshot(cx)
+                        .excerpts()
+                        .map(|(excerpt_id, buffer, range)| {
+                            (excerpt_id, buffer.clone(), range.context)
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .ok()?;
+            let excerpts_to_remove = cx
+                .background_executor()
+                .spawn(async move {
+                    let mut excerpts_to_remove = Vec::new();
+                    for (excerpt_id, buffer, range) in excerpts {
+                        let path_matches = !query.filters_path()
+                            || buffer
+                                .file()
+                                .is_some_and(|file| query.file_matches(file.path()));
+                        let range = range.start.to_offset(&buffer)..range.end.to_offset(&buffer);
+                        if !path_matches || query.search(&buffer, Some(range)).await.is_empty() {
+                            excerpts_to_remove.push(excerpt_id);
+                        }
+                    }
+                    excerpts_to_remove
+                })
+                .await;
+
+            this.update(&mut cx, |this, cx| {
+                this.remove_excerpts(excerpts_to_remove, cx);
+                if this.search_id == search_id {
+                    this.pending_search.take();
+                }
+                cx.notify();
+            })
+            .ok()?;
+
+            None
+        }));
+        cx.notify();
+    }
+
+    /// Drops matches from the results, along with the excerpts left without any match,
+    /// so that they are skipped by a subsequent replace-all.
+    fn exclude_matches(
+        &mut self,
+        ranges_to_exclude: &[Range<Anchor>],
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.match_ranges
+            .retain(|range| !ranges_to_exclude.contains(range));
+        let excerpts_with_matches = self
+            .match_ranges
+            .iter()
+            .map(|range| range.start.excerpt_id)
+            .collect::<HashSet<_>>();
+        let excerpts_to_remove = self
+            .excerpts
+            .read(cx)
+            .excerpt_ids()
+            .into_iter()
+            .filter(|excerpt_id| !excerpts_with_matches.contains(excerpt_id))
+            .collect();
+        self.remove_excerpts(excerpts_to_remove, cx);
+        cx.notify();
+    }
+
+    /// Drops every match of a file from the results.
+    fn exclude_buffer(&mut self, buffer_id: BufferId, cx: &mut ModelContext<Self>) {
+        let excerpts_to_remove = self
+            .excerpts
+            .read(cx)
+            .snapshot(cx)
+            .excerpts()
+            .filter(|(_, buffer, _)| buffer.remote_id() == buffer_id)
+            .map(|(excerpt_id, _, _)| excerpt_id)
+            .collect();
+        self.remove_excerpts(excerpts_to_remove, cx);
+        cx.notify();
+    }
+
+    fn remove_excerpts(&mut self, excerpt_ids: Vec<ExcerptId>, cx: &mut ModelContext<Self>) {
+        self.match_ranges
+            .retain(|range| !excerpt_ids.contains(&range.start.excerpt_id));
+        if self.match_ranges.is_empty() {
+            self.no_results = Some(true);
+        }
+        self.excerpts
+            .update(cx, |excerpts, cx| excerpts.remove_excerpts(excerpt_ids, cx));
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -427,7 +616,13 @@ impl Item for ProjectSearchView {
             .last_search_query_text
             .as_ref()
             .map(|query| {
-                let query = query.replace('\n', "");
+                let model = self.model.read(cx);
+                let query = model
+                    .refinements
+                    .iter()
+                    .fold(query.replace('\n', ""), |query, refinement| {
+                        format!("{query} › {}", refinement.replace('\n', ""))
+                    });
                 let query_text = util::truncate_and_trailoff(&query, MAX_TAB_TITLE_LEN);
                 query_text.into()
             });
@@ -573,6 +768,14 @@ impl ProjectSearchView {
 
     fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut ViewContext<Self>) {
         self.search_options.toggle(option);
+        // Regular expressions and structural patterns are different query syntaxes.
+        if self.search_options.contains(option) {
+            if option == SearchOptions::REGEX {
+                self.search_options.remove(SearchOptions::STRUCTURAL);
+            } else if option == SearchOptions::STRUCTURAL {
+                self.search_options.remove(SearchOptions::REGEX);
+            }
+        }
         ActiveSettings::update_global(cx, |settings, cx| {
             settings.0.insert(
                 self.model.read(cx).project.downgrade(),
@@ -593,10 +796,7 @@ impl ProjectSearchView {
             return;
         };
 
-        let query = self.model.read(cx).active_query.clone();
-        if let Some(query) = query {
-            let query = query.with_replacement(self.replacement(cx));
-
+        if let Some(query) = self.replacement_query(cx) {
             // TODO: Do we need the clone here?
             let mat = self.model.read(cx).match_ranges[active_index].clone();
             self.results_editor.update(cx, |editor, cx| {
@@ -608,15 +808,25 @@ impl ProjectSearchView {
     pub fn replacement(&self, cx: &AppContext) -> String {
         self.replacement_editor.read(cx).text(cx)
     }
+
+    fn replacement_query(&self, cx: &AppContext) -> Option<SearchQuery> {
+        let query = self.model.read(cx).active_query.clone()?;
+        Some(
+            query
+                .with_replacement(self.replacement(cx))
+                .with_preserved_case(self.preserve_case),
+        )
+    }
+
+    /// Replaces all matches, or only the accepted ones when previewing the replacements.
     fn replace_all(&mut self, _: &ReplaceAll, cx: &mut ViewContext<Self>) {
         if self.active_match_index.is_none() {
             return;
         }
 
-        let Some(query) = self.model.read(cx).active_query.as_ref() else {
+        let Some(query) = self.replacement_query(cx) else {
             return;
         };
-        let query = query.clone().with_replacement(self.replacement(cx));
 
         let match_ranges = self
             .model
@@ -625,13 +835,213 @@ impl ProjectSearchView {
             return;
         }
 
+        let rejected_replacements = if self.replace_preview {
+            mem::take(&mut self.rejected_replacements)
+        } else {
+            Vec::new()
+        };
         self.results_editor.update(cx, |editor, cx| {
-            editor.replace_all(&mut match_ranges.iter(), &query, cx);
+            editor.replace_all(
+                &mut match_ranges
+                    .iter()
+                    .filter(|range| !rejected_replacements.contains(range)),
+                &query,
+                cx,
+            );
         });
 
         self.model.update(cx, |model, _cx| {
             model.match_ranges = match_ranges;
         });
+
+        if self.replace_preview {
+            self.replace_preview = false;
+            self.update_replace_preview(cx);
+            cx.notify();
+        }
+    }
+
+    fn toggle_replace_preview(&mut self, cx: &mut ViewContext<Self>) {
+        self.replace_preview = !self.replace_preview;
+        self.rejected_replacements.clear();
+        self.update_replace_preview(cx);
+        cx.notify();
+    }
+
+    fn toggle_preserve_case(&mut self, cx: &mut ViewContext<Self>) {
+        self.preserve_case = !self.preserve_case;
+        self.update_replace_preview(cx);
+        cx.notify();
+    }
+
+    /// Accepts or rejects the replacement of the active match in the preview.
+    fn toggle_match_replacement(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.replace_preview {
+            return;
+        }
+        let Some(active_index) = self.active_match_index else {
+            return;
+        };
+        let range = self.model.read(cx).match_ranges[active_index].clone();
+        if let Some(ix) = self
+            .rejected_replacements
+            .iter()
+            .position(|rejected| *rejected == range)
+        {
+            self.rejected_replacements.remove(ix);
+        } else {
+            self.rejected_replacements.push(range);
+        }
+        self.update_replace_preview(cx);
+        cx.notify();
+    }
+
+    fn update_replace_preview(&mut self, cx: &mut ViewContext<Self>) {
+        let query = if self.replace_preview {
+            self.replacement_query(cx)
+        } else {
+            None
+        };
+        let match_ranges = self.model.read(cx).match_ranges.clone();
+        let old_blocks = mem::take(&mut self.replace_preview_blocks);
+        let rejected_replacements = &self.rejected_replacements;
+        self.replace_preview_blocks = self.results_editor.update(cx, |editor, cx| {
+            editor.remove_blocks(old_blocks, None, cx);
+            let Some(query) = query else {
+                editor.clear_highlights::<ReplacedMatches>(cx);
+                return HashSet::default();
+            };
+
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let (replaced_ranges, previews) =
+                replace_previews(&match_ranges, rejected_replacements, &query, &snapshot);
+            let deleted_color = cx.theme().status().deleted;
+            editor.highlight_text::<ReplacedMatches>(
+                replaced_ranges,
+                HighlightStyle {
+                    color: Some(deleted_color),
+                    strikethrough: Some(StrikethroughStyle {
+                        thickness: px(1.),
+                        color: Some(deleted_color),
+                    }),
+                    ..Default::default()
+                },
+                cx,
+            );
+            let blocks = previews.into_iter().map(|preview| {
+                let height = preview.text.matches('\n').count() as u32 + 1;
+                BlockProperties {
+                    placement: BlockPlacement::Below(preview.position),
+                    height,
+                    style: BlockStyle::Flex,
+                    render: Box::new(move |cx| {
+                        let highlight = HighlightStyle {
+                            color: Some(cx.theme().status().created),
+                            font_weight: Some(FontWeight::BOLD),
+                            ..Default::default()
+                        };
+                        div()
+                            .bg(cx.theme().status().created_background)
+                            .size_full()
+                            .h(height as f32 * cx.line_height)
+                            .pl(cx.gutter_dimensions.full_width())
+                            .child(
+                                StyledText::new(preview.text.clone()).with_highlights(
+                                    &cx.editor_style.text,
+                                    preview
+                                        .replacement_ranges
+                                        .iter()
+                                        .map(|range| (range.clone(), highlight)),
+                                ),
+                            )
+                            .into_any_element()
+                    }),
+                    priority: 0,
+                }
+            });
+            editor.insert_blocks(blocks, None, cx).into_iter().collect()
+        });
+    }
+
+    fn search_within_results(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.has_matches() && self.model.read(cx).pending_search.is_none() {
+            return;
+        }
+        if let Some(query) = self.build_search_query(cx) {
+            self.model
+                .update(cx, |model, cx| model.search_within_results(query, cx));
+            // Keep showing the query the matches come from, as it is the one used for replacements.
+            if let Some(text) = self.model.read(cx).last_search_query_text.clone() {
+                self.set_query(&text, cx);
+            }
+        }
+    }
+
+    fn exclude_match(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(active_index) = self.active_match_index else {
+            return;
+        };
+        let range = self.model.read(cx).match_ranges[active_index].clone();
+        self.model
+            .update(cx, |model, cx| model.exclude_matches(&[range], cx));
+    }
+
+    fn exclude_file(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(active_index) = self.active_match_index else {
+            return;
+        };
+        let model = self.model.read(cx);
+        let excerpt_id = model.match_ranges[active_index].start.excerpt_id;
+        let Some(buffer_id) = model
+            .excerpts
+            .read(cx)
+            .snapshot(cx)
+            .buffer_id_for_excerpt(excerpt_id)
+        else {
+            return;
+        };
+        self.model
+            .update(cx, |model, cx| model.exclude_buffer(buffer_id, cx));
+    }
+
+    /// Adds the query, filters and options of this search to the `saved_searches` setting, named
+    /// after the query. A saved search with the same name is replaced.
+    fn save_search(&mut self, cx: &mut ViewContext<Self>) {
+        let query = self.query_editor.read(cx).text(cx);
+        if query.is_empty() {
+            return;
+        }
+        let globs = |editor: &View<Editor>, cx: &WindowContext| {
+            editor
+                .read(cx)
+                .text(cx)
+                .split(',')
+                .map(str::trim)
+                .filter(|glob| !glob.is_empty())
+                .map(str::to_owned)
+                .collect::<Vec<_>>()
+        };
+        let saved_search = SavedSearch {
+            name: query.replace('\n', " "),
+            query,
+            include: globs(&self.included_files_editor, cx),
+            exclude: globs(&self.excluded_files_editor, cx),
+            whole_word: self.search_options.contains(SearchOptions::WHOLE_WORD),
+            case_sensitive: self.search_options.contains(SearchOptions::CASE_SENSITIVE),
+            include_ignored: self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+            include_archives: self
+                .search_options
+                .contains(SearchOptions::INCLUDE_ARCHIVES),
+            regex: self.search_options.contains(SearchOptions::REGEX),
+            structural: self.search_options.contains(SearchOptions::STRUCTURAL),
+        };
+        let fs = self.model.read(cx).project.read(cx).fs().clone();
+        settings::update_settings_file::<ProjectSettings>(fs, cx, move |settings, _| {
+            settings
+                .saved_searches
+                .retain(|existing| existing.name != saved_search.name);
+            settings.saved_searches.push(saved_search);
+        });
     }
 
     pub fn new(
@@ -698,6 +1108,16 @@ impl ProjectSearchView {
             }
             editor
         });
+        subscriptions.push(cx.subscribe(
+            &replacement_editor,
+            |this, _, event: &EditorEvent, cx| {
+                if let EditorEvent::Edited { .. } = event {
+                    if this.replace_preview {
+                        this.update_replace_preview(cx);
+                    }
+                }
+            },
+        ));
         let results_editor = cx.new_view(|cx| {
             let mut editor = Editor::for_multibuffer(excerpts, Some(project.clone()), true, cx);
             editor.set_searchable(false);
@@ -768,6 +1188,10 @@ impl ProjectSearchView {
             excluded_files_editor,
             filters_enabled,
             replace_enabled: false,
+            replace_preview: false,
+            preserve_case: false,
+            rejected_replacements: Vec::new(),
+            replace_preview_blocks: HashSet::default(),
             included_opened_only: false,
             _subscriptions: subscriptions,
         };
@@ -905,6 +1329,75 @@ impl ProjectSearchView {
         });
     }
 
+    fn run_saved_search(
+        workspace: &mut Workspace,
+        action: &RunSavedSearch,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let saved_searches = saved_searches::saved_searches(workspace.project(), cx);
+        let Some(name) = action.name.as_ref() else {
+            let weak_workspace = cx.view().downgrade();
+            workspace.toggle_modal(cx, |cx| {
+                SavedSearchesModal::new(weak_workspace, saved_searches, cx)
+            });
+            return;
+        };
+
+        if let Some(saved_search) = saved_searches.iter().find(|search| &search.name == name) {
+            Self::open_saved_search(workspace, saved_search, cx);
+        } else {
+            workspace.show_toast(
+                Toast::new(
+                    NotificationId::unique::<RunSavedSearch>(),
+                    format!("No saved search named {name:?}"),
+                ),
+                cx,
+            );
+        }
+    }
+
+    /// Opens a new search tab with the query, filters and options of a saved search, and runs it.
+    pub(crate) fn open_saved_search(
+        workspace: &mut Workspace,
+        saved_search: &SavedSearch,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let mut options = SearchOptions::NONE;
+        options.set(SearchOptions::WHOLE_WORD, saved_search.whole_word);
+        options.set(SearchOptions::CASE_SENSITIVE, saved_search.case_sensitive);
+        options.set(SearchOptions::INCLUDE_IGNORED, saved_search.include_ignored);
+        options.set(
+            SearchOptions::INCLUDE_ARCHIVES,
+            saved_search.include_archives,
+        );
+        options.set(SearchOptions::REGEX, saved_search.regex);
+        options.set(SearchOptions::STRUCTURAL, saved_search.structural);
+        let settings = ProjectSearchSettings {
+            search_options: options,
+            filters_enabled: !saved_search.include.is_empty() || !saved_search.exclude.is_empty(),
+        };
+
+        let weak_workspace = cx.view().downgrade();
+        let model = cx.new_model(|cx| ProjectSearch::new(workspace.project().clone(), cx));
+        let view =
+            cx.new_view(|cx| ProjectSearchView::new(weak_workspace, model, cx, Some(settings)));
+        workspace.add_item_to_active_pane(Box::new(view.clone()), None, true, cx);
+        view.update(cx, |search, cx| {
+            search.set_query(&saved_search.query, cx);
+            search.set_search_editor(
+                SearchInputKind::Include,
+                &saved_search.include.join(", "),
+                cx,
+            );
+            search.set_search_editor(
+                SearchInputKind::Exclude,
+                &saved_search.exclude.join(", "),
+                cx,
+            );
+            search.search(cx);
+        });
+    }
+
     fn search(&mut self, cx: &mut ViewContext<Self>) {
         if let Some(query) = self.build_search_query(cx) {
             self.model.update(cx, |model, cx| model.search(query, cx));
@@ -959,8 +1452,16 @@ impl ProjectSearchView {
                 }
             };
 
-        let query = if self.search_options.contains(SearchOptions::REGEX) {
-            match SearchQuery::regex(
+        let query = if self.search_options.contains(SearchOptions::STRUCTURAL) {
+            SearchQuery::structural(
+                text,
+                self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+                included_files,
+                excluded_files,
+                open_buffers,
+            )
+        } else if self.search_options.contains(SearchOptions::REGEX) {
+            SearchQuery::regex(
                 text,
                 self.search_options.contains(SearchOptions::WHOLE_WORD),
                 self.search_options.contains(SearchOptions::CASE_SENSITIVE),
@@ -968,26 +1469,9 @@ impl ProjectSearchView {
                 included_files,
                 excluded_files,
                 open_buffers,
-            ) {
-                Ok(query) => {
-                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
-                    if should_unmark_error {
-                        cx.notify();
-                    }
-
-                    Some(query)
-                }
-                Err(_e) => {
-                    let should_mark_error = self.panels_with_errors.insert(InputPanel::Query);
-                    if should_mark_error {
-                        cx.notify();
-                    }
-
-                    None
-                }
-            }
+            )
         } else {
-            match SearchQuery::text(
+            SearchQuery::text(
                 text,
                 self.search_options.contains(SearchOptions::WHOLE_WORD),
                 self.search_options.contains(SearchOptions::CASE_SENSITIVE),
@@ -995,23 +1479,27 @@ impl ProjectSearchView {
                 included_files,
                 excluded_files,
                 open_buffers,
-            ) {
-                Ok(query) => {
-                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
-                    if should_unmark_error {
-                        cx.notify();
-                    }
-
-                    Some(query)
+            )
+        };
+        let include_archives = self
+            .search_options
+            .contains(SearchOptions::INCLUDE_ARCHIVES);
+        let query = match query.map(|query| query.with_archives(include_archives)) {
+            Ok(query) => {
+                let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
+                if should_unmark_error {
+                    cx.notify();
                 }
-                Err(_e) => {
-                    let should_mark_error = self.panels_with_errors.insert(InputPanel::Query);
-                    if should_mark_error {
-                        cx.notify();
-                    }
 
-                    None
+                Some(query)
+            }
+            Err(_e) => {
+                let should_mark_error = self.panels_with_errors.insert(InputPanel::Query);
+                if should_mark_error {
+                    cx.notify();
                 }
+
+                None
             }
         };
         if !self.panels_with_errors.is_empty() {
@@ -1114,6 +1602,7 @@ impl ProjectSearchView {
 
     fn model_changed(&mut self, cx: &mut ViewContext<Self>) {
         let match_ranges = self.model.read(cx).match_ranges.clone();
+        let active_query = self.model.read(cx).active_query.clone();
         if match_ranges.is_empty() {
             self.active_match_index = None;
         } else {
@@ -1121,6 +1610,9 @@ impl ProjectSearchView {
             self.update_match_index(cx);
             let prev_search_id = mem::replace(&mut self.search_id, self.model.read(cx).search_id);
             let is_new_search = self.search_id != prev_search_id;
+            if is_new_search {
+                self.rejected_replacements.clear();
+            }
             self.results_editor.update(cx, |editor, cx| {
                 if is_new_search {
                     let range_to_select = match_ranges
@@ -1136,11 +1628,33 @@ impl ProjectSearchView {
                     |theme| theme.search_match_background,
                     cx,
                 );
+                match active_query.filter(|query| query.is_structural()) {
+                    Some(query) => {
+                        let capture_ranges = structural_capture_ranges(
+                            &match_ranges,
+                            &query,
+                            &editor.buffer().read(cx).snapshot(cx),
+                        );
+                        editor.highlight_text::<StructuralCaptures>(
+                            capture_ranges,
+                            HighlightStyle {
+                                color: Some(cx.theme().colors().text_accent),
+                                font_weight: Some(FontWeight::BOLD),
+                                ..Default::default()
+                            },
+                            cx,
+                        );
+                    }
+                    None => editor.clear_highlights::<StructuralCaptures>(cx),
+                }
             });
             if is_new_search && self.query_editor.focus_handle(cx).is_focused(cx) {
                 self.focus_results_editor(cx);
             }
         }
+        if self.replace_preview {
+            self.update_replace_preview(cx);
+        }
 
         cx.emit(ViewEvent::UpdateTab);
         cx.notify();
@@ -1342,10 +1856,57 @@ impl ProjectSearchBar {
         }
     }
 
+    fn search_within_results(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.search_within_results(cx));
+        }
+    }
+
+    fn exclude_match(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.exclude_match(cx));
+        }
+    }
+
+    fn exclude_file(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.exclude_file(cx));
+        }
+    }
+
+    fn save_search(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.save_search(cx));
+        }
+    }
+
+    fn toggle_replace_preview(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.toggle_replace_preview(cx));
+        }
+    }
+
+    fn toggle_preserve_case(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.toggle_preserve_case(cx));
+        }
+    }
+
+    fn toggle_match_replacement(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.toggle_match_replacement(cx)
+            });
+        }
+    }
+
     fn toggle_replace(&mut self, _: &ToggleReplace, cx: &mut ViewContext<Self>) {
         if let Some(search) = &self.active_project_search {
             search.update(cx, |this, cx| {
                 this.replace_enabled = !this.replace_enabled;
+                if !this.replace_enabled && this.replace_preview {
+                    this.toggle_replace_preview(cx);
+                }
                 let editor_to_focus = if this.replace_enabled {
                     this.replacement_editor.focus_handle(cx)
                 } else {
@@ -1592,6 +2153,13 @@ impl Render for ProjectSearchBar {
                         cx.listener(|this, _, cx| {
                             this.toggle_search_option(SearchOptions::REGEX, cx);
                         }),
+                    ))
+                    .child(SearchOptions::STRUCTURAL.as_button(
+                        self.is_option_enabled(SearchOptions::STRUCTURAL, cx),
+                        focus_handle.clone(),
+                        cx.listener(|this, _, cx| {
+                            this.toggle_search_option(SearchOptions::STRUCTURAL, cx);
+                        }),
                     )),
             );
 
@@ -1643,6 +2211,24 @@ impl Render for ProjectSearchBar {
                                 )
                             }
                         }),
+                )
+                .child(
+                    IconButton::new("project-search-within-results", IconName::SearchSelection)
+                        .disabled(search.active_match_index.is_none())
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.search_within_results(cx);
+                        }))
+                        .tooltip({
+                            let focus_handle = focus_handle.clone();
+                            move |cx| {
+                                Tooltip::for_action_in(
+                                    "Search within results",
+                                    &SearchWithinResults,
+                                    &focus_handle,
+                                    cx,
+                                )
+                            }
+                        }),
                 ),
         );
 
@@ -1749,6 +2335,62 @@ impl Render for ProjectSearchBar {
             let focus_handle = search.replacement_editor.read(cx).focus_handle(cx);
             let replace_actions = h_flex().when(search.replace_enabled, |this| {
                 this.child(
+                    IconButton::new("project-search-preserve-case", IconName::CaseSensitive)
+                        .selected(search.preserve_case)
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.toggle_preserve_case(cx);
+                        }))
+                        .tooltip({
+                            let focus_handle = focus_handle.clone();
+                            move |cx| {
+                                Tooltip::for_action_in(
+                                    "Preserve case when replacing",
+                                    &TogglePreserveCase,
+                                    &focus_handle,
+                                    cx,
+                                )
+                            }
+                        }),
+                )
+                .child(
+                    IconButton::new("project-search-replace-preview", IconName::Diff)
+                        .selected(search.replace_preview)
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.toggle_replace_preview(cx);
+                        }))
+                        .tooltip({
+                            let focus_handle = focus_handle.clone();
+                            move |cx| {
+                                Tooltip::for_action_in(
+                                    "Preview replacements",
+                                    &ToggleReplacePreview,
+                                    &focus_handle,
+                                    cx,
+                                )
+                            }
+                        }),
+                )
+                .when(search.replace_preview, |this| {
+                    this.child(
+                        IconButton::new("project-search-toggle-match-replacement", IconName::Check)
+                            .disabled(search.active_match_index.is_none())
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.toggle_match_replacement(cx);
+                            }))
+                            .tooltip({
+                                let focus_handle = focus_handle.clone();
+                                move |cx| {
+                                    Tooltip::for_action_in(
+                                        "Accept or reject the replacement of the current match",
+                                        &ToggleMatchReplacement,
+                                        &focus_handle,
+                                        cx,
+                                    )
+                                }
+                            }),
+                    )
+                })
+                .child(
                     IconButton::new("project-search-replace-next", IconName::ReplaceNext)
                         .on_click(cx.listener(|this, _, cx| {
                             if let Some(search) = this.active_project_search.as_ref() {
@@ -1780,9 +2422,14 @@ impl Render for ProjectSearchBar {
                         }))
                         .tooltip({
                             let focus_handle = focus_handle.clone();
+                            let replace_preview = search.replace_preview;
                             move |cx| {
                                 Tooltip::for_action_in(
-                                    "Replace all matches",
+                                    if replace_preview {
+                                        "Replace accepted matches"
+                                    } else {
+                                        "Replace all matches"
+                                    },
                                     &ReplaceAll,
                                     &focus_handle,
                                     cx,
@@ -1861,6 +2508,17 @@ impl Render for ProjectSearchBar {
                         }),
                     ),
                 )
+                .child(
+                    SearchOptions::INCLUDE_ARCHIVES.as_button(
+                        search
+                            .search_options
+                            .contains(SearchOptions::INCLUDE_ARCHIVES),
+                        focus_handle.clone(),
+                        cx.listener(|this, _, cx| {
+                            this.toggle_search_option(SearchOptions::INCLUDE_ARCHIVES, cx);
+                        }),
+                    ),
+                )
         });
         let mut key_context = KeyContext::default();
         key_context.add("ProjectSearchBar");
@@ -1910,6 +2568,9 @@ impl Render for ProjectSearchBar {
                 this.on_action(cx.listener(|this, _: &ToggleIncludeIgnored, cx| {
                     this.toggle_search_option(SearchOptions::INCLUDE_IGNORED, cx);
                 }))
+                .on_action(cx.listener(|this, _: &ToggleIncludeArchives, cx| {
+                    this.toggle_search_option(SearchOptions::INCLUDE_ARCHIVES, cx);
+                }))
             })
             .on_action(cx.listener(Self::select_next_match))
             .on_action(cx.listener(Self::select_prev_match))
@@ -1996,6 +2657,124 @@ fn register_workspace_action_for_present_search<A: Action>(
     });
 }
 
+/// Highlights the matches replaced in the replace preview.
+enum ReplacedMatches {}
+
+/// The lines with accepted replacements, as they will read once replaced.
+struct ReplacePreview {
+    position: Anchor,
+    text: SharedString,
+    replacement_ranges: Vec<Range<usize>>,
+}
+
+/// Returns the matches to replace, and the preview of the lines containing them.
+fn replace_previews(
+    match_ranges: &[Range<Anchor>],
+    rejected_replacements: &[Range<Anchor>],
+    query: &SearchQuery,
+    snapshot: &MultiBufferSnapshot,
+) -> (Vec<Range<Anchor>>, Vec<ReplacePreview>) {
+    let mut replaced_ranges = Vec::new();
+    let mut previews = Vec::new();
+    // The rows of the lines being previewed, along with their replacements.
+    let mut pending: Option<(Range<u32>, Vec<(Range<usize>, String)>)> = None;
+    for range in match_ranges {
+        if rejected_replacements.contains(range) {
+            continue;
+        }
+        let Some(replacement) = replacement_for_match(range, query, snapshot) else {
+            continue;
+        };
+        replaced_ranges.push(range.clone());
+
+        let start_row = range.start.to_point(snapshot).row;
+        let end_row = range.end.to_point(snapshot).row;
+        let offsets = range.start.to_offset(snapshot)..range.end.to_offset(snapshot);
+        match pending.as_mut() {
+            Some((rows, replacements)) if start_row <= rows.end => {
+                rows.end = rows.end.max(end_row);
+                replacements.push((offsets, replacement));
+            }
+            _ => {
+                previews.extend(
+                    pending
+                        .take()
+                        .map(|(rows, replacements)| replace_preview(rows, replacements, snapshot)),
+                );
+                pending = Some((start_row..end_row, vec![(offsets, replacement)]));
+            }
+        }
+    }
+    previews
+        .extend(pending.map(|(rows, replacements)| replace_preview(rows, replacements, snapshot)));
+    (replaced_ranges, previews)
+}
+
+fn replace_preview(
+    rows: Range<u32>,
+    replacements: Vec<(Range<usize>, String)>,
+    snapshot: &MultiBufferSnapshot,
+) -> ReplacePreview {
+    let start = snapshot.point_to_offset(language::Point::new(rows.start, 0));
+    let end = snapshot.clip_point(language::Point::new(rows.end, u32::MAX), Bias::Left);
+    let mut text = String::new();
+    let mut replacement_ranges = Vec::new();
+    let mut last_offset = start;
+    for (range, replacement) in replacements {
+        text.extend(snapshot.text_for_range(last_offset..range.start));
+        replacement_ranges.push(text.len()..text.len() + replacement.len());
+        text.push_str(&replacement);
+        last_offset = range.end;
+    }
+    text.extend(snapshot.text_for_range(last_offset..snapshot.point_to_offset(end)));
+    ReplacePreview {
+        position: snapshot.anchor_before(end),
+        text: text.into(),
+        replacement_ranges,
+    }
+}
+
+fn replacement_for_match(
+    range: &Range<Anchor>,
+    query: &SearchQuery,
+    snapshot: &MultiBufferSnapshot,
+) -> Option<String> {
+    let buffer = snapshot.buffer_for_excerpt(range.start.excerpt_id)?;
+    let text = snapshot.text_for_range(range.clone()).collect::<String>();
+    let buffer_range =
+        range.start.text_anchor.to_offset(buffer)..range.end.text_anchor.to_offset(buffer);
+    query
+        .replacement_for_range(buffer, buffer_range, &text)
+        .map(|replacement| replacement.into_owned())
+}
+
+fn structural_capture_ranges(
+    match_ranges: &[Range<Anchor>],
+    query: &SearchQuery,
+    snapshot: &MultiBufferSnapshot,
+) -> Vec<Range<Anchor>> {
+    let mut capture_ranges = Vec::new();
+    for match_range in match_ranges {
+        let excerpt_id = match_range.start.excerpt_id;
+        let Some(buffer) = snapshot.buffer_for_excerpt(excerpt_id) else {
+            continue;
+        };
+        let range = match_range.start.text_anchor.to_offset(buffer)
+            ..match_range.end.text_anchor.to_offset(buffer);
+        let Some(mat) = query.structural_match(buffer, range) else {
+            continue;
+        };
+        capture_ranges.extend(mat.captures.into_iter().filter_map(|capture| {
+            let start =
+                snapshot.anchor_in_excerpt(excerpt_id, buffer.anchor_after(capture.range.start))?;
+            let end =
+                snapshot.anchor_in_excerpt(excerpt_id, buffer.anchor_before(capture.range.end))?;
+            Some(start..end)
+        }));
+    }
+    capture_ranges
+}
+
 #[cfg(any(test, feature = "test-support"))]
 pub fn perform_project_search(
     search_view: &View<ProjectSearchView>,
@@ -3589,6 +4368,182 @@ pub mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_search_within_results_and_exclusions(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+                "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let workspace = window.root(cx).unwrap();
+        let search = cx.new_model(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|cx| {
+            ProjectSearchView::new(workspace.downgrade(), search.clone(), cx, None)
+        });
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 5);
+                search_view
+                    .query_editor
+                    .update(cx, |query_editor, cx| query_editor.set_text("TWO", cx));
+                search_view.search_within_results(cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\n\nconst THREE: usize = one::ONE + two::TWO;\n\n\n\n\nconst TWO: usize = one::ONE + one::ONE;\n"
+                );
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 3);
+                assert_eq!(search_view.query_editor.read(cx).text(cx), "ONE");
+                assert_eq!(
+                    search_view.tab_content_text(cx).as_deref(),
+                    Some("ONE › TWO")
+                );
+                assert_eq!(search_view.active_match_index, Some(0));
+
+                search_view.exclude_match(cx);
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\n\nconst TWO: usize = one::ONE + one::ONE;\n"
+                );
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 2);
+
+                search_view.exclude_file(cx);
+                assert!(search_view.model.read(cx).match_ranges.is_empty());
+                assert_eq!(search_view.active_match_index, None);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_search_within_pending_results(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let workspace = window.root(cx).unwrap();
+        let search = cx.new_model(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|cx| {
+            ProjectSearchView::new(workspace.downgrade(), search.clone(), cx, None)
+        });
+
+        // The refinement waits for the search to finish instead of replacing it.
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view
+                    .query_editor
+                    .update(cx, |query_editor, cx| query_editor.set_text("ONE", cx));
+                search_view.search(cx);
+                search_view
+                    .query_editor
+                    .update(cx, |query_editor, cx| query_editor.set_text("TWO", cx));
+                search_view.search_within_results(cx);
+                search_view
+                    .query_editor
+                    .update(cx, |query_editor, cx| query_editor.set_text("THREE", cx));
+                search_view.search_within_results(cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        search_view
+            .update(cx, |search_view, cx| {
+                let model = search_view.model.read(cx);
+                assert!(model.pending_search.is_none());
+                assert_eq!(model.match_ranges.len(), 1);
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\n\nconst THREE: usize = one::ONE + two::TWO;\n"
+                );
+                assert_eq!(
+                    search_view.tab_content_text(cx).as_deref(),
+                    Some("ONE › TWO › THREE")
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_replace_preview(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "let one = ONE + One;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let workspace = window.root(cx).unwrap();
+        let search = cx.new_model(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|cx| {
+            ProjectSearchView::new(workspace.downgrade(), search.clone(), cx, None)
+        });
+
+        perform_search(search_view, "one", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 3);
+                search_view
+                    .replacement_editor
+                    .update(cx, |editor, cx| editor.set_text("two", cx));
+                search_view.toggle_preserve_case(cx);
+                search_view.toggle_replace_preview(cx);
+                assert_eq!(search_view.replace_preview_blocks.len(), 1);
+
+                // Keep the first match as is.
+                assert_eq!(search_view.active_match_index, Some(0));
+                search_view.toggle_match_replacement(cx);
+                assert_eq!(search_view.rejected_replacements.len(), 1);
+
+                search_view.replace_all(&ReplaceAll, cx);
+                assert!(!search_view.replace_preview);
+                assert!(search_view.replace_preview_blocks.is_empty());
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\n\nlet one = TWO + Two;\n"
+                );
+            })
+            .unwrap();
+    }
+
     fn perform_search(
         search_view: WindowHandle<ProjectSearchView>,
         text: impl Into<Arc<str>>,
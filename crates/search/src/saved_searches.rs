@@ -0,0 +1,230 @@
+use crate::ProjectSearchView;
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model, Render, View,
+    ViewContext, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use project::{
+    project_settings::{ProjectSettings, SavedSearch},
+    Project,
+};
+use settings::{Settings, SettingsLocation};
+use std::{path::Path, sync::Arc};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+/// Collects the saved searches from the settings of every worktree in the project, falling back
+/// to the user settings. Searches are deduplicated by name.
+pub(crate) fn saved_searches(project: &Model<Project>, cx: &AppContext) -> Vec<SavedSearch> {
+    let mut saved_searches = Vec::<SavedSearch>::new();
+    let worktree_settings = project
+        .read(cx)
+        .visible_worktrees(cx)
+        .map(|worktree| {
+            ProjectSettings::get(
+                Some(SettingsLocation {
+                    worktree_id: worktree.read(cx).id(),
+                    path: Path::new(""),
+                }),
+                cx,
+            )
+        })
+        .collect::<Vec<_>>();
+    for settings in worktree_settings
+        .into_iter()
+        .chain([ProjectSettings::get_global(cx)])
+    {
+        for saved_search in &settings.saved_searches {
+            if !saved_searches
+                .iter()
+                .any(|existing| existing.name == saved_search.name)
+            {
+                saved_searches.push(saved_search.clone());
+            }
+        }
+    }
+    saved_searches
+}
+
+pub(crate) struct SavedSearchesModal {
+    picker: View<Picker<SavedSearchesDelegate>>,
+}
+
+impl SavedSearchesModal {
+    pub(crate) fn new(
+        workspace: WeakView<Workspace>,
+        saved_searches: Vec<SavedSearch>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let delegate = SavedSearchesDelegate::new(cx.view().downgrade(), workspace, saved_searches);
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for SavedSearchesModal {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for SavedSearchesModal {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for SavedSearchesModal {}
+impl ModalView for SavedSearchesModal {}
+
+pub(crate) struct SavedSearchesDelegate {
+    modal: WeakView<SavedSearchesModal>,
+    workspace: WeakView<Workspace>,
+    saved_searches: Vec<SavedSearch>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl SavedSearchesDelegate {
+    fn new(
+        modal: WeakView<SavedSearchesModal>,
+        workspace: WeakView<Workspace>,
+        saved_searches: Vec<SavedSearch>,
+    ) -> Self {
+        let candidates = saved_searches
+            .iter()
+            .enumerate()
+            .map(|(candidate_id, search)| {
+                StringMatchCandidate::new(candidate_id, search.name.clone())
+            })
+            .collect();
+        Self {
+            modal,
+            workspace,
+            saved_searches,
+            candidates,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for SavedSearchesDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Run a saved search...".into()
+    }
+
+    fn no_matches_text(&self, _cx: &mut WindowContext) -> SharedString {
+        if self.saved_searches.is_empty() {
+            "No saved searches. Add some to the `saved_searches` setting.".into()
+        } else {
+            "No matches".into()
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> gpui::Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, candidate)| StringMatch {
+                        candidate_id: index,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(mat) = self.matches.get(self.selected_index) {
+            let saved_search = self.saved_searches[mat.candidate_id].clone();
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    ProjectSearchView::open_saved_search(workspace, &saved_search, cx);
+                })
+                .log_err();
+        }
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.modal
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        let saved_search = &self.saved_searches[mat.candidate_id];
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(
+                    v_flex()
+                        .child(HighlightedLabel::new(
+                            mat.string.clone(),
+                            mat.positions.clone(),
+                        ))
+                        .child(
+                            Label::new(saved_search.query.clone())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                ),
+        )
+    }
+}
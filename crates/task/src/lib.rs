@@ -13,7 +13,7 @@ use std::borrow::Cow;
 use std::path::PathBuf;
 use std::str::FromStr;
 
-pub use task_template::{HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates};
+pub use task_template::{HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates, TerminalSplit};
 pub use vscode_format::VsCodeTaskFile;
 
 /// Task identifier, unique within the application.
@@ -49,6 +49,8 @@ pub struct SpawnInTerminal {
     pub reveal: RevealStrategy,
     /// What to do with the terminal pane and tab, after the command had finished.
     pub hide: HideStrategy,
+    /// Which split of the terminal panel to open the task's terminal in, if it has none yet.
+    pub split: Option<TerminalSplit>,
     /// Which shell to use when spawning the task.
     pub shell: Shell,
 }
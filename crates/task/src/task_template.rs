@@ -51,6 +51,13 @@ pub struct TaskTemplate {
     /// * `on_success` — hide the terminal tab on task success only, otherwise behaves similar to `always`.
     #[serde(default)]
     pub hide: HideStrategy,
+    /// Which split of the terminal panel to open the task's terminal in, when the task has no
+    /// terminal yet (later runs stay in the split of their previous terminal):
+    /// * not set — the active split (default)
+    /// * `up`, `down`, `left` or `right` — the split next to the active one in that direction,
+    ///   which is created if there is none
+    #[serde(default)]
+    pub split: Option<TerminalSplit>,
     /// Represents the tags which this template attaches to. Adding this removes this task from other UI.
     #[serde(default)]
     pub tags: Vec<String>,
@@ -83,6 +90,20 @@ pub enum HideStrategy {
     OnSuccess,
 }
 
+/// Which split of the terminal panel to open a task's terminal in, relative to the active one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminalSplit {
+    /// The split above the active one.
+    Up,
+    /// The split below the active one.
+    Down,
+    /// The split left of the active one.
+    Left,
+    /// The split right of the active one.
+    Right,
+}
+
 /// A group of Tasks defined in a JSON file.
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct TaskTemplates(pub Vec<TaskTemplate>);
@@ -227,6 +248,7 @@ impl TaskTemplate {
                 allow_concurrent_runs: self.allow_concurrent_runs,
                 reveal: self.reveal,
                 hide: self.hide,
+                split: self.split,
                 shell: self.shell.clone(),
             }),
         })
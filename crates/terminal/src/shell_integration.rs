@@ -0,0 +1,542 @@
+//! Support for shell integration through `OSC 133` (FinalTerm) semantic prompt marks.
+//!
+//! Shells report the boundaries of their prompts, of the command line being edited and of
+//! the command's output with `OSC 133 ; {A,B,C,D}` escape sequences. Alacritty ignores those,
+//! so we filter them out of the PTY output before it reaches the parser and replace each of
+//! them with a cursor position request. Once Alacritty parsed that request, and before it gets
+//! any more output, the mark is recorded at the cursor in [`ShellMarks`], which keeps the marks
+//! next to the grid rather than in it.
+
+#[cfg(unix)]
+mod pty;
+
+use std::{
+    collections::BTreeMap,
+    env,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use alacritty_terminal::{
+    grid::Dimensions,
+    index::{Boundary, Column, Line, Point as AlacPoint},
+    term::TermMode,
+    Term,
+};
+use anyhow::Result;
+use collections::HashMap;
+use task::Shell;
+
+#[cfg(unix)]
+pub(crate) use pty::{MarkProbes, ShellIntegrationPty};
+
+/// The number of lines Alacritty keeps in the scrollback on top of the configured amount, so
+/// that lines only leave the scrollback when [`ShellMarks`] trims it and knows about it.
+pub(crate) const SCROLLBACK_MARGIN: usize = 1_000;
+
+/// A semantic mark emitted by the shell.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShellMark {
+    /// `OSC 133 ; A`: the shell is about to print its prompt.
+    PromptStart,
+    /// `OSC 133 ; B`: the prompt has been printed and the user starts typing the command.
+    CommandStart,
+    /// `OSC 133 ; C`: the command has been submitted and its output starts.
+    OutputStart { command: Option<String> },
+    /// `OSC 133 ; D [; exit_code]`: the command has finished.
+    CommandFinished { exit_code: Option<i32> },
+}
+
+impl ShellMark {
+    /// Parses the parameters of an `OSC 133` sequence, i.e. everything after `133;`.
+    fn parse(payload: &str) -> Option<Self> {
+        let mut params = payload.split(';');
+        let mark = match params.next()? {
+            "A" => Self::PromptStart,
+            "B" => Self::CommandStart,
+            "C" => Self::OutputStart {
+                command: params.find_map(|param| {
+                    if let Some(url_encoded) = param.strip_prefix("cmdline_url=") {
+                        Some(percent_decode(url_encoded))
+                    } else {
+                        param.strip_prefix("cmdline=").map(str::to_string)
+                    }
+                }),
+            },
+            "D" => Self::CommandFinished {
+                exit_code: params.next().and_then(|code| code.trim().parse().ok()),
+            },
+            _ => return None,
+        };
+        Some(mark)
+    }
+}
+
+/// The state of a command, as reported through shell integration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandStatus {
+    Running,
+    Finished { exit_code: Option<i32> },
+}
+
+/// A prompt, the command typed at it and that command's output, located in the terminal grid.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CommandBlock {
+    pub prompt_start: Option<AlacPoint>,
+    pub command_start: Option<AlacPoint>,
+    pub output_start: Option<AlacPoint>,
+    /// The first cell after the command's output, where the shell reported the command's exit.
+    pub end: Option<AlacPoint>,
+    pub command: Option<String>,
+    pub exit_code: Option<i32>,
+}
+
+impl CommandBlock {
+    /// The line this block starts on, which is where its prompt is printed.
+    pub fn start_line(&self) -> Option<Line> {
+        self.prompt_start
+            .or(self.command_start)
+            .or(self.output_start)
+            .map(|point| point.line)
+    }
+
+    pub fn status(&self) -> Option<CommandStatus> {
+        if self.end.is_some() {
+            Some(CommandStatus::Finished {
+                exit_code: self.exit_code,
+            })
+        } else if self.output_start.is_some() {
+            Some(CommandStatus::Running)
+        } else {
+            None
+        }
+    }
+
+    /// The cells printed by the command, if it finished and printed anything.
+    pub fn output_range<T>(&self, term: &Term<T>) -> Option<RangeInclusive<AlacPoint>> {
+        let start = self.output_start?;
+        let end = self.end?;
+        if end <= start {
+            return None;
+        }
+        Some(start..=end.sub(term, Boundary::Grid, 1))
+    }
+}
+
+/// The shell marks of a terminal, keyed by line.
+///
+/// Lines are counted from the first line the terminal printed, so that the keys stay the same
+/// while the grid scrolls. Alacritty doesn't tell when it drops lines from the top of a full
+/// scrollback, so its scrollback is [`SCROLLBACK_MARGIN`] lines larger than configured and
+/// every call to [`ShellMarks::observe`] trims it back, counting the lines it drops.
+#[derive(Debug)]
+pub struct ShellMarks {
+    lines: BTreeMap<u64, Vec<(Column, ShellMark)>>,
+    /// The number of lines dropped from the top of the scrollback so far.
+    dropped_lines: u64,
+    history_size: usize,
+    history_limit: usize,
+}
+
+impl ShellMarks {
+    pub(crate) fn new(history_limit: usize) -> Self {
+        Self {
+            lines: BTreeMap::new(),
+            dropped_lines: 0,
+            history_size: 0,
+            history_limit,
+        }
+    }
+
+    fn line_key(&self, line: Line) -> u64 {
+        (self.dropped_lines as i64 + self.history_size as i64 + line.0 as i64).max(0) as u64
+    }
+
+    fn grid_line(&self, key: u64) -> Line {
+        Line((key as i64 - self.dropped_lines as i64 - self.history_size as i64) as i32)
+    }
+
+    /// Accounts for the lines that left the scrollback since the last call and trims the
+    /// scrollback to its configured size. Must be called with the terminal locked before
+    /// the marks are read, as the output parsed in the meantime may have scrolled the grid.
+    pub(crate) fn observe<T>(&mut self, term: &mut Term<T>) {
+        // The alternate screen has no scrollback and leaves the primary grid untouched.
+        if term.mode().contains(TermMode::ALT_SCREEN) {
+            return;
+        }
+
+        let history_size = term.grid().history_size();
+        if history_size >= self.history_limit + SCROLLBACK_MARGIN {
+            // Alacritty may have dropped lines on its own, so the keys can't be trusted anymore.
+            self.lines.clear();
+        } else if history_size < self.history_size {
+            // The scrollback was cleared.
+            self.dropped_lines += (self.history_size - history_size) as u64;
+        }
+        self.history_size = history_size;
+
+        if history_size > self.history_limit {
+            let grid = term.grid_mut();
+            grid.update_history(self.history_limit);
+            grid.update_history(self.history_limit + SCROLLBACK_MARGIN);
+            self.dropped_lines += (history_size - self.history_limit) as u64;
+            self.history_size = self.history_limit;
+        }
+
+        self.lines = self.lines.split_off(&self.dropped_lines);
+    }
+
+    /// Records a mark where the shell emitted it, which is at the cursor.
+    pub(crate) fn record<T>(&mut self, term: &mut Term<T>, mark: ShellMark) {
+        self.observe(term);
+        if term.mode().contains(TermMode::ALT_SCREEN) {
+            return;
+        }
+
+        let cursor = &term.grid().cursor;
+        let (line, column) = if cursor.input_needs_wrap {
+            // The next character is printed at the start of the next line.
+            (self.line_key(cursor.point.line) + 1, Column(0))
+        } else {
+            (self.line_key(cursor.point.line), cursor.point.column)
+        };
+        self.lines.entry(line).or_default().push((column, mark));
+    }
+
+    /// Updates the marks after the terminal was resized. Alacritty moves lines between the
+    /// screen and the scrollback when the number of lines changes, which keeps the keys valid,
+    /// but it reflows the text when the number of columns changes, which doesn't.
+    pub(crate) fn resized<T>(&mut self, term: &Term<T>, columns_changed: bool) {
+        // The primary grid is resized too, behind the alternate screen.
+        if columns_changed || term.mode().contains(TermMode::ALT_SCREEN) {
+            self.lines.clear();
+        }
+        if !term.mode().contains(TermMode::ALT_SCREEN) {
+            self.history_size = term.grid().history_size();
+        }
+    }
+
+    /// Removes the marks of the given line and returns them.
+    pub(crate) fn take_line<T>(
+        &mut self,
+        term: &mut Term<T>,
+        line: Line,
+    ) -> Vec<(Column, ShellMark)> {
+        self.observe(term);
+        let key = self.line_key(line);
+        self.lines.remove(&key).unwrap_or_default()
+    }
+
+    /// Replaces all marks with the given marks of a single line.
+    pub(crate) fn replace_all<T>(
+        &mut self,
+        term: &mut Term<T>,
+        line: Line,
+        marks: Vec<(Column, ShellMark)>,
+    ) {
+        self.observe(term);
+        self.lines.clear();
+        if !marks.is_empty() {
+            self.lines.insert(self.line_key(line), marks);
+        }
+    }
+
+    /// Collects the command blocks whose marks are within the given lines of the grid.
+    /// Call [`ShellMarks::observe`] first.
+    pub fn command_blocks(&self, lines: RangeInclusive<Line>) -> Vec<CommandBlock> {
+        fn block_accepting(
+            blocks: &mut Vec<CommandBlock>,
+            accepts: impl Fn(&CommandBlock) -> bool,
+        ) -> &mut CommandBlock {
+            if !blocks.last().is_some_and(accepts) {
+                blocks.push(CommandBlock::default());
+            }
+            blocks.last_mut().unwrap()
+        }
+
+        let mut blocks = Vec::new();
+        let keys = self.line_key(*lines.start())..=self.line_key(*lines.end());
+        for (&key, marks) in self.lines.range(keys) {
+            let line = self.grid_line(key);
+            for (column, mark) in marks {
+                let point = AlacPoint::new(line, *column);
+                match mark.clone() {
+                    ShellMark::PromptStart => blocks.push(CommandBlock {
+                        prompt_start: Some(point),
+                        ..CommandBlock::default()
+                    }),
+                    ShellMark::CommandStart => {
+                        let block = block_accepting(&mut blocks, |block| {
+                            block.command_start.is_none()
+                                && block.output_start.is_none()
+                                && block.end.is_none()
+                        });
+                        block.command_start = Some(point);
+                    }
+                    ShellMark::OutputStart { command } => {
+                        let block = block_accepting(&mut blocks, |block| {
+                            block.output_start.is_none() && block.end.is_none()
+                        });
+                        block.output_start = Some(point);
+                        block.command = command;
+                    }
+                    ShellMark::CommandFinished { exit_code } => {
+                        let block = block_accepting(&mut blocks, |block| block.end.is_none());
+                        block.end = Some(point);
+                        block.exit_code = exit_code;
+                    }
+                }
+            }
+        }
+        blocks
+    }
+}
+
+const BASH_SCRIPT: &str = include_str!("shell_integration/zed.bash");
+const FISH_SCRIPT: &str = include_str!("shell_integration/zed.fish");
+const ZSH_SCRIPTS: [(&str, &str); 4] = [
+    (".zshenv", include_str!("shell_integration/zshenv.zsh")),
+    (".zprofile", include_str!("shell_integration/zprofile.zsh")),
+    (".zshrc", include_str!("shell_integration/zshrc.zsh")),
+    (".zlogin", include_str!("shell_integration/zlogin.zsh")),
+];
+
+/// Changes the shell invocation and its environment so that bash, zsh and fish load Zed's
+/// shell integration on startup. Other shells, and shells started with explicit arguments,
+/// are returned unchanged.
+pub(crate) fn inject(shell: Shell, env: &mut HashMap<String, String>) -> Result<Shell> {
+    let is_system_shell = matches!(shell, Shell::System);
+    let program = match &shell {
+        Shell::System => match env::var("SHELL") {
+            Ok(program) => program,
+            Err(_) => return Ok(shell),
+        },
+        Shell::Program(program) => program.clone(),
+        Shell::WithArguments { .. } => return Ok(shell),
+    };
+    let Some(shell_name) = Path::new(&program)
+        .file_name()
+        .and_then(|name| name.to_str())
+    else {
+        return Ok(shell);
+    };
+    // macOS terminals start login shells, which Alacritty does for the system shell.
+    let login = is_system_shell && cfg!(target_os = "macos");
+
+    let dir = paths::temp_dir().join("shell_integration");
+    let shell = match shell_name {
+        "bash" => {
+            let script = write_script(&dir, "zed.bash", BASH_SCRIPT)?;
+            if login {
+                env.insert("ZED_SHELL_INTEGRATION_LOGIN".into(), "1".into());
+            }
+            Shell::WithArguments {
+                program,
+                args: vec!["--init-file".into(), script.to_string_lossy().into_owned()],
+                title_override: None,
+            }
+        }
+        "zsh" => {
+            let zsh_dir = dir.join("zsh");
+            for (name, contents) in ZSH_SCRIPTS {
+                write_script(&zsh_dir, name, contents)?;
+            }
+            let user_zdotdir = env
+                .get("ZDOTDIR")
+                .cloned()
+                .or_else(|| env::var("ZDOTDIR").ok());
+            if let Some(user_zdotdir) = user_zdotdir {
+                env.insert("ZED_USER_ZDOTDIR".into(), user_zdotdir);
+            }
+            env.insert("ZDOTDIR".into(), zsh_dir.to_string_lossy().into_owned());
+            shell
+        }
+        "fish" => {
+            let script = write_script(&dir, "zed.fish", FISH_SCRIPT)?;
+            let mut args = Vec::new();
+            if login {
+                args.push("--login".into());
+            }
+            args.push("--init-command".into());
+            args.push(format!("source {}", shell_quote(&script.to_string_lossy())));
+            Shell::WithArguments {
+                program,
+                args,
+                title_override: None,
+            }
+        }
+        _ => shell,
+    };
+    Ok(shell)
+}
+
+fn write_script(dir: &Path, name: &str, contents: &str) -> Result<PathBuf> {
+    let path = dir.join(name);
+    if std::fs::read(&path).ok().as_deref() != Some(contents.as_bytes()) {
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(&path, contents)?;
+    }
+    Ok(path)
+}
+
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn percent_decode(text: &str) -> String {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut input = text.bytes();
+    while let Some(byte) = input.next() {
+        if byte == b'%' {
+            let hex = input.clone().take(2).collect::<Vec<_>>();
+            if let Some(decoded) = std::str::from_utf8(&hex)
+                .ok()
+                .filter(|hex| hex.len() == 2)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                bytes.push(decoded);
+                input.nth(1);
+                continue;
+            }
+        }
+        bytes.push(byte);
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use alacritty_terminal::{event::VoidListener, term::Config, vte::ansi::Handler};
+
+    use crate::TerminalSize;
+
+    use super::*;
+
+    fn write(term: &mut Term<VoidListener>, text: &str) {
+        for c in text.chars() {
+            match c {
+                '\r' => term.carriage_return(),
+                '\n' => term.linefeed(),
+                _ => term.input(c),
+            }
+        }
+    }
+
+    fn point(line: i32, column: usize) -> AlacPoint {
+        AlacPoint::new(Line(line), Column(column))
+    }
+
+    #[test]
+    fn test_mark_parsing() {
+        assert_eq!(ShellMark::parse("A"), Some(ShellMark::PromptStart));
+        assert_eq!(ShellMark::parse("A;k=s"), Some(ShellMark::PromptStart));
+        assert_eq!(
+            ShellMark::parse("C;cmdline_url=echo%20%22h%C3%A9%22%3B%20ls"),
+            Some(ShellMark::OutputStart {
+                command: Some("echo \"hé\"; ls".to_string())
+            })
+        );
+        assert_eq!(
+            ShellMark::parse("D;127"),
+            Some(ShellMark::CommandFinished {
+                exit_code: Some(127)
+            })
+        );
+        assert_eq!(
+            ShellMark::parse("D"),
+            Some(ShellMark::CommandFinished { exit_code: None })
+        );
+        assert_eq!(ShellMark::parse("Z"), None);
+    }
+
+    #[test]
+    fn test_command_blocks() {
+        let config = Config::default();
+        let mut marks = ShellMarks::new(config.scrolling_history);
+        let mut term = Term::new(config, &TerminalSize::default(), VoidListener);
+
+        marks.record(&mut term, ShellMark::PromptStart);
+        write(&mut term, "$ ");
+        marks.record(&mut term, ShellMark::CommandStart);
+        write(&mut term, "ls\r\n");
+        marks.record(
+            &mut term,
+            ShellMark::OutputStart {
+                command: Some("ls -a".to_string()),
+            },
+        );
+        write(&mut term, "a\r\nb\r\n");
+        marks.record(&mut term, ShellMark::CommandFinished { exit_code: Some(2) });
+        marks.record(&mut term, ShellMark::PromptStart);
+        write(&mut term, "$ ");
+        marks.record(&mut term, ShellMark::CommandStart);
+
+        marks.observe(&mut term);
+        let blocks = marks.command_blocks(term.topmost_line()..=term.bottommost_line());
+        assert_eq!(
+            blocks,
+            vec![
+                CommandBlock {
+                    prompt_start: Some(point(0, 0)),
+                    command_start: Some(point(0, 2)),
+                    output_start: Some(point(1, 0)),
+                    end: Some(point(3, 0)),
+                    command: Some("ls -a".to_string()),
+                    exit_code: Some(2),
+                },
+                CommandBlock {
+                    prompt_start: Some(point(3, 0)),
+                    command_start: Some(point(3, 2)),
+                    ..CommandBlock::default()
+                },
+            ]
+        );
+        assert_eq!(
+            blocks[0].status(),
+            Some(CommandStatus::Finished { exit_code: Some(2) })
+        );
+        assert_eq!(blocks[1].status(), None);
+        assert_eq!(
+            blocks[0].output_range(&term),
+            Some(point(1, 0)..=point(2, term.columns() - 1))
+        );
+        // Marks are not part of the grid's text.
+        assert_eq!(term.bounds_to_string(point(0, 0), point(0, 3)), "$ ls");
+    }
+
+    #[test]
+    fn test_marks_follow_scrolling() {
+        let config = Config {
+            scrolling_history: 2 + SCROLLBACK_MARGIN,
+            ..Config::default()
+        };
+        let mut marks = ShellMarks::new(2);
+        let mut term = Term::new(config, &TerminalSize::default(), VoidListener);
+        let prompt_lines = |marks: &mut ShellMarks, term: &mut Term<VoidListener>| {
+            marks.observe(term);
+            marks
+                .command_blocks(term.topmost_line()..=term.bottommost_line())
+                .iter()
+                .filter_map(CommandBlock::start_line)
+                .collect::<Vec<_>>()
+        };
+
+        // Six lines fit on the screen, so the prompt moves into the scrollback.
+        marks.record(&mut term, ShellMark::PromptStart);
+        write(&mut term, "$ x\r\n1\r\n2\r\n3\r\n4\r\n");
+        marks.record(&mut term, ShellMark::PromptStart);
+        write(&mut term, "$ y\r\n");
+        assert_eq!(prompt_lines(&mut marks, &mut term), vec![Line(-1), Line(4)]);
+
+        // The scrollback is trimmed to its configured size, dropping the first prompt.
+        write(&mut term, "5\r\n6\r\n");
+        assert_eq!(prompt_lines(&mut marks, &mut term), vec![Line(2)]);
+        assert_eq!(term.grid().history_size(), 2);
+
+        // Clearing the scrollback keeps the marks on the screen where they are.
+        term.grid_mut().clear_history();
+        assert_eq!(prompt_lines(&mut marks, &mut term), vec![Line(2)]);
+        write(&mut term, "7\r\n8\r\n9\r\n");
+        assert_eq!(prompt_lines(&mut marks, &mut term), vec![Line(-1)]);
+    }
+}
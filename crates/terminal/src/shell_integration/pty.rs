@@ -0,0 +1,590 @@
+//! Extracts the shell marks from the PTY output, see [`super`].
+
+use std::{
+    io::{self, Read, Write},
+    iter, mem,
+    ops::Range,
+    os::unix::net::UnixStream,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use alacritty_terminal::{
+    event::{OnResize, WindowSize},
+    sync::FairMutex,
+    tty::{self, ChildEvent, EventedPty, EventedReadWrite},
+    Term,
+};
+use collections::VecDeque;
+use parking_lot::Mutex;
+use polling::{Event as PollingEvent, PollMode, Poller};
+
+use super::{ShellMark, ShellMarks};
+use crate::ZedListener;
+
+const OSC_133_PREFIX: &[u8] = b"133;";
+/// Marks with a payload longer than this (usually because of a very long command line) are
+/// truncated down to their kind and exit status.
+const MAX_MARK_PAYLOAD_LEN: usize = 1024;
+const MAX_CSI_PARAMS_LEN: usize = 16;
+/// The most bytes [`MarkFilter::advance`] writes for a single byte of input.
+const MAX_OUTPUT_PER_BYTE: usize = 16;
+const CURSOR_POSITION_REQUEST: &[u8] = b"\x1b[6n";
+const INPUT_BUFFER_SIZE: usize = 0x1_0000;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum FilterState {
+    #[default]
+    Ground,
+    /// An escape character was received and has not been written yet.
+    Escape,
+    EscapeIntermediate,
+    Csi,
+    /// Collecting an OSC sequence until it is known whether it is a shell mark.
+    Osc,
+    OscEscape,
+    /// Passing through a string sequence (OSC, DCS, SOS, PM or APC) we are not interested in.
+    String {
+        bell_terminates: bool,
+    },
+    StringEscape,
+}
+
+/// Replaces the `OSC 133` sequences in a stream of PTY output with cursor position requests,
+/// and counts the cursor position requests made by the application.
+#[derive(Default)]
+struct MarkFilter {
+    state: FilterState,
+    osc: Vec<u8>,
+    osc_truncated: bool,
+    csi_params: Vec<u8>,
+    /// A byte that terminated a mark and still has to be processed.
+    deferred_byte: Option<u8>,
+    /// The mark that was replaced by the last request written, which ends the output.
+    completed_mark: Option<ShellMark>,
+    /// The number of cursor position requests of the application written since this was reset.
+    position_requests: usize,
+}
+
+impl MarkFilter {
+    /// Filters `input` into `output` until a mark was replaced or `output` would grow past
+    /// `output_capacity`, and returns the number of input bytes consumed.
+    fn process(&mut self, input: &[u8], output: &mut Vec<u8>, output_capacity: usize) -> usize {
+        if output.len() + MAX_OUTPUT_PER_BYTE > output_capacity {
+            return 0;
+        }
+        if let Some(byte) = self.deferred_byte.take() {
+            self.advance(byte, output);
+        }
+
+        for (ix, &byte) in input.iter().enumerate() {
+            if output.len() + MAX_OUTPUT_PER_BYTE > output_capacity {
+                return ix;
+            }
+            self.advance(byte, output);
+            if self.completed_mark.is_some() {
+                return ix + 1;
+            }
+        }
+        input.len()
+    }
+
+    fn advance(&mut self, byte: u8, output: &mut Vec<u8>) {
+        match self.state {
+            FilterState::Ground => match byte {
+                0x1b => self.state = FilterState::Escape,
+                _ => output.push(byte),
+            },
+            FilterState::Escape => match byte {
+                b']' => {
+                    self.osc.clear();
+                    self.osc_truncated = false;
+                    self.state = FilterState::Osc;
+                }
+                0x1b => output.push(0x1b),
+                _ => {
+                    output.extend_from_slice(&[0x1b, byte]);
+                    self.csi_params.clear();
+                    self.state = match byte {
+                        b'[' => FilterState::Csi,
+                        b'P' | b'X' | b'^' | b'_' => FilterState::String {
+                            bell_terminates: false,
+                        },
+                        // Intermediate bytes and control characters do not end the sequence.
+                        0x00..=0x2f => FilterState::EscapeIntermediate,
+                        _ => FilterState::Ground,
+                    };
+                }
+            },
+            FilterState::EscapeIntermediate => {
+                if byte == 0x1b {
+                    self.state = FilterState::Escape;
+                } else {
+                    output.push(byte);
+                    if (0x30..=0x7e).contains(&byte) {
+                        self.state = FilterState::Ground;
+                    }
+                }
+            }
+            FilterState::Csi => {
+                if byte == 0x1b {
+                    self.state = FilterState::Escape;
+                    return;
+                }
+
+                output.push(byte);
+                match byte {
+                    0x40..=0x7e => {
+                        if byte == b'n' && self.requests_cursor_position() {
+                            self.position_requests += 1;
+                        }
+                        self.state = FilterState::Ground;
+                    }
+                    0x18 | 0x1a => self.state = FilterState::Ground,
+                    0x20..=0x3f if self.csi_params.len() < MAX_CSI_PARAMS_LEN => {
+                        self.csi_params.push(byte)
+                    }
+                    _ => {}
+                }
+            }
+            FilterState::Osc => match byte {
+                0x07 => {
+                    self.finish_osc(&[0x07], output);
+                    self.state = FilterState::Ground;
+                }
+                0x1b => self.state = FilterState::OscEscape,
+                _ => {
+                    let len = self.osc.len();
+                    if len < OSC_133_PREFIX.len() && byte != OSC_133_PREFIX[len] {
+                        output.extend_from_slice(b"\x1b]");
+                        output.extend_from_slice(&self.osc);
+                        output.push(byte);
+                        self.osc.clear();
+                        self.state = FilterState::String {
+                            bell_terminates: true,
+                        };
+                    } else if self.osc.len() < OSC_133_PREFIX.len() + MAX_MARK_PAYLOAD_LEN {
+                        self.osc.push(byte);
+                    } else {
+                        self.osc_truncated = true;
+                    }
+                }
+            },
+            FilterState::OscEscape => {
+                self.finish_osc(b"\x1b\\", output);
+                self.state = FilterState::Ground;
+                if byte != b'\\' {
+                    // A new escape sequence implicitly terminated the OSC. It must not be
+                    // parsed before the mark is recorded.
+                    self.state = FilterState::Escape;
+                    if self.completed_mark.is_some() {
+                        self.deferred_byte = Some(byte);
+                    } else {
+                        self.advance(byte, output);
+                    }
+                }
+            }
+            FilterState::String { bell_terminates } => match byte {
+                0x1b => self.state = FilterState::StringEscape,
+                0x07 if bell_terminates => {
+                    output.push(byte);
+                    self.state = FilterState::Ground;
+                }
+                _ => output.push(byte),
+            },
+            FilterState::StringEscape => {
+                self.state = FilterState::Escape;
+                self.advance(byte, output);
+            }
+        }
+    }
+
+    /// Whether the CSI sequence ending in `n` is a device status report that Alacritty
+    /// answers with the cursor position.
+    fn requests_cursor_position(&self) -> bool {
+        self.csi_params
+            .iter()
+            .all(|byte| byte.is_ascii_digit() || *byte == b';' || *byte == b':')
+            && self
+                .csi_params
+                .split(|byte| *byte == b';' || *byte == b':')
+                .next()
+                .and_then(|param| std::str::from_utf8(param).ok()?.parse::<u16>().ok())
+                == Some(6)
+    }
+
+    fn finish_osc(&mut self, terminator: &[u8], output: &mut Vec<u8>) {
+        let Some(payload) = self.osc.strip_prefix(OSC_133_PREFIX) else {
+            output.extend_from_slice(b"\x1b]");
+            output.extend_from_slice(&self.osc);
+            output.extend_from_slice(terminator);
+            return;
+        };
+
+        let payload = if self.osc_truncated {
+            let kind_len = payload
+                .iter()
+                .position(|&byte| byte == b';')
+                .unwrap_or(payload.len());
+            // The exit status is the only parameter of `D` marks we care about, and is short.
+            if payload.starts_with(b"D") {
+                &payload[..payload.len().min(16)]
+            } else {
+                &payload[..kind_len]
+            }
+        } else {
+            payload
+        };
+
+        if let Some(mark) = ShellMark::parse(&String::from_utf8_lossy(payload)) {
+            output.extend_from_slice(CURSOR_POSITION_REQUEST);
+            self.completed_mark = Some(mark);
+        }
+    }
+}
+
+/// Tells the replies to the cursor position requests that replaced marks apart from the ones
+/// the application asked for. Shared between the PTY and the terminal's listener.
+pub(crate) struct MarkProbes {
+    state: Mutex<ProbeState>,
+    wake_tx: UnixStream,
+}
+
+#[derive(Default)]
+struct ProbeState {
+    /// Who sent the cursor position requests Alacritty has yet to answer, oldest first.
+    pending: VecDeque<Probe>,
+    /// Whether Alacritty answered the request that replaced the last mark.
+    mark_parsed: bool,
+}
+
+enum Probe {
+    Application,
+    Mark,
+}
+
+impl MarkProbes {
+    /// Returns the probes, and the end of the pipe that becomes readable whenever the PTY
+    /// has to be read again even though the shell did not write anything.
+    pub(crate) fn new() -> io::Result<(Arc<Self>, UnixStream)> {
+        let (wake_tx, wake_rx) = UnixStream::pair()?;
+        wake_tx.set_nonblocking(true)?;
+        wake_rx.set_nonblocking(true)?;
+        let probes = Self {
+            state: Mutex::new(ProbeState::default()),
+            wake_tx,
+        };
+        Ok((Arc::new(probes), wake_rx))
+    }
+
+    /// Returns whether the given write to the PTY answers a request that replaced a mark,
+    /// in which case it must not reach the shell.
+    pub(crate) fn intercept_reply(&self, text: &str) -> bool {
+        let is_position_report = text
+            .strip_prefix("\x1b[")
+            .and_then(|params| params.strip_suffix('R'))
+            .is_some_and(|params| {
+                params
+                    .bytes()
+                    .all(|byte| byte.is_ascii_digit() || byte == b';')
+            });
+        if !is_position_report {
+            return false;
+        }
+
+        let mut state = self.state.lock();
+        match state.pending.pop_front() {
+            Some(Probe::Mark) => {
+                state.mark_parsed = true;
+                drop(state);
+                self.wake();
+                true
+            }
+            Some(Probe::Application) | None => false,
+        }
+    }
+
+    fn wake(&self) {
+        // When the pipe is full, the event loop is going to wake up anyway.
+        (&self.wake_tx).write_all(&[0]).ok();
+    }
+}
+
+/// Wraps the PTY so that shell marks are extracted from its output before Alacritty parses it.
+///
+/// Alacritty stops reading after a while and waits for the PTY to become readable again, so
+/// the output handed over at once is never larger than what was read, and the wake-up pipe
+/// is written to whenever read output remains buffered.
+pub(crate) struct ShellIntegrationPty {
+    pty: tty::Pty,
+    term: Arc<FairMutex<Term<ZedListener>>>,
+    marks: Arc<Mutex<ShellMarks>>,
+    probes: Arc<MarkProbes>,
+    wake_rx: UnixStream,
+    filter: MarkFilter,
+    input: Box<[u8]>,
+    unfiltered: Range<usize>,
+    output: Vec<u8>,
+    /// The mark whose request was handed to Alacritty, and which is yet to be recorded.
+    pending_mark: Option<ShellMark>,
+    marks_detected: Arc<AtomicBool>,
+}
+
+impl ShellIntegrationPty {
+    pub(crate) fn new(
+        pty: tty::Pty,
+        term: Arc<FairMutex<Term<ZedListener>>>,
+        marks: Arc<Mutex<ShellMarks>>,
+        probes: Arc<MarkProbes>,
+        wake_rx: UnixStream,
+        marks_detected: Arc<AtomicBool>,
+    ) -> Self {
+        Self {
+            pty,
+            term,
+            marks,
+            probes,
+            wake_rx,
+            filter: MarkFilter::default(),
+            input: vec![0; INPUT_BUFFER_SIZE].into_boxed_slice(),
+            unfiltered: 0..0,
+            output: Vec::new(),
+            pending_mark: None,
+            marks_detected,
+        }
+    }
+
+    /// Records the pending mark at the cursor, once Alacritty parsed the request that replaced
+    /// it. Returns false while that's not possible yet.
+    fn record_pending_mark(&mut self) -> bool {
+        if !self.probes.state.lock().mark_parsed {
+            // The listener wakes us up when the reply comes in.
+            return false;
+        }
+        // The event loop keeps the terminal locked until it goes back to polling.
+        let Some(mut term) = self.term.try_lock_unfair() else {
+            self.probes.wake();
+            return false;
+        };
+        if let Some(mark) = self.pending_mark.take() {
+            self.marks.lock().record(&mut *term, mark);
+            self.marks_detected.store(true, Ordering::Relaxed);
+        }
+        true
+    }
+}
+
+impl Read for ShellIntegrationPty {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while (&self.wake_rx).read(&mut [0; 64]).is_ok_and(|len| len > 0) {}
+        if self.pending_mark.is_some() && !self.record_pending_mark() {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+
+        loop {
+            if self.unfiltered.is_empty() {
+                let len = self.pty.reader().read(&mut self.input)?;
+                if len == 0 {
+                    return Ok(0);
+                }
+                self.unfiltered = 0..len;
+            }
+
+            self.output.clear();
+            let consumed = self.filter.process(
+                &self.input[self.unfiltered.clone()],
+                &mut self.output,
+                buf.len(),
+            );
+            self.unfiltered.start += consumed;
+
+            // The requests must be known before Alacritty parses them and replies.
+            let mut probes = self.probes.state.lock();
+            let position_requests = mem::take(&mut self.filter.position_requests);
+            probes
+                .pending
+                .extend(iter::repeat_with(|| Probe::Application).take(position_requests));
+            if let Some(mark) = self.filter.completed_mark.take() {
+                probes.pending.push_back(Probe::Mark);
+                probes.mark_parsed = false;
+                self.pending_mark = Some(mark);
+            }
+            drop(probes);
+
+            if !self.unfiltered.is_empty() {
+                self.probes.wake();
+            }
+            if !self.output.is_empty() {
+                buf[..self.output.len()].copy_from_slice(&self.output);
+                return Ok(self.output.len());
+            }
+            if consumed == 0 {
+                // Alacritty parses what it has and comes back with more room.
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+        }
+    }
+}
+
+impl EventedReadWrite for ShellIntegrationPty {
+    type Reader = Self;
+    type Writer = <tty::Pty as EventedReadWrite>::Writer;
+
+    unsafe fn register(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: PollingEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.register(poll, interest, mode)?;
+        poll.add_with_mode(&self.wake_rx, PollingEvent::readable(interest.key), mode)
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: PollingEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.reregister(poll, interest, mode)?;
+        poll.modify_with_mode(&self.wake_rx, PollingEvent::readable(interest.key), mode)
+    }
+
+    fn deregister(&mut self, poll: &Arc<Poller>) -> io::Result<()> {
+        self.pty.deregister(poll)?;
+        poll.delete(&self.wake_rx)
+    }
+
+    fn reader(&mut self) -> &mut Self::Reader {
+        self
+    }
+
+    fn writer(&mut self) -> &mut Self::Writer {
+        self.pty.writer()
+    }
+}
+
+impl EventedPty for ShellIntegrationPty {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.pty.next_child_event()
+    }
+}
+
+impl OnResize for ShellIntegrationPty {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.pty.on_resize(window_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Filters the chunks, returning the output with a `|` where the filter stopped
+    /// because of a mark, and the marks.
+    fn filter(chunks: &[&[u8]]) -> (String, Vec<ShellMark>) {
+        let mut filter = MarkFilter::default();
+        let mut output = Vec::new();
+        let mut marks = Vec::new();
+        for chunk in chunks {
+            let mut chunk = *chunk;
+            loop {
+                let consumed = filter.process(chunk, &mut output, usize::MAX);
+                chunk = &chunk[consumed..];
+                match filter.completed_mark.take() {
+                    Some(mark) => {
+                        output.push(b'|');
+                        marks.push(mark);
+                    }
+                    None => break,
+                }
+            }
+        }
+        (String::from_utf8(output).unwrap(), marks)
+    }
+
+    #[test]
+    fn test_mark_filter() {
+        // Marks are replaced by cursor position requests, and end the output.
+        assert_eq!(
+            filter(&[b"\x1b]133;A\x07\x1b[32m$ \x1b]133;B\x1b\\ls"]),
+            (
+                "\x1b[6n|\x1b[32m$ \x1b[6n|ls".to_string(),
+                vec![ShellMark::PromptStart, ShellMark::CommandStart]
+            )
+        );
+
+        // Sequences split across reads.
+        assert_eq!(
+            filter(&[b"\x1b]13", b"3;D;1\x07\r\n\xc3", b"\xa9"]),
+            (
+                "\x1b[6n|\r\n\u{e9}".to_string(),
+                vec![ShellMark::CommandFinished { exit_code: Some(1) }]
+            )
+        );
+
+        // Other OSC, DCS and CSI sequences pass through untouched.
+        assert_eq!(
+            filter(&[b"\x1b]0;title\x07\x1bP1$r\x1b\\\x1b[1;2Hx"]),
+            (
+                "\x1b]0;title\x07\x1bP1$r\x1b\\\x1b[1;2Hx".to_string(),
+                vec![]
+            )
+        );
+        assert_eq!(
+            filter(&[b"\x1b]1337;File=x\x1b\\y"]),
+            ("\x1b]1337;File=x\x1b\\y".to_string(), vec![])
+        );
+
+        // An escape sequence terminates the OSC implicitly, and is only written afterwards.
+        assert_eq!(
+            filter(&[b"\x1b]133;C\x1b[0mok"]),
+            (
+                "\x1b[6n|\x1b[0mok".to_string(),
+                vec![ShellMark::OutputStart { command: None }]
+            )
+        );
+    }
+
+    #[test]
+    fn test_mark_filter_output_capacity() {
+        let mut filter = MarkFilter::default();
+        let mut output = Vec::new();
+        let input = [b'x'; 64];
+        let consumed = filter.process(&input, &mut output, 2 * MAX_OUTPUT_PER_BYTE);
+        assert_eq!(consumed, MAX_OUTPUT_PER_BYTE + 1);
+        assert_eq!(output.len(), consumed);
+    }
+
+    #[test]
+    fn test_cursor_position_requests() {
+        let mut filter = MarkFilter::default();
+        let mut output = Vec::new();
+        filter.process(
+            b"\x1b[6n\x1b[?6n\x1b[5n\x1b[6;1n\x1b]133;A\x07",
+            &mut output,
+            usize::MAX,
+        );
+        assert_eq!(filter.position_requests, 2);
+        assert_eq!(filter.completed_mark, Some(ShellMark::PromptStart));
+    }
+
+    #[test]
+    fn test_intercept_reply() {
+        let (probes, _wake_rx) = MarkProbes::new().unwrap();
+        probes
+            .state
+            .lock()
+            .pending
+            .extend([Probe::Application, Probe::Mark]);
+
+        assert!(!probes.intercept_reply("\x1b[0n"));
+        assert!(!probes.intercept_reply("\x1b[3;1R"));
+        assert!(!probes.state.lock().mark_parsed);
+        assert!(probes.intercept_reply("\x1b[4;3R"));
+        assert!(probes.state.lock().mark_parsed);
+        assert!(!probes.intercept_reply("\x1b[4;3R"));
+    }
+}
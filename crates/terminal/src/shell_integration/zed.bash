@@ -0,0 +1,73 @@
+# Zed shell integration for bash.
+#
+# Started with `bash --init-file`, so the user's own startup files are sourced from here.
+
+if [[ -n "$ZED_SHELL_INTEGRATION_LOGIN" ]]; then
+    unset ZED_SHELL_INTEGRATION_LOGIN
+    [[ -r /etc/profile ]] && . /etc/profile
+    for __zed_profile in ~/.bash_profile ~/.bash_login ~/.profile; do
+        if [[ -r "$__zed_profile" ]]; then
+            . "$__zed_profile"
+            break
+        fi
+    done
+    unset __zed_profile
+else
+    [[ -r ~/.bashrc ]] && . ~/.bashrc
+fi
+
+if [[ -z "$__zed_shell_integration" && $- == *i* ]]; then
+    __zed_shell_integration=1
+    __zed_at_prompt=0
+    __zed_in_command=0
+
+    __zed_urlencode() {
+        local LC_ALL=C text="$1" encoded="" c i
+        for (( i = 0; i < ${#text}; i++ )); do
+            c="${text:i:1}"
+            case "$c" in
+                [a-zA-Z0-9._~/-]) encoded+="$c" ;;
+                *) printf -v c '%%%02X' "'$c"; encoded+="$c" ;;
+            esac
+        done
+        printf '%s' "$encoded"
+    }
+
+    __zed_precmd() {
+        local status=$?
+        if [[ "$__zed_in_command" == 1 ]]; then
+            printf '\e]133;D;%s\a' "$status"
+            __zed_in_command=0
+        fi
+        printf '\e]133;A\a'
+        return $status
+    }
+
+    __zed_prompt_ready() {
+        __zed_at_prompt=1
+        if [[ "$PS1" != *'133;B'* ]]; then
+            PS1="$PS1"'\[\e]133;B\a\]'
+        fi
+    }
+
+    __zed_preexec() {
+        [[ "$__zed_at_prompt" == 1 ]] || return
+        [[ -n "$COMP_LINE" ]] && return
+        __zed_at_prompt=0
+        # The first command run after an empty command line is the prompt command itself.
+        [[ "$BASH_COMMAND" == __zed_precmd* ]] && return
+        __zed_in_command=1
+        local command
+        command="$(HISTTIMEFORMAT= builtin history 1)"
+        if [[ "$command" =~ ^[[:space:]]*[0-9]+\*?[[:space:]]+(.*)$ ]]; then
+            command="${BASH_REMATCH[1]}"
+        fi
+        printf '\e]133;C;cmdline_url=%s\a' "$(__zed_urlencode "$command")"
+    }
+
+    PROMPT_COMMAND="__zed_precmd${PROMPT_COMMAND:+; $PROMPT_COMMAND}; __zed_prompt_ready"
+    # Do not override a DEBUG trap set up by the user, e.g. through bash-preexec.
+    if [[ -z "$(trap -p DEBUG)" ]]; then
+        trap '__zed_preexec' DEBUG
+    fi
+fi
@@ -0,0 +1,26 @@
+# Zed shell integration for fish.
+#
+# Sourced through `fish --init-command`, after the user's configuration.
+
+if not set -q __zed_shell_integration; and status is-interactive
+    set -g __zed_shell_integration 1
+
+    function __zed_prompt_start --on-event fish_prompt
+        printf '\e]133;A\a'
+    end
+
+    function __zed_preexec --on-event fish_preexec
+        printf '\e]133;C;cmdline_url=%s\a' (string escape --style=url -- $argv[1])
+    end
+
+    function __zed_postexec --on-event fish_postexec
+        set -l last_status $status
+        printf '\e]133;D;%s\a' $last_status
+    end
+
+    functions -q fish_prompt; and functions -c fish_prompt __zed_user_fish_prompt
+    function fish_prompt
+        functions -q __zed_user_fish_prompt; and __zed_user_fish_prompt
+        printf '\e]133;B\a'
+    end
+end
@@ -3,11 +3,12 @@ pub mod mappings;
 pub use alacritty_terminal;
 
 mod pty_info;
+pub mod shell_integration;
 pub mod terminal_settings;
 
 use alacritty_terminal::{
-    event::{Event as AlacTermEvent, EventListener, Notify, WindowSize},
-    event_loop::{EventLoop, Msg, Notifier},
+    event::{Event as AlacTermEvent, EventListener, Notify, OnResize, WindowSize},
+    event_loop::{EventLoop, EventLoopSender, Msg, Notifier},
     grid::{Dimensions, Scroll as AlacScroll},
     index::{Boundary, Column, Direction as AlacDirection, Line, Point as AlacPoint},
     selection::{Selection, SelectionRange, SelectionType},
@@ -17,10 +18,10 @@ use alacritty_terminal::{
         search::{Match, RegexIter, RegexSearch},
         Config, RenderableCursor, TermMode,
     },
-    tty::{self},
+    tty::{self, EventedPty},
     vi_mode::{ViModeCursor, ViMotion},
     vte::ansi::{
-        ClearMode, CursorStyle as AlacCursorStyle, Handler, NamedPrivateMode, PrivateMode,
+        Attr, ClearMode, CursorStyle as AlacCursorStyle, Handler, NamedPrivateMode, PrivateMode,
     },
     Term,
 };
@@ -38,21 +39,28 @@ use mappings::mouse::{
 
 use collections::{HashMap, VecDeque};
 use futures::StreamExt;
+use parking_lot::Mutex;
 use pty_info::PtyProcessInfo;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
+use shell_integration::{CommandBlock, CommandStatus, ShellMarks};
+#[cfg(unix)]
+use shell_integration::{MarkProbes, ShellIntegrationPty};
 use smol::channel::{Receiver, Sender};
 use task::{HideStrategy, Shell, TaskId};
 use terminal_settings::{AlternateScroll, CursorShape, TerminalSettings};
 use theme::{ActiveTheme, Theme};
-use util::{paths::home_dir, truncate_and_trailoff};
+use util::{paths::home_dir, truncate_and_trailoff, ResultExt};
 
 use std::{
     cmp::{self, min},
     fmt::Display,
     ops::{Deref, Index, RangeInclusive},
     path::PathBuf,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     time::Duration,
 };
 use thiserror::Error;
@@ -79,6 +87,11 @@ actions!(
         ScrollPageDown,
         ScrollToTop,
         ScrollToBottom,
+        ScrollToPreviousPrompt,
+        ScrollToNextPrompt,
+        SelectLastCommandOutput,
+        CopyLastCommandOutput,
+        RerunCommand,
         ToggleViMode,
     ]
 );
@@ -148,11 +161,32 @@ enum InternalEvent {
 
 ///A translation struct for Alacritty to communicate with us from their event loop
 #[derive(Clone)]
-pub struct ZedListener(pub UnboundedSender<AlacTermEvent>);
+pub struct ZedListener {
+    events_tx: UnboundedSender<AlacTermEvent>,
+    /// Set with shell integration, whose cursor position requests must not be answered.
+    #[cfg(unix)]
+    mark_probes: Option<Arc<MarkProbes>>,
+}
+
+impl ZedListener {
+    pub fn new(events_tx: UnboundedSender<AlacTermEvent>) -> Self {
+        Self {
+            events_tx,
+            #[cfg(unix)]
+            mark_probes: None,
+        }
+    }
+}
 
 impl EventListener for ZedListener {
     fn send_event(&self, event: AlacTermEvent) {
-        self.0.unbounded_send(event).ok();
+        #[cfg(unix)]
+        if let (AlacTermEvent::PtyWrite(text), Some(mark_probes)) = (&event, &self.mark_probes) {
+            if mark_probes.intercept_reply(text) {
+                return;
+            }
+        }
+        self.events_tx.unbounded_send(event).ok();
     }
 }
 
@@ -315,6 +349,18 @@ const URL_REGEX: &str = r#"(ipfs:|ipns:|magnet:|mailto:|gemini://|gopher://|http
 const WORD_REGEX: &str =
     r#"[\$\+\w.\[\]:/\\@\-~()]+(?:\((?:\d+|\d+,\d+)\))|[\$\+\w.\[\]:/\\@\-~()]+"#;
 
+fn spawn_event_loop<T: EventedPty + OnResize + Send + 'static>(
+    term: Arc<FairMutex<Term<ZedListener>>>,
+    listener: ZedListener,
+    pty: T,
+    hold: bool,
+) -> Result<EventLoopSender> {
+    let event_loop = EventLoop::new(term, listener, pty, hold, false)?;
+    let pty_tx = event_loop.channel();
+    let _io_thread = event_loop.spawn(); // DANGER
+    Ok(pty_tx)
+}
+
 pub struct TerminalBuilder {
     terminal: Terminal,
     events_rx: UnboundedReceiver<AlacTermEvent>,
@@ -330,7 +376,9 @@ impl TerminalBuilder {
         cursor_shape: CursorShape,
         alternate_scroll: AlternateScroll,
         max_scroll_history_lines: Option<usize>,
+        shell_integration: bool,
         is_ssh_terminal: bool,
+        restored_scrollback: Option<&str>,
         window: AnyWindowHandle,
         completion_tx: Sender<()>,
         cx: &AppContext,
@@ -353,6 +401,18 @@ impl TerminalBuilder {
 
         let mut terminal_title_override = None;
 
+        // Shell marks are extracted from the output on Unix only.
+        let shell_integration = shell_integration && cfg!(unix);
+        // Remote shells and tasks may still report marks, but we only set up the integration
+        // for interactive local shells.
+        let shell = if shell_integration && task.is_none() && !is_ssh_terminal {
+            shell_integration::inject(shell.clone(), &mut env)
+                .log_err()
+                .unwrap_or(shell)
+        } else {
+            shell
+        };
+
         let pty_options = {
             let alac_shell = match shell.clone() {
                 Shell::System => None,
@@ -394,7 +454,11 @@ impl TerminalBuilder {
                 .min(MAX_SCROLL_HISTORY_LINES)
         };
         let config = Config {
-            scrolling_history,
+            scrolling_history: if shell_integration {
+                scrolling_history + shell_integration::SCROLLBACK_MARGIN
+            } else {
+                scrolling_history
+            },
             default_cursor_style,
             ..Config::default()
         };
@@ -402,18 +466,29 @@ impl TerminalBuilder {
         //Spawn a task so the Alacritty EventLoop can communicate with us in a view context
         //TODO: Remove with a bounded sender which can be dispatched on &self
         let (events_tx, events_rx) = unbounded();
+        #[allow(unused_mut)]
+        let mut listener = ZedListener::new(events_tx.clone());
+        #[cfg(unix)]
+        let mark_probes = if shell_integration {
+            let (mark_probes, wake_rx) = MarkProbes::new()?;
+            listener.mark_probes = Some(mark_probes.clone());
+            Some((mark_probes, wake_rx))
+        } else {
+            None
+        };
         //Set up the terminal...
-        let mut term = Term::new(
-            config.clone(),
-            &TerminalSize::default(),
-            ZedListener(events_tx.clone()),
-        );
+        let mut term = Term::new(config.clone(), &TerminalSize::default(), listener.clone());
 
         //Alacritty defaults to alternate scrolling being on, so we just need to turn it off.
         if let AlternateScroll::Off = alternate_scroll {
             term.unset_private_mode(PrivateMode::Named(NamedPrivateMode::AlternateScroll));
         }
 
+        // Written before the shell starts, so that its output follows the restored lines.
+        if let Some(scrollback) = restored_scrollback {
+            write_scrollback(&mut term, scrollback);
+        }
+
         let term = Arc::new(FairMutex::new(term));
 
         //Setup the pty...
@@ -433,19 +508,28 @@ impl TerminalBuilder {
         };
 
         let pty_info = PtyProcessInfo::new(&pty);
-
-        //And connect them together
-        let event_loop = EventLoop::new(
-            term.clone(),
-            ZedListener(events_tx.clone()),
-            pty,
-            pty_options.hold,
-            false,
-        )?;
-
-        //Kick things off
-        let pty_tx = event_loop.channel();
-        let _io_thread = event_loop.spawn(); // DANGER
+        let shell_marks_detected = Arc::new(AtomicBool::new(false));
+        let shell_marks =
+            shell_integration.then(|| Arc::new(Mutex::new(ShellMarks::new(scrolling_history))));
+
+        //And connect them together, then kick things off
+        #[cfg(unix)]
+        let pty_tx = match (mark_probes, &shell_marks) {
+            (Some((mark_probes, wake_rx)), Some(shell_marks)) => {
+                let pty = ShellIntegrationPty::new(
+                    pty,
+                    term.clone(),
+                    shell_marks.clone(),
+                    mark_probes,
+                    wake_rx,
+                    shell_marks_detected.clone(),
+                );
+                spawn_event_loop(term.clone(), listener, pty, pty_options.hold)?
+            }
+            _ => spawn_event_loop(term.clone(), listener, pty, pty_options.hold)?,
+        };
+        #[cfg(not(unix))]
+        let pty_tx = spawn_event_loop(term.clone(), listener, pty, pty_options.hold)?;
 
         let terminal = Terminal {
             task,
@@ -471,6 +555,8 @@ impl TerminalBuilder {
             word_regex: RegexSearch::new(WORD_REGEX).unwrap(),
             vi_mode_enabled: false,
             is_ssh_terminal,
+            shell_marks,
+            shell_marks_detected,
         };
 
         Ok(TerminalBuilder {
@@ -569,6 +655,14 @@ pub struct TerminalContent {
     pub cursor_char: char,
     pub size: TerminalSize,
     pub last_hovered_word: Option<HoveredWord>,
+    pub prompt_marks: Vec<PromptMark>,
+}
+
+/// A prompt in the visible part of the terminal, along with the status of the command run from it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PromptMark {
+    pub line: Line,
+    pub status: CommandStatus,
 }
 
 #[derive(Clone)]
@@ -593,6 +687,7 @@ impl Default for TerminalContent {
             cursor_char: Default::default(),
             size: Default::default(),
             last_hovered_word: None,
+            prompt_marks: Vec::new(),
         }
     }
 }
@@ -629,6 +724,10 @@ pub struct Terminal {
     task: Option<TaskState>,
     vi_mode_enabled: bool,
     is_ssh_terminal: bool,
+    /// The marks reported by the shell, when shell integration is enabled.
+    shell_marks: Option<Arc<Mutex<ShellMarks>>>,
+    /// Whether the shell reported prompt marks, see [`shell_integration`].
+    shell_marks_detected: Arc<AtomicBool>,
 }
 
 pub struct TaskState {
@@ -753,9 +852,21 @@ impl Terminal {
 
                 self.pty_tx.0.send(Msg::Resize(new_size.into())).ok();
 
+                let columns_changed = term.columns() != new_size.columns();
+                if let Some(shell_marks) = &self.shell_marks {
+                    shell_marks.lock().observe(term);
+                }
                 term.resize(new_size);
+                if let Some(shell_marks) = &self.shell_marks {
+                    shell_marks.lock().resized(term, columns_changed);
+                }
             }
             InternalEvent::Clear => {
+                let prompt_marks = self.shell_marks.as_ref().map(|shell_marks| {
+                    let cursor_line = term.grid().cursor.point.line;
+                    shell_marks.lock().take_line(term, cursor_line)
+                });
+
                 // Clear back buffer
                 term.clear_screen(ClearMode::Saved);
 
@@ -785,6 +896,12 @@ impl Terminal {
                     term.grid_mut().reset_region((new_cursor.line + 1)..);
                 }
 
+                if let (Some(shell_marks), Some(prompt_marks)) = (&self.shell_marks, prompt_marks) {
+                    shell_marks
+                        .lock()
+                        .replace_all(term, new_cursor.line, prompt_marks);
+                }
+
                 cx.emit(Event::Wakeup);
             }
             InternalEvent::Scroll(scroll) => {
@@ -1299,10 +1416,22 @@ impl Terminal {
             self.process_terminal_event(&e, &mut terminal, cx)
         }
 
-        self.last_content = Self::make_content(&terminal, &self.last_content);
+        let prompt_marks = match &self.shell_marks {
+            Some(shell_marks) => {
+                let mut shell_marks = shell_marks.lock();
+                shell_marks.observe(&mut terminal);
+                Self::visible_prompt_marks(&terminal, &shell_marks)
+            }
+            None => Vec::new(),
+        };
+        self.last_content = Self::make_content(&terminal, &self.last_content, prompt_marks);
     }
 
-    fn make_content(term: &Term<ZedListener>, last_content: &TerminalContent) -> TerminalContent {
+    fn make_content(
+        term: &Term<ZedListener>,
+        last_content: &TerminalContent,
+        prompt_marks: Vec<PromptMark>,
+    ) -> TerminalContent {
         let content = term.renderable_content();
         TerminalContent {
             cells: content
@@ -1327,6 +1456,155 @@ impl Terminal {
             cursor_char: term.grid()[content.cursor.point].c,
             size: last_content.size,
             last_hovered_word: last_content.last_hovered_word.clone(),
+            prompt_marks,
+        }
+    }
+
+    fn visible_prompt_marks(term: &Term<ZedListener>, shell_marks: &ShellMarks) -> Vec<PromptMark> {
+        let top = Line(-(term.grid().display_offset() as i32));
+        let bottom = top + term.screen_lines() - 1;
+        // Commands finish below their prompt, so the marks of the rest of the grid count too.
+        shell_marks
+            .command_blocks(top..=term.bottommost_line())
+            .into_iter()
+            .filter_map(|block| {
+                let line = block.start_line()?;
+                let status = block.status()?;
+                (top..=bottom)
+                    .contains(&line)
+                    .then_some(PromptMark { line, status })
+            })
+            .collect()
+    }
+
+    /// Whether the shell running in this terminal reports its prompts and commands.
+    pub fn shell_marks_detected(&self) -> bool {
+        self.shell_marks_detected.load(Ordering::Relaxed)
+    }
+
+    fn command_blocks(&self) -> Vec<CommandBlock> {
+        let Some(shell_marks) = &self.shell_marks else {
+            return Vec::new();
+        };
+        let mut term = self.term.lock();
+        let mut shell_marks = shell_marks.lock();
+        shell_marks.observe(&mut term);
+        shell_marks.command_blocks(term.topmost_line()..=term.bottommost_line())
+    }
+
+    /// The command block at the top of the viewport when scrolled up, or the latest one
+    /// otherwise, that satisfies the given predicate.
+    fn command_block_at_viewport(
+        &self,
+        predicate: impl Fn(&CommandBlock) -> bool,
+    ) -> Option<CommandBlock> {
+        let top = Line(-(self.last_content.display_offset as i32));
+        let at_bottom = self.last_content.display_offset == 0;
+        self.command_blocks().into_iter().rev().find(|block| {
+            (at_bottom || block.start_line().is_some_and(|line| line <= top)) && predicate(block)
+        })
+    }
+
+    pub fn scroll_to_previous_prompt(&mut self) {
+        let top = Line(-(self.last_content.display_offset as i32));
+        let prompt_line = self
+            .command_blocks()
+            .iter()
+            .rev()
+            .filter_map(CommandBlock::start_line)
+            .find(|line| *line < top);
+        if let Some(line) = prompt_line {
+            self.scroll_to_line(line);
+        }
+    }
+
+    pub fn scroll_to_next_prompt(&mut self) {
+        let top = Line(-(self.last_content.display_offset as i32));
+        let prompt_line = self
+            .command_blocks()
+            .iter()
+            .filter_map(CommandBlock::start_line)
+            .find(|line| *line > top);
+        match prompt_line {
+            Some(line) => self.scroll_to_line(line),
+            None => self.scroll_to_bottom(),
+        }
+    }
+
+    /// Scrolls so that the given line is at the top of the viewport, as far as possible.
+    fn scroll_to_line(&mut self, line: Line) {
+        let display_offset = self.last_content.display_offset as i32;
+        let target_offset = (-line.0).max(0);
+        self.events
+            .push_back(InternalEvent::Scroll(AlacScroll::Delta(
+                target_offset - display_offset,
+            )));
+    }
+
+    fn last_command_output(&self) -> Option<RangeInclusive<AlacPoint>> {
+        let block = self.command_block_at_viewport(|block| block.end.is_some())?;
+        let term = self.term.lock();
+        block.output_range(&term)
+    }
+
+    pub fn select_last_command_output(&mut self) {
+        if let Some(output) = self.last_command_output() {
+            self.set_selection(Some((make_selection(&output), *output.end())));
+            self.events
+                .push_back(InternalEvent::ScrollToAlacPoint(*output.start()));
+        }
+    }
+
+    pub fn copy_last_command_output(&mut self, cx: &mut ModelContext<Self>) {
+        if let Some(output) = self.last_command_output() {
+            let text = self
+                .term
+                .lock()
+                .bounds_to_string(*output.start(), *output.end());
+            cx.write_to_clipboard(ClipboardItem::new_string(text.trim_end().to_string()));
+        }
+    }
+
+    /// Runs again the command at the top of the viewport, or the last command when
+    /// the terminal is not scrolled. Does nothing while a command is still running.
+    pub fn rerun_command(&mut self) {
+        let blocks = self.command_blocks();
+        if blocks
+            .last()
+            .map_or(true, |block| block.status() == Some(CommandStatus::Running))
+        {
+            return;
+        }
+
+        let command = self
+            .command_block_at_viewport(|block| block.output_start.is_some())
+            .and_then(|block| match block.command {
+                Some(command) => Some(command),
+                None => {
+                    let start = block.command_start?;
+                    let end = block.output_start?;
+                    let term = self.term.lock();
+                    let end = end.sub(&*term, Boundary::Grid, 1);
+                    (start <= end).then(|| term.bounds_to_string(start, end))
+                }
+            });
+        if let Some(command) = command.filter(|command| !command.trim().is_empty()) {
+            self.paste(command.trim());
+            self.input("\r".to_string());
+        }
+    }
+
+    /// The text of the primary screen and of its scrollback history, up to the configured
+    /// number of history lines. Returns `None` while an application uses the alternate screen.
+    pub fn scrollback_text(&self) -> Option<String> {
+        scrollback_text(&self.term.lock(), self.term_config.scrolling_history)
+    }
+
+    /// Shows the title of a previous session until the shell sets its own.
+    pub fn restore_title(&mut self, title: String, cx: &mut ModelContext<Self>) {
+        if !title.is_empty() {
+            self.breadcrumb_text = title;
+            cx.emit(Event::BreadcrumbsChanged);
         }
     }
 
@@ -1794,6 +2072,37 @@ fn task_summary(task: &TaskState, error_code: Option<i32>) -> (bool, String, Str
     (success, task_line, command_line)
 }
 
+/// Returns the last `max_lines` lines of the terminal's text, unless it shows the alternate screen.
+fn scrollback_text<T>(term: &Term<T>, max_lines: usize) -> Option<String> {
+    if max_lines == 0 || term.mode().contains(TermMode::ALT_SCREEN) {
+        return None;
+    }
+
+    let first_line = cmp::max(
+        term.topmost_line(),
+        term.bottommost_line() - (max_lines - 1),
+    );
+    let start = AlacPoint::new(first_line, Column(0));
+    let end = AlacPoint::new(term.bottommost_line(), term.last_column());
+    let text = term.bounds_to_string(start, end);
+    let text = text.trim_end();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Writes the scrollback of a previous session into the terminal, dimmed to set it apart
+/// from the output of the new shell.
+fn write_scrollback<T: EventListener>(term: &mut Term<T>, scrollback: &str) {
+    term.terminal_attribute(Attr::Dim);
+    for line in scrollback.lines() {
+        for c in line.chars().filter(|c| !c.is_control()) {
+            term.input(c);
+        }
+        term.carriage_return();
+        term.linefeed();
+    }
+    term.terminal_attribute(Attr::Reset);
+}
+
 /// Appends a stringified task summary to the terminal, after its output.
 ///
 /// SAFETY: This function should only be called after terminal's PTY is no longer alive.
@@ -1973,16 +2282,51 @@ pub fn rgba_color(r: u8, g: u8, b: u8) -> Hsla {
 #[cfg(test)]
 mod tests {
     use alacritty_terminal::{
+        event::VoidListener,
         index::{Column, Line, Point as AlacPoint},
-        term::cell::Cell,
+        term::{cell::Cell, Config},
+        Term,
     };
     use gpui::{point, size, Pixels};
     use rand::{distributions::Alphanumeric, rngs::ThreadRng, thread_rng, Rng};
 
     use crate::{
-        content_index_for_mouse, rgb_for_index, IndexedCell, TerminalContent, TerminalSize,
+        content_index_for_mouse, rgb_for_index, scrollback_text, write_scrollback, IndexedCell,
+        TerminalContent, TerminalSize,
     };
 
+    #[test]
+    fn test_scrollback_round_trip() {
+        let scrollback = (1..=20)
+            .map(|ix| format!("line {ix}"))
+            .chain(["", "$ echo done", "done"].map(String::from))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut term = Term::new(Config::default(), &TerminalSize::default(), VoidListener);
+        write_scrollback(&mut term, &scrollback);
+        // The restored lines end with a line break, so the new shell starts on a fresh line.
+        assert_eq!(term.grid().cursor.point, AlacPoint::new(Line(5), Column(0)));
+        assert_eq!(
+            scrollback_text(&term, 10_000).as_deref(),
+            Some(scrollback.as_str())
+        );
+
+        let mut restored_term =
+            Term::new(Config::default(), &TerminalSize::default(), VoidListener);
+        write_scrollback(&mut restored_term, &scrollback_text(&term, 10_000).unwrap());
+        assert_eq!(
+            scrollback_text(&restored_term, 10_000).as_deref(),
+            Some(scrollback.as_str())
+        );
+
+        // Only the last lines are kept when the history is smaller than the scrollback.
+        assert_eq!(
+            scrollback_text(&term, 3).as_deref(),
+            Some("$ echo done\ndone")
+        );
+    }
+
     #[test]
     fn test_rgb_for_index() {
         // Test every possible value in the color cube.
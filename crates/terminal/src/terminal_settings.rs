@@ -46,6 +46,8 @@ pub struct TerminalSettings {
     pub default_height: Pixels,
     pub detect_venv: VenvSettings,
     pub max_scroll_history_lines: Option<usize>,
+    pub shell_integration: bool,
+    pub restore_scrollback: bool,
     pub toolbar: Toolbar,
 }
 
@@ -185,6 +187,17 @@ pub struct TerminalSettingsContent {
     ///
     /// Default: 10_000
     pub max_scroll_history_lines: Option<usize>,
+    /// Whether to set up shell integration for bash, zsh and fish, which lets Zed
+    /// know where prompts, commands and their output are, and how commands exited.
+    /// Existing terminals will not pick up this change until they are recreated.
+    ///
+    /// Default: false
+    pub shell_integration: Option<bool>,
+    /// Whether to save the scrollback and title of terminals, and show them above the
+    /// new shell's prompt when the workspace is reopened. Task terminals are never saved.
+    ///
+    /// Default: true
+    pub restore_scrollback: Option<bool>,
     /// Toolbar related settings
     pub toolbar: Option<ToolbarContent>,
 }
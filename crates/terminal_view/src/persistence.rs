@@ -35,6 +35,10 @@ define_connection! {
             DROP TABLE terminals;
 
             ALTER TABLE terminals2 RENAME TO terminals;
+        ),
+        sql!(
+            ALTER TABLE terminals ADD COLUMN scrollback TEXT;
+            ALTER TABLE terminals ADD COLUMN title TEXT;
         )];
 }
 
@@ -57,8 +61,25 @@ impl TerminalDb {
             workspace_id: WorkspaceId,
             working_directory: PathBuf
         ) -> Result<()> {
-            INSERT OR REPLACE INTO terminals(item_id, workspace_id, working_directory)
-            VALUES (?, ?, ?)
+            INSERT INTO terminals(item_id, workspace_id, working_directory)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT DO UPDATE SET
+                working_directory = ?3
+        }
+    }
+
+    query! {
+        pub async fn save_scrollback(
+            item_id: ItemId,
+            workspace_id: WorkspaceId,
+            scrollback: Option<String>,
+            title: Option<String>
+        ) -> Result<()> {
+            INSERT INTO terminals(item_id, workspace_id, scrollback, title)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT DO UPDATE SET
+                scrollback = ?3,
+                title = ?4
         }
     }
 
@@ -70,6 +91,14 @@ impl TerminalDb {
         }
     }
 
+    query! {
+        pub async fn get_scrollback(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<(Option<String>, Option<String>)>> {
+            SELECT scrollback, title
+            FROM terminals
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
+
     pub async fn delete_unloaded_items(
         &self,
         workspace: WorkspaceId,
@@ -96,3 +125,69 @@ impl TerminalDb {
         .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_save_and_get_scrollback() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        let scrollback = (
+            Some("$ ls\nCargo.toml".to_string()),
+            Some("zsh".to_string()),
+        );
+
+        TERMINAL_DB
+            .save_working_directory(1234, workspace_id, PathBuf::from("/tmp"))
+            .await
+            .unwrap();
+        TERMINAL_DB
+            .save_scrollback(
+                1234,
+                workspace_id,
+                scrollback.0.clone(),
+                scrollback.1.clone(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            TERMINAL_DB
+                .get_scrollback(1234, workspace_id)
+                .await
+                .unwrap(),
+            Some(scrollback.clone())
+        );
+
+        // Saving the working directory again keeps the scrollback.
+        TERMINAL_DB
+            .save_working_directory(1234, workspace_id, PathBuf::from("/tmp/a"))
+            .await
+            .unwrap();
+        assert_eq!(
+            TERMINAL_DB
+                .get_working_directory(1234, workspace_id)
+                .unwrap(),
+            Some(PathBuf::from("/tmp/a"))
+        );
+        assert_eq!(
+            TERMINAL_DB
+                .get_scrollback(1234, workspace_id)
+                .await
+                .unwrap(),
+            Some(scrollback)
+        );
+
+        TERMINAL_DB
+            .save_scrollback(1234, workspace_id, None, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            TERMINAL_DB
+                .get_scrollback(1234, workspace_id)
+                .await
+                .unwrap(),
+            Some((None, None))
+        );
+    }
+}
@@ -20,8 +20,9 @@ use terminal::{
             CursorShape as AlacCursorShape, NamedColor,
         },
     },
+    shell_integration::CommandStatus,
     terminal_settings::TerminalSettings,
-    HoveredWord, IndexedCell, Terminal, TerminalContent, TerminalSize,
+    HoveredWord, IndexedCell, PromptMark, Terminal, TerminalContent, TerminalSize,
 };
 use theme::{ActiveTheme, Theme, ThemeSettings};
 use ui::{ParentElement, Tooltip};
@@ -45,6 +46,8 @@ pub struct LayoutState {
     display_offset: usize,
     hyperlink_tooltip: Option<AnyElement>,
     gutter: Pixels,
+    /// Display lines of the visible prompts, with the color representing their command's status.
+    prompt_markers: Vec<(i32, Hsla)>,
     last_hovered_word: Option<HoveredWord>,
     block_below_cursor_element: Option<AnyElement>,
 }
@@ -735,11 +738,27 @@ impl Element for TerminalElement {
                     cursor_char,
                     selection,
                     cursor,
+                    prompt_marks,
                     ..
                 } = &self.terminal.read(cx).last_content;
                 let mode = *mode;
                 let display_offset = *display_offset;
 
+                let prompt_markers = prompt_marks
+                    .iter()
+                    .map(|PromptMark { line, status }| {
+                        let color = match status {
+                            CommandStatus::Running => theme.status().info,
+                            CommandStatus::Finished { exit_code: Some(0) } => {
+                                theme.status().success
+                            }
+                            CommandStatus::Finished { exit_code: Some(_) } => theme.status().error,
+                            CommandStatus::Finished { exit_code: None } => theme.status().hidden,
+                        };
+                        (line.0 + display_offset as i32, color)
+                    })
+                    .collect();
+
                 // searches, highlights to a single range representations
                 let mut relative_highlighted_ranges = Vec::new();
                 for search_match in search_matches {
@@ -855,6 +874,7 @@ impl Element for TerminalElement {
                     display_offset,
                     hyperlink_tooltip,
                     gutter,
+                    prompt_markers,
                     last_hovered_word,
                     block_below_cursor_element,
                 }
@@ -919,6 +939,19 @@ impl Element for TerminalElement {
                         rect.paint(origin, &layout.dimensions, cx);
                     }
 
+                    let marker_width = (layout.gutter * 0.25).max(px(2.));
+                    for (line, color) in &layout.prompt_markers {
+                        let marker_origin = point(
+                            bounds.origin.x + (layout.gutter - marker_width) / 2.,
+                            origin.y + *line as f32 * layout.dimensions.line_height,
+                        );
+                        let marker_size = size(marker_width, layout.dimensions.line_height);
+                        cx.paint_quad(
+                            fill(Bounds::new(marker_origin, marker_size), *color)
+                                .corner_radii(marker_width / 2.),
+                        );
+                    }
+
                     for (relative_highlighted_range, color) in
                         layout.relative_highlighted_ranges.iter()
                     {
@@ -5,16 +5,17 @@ use collections::{HashMap, HashSet};
 use db::kvp::KEY_VALUE_STORE;
 use futures::future::join_all;
 use gpui::{
-    actions, Action, AnchorCorner, AnyView, AppContext, AsyncWindowContext, Entity, EventEmitter,
-    ExternalPaths, FocusHandle, FocusableView, IntoElement, Model, ParentElement, Pixels, Render,
-    Styled, Subscription, Task, View, ViewContext, VisualContext, WeakView, WindowContext,
+    actions, div, Action, AnchorCorner, AnyView, AppContext, AsyncWindowContext, Axis, Entity,
+    EventEmitter, ExternalPaths, FocusHandle, FocusableView, InteractiveElement, IntoElement,
+    Model, ParentElement, Pixels, Render, Styled, Subscription, Task, View, ViewContext,
+    VisualContext, WeakView, WindowContext,
 };
 use itertools::Itertools;
-use project::{terminals::TerminalKind, Fs, ProjectEntryId};
+use project::{terminals::TerminalKind, Fs, Project, ProjectEntryId};
 use search::{buffer_search::DivRegistrar, BufferSearchBar};
 use serde::{Deserialize, Serialize};
 use settings::Settings;
-use task::{RevealStrategy, Shell, SpawnInTerminal, TaskId};
+use task::{RevealStrategy, Shell, SpawnInTerminal, TaskId, TerminalSplit};
 use terminal::{
     terminal_settings::{TerminalDockPosition, TerminalSettings},
     Terminal,
@@ -27,9 +28,11 @@ use util::{ResultExt, TryFutureExt};
 use workspace::{
     dock::{DockPosition, Panel, PanelEvent},
     item::SerializableItem,
-    pane,
+    move_item, pane,
     ui::IconName,
-    DraggedTab, ItemId, NewTerminal, Pane, ToggleZoom, Workspace,
+    ActivateNextPane, ActivatePane, ActivatePaneInDirection, ActivatePreviousPane, DraggedTab,
+    ItemId, Member, NewTerminal, Pane, PaneAxis, PaneGroup, SplitDirection, SplitDown, SplitLeft,
+    SplitRight, SplitUp, SwapPaneInDirection, ToggleZoom, Workspace, WorkspaceId,
 };
 
 use anyhow::Result;
@@ -59,13 +62,15 @@ pub fn init(cx: &mut AppContext) {
 }
 
 pub struct TerminalPanel {
-    pane: View<Pane>,
+    active_pane: View<Pane>,
+    center: PaneGroup,
     fs: Arc<dyn Fs>,
     workspace: WeakView<Workspace>,
     width: Option<Pixels>,
     height: Option<Pixels>,
     pending_serialization: Task<Option<()>>,
     pending_terminals_to_add: usize,
+    zoomed: bool,
     _subscriptions: Vec<Subscription>,
     deferred_tasks: HashMap<TaskId, Task<()>>,
     enabled: bool,
@@ -75,94 +80,26 @@ pub struct TerminalPanel {
 
 impl TerminalPanel {
     fn new(workspace: &Workspace, cx: &mut ViewContext<Self>) -> Self {
-        let pane = cx.new_view(|cx| {
-            let mut pane = Pane::new(
-                workspace.weak_handle(),
-                workspace.project().clone(),
-                Default::default(),
-                None,
-                NewTerminal.boxed_clone(),
-                cx,
-            );
-            pane.set_can_split(false, cx);
-            pane.set_can_navigate(false, cx);
-            pane.display_nav_history_buttons(None);
-            pane.set_should_display_tab_bar(|_| true);
-
-            let is_local = workspace.project().read(cx).is_local();
-            let workspace = workspace.weak_handle();
-            pane.set_custom_drop_handle(cx, move |pane, dropped_item, cx| {
-                if let Some(tab) = dropped_item.downcast_ref::<DraggedTab>() {
-                    let item = if &tab.pane == cx.view() {
-                        pane.item_for_index(tab.ix)
-                    } else {
-                        tab.pane.read(cx).item_for_index(tab.ix)
-                    };
-                    if let Some(item) = item {
-                        if item.downcast::<TerminalView>().is_some() {
-                            return ControlFlow::Continue(());
-                        } else if let Some(project_path) = item.project_path(cx) {
-                            if let Some(entry_path) = workspace
-                                .update(cx, |workspace, cx| {
-                                    workspace
-                                        .project()
-                                        .read(cx)
-                                        .absolute_path(&project_path, cx)
-                                })
-                                .log_err()
-                                .flatten()
-                            {
-                                add_paths_to_terminal(pane, &[entry_path], cx);
-                            }
-                        }
-                    }
-                } else if let Some(&entry_id) = dropped_item.downcast_ref::<ProjectEntryId>() {
-                    if let Some(entry_path) = workspace
-                        .update(cx, |workspace, cx| {
-                            let project = workspace.project().read(cx);
-                            project
-                                .path_for_entry(entry_id, cx)
-                                .and_then(|project_path| project.absolute_path(&project_path, cx))
-                        })
-                        .log_err()
-                        .flatten()
-                    {
-                        add_paths_to_terminal(pane, &[entry_path], cx);
-                    }
-                } else if is_local {
-                    if let Some(paths) = dropped_item.downcast_ref::<ExternalPaths>() {
-                        add_paths_to_terminal(pane, paths.paths(), cx);
-                    }
-                }
-
-                ControlFlow::Break(())
-            });
-            let buffer_search_bar = cx.new_view(search::BufferSearchBar::new);
-            pane.toolbar()
-                .update(cx, |toolbar, cx| toolbar.add_item(buffer_search_bar, cx));
-            pane
-        });
-        let subscriptions = vec![
-            cx.observe(&pane, |_, _, cx| cx.notify()),
-            cx.subscribe(&pane, Self::handle_pane_event),
-        ];
-        let project = workspace.project().read(cx);
-        let enabled = project.supports_terminal(cx);
+        let project = workspace.project().clone();
+        let pane = new_terminal_pane(workspace.weak_handle(), project.clone(), cx);
+        let enabled = project.read(cx).supports_terminal(cx);
         let this = Self {
-            pane,
+            center: PaneGroup::new(pane.clone()),
+            active_pane: pane.clone(),
             fs: workspace.app_state().fs.clone(),
             workspace: workspace.weak_handle(),
             pending_serialization: Task::ready(None),
             width: None,
             height: None,
             pending_terminals_to_add: 0,
+            zoomed: false,
             deferred_tasks: HashMap::default(),
-            _subscriptions: subscriptions,
+            _subscriptions: Vec::new(),
             enabled,
             assistant_enabled: false,
             assistant_tab_bar_button: None,
         };
-        this.apply_tab_bar_buttons(cx);
+        this.apply_tab_bar_buttons(&pane, cx);
         this
     }
 
@@ -170,7 +107,7 @@ impl TerminalPanel {
         self.assistant_enabled = enabled;
         if enabled {
             let focus_handle = self
-                .pane
+                .active_pane
                 .read(cx)
                 .active_item()
                 .map(|item| item.focus_handle(cx))
@@ -182,12 +119,14 @@ impl TerminalPanel {
         } else {
             self.assistant_tab_bar_button = None;
         }
-        self.apply_tab_bar_buttons(cx);
+        for pane in self.center.panes() {
+            self.apply_tab_bar_buttons(pane, cx);
+        }
     }
 
-    fn apply_tab_bar_buttons(&self, cx: &mut ViewContext<Self>) {
+    fn apply_tab_bar_buttons(&self, terminal_pane: &View<Pane>, cx: &mut ViewContext<Self>) {
         let assistant_tab_bar_button = self.assistant_tab_bar_button.clone();
-        self.pane.update(cx, |pane, cx| {
+        terminal_pane.update(cx, |pane, cx| {
             pane.set_render_tab_bar_buttons(cx, move |pane, cx| {
                 if !pane.has_focus(cx) && !pane.context_menu_focused(cx) {
                     return (None, None);
@@ -225,6 +164,25 @@ impl TerminalPanel {
                                 Some(menu)
                             }),
                     )
+                    .child(
+                        PopoverMenu::new("terminal-tab-bar-split")
+                            .trigger(
+                                IconButton::new("split", IconName::Split)
+                                    .icon_size(IconSize::Small)
+                                    .tooltip(|cx| Tooltip::text("Split Pane", cx)),
+                            )
+                            .anchor(AnchorCorner::TopRight)
+                            .with_handle(pane.split_item_context_menu_handle.clone())
+                            .menu(move |cx| {
+                                ContextMenu::build(cx, |menu, _| {
+                                    menu.action("Split Right", SplitRight.boxed_clone())
+                                        .action("Split Left", SplitLeft.boxed_clone())
+                                        .action("Split Up", SplitUp.boxed_clone())
+                                        .action("Split Down", SplitDown.boxed_clone())
+                                })
+                                .into()
+                            }),
+                    )
                     .child({
                         let zoomed = pane.is_zoomed();
                         IconButton::new("toggle_zoom", IconName::Maximize)
@@ -264,7 +222,7 @@ impl TerminalPanel {
             .log_err()
             .flatten();
 
-        let (panel, pane, items) = workspace.update(&mut cx, |workspace, cx| {
+        let (panel, items) = workspace.update(&mut cx, |workspace, cx| {
             let panel = cx.new_view(|cx| TerminalPanel::new(workspace, cx));
             let items = if let Some((serialized_panel, database_id)) =
                 serialized_panel.as_ref().zip(workspace.database_id())
@@ -273,27 +231,33 @@ impl TerminalPanel {
                     cx.notify();
                     panel.height = serialized_panel.height.map(|h| h.round());
                     panel.width = serialized_panel.width.map(|w| w.round());
-                    panel.pane.update(cx, |_, cx| {
-                        serialized_panel
-                            .items
-                            .iter()
-                            .map(|item_id| {
-                                TerminalView::deserialize(
-                                    workspace.project().clone(),
-                                    workspace.weak_handle(),
-                                    database_id,
-                                    *item_id,
-                                    cx,
-                                )
-                            })
-                            .collect::<Vec<_>>()
-                    })
+                    let mut items = Vec::new();
+                    match &serialized_panel.items {
+                        SerializedItems::NoSplits(item_ids) => {
+                            let pane = panel.active_pane.clone();
+                            items.push((
+                                pane.clone(),
+                                deserialize_terminals(&pane, item_ids, workspace, database_id, cx),
+                            ));
+                        }
+                        SerializedItems::WithSplits(serialized_group) => {
+                            let root = panel.deserialize_pane_group(
+                                serialized_group,
+                                workspace,
+                                database_id,
+                                &mut items,
+                                cx,
+                            );
+                            panel.center = PaneGroup::with_root(root);
+                            panel.active_pane = panel.center.first_pane();
+                        }
+                    }
+                    items
                 })
             } else {
                 Vec::new()
             };
-            let pane = panel.read(cx).pane.clone();
-            (panel, pane, items)
+            (panel, items)
         })?;
 
         if let Some(workspace) = workspace.upgrade() {
@@ -311,28 +275,56 @@ impl TerminalPanel {
                 .ok();
         }
 
-        let pane = pane.downgrade();
-        let items = futures::future::join_all(items).await;
+        let active_item_id = serialized_panel
+            .as_ref()
+            .and_then(|panel| panel.active_item_id);
         let mut alive_item_ids = Vec::new();
-        pane.update(&mut cx, |pane, cx| {
-            let active_item_id = serialized_panel
-                .as_ref()
-                .and_then(|panel| panel.active_item_id);
-            let mut active_ix = None;
-            for item in items {
-                if let Some(item) = item.log_err() {
-                    let item_id = item.entity_id().as_u64();
-                    pane.add_item(Box::new(item), false, false, None, cx);
-                    alive_item_ids.push(item_id as ItemId);
-                    if Some(item_id) == active_item_id {
-                        active_ix = Some(pane.items_len() - 1);
+        for (pane, items) in items {
+            let items = futures::future::join_all(items).await;
+            pane.update(&mut cx, |pane, cx| {
+                let mut active_ix = None;
+                for item in items {
+                    if let Some(item) = item.log_err() {
+                        let item_id = item.entity_id().as_u64();
+                        pane.add_item(Box::new(item), false, false, None, cx);
+                        alive_item_ids.push(item_id as ItemId);
+                        if Some(item_id) == active_item_id {
+                            active_ix = Some(pane.items_len() - 1);
+                        }
                     }
                 }
-            }
 
-            if let Some(active_ix) = active_ix {
-                pane.activate_item(active_ix, false, false, cx)
+                if let Some(active_ix) = active_ix {
+                    pane.activate_item(active_ix, false, false, cx)
+                }
+            })?;
+        }
+
+        panel.update(&mut cx, |panel, cx| {
+            // Splits whose terminals could not be restored are dropped.
+            for pane in panel
+                .center
+                .panes()
+                .into_iter()
+                .cloned()
+                .collect::<Vec<_>>()
+            {
+                if pane.read(cx).items_len() == 0 {
+                    panel.center.remove(&pane).log_err();
+                }
             }
+            panel.active_pane = panel
+                .center
+                .panes()
+                .into_iter()
+                .find(|pane| {
+                    pane.read(cx)
+                        .active_item()
+                        .is_some_and(|item| Some(item.item_id().as_u64()) == active_item_id)
+                })
+                .cloned()
+                .unwrap_or_else(|| panel.center.first_pane());
+            cx.notify();
         })?;
 
         // Since panels/docks are loaded outside from the workspace, we cleanup here, instead of through the workspace.
@@ -350,30 +342,242 @@ impl TerminalPanel {
         Ok(panel)
     }
 
+    fn deserialize_pane_group(
+        &mut self,
+        serialized_group: &SerializedPaneGroup,
+        workspace: &Workspace,
+        database_id: WorkspaceId,
+        items: &mut Vec<(View<Pane>, Vec<Task<Result<View<TerminalView>>>>)>,
+        cx: &mut ViewContext<Self>,
+    ) -> Member {
+        match serialized_group {
+            SerializedPaneGroup::Group {
+                axis,
+                flexes,
+                children,
+            } => {
+                let members = children
+                    .iter()
+                    .map(|child| {
+                        self.deserialize_pane_group(child, workspace, database_id, items, cx)
+                    })
+                    .collect::<Vec<_>>();
+                let flexes = flexes
+                    .clone()
+                    .filter(|flexes| flexes.len() == members.len());
+                Member::Axis(PaneAxis::load((*axis).into(), members, flexes))
+            }
+            SerializedPaneGroup::Pane(serialized_pane) => {
+                let pane =
+                    new_terminal_pane(workspace.weak_handle(), workspace.project().clone(), cx);
+                self.apply_tab_bar_buttons(&pane, cx);
+                items.push((
+                    pane.clone(),
+                    deserialize_terminals(
+                        &pane,
+                        &serialized_pane.children,
+                        workspace,
+                        database_id,
+                        cx,
+                    ),
+                ));
+                Member::Pane(pane)
+            }
+        }
+    }
+
     fn handle_pane_event(
         &mut self,
-        _pane: View<Pane>,
+        pane: View<Pane>,
         event: &pane::Event,
         cx: &mut ViewContext<Self>,
     ) {
         match event {
             pane::Event::ActivateItem { .. } => self.serialize(cx),
             pane::Event::RemovedItem { .. } => self.serialize(cx),
-            pane::Event::Remove { .. } => cx.emit(PanelEvent::Close),
+            pane::Event::Remove { focus_on_pane } => {
+                if self.center.panes().len() == 1 {
+                    cx.emit(PanelEvent::Close);
+                    return;
+                }
+
+                self.center.remove(&pane).log_err();
+                let next_pane = focus_on_pane
+                    .clone()
+                    .filter(|pane| self.center.panes().contains(&pane))
+                    .unwrap_or_else(|| self.center.first_pane());
+                if self.active_pane == pane {
+                    self.active_pane = next_pane.clone();
+                }
+                cx.focus_view(&next_pane);
+                self.serialize(cx);
+                cx.notify();
+            }
             pane::Event::ZoomIn => cx.emit(PanelEvent::ZoomIn),
             pane::Event::ZoomOut => cx.emit(PanelEvent::ZoomOut),
 
             pane::Event::AddItem { item } => {
                 if let Some(workspace) = self.workspace.upgrade() {
-                    let pane = self.pane.clone();
                     workspace.update(cx, |workspace, cx| item.added_to_pane(workspace, pane, cx))
                 }
             }
 
+            pane::Event::Split(direction) => {
+                let Some(new_pane) = self.new_pane_with_cloned_active_terminal(&pane, cx) else {
+                    return;
+                };
+                self.center.split(&pane, &new_pane, *direction).log_err();
+                cx.focus_view(&new_pane);
+                self.serialize(cx);
+                cx.notify();
+            }
+
+            pane::Event::Focus => {
+                if self.active_pane != pane {
+                    self.active_pane = pane;
+                    cx.notify();
+                }
+            }
+
             _ => {}
         }
     }
 
+    fn new_pane(&mut self, cx: &mut ViewContext<Self>) -> Option<View<Pane>> {
+        let project = self
+            .workspace
+            .update(cx, |workspace, _| workspace.project().clone())
+            .ok()?;
+        let pane = new_terminal_pane(self.workspace.clone(), project, cx);
+        self.apply_tab_bar_buttons(&pane, cx);
+        let zoomed = self.zoomed;
+        pane.update(cx, |pane, cx| pane.set_zoomed(zoomed, cx));
+        Some(pane)
+    }
+
+    /// Creates a pane with a new shell, started in the working directory of the
+    /// terminal that is active in `pane`.
+    fn new_pane_with_cloned_active_terminal(
+        &mut self,
+        pane: &View<Pane>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<Pane>> {
+        let workspace = self.workspace.upgrade()?;
+        let working_directory = pane
+            .read(cx)
+            .active_item()
+            .and_then(|item| item.downcast::<TerminalView>())
+            .and_then(|terminal_view| {
+                terminal_view
+                    .read(cx)
+                    .terminal()
+                    .read(cx)
+                    .working_directory()
+            })
+            .or_else(|| default_working_directory(workspace.read(cx), cx));
+        let database_id = workspace.read(cx).database_id();
+        let project = workspace.read(cx).project().clone();
+        let window = cx.window_handle();
+        let terminal = project
+            .update(cx, |project, cx| {
+                project.create_terminal(TerminalKind::Shell(working_directory), window, cx)
+            })
+            .log_err()?;
+        let terminal_view =
+            Box::new(cx.new_view(|cx| {
+                TerminalView::new(terminal, workspace.downgrade(), database_id, cx)
+            }));
+        let new_pane = self.new_pane(cx)?;
+        new_pane.update(cx, |pane, cx| {
+            pane.add_item(terminal_view, true, true, None, cx);
+        });
+        Some(new_pane)
+    }
+
+    /// Moves a terminal into a new pane next to `target_pane`, as when a tab is dropped
+    /// onto the edge of a pane.
+    fn split_with_terminal(
+        &mut self,
+        target_pane: &View<Pane>,
+        source_pane: &View<Pane>,
+        item_id: gpui::EntityId,
+        direction: SplitDirection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(new_pane) = self.new_pane(cx) else {
+            return;
+        };
+        if self
+            .center
+            .split(target_pane, &new_pane, direction)
+            .log_err()
+            .is_none()
+        {
+            return;
+        }
+        move_item(source_pane, &new_pane, item_id, 0, cx);
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn activate_pane_in_direction(
+        &mut self,
+        direction: SplitDirection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(pane) = self
+            .center
+            .find_pane_in_direction(&self.active_pane, direction, cx)
+            .cloned()
+        {
+            cx.focus_view(&pane);
+        } else {
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    workspace.activate_pane_in_direction(direction, cx)
+                })
+                .ok();
+        }
+    }
+
+    fn swap_pane_in_direction(&mut self, direction: SplitDirection, cx: &mut ViewContext<Self>) {
+        if let Some(to) = self
+            .center
+            .find_pane_in_direction(&self.active_pane, direction, cx)
+            .cloned()
+        {
+            self.center.swap(&self.active_pane.clone(), &to);
+            self.serialize(cx);
+            cx.notify();
+        }
+    }
+
+    fn activate_pane_at_index(&mut self, index: usize, cx: &mut ViewContext<Self>) {
+        let panes = self.center.panes();
+        if let Some(pane) = panes.get(index).map(|pane| (*pane).clone()) {
+            cx.focus_view(&pane);
+        } else {
+            let active_pane = self.active_pane.clone();
+            self.handle_pane_event(active_pane, &pane::Event::Split(SplitDirection::Right), cx);
+        }
+    }
+
+    fn activate_next_pane(&mut self, cx: &mut ViewContext<Self>) {
+        let panes = self.center.panes();
+        if let Some(ix) = panes.iter().position(|pane| **pane == self.active_pane) {
+            let next_pane = panes[(ix + 1) % panes.len()].clone();
+            cx.focus_view(&next_pane);
+        }
+    }
+
+    fn activate_previous_pane(&mut self, cx: &mut ViewContext<Self>) {
+        let panes = self.center.panes();
+        if let Some(ix) = panes.iter().position(|pane| **pane == self.active_pane) {
+            let prev_pane = panes[ix.checked_sub(1).unwrap_or(panes.len() - 1)].clone();
+            cx.focus_view(&prev_pane);
+        }
+    }
+
     pub fn open_terminal(
         workspace: &mut Workspace,
         action: &workspace::OpenTerminal,
@@ -393,7 +597,6 @@ impl TerminalPanel {
             })
             .detach_and_log_err(cx);
     }
-
     fn spawn_task(&mut self, spawn_in_terminal: &SpawnInTerminal, cx: &mut ViewContext<Self>) {
         let mut spawn_task = spawn_in_terminal.clone();
         // Set up shell args unconditionally, as tasks are always spawned inside of a shell.
@@ -490,7 +693,7 @@ impl TerminalPanel {
                 .detach_and_log_err(cx);
             return;
         }
-        let (existing_item_index, existing_terminal) = terminals_for_task
+        let existing_terminal = terminals_for_task
             .last()
             .expect("covered no terminals case above")
             .clone();
@@ -499,7 +702,7 @@ impl TerminalPanel {
                 !use_new_terminal,
                 "Should have handled 'allow_concurrent_runs && use_new_terminal' case above"
             );
-            self.replace_terminal(spawn_task, existing_item_index, existing_terminal, cx);
+            self.replace_terminal(spawn_task, existing_terminal, cx);
         } else {
             self.deferred_tasks.insert(
                 spawn_in_terminal.id.clone(),
@@ -512,12 +715,7 @@ impl TerminalPanel {
                                     .spawn_in_new_terminal(spawn_task, cx)
                                     .detach_and_log_err(cx);
                             } else {
-                                terminal_panel.replace_terminal(
-                                    spawn_task,
-                                    existing_item_index,
-                                    existing_terminal,
-                                    cx,
-                                );
+                                terminal_panel.replace_terminal(spawn_task, existing_terminal, cx);
                             }
                         })
                         .ok();
@@ -554,31 +752,75 @@ impl TerminalPanel {
             .detach_and_log_err(cx);
     }
 
-    fn terminals_for_task(
-        &self,
-        label: &str,
-        cx: &mut AppContext,
-    ) -> Vec<(usize, View<TerminalView>)> {
-        self.pane
-            .read(cx)
-            .items()
-            .enumerate()
-            .filter_map(|(index, item)| Some((index, item.act_as::<TerminalView>(cx)?)))
-            .filter_map(|(index, terminal_view)| {
-                let task_state = terminal_view.read(cx).terminal().read(cx).task()?;
-                if &task_state.full_label == label {
-                    Some((index, terminal_view))
-                } else {
-                    None
-                }
+    fn terminals_for_task(&self, label: &str, cx: &mut AppContext) -> Vec<View<TerminalView>> {
+        self.center
+            .panes()
+            .into_iter()
+            .flat_map(|pane| {
+                pane.read(cx)
+                    .items()
+                    .filter_map(|item| item.act_as::<TerminalView>(cx))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|terminal_view| {
+                terminal_view
+                    .read(cx)
+                    .terminal()
+                    .read(cx)
+                    .task()
+                    .is_some_and(|task_state| task_state.full_label == label)
             })
             .collect()
     }
 
-    fn activate_terminal_view(&self, item_index: usize, cx: &mut WindowContext) {
-        self.pane.update(cx, |pane, cx| {
-            pane.activate_item(item_index, true, true, cx)
-        })
+    fn pane_for_terminal(
+        &self,
+        terminal_view: &View<TerminalView>,
+        cx: &AppContext,
+    ) -> Option<View<Pane>> {
+        self.center
+            .panes()
+            .into_iter()
+            .find(|pane| pane.read(cx).index_for_item(terminal_view).is_some())
+            .cloned()
+    }
+
+    /// Returns the split next to the active one in the direction of `split`, splitting the
+    /// active one if there is none.
+    fn pane_for_split(
+        &mut self,
+        split: TerminalSplit,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<Pane>> {
+        let direction = match split {
+            TerminalSplit::Up => SplitDirection::Up,
+            TerminalSplit::Down => SplitDirection::Down,
+            TerminalSplit::Left => SplitDirection::Left,
+            TerminalSplit::Right => SplitDirection::Right,
+        };
+        if let Some(pane) = self
+            .center
+            .find_pane_in_direction(&self.active_pane, direction, cx)
+            .cloned()
+        {
+            return Some(pane);
+        }
+
+        let new_pane = self.new_pane(cx)?;
+        self.center
+            .split(&self.active_pane, &new_pane, direction)
+            .log_err()?;
+        Some(new_pane)
+    }
+
+    fn activate_terminal_view(&self, terminal_view: &View<TerminalView>, cx: &mut WindowContext) {
+        if let Some(pane) = self.pane_for_terminal(terminal_view, cx) {
+            pane.update(cx, |pane, cx| {
+                if let Some(item_index) = pane.index_for_item(terminal_view) {
+                    pane.activate_item(item_index, true, true, cx)
+                }
+            })
+        }
     }
 
     fn add_terminal(
@@ -593,11 +835,27 @@ impl TerminalPanel {
             )));
         }
 
+        // Task terminals go into the split that holds the previous runs of the same task,
+        // so that a task can be kept in a split of its own, or else into the split they target.
+        let pane = match &kind {
+            TerminalKind::Task(spawn_task) => {
+                let previous_pane = self
+                    .terminals_for_task(&spawn_task.full_label, cx)
+                    .last()
+                    .and_then(|terminal_view| self.pane_for_terminal(terminal_view, cx));
+                previous_pane.or_else(|| {
+                    spawn_task
+                        .split
+                        .and_then(|split| self.pane_for_split(split, cx))
+                })
+            }
+            TerminalKind::Shell(_) => None,
+        }
+        .unwrap_or_else(|| self.active_pane.clone());
         let workspace = self.workspace.clone();
         self.pending_terminals_to_add += 1;
 
         cx.spawn(|terminal_panel, mut cx| async move {
-            let pane = terminal_panel.update(&mut cx, |this, _| this.pane.clone())?;
             let result = workspace.update(&mut cx, |workspace, cx| {
                 let window = cx.window_handle();
                 let terminal = workspace
@@ -631,23 +889,13 @@ impl TerminalPanel {
 
     fn serialize(&mut self, cx: &mut ViewContext<Self>) {
         let mut items_to_serialize = HashSet::default();
-        let items = self
-            .pane
-            .read(cx)
-            .items()
-            .filter_map(|item| {
-                let terminal_view = item.act_as::<TerminalView>(cx)?;
-                if terminal_view.read(cx).terminal().read(cx).task().is_some() {
-                    None
-                } else {
-                    let id = item.item_id().as_u64();
-                    items_to_serialize.insert(id);
-                    Some(id)
-                }
-            })
-            .collect::<Vec<_>>();
+        let items = SerializedItems::WithSplits(serialize_pane_group(
+            &self.center.root,
+            &mut items_to_serialize,
+            cx,
+        ));
         let active_item_id = self
-            .pane
+            .active_pane
             .read(cx)
             .active_item()
             .map(|item| item.item_id().as_u64())
@@ -676,7 +924,6 @@ impl TerminalPanel {
     fn replace_terminal(
         &self,
         spawn_task: SpawnInTerminal,
-        terminal_item_index: usize,
         terminal_to_replace: View<TerminalView>,
         cx: &mut ViewContext<'_, Self>,
     ) -> Option<()> {
@@ -698,7 +945,7 @@ impl TerminalPanel {
 
         match reveal {
             RevealStrategy::Always => {
-                self.activate_terminal_view(terminal_item_index, cx);
+                self.activate_terminal_view(&terminal_to_replace, cx);
                 let task_workspace = self.workspace.clone();
                 cx.spawn(|_, mut cx| async move {
                     task_workspace
@@ -714,7 +961,15 @@ impl TerminalPanel {
     }
 
     fn has_no_terminals(&self, cx: &WindowContext) -> bool {
-        self.pane.read(cx).items_len() == 0 && self.pending_terminals_to_add == 0
+        self.terminal_count(cx) == 0 && self.pending_terminals_to_add == 0
+    }
+
+    fn terminal_count(&self, cx: &WindowContext) -> usize {
+        self.center
+            .panes()
+            .into_iter()
+            .map(|pane| pane.read(cx).items_len())
+            .sum()
     }
 
     pub fn assistant_enabled(&self) -> bool {
@@ -722,11 +977,165 @@ impl TerminalPanel {
     }
 }
 
+fn new_terminal_pane(
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    cx: &mut ViewContext<TerminalPanel>,
+) -> View<Pane> {
+    let is_local = project.read(cx).is_local();
+    let terminal_panel = cx.view().downgrade();
+    let pane = cx.new_view(|cx| {
+        let mut pane = Pane::new(
+            workspace.clone(),
+            project.clone(),
+            Default::default(),
+            None,
+            NewTerminal.boxed_clone(),
+            cx,
+        );
+        pane.set_can_navigate(false, cx);
+        pane.display_nav_history_buttons(None);
+        pane.set_should_display_tab_bar(|_| true);
+
+        pane.set_custom_drop_handle(cx, move |pane, dropped_item, cx| {
+            if let Some(tab) = dropped_item.downcast_ref::<DraggedTab>() {
+                let item = if &tab.pane == cx.view() {
+                    pane.item_for_index(tab.ix)
+                } else {
+                    tab.pane.read(cx).item_for_index(tab.ix)
+                };
+                if let Some(item) = item {
+                    if item.downcast::<TerminalView>().is_some() {
+                        let Some(split_direction) = pane.drag_split_direction() else {
+                            return ControlFlow::Continue(());
+                        };
+                        let target_pane = cx.view().clone();
+                        let source_pane = tab.pane.clone();
+                        let item_id = item.item_id();
+                        let terminal_panel = terminal_panel.clone();
+                        cx.spawn(|_, mut cx| async move {
+                            terminal_panel
+                                .update(&mut cx, |terminal_panel, cx| {
+                                    terminal_panel.split_with_terminal(
+                                        &target_pane,
+                                        &source_pane,
+                                        item_id,
+                                        split_direction,
+                                        cx,
+                                    );
+                                })
+                                .ok();
+                        })
+                        .detach();
+                    } else if let Some(project_path) = item.project_path(cx) {
+                        if let Some(entry_path) = workspace
+                            .update(cx, |workspace, cx| {
+                                workspace
+                                    .project()
+                                    .read(cx)
+                                    .absolute_path(&project_path, cx)
+                            })
+                            .log_err()
+                            .flatten()
+                        {
+                            add_paths_to_terminal(pane, &[entry_path], cx);
+                        }
+                    }
+                }
+            } else if let Some(&entry_id) = dropped_item.downcast_ref::<ProjectEntryId>() {
+                if let Some(entry_path) = workspace
+                    .update(cx, |workspace, cx| {
+                        let project = workspace.project().read(cx);
+                        project
+                            .path_for_entry(entry_id, cx)
+                            .and_then(|project_path| project.absolute_path(&project_path, cx))
+                    })
+                    .log_err()
+                    .flatten()
+                {
+                    add_paths_to_terminal(pane, &[entry_path], cx);
+                }
+            } else if is_local {
+                if let Some(paths) = dropped_item.downcast_ref::<ExternalPaths>() {
+                    add_paths_to_terminal(pane, paths.paths(), cx);
+                }
+            }
+
+            ControlFlow::Break(())
+        });
+        let buffer_search_bar = cx.new_view(search::BufferSearchBar::new);
+        pane.toolbar()
+            .update(cx, |toolbar, cx| toolbar.add_item(buffer_search_bar, cx));
+        pane
+    });
+    cx.observe(&pane, |_, _, cx| cx.notify()).detach();
+    cx.subscribe(&pane, TerminalPanel::handle_pane_event)
+        .detach();
+    pane
+}
+
+fn deserialize_terminals(
+    pane: &View<Pane>,
+    item_ids: &[u64],
+    workspace: &Workspace,
+    database_id: WorkspaceId,
+    cx: &mut WindowContext,
+) -> Vec<Task<Result<View<TerminalView>>>> {
+    pane.update(cx, |_, cx| {
+        item_ids
+            .iter()
+            .map(|item_id| {
+                TerminalView::deserialize(
+                    workspace.project().clone(),
+                    workspace.weak_handle(),
+                    database_id,
+                    *item_id,
+                    cx,
+                )
+            })
+            .collect()
+    })
+}
+
+fn serialize_pane_group(
+    member: &Member,
+    items_to_serialize: &mut HashSet<u64>,
+    cx: &WindowContext,
+) -> SerializedPaneGroup {
+    match member {
+        Member::Axis(axis) => SerializedPaneGroup::Group {
+            axis: axis.axis.into(),
+            flexes: Some(axis.flexes.lock().clone()),
+            children: axis
+                .members
+                .iter()
+                .map(|member| serialize_pane_group(member, items_to_serialize, cx))
+                .collect(),
+        },
+        Member::Pane(pane) => SerializedPaneGroup::Pane(SerializedPane {
+            children: pane
+                .read(cx)
+                .items()
+                .filter_map(|item| {
+                    let terminal_view = item.act_as::<TerminalView>(cx)?;
+                    if terminal_view.read(cx).terminal().read(cx).task().is_some() {
+                        None
+                    } else {
+                        let id = item.item_id().as_u64();
+                        items_to_serialize.insert(id);
+                        Some(id)
+                    }
+                })
+                .collect(),
+        }),
+    }
+}
+
 async fn wait_for_terminals_tasks(
-    terminals_for_task: Vec<(usize, View<TerminalView>)>,
+    terminals_for_task: Vec<View<TerminalView>>,
     cx: &mut AsyncWindowContext,
 ) {
-    let pending_tasks = terminals_for_task.iter().filter_map(|(_, terminal)| {
+    let pending_tasks = terminals_for_task.iter().filter_map(|terminal| {
         terminal
             .update(cx, |terminal_view, cx| {
                 terminal_view
@@ -761,7 +1170,7 @@ impl Render for TerminalPanel {
         let mut registrar = DivRegistrar::new(
             |panel, cx| {
                 panel
-                    .pane
+                    .active_pane
                     .read(cx)
                     .toolbar()
                     .read(cx)
@@ -770,13 +1179,46 @@ impl Render for TerminalPanel {
             cx,
         );
         BufferSearchBar::register(&mut registrar);
-        registrar.into_div().size_full().child(self.pane.clone())
+        let registrar = registrar
+            .into_div()
+            .size_full()
+            .on_action(
+                cx.listener(|terminal_panel, action: &ActivatePaneInDirection, cx| {
+                    terminal_panel.activate_pane_in_direction(action.0, cx);
+                }),
+            )
+            .on_action(
+                cx.listener(|terminal_panel, action: &SwapPaneInDirection, cx| {
+                    terminal_panel.swap_pane_in_direction(action.0, cx);
+                }),
+            )
+            .on_action(cx.listener(|terminal_panel, action: &ActivatePane, cx| {
+                terminal_panel.activate_pane_at_index(action.0, cx);
+            }))
+            .on_action(cx.listener(|terminal_panel, _: &ActivateNextPane, cx| {
+                terminal_panel.activate_next_pane(cx);
+            }))
+            .on_action(cx.listener(|terminal_panel, _: &ActivatePreviousPane, cx| {
+                terminal_panel.activate_previous_pane(cx);
+            }));
+        self.workspace
+            .update(cx, |workspace, cx| {
+                registrar
+                    .child(self.center.render_in_panel(
+                        workspace.project(),
+                        &self.active_pane,
+                        workspace.app_state(),
+                        cx,
+                    ))
+                    .into_any_element()
+            })
+            .unwrap_or_else(|_| div().into_any_element())
     }
 }
 
 impl FocusableView for TerminalPanel {
     fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
-        self.pane.focus_handle(cx)
+        self.active_pane.focus_handle(cx)
     }
 }
 
@@ -827,12 +1269,15 @@ impl Panel for TerminalPanel {
         cx.notify();
     }
 
-    fn is_zoomed(&self, cx: &WindowContext) -> bool {
-        self.pane.read(cx).is_zoomed()
+    fn is_zoomed(&self, _: &WindowContext) -> bool {
+        self.zoomed
     }
 
     fn set_zoomed(&mut self, zoomed: bool, cx: &mut ViewContext<Self>) {
-        self.pane.update(cx, |pane, cx| pane.set_zoomed(zoomed, cx));
+        self.zoomed = zoomed;
+        for pane in self.center.panes() {
+            pane.update(cx, |pane, cx| pane.set_zoomed(zoomed, cx));
+        }
     }
 
     fn set_active(&mut self, active: bool, cx: &mut ViewContext<Self>) {
@@ -852,7 +1297,7 @@ impl Panel for TerminalPanel {
     }
 
     fn icon_label(&self, cx: &WindowContext) -> Option<String> {
-        let count = self.pane.read(cx).items_len();
+        let count = self.terminal_count(cx);
         if count == 0 {
             None
         } else {
@@ -881,7 +1326,7 @@ impl Panel for TerminalPanel {
     }
 
     fn pane(&self) -> Option<View<Pane>> {
-        Some(self.pane.clone())
+        Some(self.active_pane.clone())
     }
 }
 
@@ -905,12 +1350,59 @@ impl Render for InlineAssistTabBarButton {
 
 #[derive(Serialize, Deserialize)]
 struct SerializedTerminalPanel {
-    items: Vec<u64>,
+    items: SerializedItems,
     active_item_id: Option<u64>,
     width: Option<Pixels>,
     height: Option<Pixels>,
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum SerializedItems {
+    /// The terminals of a panel saved before it could be split.
+    NoSplits(Vec<u64>),
+    WithSplits(SerializedPaneGroup),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum SerializedPaneGroup {
+    Pane(SerializedPane),
+    Group {
+        axis: SerializedAxis,
+        flexes: Option<Vec<f32>>,
+        children: Vec<SerializedPaneGroup>,
+    },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct SerializedPane {
+    children: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum SerializedAxis {
+    Horizontal,
+    Vertical,
+}
+
+impl From<Axis> for SerializedAxis {
+    fn from(axis: Axis) -> Self {
+        match axis {
+            Axis::Horizontal => Self::Horizontal,
+            Axis::Vertical => Self::Vertical,
+        }
+    }
+}
+
+impl From<SerializedAxis> for Axis {
+    fn from(axis: SerializedAxis) -> Self {
+        match axis {
+            SerializedAxis::Horizontal => Self::Horizontal,
+            SerializedAxis::Vertical => Self::Vertical,
+        }
+    }
+}
+
 fn retrieve_system_shell() -> Option<String> {
     #[cfg(not(target_os = "windows"))]
     {
@@ -1004,3 +1496,243 @@ enum WindowsShellType {
     Cmd,
     Other,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use task::HideStrategy;
+    use workspace::AppState;
+
+    #[test]
+    fn test_serialized_panel_formats() {
+        let panel = serde_json::from_str::<SerializedTerminalPanel>(
+            r#"{"items":[1,2],"active_item_id":2,"width":null,"height":320.0}"#,
+        )
+        .unwrap();
+        assert_eq!(panel.items, SerializedItems::NoSplits(vec![1, 2]));
+        assert_eq!(panel.active_item_id, Some(2));
+
+        let items = SerializedItems::WithSplits(SerializedPaneGroup::Group {
+            axis: SerializedAxis::Horizontal,
+            flexes: Some(vec![0.5, 1.5]),
+            children: vec![
+                SerializedPaneGroup::Pane(SerializedPane {
+                    children: vec![1, 2],
+                }),
+                SerializedPaneGroup::Pane(SerializedPane { children: vec![3] }),
+            ],
+        });
+        let json = serde_json::to_string(&items).unwrap();
+        assert_eq!(
+            serde_json::from_str::<SerializedItems>(&json).unwrap(),
+            items
+        );
+    }
+
+    #[gpui::test]
+    async fn test_split_and_close_pane(cx: &mut TestAppContext) {
+        let (terminal_panel, cx) = init_test(cx).await;
+        add_terminal(&terminal_panel, TerminalKind::Shell(None), cx).await;
+        let first_pane = terminal_panel.update(cx, |panel, _| panel.active_pane.clone());
+        cx.focus_view(&first_pane);
+
+        first_pane.update(cx, |pane, cx| pane.split(SplitDirection::Right, cx));
+        cx.run_until_parked();
+        let second_pane = terminal_panel.update(cx, |panel, cx| {
+            assert_eq!(panel.center.panes().len(), 2);
+            assert_eq!(panel.terminal_count(cx), 2);
+            assert_ne!(panel.active_pane, first_pane);
+            assert!(panel.active_pane.read(cx).has_focus(cx));
+            assert_eq!(panel.active_pane.read(cx).items_len(), 1);
+            panel.active_pane.clone()
+        });
+
+        terminal_panel.update(cx, |panel, cx| {
+            panel.activate_pane_in_direction(SplitDirection::Left, cx)
+        });
+        cx.run_until_parked();
+        terminal_panel.update(cx, |panel, _| assert_eq!(panel.active_pane, first_pane));
+
+        // Closing the last terminal of a split removes it and focuses the split it was
+        // opened from.
+        cx.focus_view(&second_pane);
+        cx.run_until_parked();
+        second_pane.update(cx, |pane, cx| pane.remove_item(0, false, true, cx));
+        cx.run_until_parked();
+        terminal_panel.update(cx, |panel, cx| {
+            assert_eq!(panel.center.panes(), vec![&first_pane]);
+            assert_eq!(panel.active_pane, first_pane);
+            assert!(first_pane.read(cx).has_focus(cx));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_task_terminals_stay_in_their_split(cx: &mut TestAppContext) {
+        let (terminal_panel, cx) = init_test(cx).await;
+        add_terminal(&terminal_panel, TerminalKind::Task(task("build")), cx).await;
+        let task_pane = terminal_panel.update(cx, |panel, _| panel.active_pane.clone());
+        cx.focus_view(&task_pane);
+
+        task_pane.update(cx, |pane, cx| pane.split(SplitDirection::Down, cx));
+        cx.run_until_parked();
+        let shell_pane = terminal_panel.update(cx, |panel, _| panel.active_pane.clone());
+        assert_ne!(shell_pane, task_pane);
+
+        add_terminal(&terminal_panel, TerminalKind::Task(task("build")), cx).await;
+        add_terminal(&terminal_panel, TerminalKind::Task(task("test")), cx).await;
+        cx.run_until_parked();
+        terminal_panel.update(cx, |panel, cx| {
+            assert_eq!(panel.terminals_for_task("build", cx).len(), 2);
+            for terminal_view in panel.terminals_for_task("build", cx) {
+                assert_eq!(
+                    panel.pane_for_terminal(&terminal_view, cx),
+                    Some(task_pane.clone())
+                );
+            }
+            // Tasks that have not run yet open in the active split.
+            let test_terminals = panel.terminals_for_task("test", cx);
+            assert_eq!(test_terminals.len(), 1);
+            assert_eq!(
+                panel.pane_for_terminal(&test_terminals[0], cx),
+                Some(shell_pane.clone())
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_task_terminals_open_in_their_target_split(cx: &mut TestAppContext) {
+        let (terminal_panel, cx) = init_test(cx).await;
+        add_terminal(&terminal_panel, TerminalKind::Shell(None), cx).await;
+        let shell_pane = terminal_panel.update(cx, |panel, _| panel.active_pane.clone());
+        cx.focus_view(&shell_pane);
+
+        let build = SpawnInTerminal {
+            split: Some(TerminalSplit::Right),
+            ..task("build")
+        };
+        add_terminal(&terminal_panel, TerminalKind::Task(build.clone()), cx).await;
+        let build_pane = terminal_panel.update(cx, |panel, cx| {
+            assert_eq!(panel.center.panes().len(), 2);
+            assert_eq!(panel.active_pane, shell_pane);
+            let build_terminals = panel.terminals_for_task("build", cx);
+            assert_eq!(build_terminals.len(), 1);
+            let build_pane = panel.pane_for_terminal(&build_terminals[0], cx).unwrap();
+            assert_ne!(build_pane, shell_pane);
+            build_pane
+        });
+
+        // Tasks targeting the same direction share the split that is there.
+        let lint = SpawnInTerminal {
+            split: Some(TerminalSplit::Right),
+            ..task("lint")
+        };
+        add_terminal(&terminal_panel, TerminalKind::Task(lint), cx).await;
+        add_terminal(&terminal_panel, TerminalKind::Task(build), cx).await;
+        let test = SpawnInTerminal {
+            split: Some(TerminalSplit::Down),
+            ..task("test")
+        };
+        add_terminal(&terminal_panel, TerminalKind::Task(test), cx).await;
+        terminal_panel.update(cx, |panel, cx| {
+            assert_eq!(panel.center.panes().len(), 3);
+            assert_eq!(panel.active_pane, shell_pane);
+            assert_eq!(panel.terminals_for_task("build", cx).len(), 2);
+            for terminal_view in panel
+                .terminals_for_task("build", cx)
+                .into_iter()
+                .chain(panel.terminals_for_task("lint", cx))
+            {
+                assert_eq!(
+                    panel.pane_for_terminal(&terminal_view, cx),
+                    Some(build_pane.clone())
+                );
+            }
+            let test_terminals = panel.terminals_for_task("test", cx);
+            assert_eq!(test_terminals.len(), 1);
+            let test_pane = panel.pane_for_terminal(&test_terminals[0], cx).unwrap();
+            assert_ne!(test_pane, shell_pane);
+            assert_ne!(test_pane, build_pane);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_zoom_applies_to_all_splits(cx: &mut TestAppContext) {
+        let (terminal_panel, cx) = init_test(cx).await;
+        add_terminal(&terminal_panel, TerminalKind::Shell(None), cx).await;
+        let first_pane = terminal_panel.update(cx, |panel, _| panel.active_pane.clone());
+        cx.focus_view(&first_pane);
+
+        terminal_panel.update(cx, |panel, cx| {
+            panel.set_zoomed(true, cx);
+            assert!(panel.is_zoomed(cx));
+        });
+        first_pane.update(cx, |pane, cx| pane.split(SplitDirection::Right, cx));
+        cx.run_until_parked();
+        terminal_panel.update(cx, |panel, cx| {
+            assert_ne!(panel.active_pane, first_pane);
+            for pane in panel.center.panes() {
+                assert!(pane.read(cx).is_zoomed());
+            }
+
+            panel.set_zoomed(false, cx);
+            assert!(!panel.is_zoomed(cx));
+            for pane in panel.center.panes() {
+                assert!(!pane.read(cx).is_zoomed());
+            }
+        });
+    }
+
+    async fn init_test(cx: &mut TestAppContext) -> (View<TerminalPanel>, &mut VisualTestContext) {
+        cx.executor().allow_parking();
+        let app_state = cx.update(AppState::test);
+        cx.update(|cx| {
+            terminal::init(cx);
+            language::init(cx);
+            editor::init(cx);
+            Project::init_settings(cx);
+        });
+
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        let terminal_panel = workspace.update(cx, |workspace, cx| {
+            let terminal_panel = cx.new_view(|cx| TerminalPanel::new(workspace, cx));
+            workspace.add_panel(terminal_panel.clone(), cx);
+            terminal_panel
+        });
+        (terminal_panel, cx)
+    }
+
+    async fn add_terminal(
+        terminal_panel: &View<TerminalPanel>,
+        kind: TerminalKind,
+        cx: &mut VisualTestContext,
+    ) {
+        terminal_panel
+            .update(cx, |panel, cx| {
+                panel.add_terminal(kind, RevealStrategy::Never, cx)
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+    }
+
+    fn task(label: &str) -> SpawnInTerminal {
+        SpawnInTerminal {
+            id: TaskId(label.to_string()),
+            full_label: label.to_string(),
+            label: label.to_string(),
+            command: "true".to_string(),
+            args: Vec::new(),
+            command_label: label.to_string(),
+            cwd: None,
+            env: HashMap::default(),
+            use_new_terminal: true,
+            allow_concurrent_runs: true,
+            reveal: RevealStrategy::Never,
+            hide: HideStrategy::Never,
+            split: None,
+            shell: Shell::System,
+        }
+    }
+}
@@ -20,9 +20,10 @@ use terminal::{
         term::{search::RegexSearch, TermMode},
     },
     terminal_settings::{CursorShape, TerminalBlink, TerminalSettings, WorkingDirectory},
-    Clear, Copy, Event, MaybeNavigationTarget, Paste, ScrollLineDown, ScrollLineUp, ScrollPageDown,
-    ScrollPageUp, ScrollToBottom, ScrollToTop, ShowCharacterPalette, TaskStatus, Terminal,
-    TerminalSize, ToggleViMode,
+    Clear, Copy, CopyLastCommandOutput, Event, MaybeNavigationTarget, Paste, RerunCommand,
+    ScrollLineDown, ScrollLineUp, ScrollPageDown, ScrollPageUp, ScrollToBottom, ScrollToNextPrompt,
+    ScrollToPreviousPrompt, ScrollToTop, SelectLastCommandOutput, ShowCharacterPalette, TaskStatus,
+    Terminal, TerminalSize, ToggleViMode,
 };
 use terminal_element::{is_blank, TerminalElement};
 use terminal_panel::TerminalPanel;
@@ -58,6 +59,8 @@ const REGEX_SPECIAL_CHARS: &[char] = &[
 
 const CURSOR_BLINK_INTERVAL: Duration = Duration::from_millis(500);
 
+const SCROLLBACK_SERIALIZATION_DELAY: Duration = Duration::from_secs(2);
+
 const GIT_DIFF_PATH_PREFIXES: &[char] = &['a', 'b'];
 
 ///Event to transmit the scroll from the element to the view
@@ -112,6 +115,7 @@ pub struct TerminalView {
     show_title: bool,
     block_below_cursor: Option<Rc<BlockProperties>>,
     scroll_top: Pixels,
+    scrollback_serialization: Option<Task<()>>,
     _subscriptions: Vec<Subscription>,
     _terminal_subscriptions: Vec<Subscription>,
 }
@@ -192,6 +196,7 @@ impl TerminalView {
             show_title: TerminalSettings::get_global(cx).toolbar.title,
             block_below_cursor: None,
             scroll_top: Pixels::ZERO,
+            scrollback_serialization: None,
             _subscriptions: vec![
                 focus_in,
                 focus_out,
@@ -226,6 +231,7 @@ impl TerminalView {
             .map_or(false, |terminal_panel| {
                 terminal_panel.read(cx).assistant_enabled()
             });
+        let shell_integration = self.terminal.read(cx).shell_marks_detected();
         let context_menu = ContextMenu::build(cx, |menu, _| {
             menu.context(self.focus_handle.clone())
                 .action("New Terminal", Box::new(NewTerminal))
@@ -234,6 +240,11 @@ impl TerminalView {
                 .action("Paste", Box::new(Paste))
                 .action("Select All", Box::new(SelectAll))
                 .action("Clear", Box::new(Clear))
+                .when(shell_integration, |menu| {
+                    menu.separator()
+                        .action("Copy Last Command Output", Box::new(CopyLastCommandOutput))
+                        .action("Rerun Command", Box::new(RerunCommand))
+                })
                 .when(assistant_enabled, |menu| {
                     menu.separator()
                         .action("Inline Assist", Box::new(InlineAssist::default()))
@@ -431,6 +442,43 @@ impl TerminalView {
         cx.notify();
     }
 
+    fn scroll_to_previous_prompt(
+        &mut self,
+        _: &ScrollToPreviousPrompt,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_previous_prompt());
+        cx.notify();
+    }
+
+    fn scroll_to_next_prompt(&mut self, _: &ScrollToNextPrompt, cx: &mut ViewContext<Self>) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_next_prompt());
+        cx.notify();
+    }
+
+    fn select_last_command_output(
+        &mut self,
+        _: &SelectLastCommandOutput,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.select_last_command_output());
+        cx.notify();
+    }
+
+    fn copy_last_command_output(&mut self, _: &CopyLastCommandOutput, cx: &mut ViewContext<Self>) {
+        self.terminal
+            .update(cx, |term, cx| term.copy_last_command_output(cx));
+    }
+
+    fn rerun_command(&mut self, _: &RerunCommand, cx: &mut ViewContext<Self>) {
+        self.clear_bell(cx);
+        self.terminal.update(cx, |term, _| term.rerun_command());
+        cx.notify();
+    }
+
     fn toggle_vi_mode(&mut self, _: &ToggleViMode, cx: &mut ViewContext<Self>) {
         self.terminal.update(cx, |term, _| term.toggle_vi_mode());
         cx.notify();
@@ -489,6 +537,40 @@ impl TerminalView {
         .detach();
     }
 
+    /// Saves the scrollback and title of the terminal once its output has settled, since
+    /// snapshotting the whole scrollback on every burst of output would be wasteful.
+    fn serialize_scrollback(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(workspace_id) = self.workspace_id else {
+            return;
+        };
+        // Task output may contain secrets, so task terminals are never persisted.
+        if !TerminalSettings::get_global(cx).restore_scrollback
+            || self.terminal.read(cx).task().is_some()
+        {
+            return;
+        }
+
+        let item_id = cx.view().entity_id().as_u64();
+        self.scrollback_serialization = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor()
+                .timer(SCROLLBACK_SERIALIZATION_DELAY)
+                .await;
+            let Ok((scrollback, title)) = this.update(&mut cx, |this, cx| {
+                let terminal = this.terminal.read(cx);
+                (
+                    terminal.scrollback_text(),
+                    Some(terminal.breadcrumb_text.clone()),
+                )
+            }) else {
+                return;
+            };
+            TERMINAL_DB
+                .save_scrollback(item_id, workspace_id, scrollback, title)
+                .await
+                .log_err();
+        }));
+    }
+
     pub fn terminal(&self) -> &Model<Terminal> {
         &self.terminal
     }
@@ -642,6 +724,7 @@ fn subscribe_for_terminal_events(
                 cx.emit(Event::Wakeup);
                 cx.emit(ItemEvent::UpdateTab);
                 cx.emit(SearchEvent::MatchesInvalidated);
+                this.serialize_scrollback(cx);
             }
 
             Event::Bell => {
@@ -988,6 +1071,11 @@ impl Render for TerminalView {
             .on_action(cx.listener(TerminalView::scroll_page_down))
             .on_action(cx.listener(TerminalView::scroll_to_top))
             .on_action(cx.listener(TerminalView::scroll_to_bottom))
+            .on_action(cx.listener(TerminalView::scroll_to_previous_prompt))
+            .on_action(cx.listener(TerminalView::scroll_to_next_prompt))
+            .on_action(cx.listener(TerminalView::select_last_command_output))
+            .on_action(cx.listener(TerminalView::copy_last_command_output))
+            .on_action(cx.listener(TerminalView::rerun_command))
             .on_action(cx.listener(TerminalView::toggle_vi_mode))
             .on_action(cx.listener(TerminalView::show_character_palette))
             .on_action(cx.listener(TerminalView::select_all))
@@ -1184,23 +1272,43 @@ impl SerializableItem for TerminalView {
         &mut self,
         _workspace: &mut Workspace,
         item_id: workspace::ItemId,
-        _closing: bool,
+        closing: bool,
         cx: &mut ViewContext<Self>,
     ) -> Option<Task<gpui::Result<()>>> {
         let terminal = self.terminal().read(cx);
+        // Task output may contain secrets, so task terminals are never persisted.
         if terminal.task().is_some() {
             return None;
         }
+        let cwd = terminal.working_directory();
+
+        let workspace_id = self.workspace_id?;
+        // The scrollback is saved as output settles, which a closing workspace can't wait for.
+        let scrollback = if !TerminalSettings::get_global(cx).restore_scrollback {
+            Some((None, None))
+        } else if closing {
+            self.scrollback_serialization = None;
+            Some((
+                terminal.scrollback_text(),
+                Some(terminal.breadcrumb_text.clone()),
+            ))
+        } else {
+            None
+        };
 
-        if let Some((cwd, workspace_id)) = terminal.working_directory().zip(self.workspace_id) {
-            Some(cx.background_executor().spawn(async move {
+        Some(cx.background_executor().spawn(async move {
+            if let Some(cwd) = cwd {
                 TERMINAL_DB
                     .save_working_directory(item_id, workspace_id, cwd)
-                    .await
-            }))
-        } else {
-            None
-        }
+                    .await?;
+            }
+            if let Some((scrollback, title)) = scrollback {
+                TERMINAL_DB
+                    .save_scrollback(item_id, workspace_id, scrollback, title)
+                    .await?;
+            }
+            Ok(())
+        }))
     }
 
     fn should_serialize(&self, event: &Self::Event) -> bool {
@@ -1236,9 +1344,30 @@ impl SerializableItem for TerminalView {
                 .ok()
                 .flatten();
 
+            let restore_scrollback =
+                cx.update(|cx| TerminalSettings::get_global(cx).restore_scrollback)?;
+            let (scrollback, title) = if restore_scrollback {
+                TERMINAL_DB
+                    .get_scrollback(item_id, workspace_id)
+                    .await
+                    .log_err()
+                    .flatten()
+                    .unwrap_or_default()
+            } else {
+                (None, None)
+            };
+
             let terminal = project.update(&mut cx, |project, cx| {
-                project.create_terminal(TerminalKind::Shell(cwd), window, cx)
+                project.create_terminal_with_scrollback(
+                    TerminalKind::Shell(cwd),
+                    scrollback.as_deref().filter(|text| !text.is_empty()),
+                    window,
+                    cx,
+                )
             })??;
+            if let Some(title) = title.filter(|_| scrollback.is_some_and(|text| !text.is_empty())) {
+                terminal.update(&mut cx, |terminal, cx| terminal.restore_title(title, cx))?;
+            }
             pane.update(&mut cx, |_, cx| {
                 cx.new_view(|cx| TerminalView::new(terminal, workspace, Some(workspace_id), cx))
             })
@@ -1314,6 +1443,8 @@ impl SearchableItem for TerminalView {
                 .unwrap()),
             ),
             SearchQuery::Regex { .. } => regex_search_for_query(&query),
+            // Terminal content has no syntax tree to match against.
+            SearchQuery::Structural { .. } => None,
         };
 
         if let Some(s) = searcher {
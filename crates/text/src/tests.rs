@@ -537,6 +537,109 @@ fn test_undo_redo() {
     assert_eq!(buffer.text(), "1234");
 }
 
+#[test]
+fn test_undo_tree() {
+    let mut buffer = Buffer::new(0, BufferId::new(1).unwrap(), "abc".into());
+    buffer.set_group_interval(Duration::from_secs(0));
+    let now = Instant::now();
+
+    buffer.start_transaction_at(now + Duration::from_secs(1));
+    buffer.edit([(0..1, "")]);
+    buffer.end_transaction_at(now + Duration::from_secs(1));
+    buffer.start_transaction_at(now + Duration::from_secs(2));
+    buffer.edit([(0..1, "")]);
+    buffer.end_transaction_at(now + Duration::from_secs(2));
+    assert_eq!(buffer.text(), "c");
+
+    // Editing after undoing starts a new branch, rather than discarding the old one.
+    buffer.undo();
+    assert_eq!(buffer.undo_tree().current(), 1);
+    buffer.start_transaction_at(now + Duration::from_secs(3));
+    buffer.edit([(0..0, "X")]);
+    buffer.end_transaction_at(now + Duration::from_secs(3));
+    assert_eq!(buffer.text(), "Xbc");
+    assert!(buffer.peek_redo_stack().is_none());
+
+    let undo_tree = buffer.undo_tree();
+    assert_eq!(undo_tree.len(), 4);
+    assert_eq!(undo_tree.current(), 3);
+    assert_eq!(undo_tree.children(1).collect::<Vec<_>>(), vec![2, 3]);
+    assert_eq!(undo_tree.common_ancestor(2, 3), 1);
+    assert_eq!(undo_tree.node(2).unwrap().text.to_string(), "c");
+
+    // Restoring a node on another branch makes the undo stack lead to it.
+    buffer.restore_undo_tree_node(2);
+    assert_eq!(buffer.text(), "c");
+    assert_eq!(buffer.undo_tree().current(), 2);
+    assert_eq!(buffer.undo_tree().len(), 4);
+    buffer.undo();
+    assert_eq!(buffer.text(), "bc");
+    assert_eq!(buffer.undo_tree().current(), 1);
+    buffer.undo();
+    assert_eq!(buffer.text(), "abc");
+    assert_eq!(buffer.undo_tree().current(), 0);
+    buffer.redo();
+    assert_eq!(buffer.text(), "bc");
+    assert_eq!(buffer.undo_tree().current(), 1);
+
+    // Navigating by steps follows the order in which states were created.
+    let undo_tree = buffer.undo_tree();
+    assert_eq!(undo_tree.earlier_by_steps(5), 0);
+    assert_eq!(undo_tree.later_by_steps(1), 2);
+    assert_eq!(undo_tree.later_by_steps(5), 3);
+
+    // Navigating by time follows the time at which states were edited.
+    assert_eq!(undo_tree.earlier_by_duration(Duration::from_millis(500)), 0);
+    assert_eq!(undo_tree.later_by_duration(Duration::from_millis(1500)), 2);
+    assert_eq!(undo_tree.later_by_duration(Duration::from_secs(10)), 3);
+
+    buffer.restore_undo_tree_node(3);
+    assert_eq!(buffer.text(), "Xbc");
+    buffer.restore_undo_tree_node(0);
+    assert_eq!(buffer.text(), "abc");
+}
+
+#[test]
+fn test_undo_tree_size_limit() {
+    let mut buffer = Buffer::new(0, BufferId::new(1).unwrap(), "".into());
+    buffer.set_group_interval(Duration::from_secs(0));
+    let mut now = Instant::now();
+
+    // Create a branch that doesn't lead to the current state.
+    buffer.start_transaction_at(now);
+    buffer.edit([(0..0, "a")]);
+    buffer.end_transaction_at(now);
+    buffer.undo();
+    for _ in 0..MAX_UNDO_TREE_NODES {
+        now += Duration::from_secs(1);
+        let len = buffer.len();
+        buffer.start_transaction_at(now);
+        buffer.edit([(len..len, "b")]);
+        buffer.end_transaction_at(now);
+    }
+    assert_eq!(buffer.text(), "b".repeat(MAX_UNDO_TREE_NODES));
+
+    let undo_tree = buffer.undo_tree();
+    assert!(undo_tree.len() <= MAX_UNDO_TREE_NODES);
+    assert!(undo_tree
+        .nodes()
+        .iter()
+        .all(|node| !node.text.to_string().contains('a')));
+    let current = undo_tree.current();
+    assert_eq!(current, undo_tree.len() - 1);
+    assert_eq!(undo_tree.depth(current), undo_tree.len() - 1);
+
+    // The remaining states can still be restored.
+    buffer.undo();
+    assert_eq!(buffer.undo_tree().current(), current - 1);
+    buffer.restore_undo_tree_node(0);
+    assert_eq!(buffer.undo_tree().current(), 0);
+    assert_eq!(
+        buffer.text(),
+        buffer.undo_tree().node(0).unwrap().text.to_string()
+    );
+}
+
 #[test]
 fn test_history() {
     let mut now = Instant::now();
@@ -9,6 +9,7 @@ pub mod subscription;
 #[cfg(test)]
 mod tests;
 mod undo_map;
+mod undo_tree;
 
 pub use anchor::*;
 use anyhow::{anyhow, Context as _, Result};
@@ -39,6 +40,7 @@ pub use subscription::*;
 pub use sum_tree::Bias;
 use sum_tree::{FilterCursor, SumTree, TreeMap};
 use undo_map::UndoMap;
+pub use undo_tree::{UndoTree, UndoTreeNode, MAX_UNDO_TREE_NODES};
 
 #[cfg(any(test, feature = "test-support"))]
 use util::RandomCharIter;
@@ -52,6 +54,7 @@ pub type TransactionId = clock::Lamport;
 pub struct Buffer {
     snapshot: BufferSnapshot,
     history: History,
+    undo_tree: UndoTree,
     deferred_ops: OperationQueue<Operation>,
     deferred_replicas: HashSet<ReplicaId>,
     pub lamport_clock: clock::Lamport,
@@ -133,6 +136,10 @@ impl HistoryEntry {
     pub fn transaction_id(&self) -> TransactionId {
         self.transaction.id
     }
+
+    pub fn transaction(&self) -> &Transaction {
+        &self.transaction
+    }
 }
 
 struct History {
@@ -698,6 +705,7 @@ impl Buffer {
                 version,
                 undo_map: Default::default(),
             },
+            undo_tree: UndoTree::new(history.base_text.clone(), Instant::now()),
             history,
             deferred_ops: OperationQueue::new(),
             deferred_replicas: HashSet::default(),
@@ -720,6 +728,7 @@ impl Buffer {
         Self {
             snapshot: self.snapshot.clone(),
             history: History::new(self.base_text().clone()),
+            undo_tree: UndoTree::new(self.visible_text.clone(), Instant::now()),
             deferred_ops: OperationQueue::new(),
             deferred_replicas: HashSet::default(),
             lamport_clock: clock::Lamport::new(LOCAL_BRANCH_REPLICA_ID),
@@ -1318,6 +1327,14 @@ impl Buffer {
         self.history.redo_stack.last()
     }
 
+    pub fn undo_stack(&self) -> &[HistoryEntry] {
+        &self.history.undo_stack
+    }
+
+    pub fn redo_stack(&self) -> &[HistoryEntry] {
+        &self.history.redo_stack
+    }
+
     pub fn start_transaction(&mut self) -> Option<TransactionId> {
         self.start_transaction_at(Instant::now())
     }
@@ -1335,6 +1352,7 @@ impl Buffer {
         if let Some(entry) = self.history.end_transaction(now) {
             let since = entry.transaction.start.clone();
             let id = self.history.group().unwrap();
+            self.record_undo_tree_state(true, now);
             Some((id, since))
         } else {
             None
@@ -1362,6 +1380,7 @@ impl Buffer {
             let transaction = entry.transaction.clone();
             let transaction_id = transaction.id;
             let op = self.undo_or_redo(transaction);
+            self.record_undo_tree_state(false, Instant::now());
             Some((transaction_id, op))
         } else {
             None
@@ -1374,7 +1393,9 @@ impl Buffer {
             .remove_from_undo(transaction_id)?
             .transaction
             .clone();
-        Some(self.undo_or_redo(transaction))
+        let op = self.undo_or_redo(transaction);
+        self.record_undo_tree_state(true, Instant::now());
+        Some(op)
     }
 
     pub fn undo_to_transaction(&mut self, transaction_id: TransactionId) -> Vec<Operation> {
@@ -1385,10 +1406,12 @@ impl Buffer {
             .map(|entry| entry.transaction.clone())
             .collect::<Vec<_>>();
 
-        transactions
+        let ops = transactions
             .into_iter()
             .map(|transaction| self.undo_or_redo(transaction))
-            .collect()
+            .collect();
+        self.record_undo_tree_state(false, Instant::now());
+        ops
     }
 
     pub fn forget_transaction(&mut self, transaction_id: TransactionId) {
@@ -1404,6 +1427,7 @@ impl Buffer {
             let transaction = entry.transaction.clone();
             let transaction_id = transaction.id;
             let op = self.undo_or_redo(transaction);
+            self.record_undo_tree_state(false, Instant::now());
             Some((transaction_id, op))
         } else {
             None
@@ -1418,10 +1442,12 @@ impl Buffer {
             .map(|entry| entry.transaction.clone())
             .collect::<Vec<_>>();
 
-        transactions
+        let ops = transactions
             .into_iter()
             .map(|transaction| self.undo_or_redo(transaction))
-            .collect()
+            .collect();
+        self.record_undo_tree_state(false, Instant::now());
+        ops
     }
 
     fn undo_or_redo(&mut self, transaction: Transaction) -> Operation {
@@ -1451,6 +1477,136 @@ impl Buffer {
     pub fn push_transaction(&mut self, transaction: Transaction, now: Instant) {
         self.history.push_transaction(transaction, now);
         self.history.finalize_last_transaction();
+        self.record_undo_tree_state(true, now);
+    }
+
+    pub fn undo_tree(&self) -> &UndoTree {
+        &self.undo_tree
+    }
+
+    fn record_undo_tree_state(&mut self, edited: bool, now: Instant) {
+        let transaction_id = self
+            .history
+            .undo_stack
+            .last()
+            .map(|entry| entry.transaction.id);
+        self.undo_tree
+            .record(transaction_id, self.visible_text.clone(), edited, now);
+    }
+
+    /// Returns the buffer to the state described by the given node of its undo
+    /// tree, by undoing back to the closest state shared with the current one
+    /// and then replaying each state on the way to the given node as a new
+    /// transaction. Afterwards, the undo stack leads to the given node.
+    pub fn restore_undo_tree_node(&mut self, ix: usize) -> Vec<Operation> {
+        let mut operations = Vec::new();
+        if ix >= self.undo_tree.len() || ix == self.undo_tree.current() {
+            return operations;
+        }
+
+        let ancestor = self.undo_tree.common_ancestor(self.undo_tree.current(), ix);
+        while self.undo_tree.current() != ancestor {
+            let Some((_, operation)) = self.undo() else {
+                break;
+            };
+            operations.push(operation);
+        }
+
+        let mut path = self
+            .undo_tree
+            .ancestors(ix)
+            .take_while(|node_ix| *node_ix != self.undo_tree.current())
+            .collect::<Vec<_>>();
+        path.reverse();
+        for node_ix in path {
+            let text = self.undo_tree.nodes()[node_ix].text.to_string();
+            let now = Instant::now();
+            self.history.finalize_last_transaction();
+            self.start_transaction_at(now);
+            self.undo_tree.start_restoring(node_ix);
+            if let Some(edit) = self.edit_to_match(&text) {
+                operations.push(self.edit([edit]));
+            }
+            self.end_transaction_at(now);
+            self.history.finalize_last_transaction();
+            self.undo_tree.finish_restoring(node_ix);
+        }
+        operations
+    }
+
+    /// Returns a single edit that replaces the text between the common prefix
+    /// and suffix of the buffer's text and the given text.
+    fn edit_to_match(&self, new_text: &str) -> Option<(Range<usize>, String)> {
+        let old_text = self.text();
+        if old_text == new_text {
+            return None;
+        }
+
+        let prefix_len = old_text
+            .char_indices()
+            .zip(new_text.chars())
+            .find(|((_, old), new)| old != new)
+            .map_or(old_text.len().min(new_text.len()), |((ix, _), _)| ix);
+        let suffix_len = old_text[prefix_len..]
+            .chars()
+            .rev()
+            .zip(new_text[prefix_len..].chars().rev())
+            .take_while(|(old, new)| old == new)
+            .map(|(old, _)| old.len_utf8())
+            .sum::<usize>();
+        Some((
+            prefix_len..old_text.len() - suffix_len,
+            new_text[prefix_len..new_text.len() - suffix_len].to_string(),
+        ))
+    }
+
+    /// Discards the undo tree, replacing it with a single branch that matches
+    /// the buffer's current undo and redo stacks.
+    pub fn reset_undo_tree(&mut self) {
+        let snapshot = self.snapshot.clone();
+        let now = Instant::now();
+        let root = self
+            .history
+            .undo_stack
+            .first()
+            .map_or(snapshot.visible_text.clone(), |entry| {
+                snapshot.rope_for_version(&entry.transaction.start)
+            });
+        let redone_state = |transaction: &Transaction| {
+            let mut version = transaction.start.clone();
+            for edit_id in &transaction.edit_ids {
+                version.observe(*edit_id);
+            }
+            snapshot.rope_for_version(&version)
+        };
+        let undo_states = self
+            .history
+            .undo_stack
+            .iter()
+            .zip(
+                self.history
+                    .undo_stack
+                    .iter()
+                    .skip(1)
+                    .map(|entry| Some(&entry.transaction))
+                    .chain([None]),
+            )
+            .map(|(entry, next)| {
+                let text = match next {
+                    Some(next) => snapshot.rope_for_version(&next.start),
+                    None => snapshot.visible_text.clone(),
+                };
+                (entry.transaction.id, text)
+            });
+        let redo_states = self
+            .history
+            .redo_stack
+            .iter()
+            .rev()
+            .map(|entry| (entry.transaction.id, redone_state(&entry.transaction)));
+        let current = self.history.undo_stack.len();
+        let states = undo_states.chain(redo_states).collect::<Vec<_>>();
+        self.undo_tree.reset(root, states, current, now);
     }
 
     pub fn edited_ranges_for_transaction_id<D>(
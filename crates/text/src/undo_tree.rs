@@ -0,0 +1,300 @@
+use crate::TransactionId;
+use collections::HashMap;
+use rope::Rope;
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    mem,
+    time::{Duration, Instant},
+};
+
+/// The maximum number of states kept in an [`UndoTree`]. When it is exceeded,
+/// the oldest states that the buffer can no longer undo back to are dropped.
+pub const MAX_UNDO_TREE_NODES: usize = 1000;
+
+/// A state that a buffer's text has been in, as recorded in its [`UndoTree`].
+#[derive(Clone, Debug)]
+pub struct UndoTreeNode {
+    /// The transaction at the top of the undo stack in this state, or `None`
+    /// if the state precedes every transaction in the undo stack.
+    pub transaction_id: Option<TransactionId>,
+    /// The state from which this state was reached by editing the buffer.
+    pub parent: Option<usize>,
+    /// The buffer's text in this state.
+    pub text: Rope,
+    /// When the buffer was last edited into this state.
+    pub timestamp: Instant,
+}
+
+/// Every state a buffer's text has been in, arranged as a tree.
+///
+/// The buffer's undo and redo stacks only describe the path from the root of
+/// this tree to its most recently edited leaf. Editing the buffer after undoing
+/// discards the redo stack, but the states it led to are kept here as a
+/// separate branch, which can be restored with [`crate::Buffer::restore_undo_tree_node`].
+///
+/// Nodes are stored in the order in which they were created. The tree holds at
+/// most [`MAX_UNDO_TREE_NODES`] nodes: beyond that, branches that don't lead to
+/// the current state are dropped, oldest first, followed by the oldest states
+/// leading to it.
+#[derive(Clone, Debug)]
+pub struct UndoTree {
+    nodes: Vec<UndoTreeNode>,
+    /// The newest node recorded for each transaction.
+    nodes_by_transaction: HashMap<Option<TransactionId>, usize>,
+    current: usize,
+    restoring: Option<usize>,
+}
+
+impl UndoTree {
+    pub(crate) fn new(text: Rope, now: Instant) -> Self {
+        Self {
+            nodes: vec![UndoTreeNode {
+                transaction_id: None,
+                parent: None,
+                text,
+                timestamp: now,
+            }],
+            nodes_by_transaction: HashMap::from_iter([(None, 0)]),
+            current: 0,
+            restoring: None,
+        }
+    }
+
+    pub fn nodes(&self) -> &[UndoTreeNode] {
+        &self.nodes
+    }
+
+    pub fn node(&self, ix: usize) -> Option<&UndoTreeNode> {
+        self.nodes.get(ix)
+    }
+
+    /// The index of the node describing the buffer's current state.
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn children(&self, ix: usize) -> impl '_ + Iterator<Item = usize> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(move |(_, node)| node.parent == Some(ix))
+            .map(|(child_ix, _)| child_ix)
+    }
+
+    /// Returns the given node followed by each of its ancestors, up to the root.
+    pub fn ancestors(&self, ix: usize) -> impl '_ + Iterator<Item = usize> {
+        std::iter::successors(Some(ix), |ix| self.nodes.get(*ix)?.parent)
+    }
+
+    /// Returns the number of ancestors of the given node.
+    pub fn depth(&self, ix: usize) -> usize {
+        self.ancestors(ix).count() - 1
+    }
+
+    pub fn common_ancestor(&self, a: usize, b: usize) -> usize {
+        let a_ancestors = self.ancestors(a).collect::<Vec<_>>();
+        self.ancestors(b)
+            .find(|ix| a_ancestors.contains(ix))
+            .unwrap_or(0)
+    }
+
+    /// Returns the node `steps` states before the current one, in the order in
+    /// which states were created.
+    pub fn earlier_by_steps(&self, steps: usize) -> usize {
+        self.current.saturating_sub(steps)
+    }
+
+    /// Returns the node `steps` states after the current one, in the order in
+    /// which states were created.
+    pub fn later_by_steps(&self, steps: usize) -> usize {
+        self.current.saturating_add(steps).min(self.nodes.len() - 1)
+    }
+
+    /// Returns the most recently edited node that was edited at least `duration`
+    /// before the current one, or the root if there is no such node.
+    pub fn earlier_by_duration(&self, duration: Duration) -> usize {
+        let current = &self.nodes[self.current];
+        current
+            .timestamp
+            .checked_sub(duration)
+            .and_then(|target| self.latest_at_or_before(target))
+            .unwrap_or(0)
+    }
+
+    /// Returns the most recently edited node that was edited at most `duration`
+    /// after the current one.
+    pub fn later_by_duration(&self, duration: Duration) -> usize {
+        let current = &self.nodes[self.current];
+        current
+            .timestamp
+            .checked_add(duration)
+            .and_then(|target| self.latest_at_or_before(target))
+            .filter(|ix| self.nodes[*ix].timestamp > current.timestamp)
+            .unwrap_or(self.current)
+    }
+
+    fn latest_at_or_before(&self, target: Instant) -> Option<usize> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.timestamp <= target)
+            .max_by_key(|(ix, node)| (node.timestamp, *ix))
+            .map(|(ix, _)| ix)
+    }
+
+    /// Records the buffer's state after its history changed.
+    ///
+    /// If the transaction at the top of the undo stack already has a node, that
+    /// node becomes current, and its text is updated if the buffer was edited
+    /// (rather than undone or redone) into that state. Otherwise, a new child
+    /// of the current node is created.
+    pub(crate) fn record(
+        &mut self,
+        transaction_id: Option<TransactionId>,
+        text: Rope,
+        edited: bool,
+        now: Instant,
+    ) {
+        if let Some(ix) = self.restoring.take() {
+            let node = &mut self.nodes[ix];
+            let old_transaction_id = mem::replace(&mut node.transaction_id, transaction_id);
+            node.text = text;
+            if self.nodes_by_transaction.get(&old_transaction_id) == Some(&ix) {
+                self.nodes_by_transaction.remove(&old_transaction_id);
+            }
+            self.nodes_by_transaction.insert(transaction_id, ix);
+            self.current = ix;
+        } else if let Some(&ix) = self.nodes_by_transaction.get(&transaction_id) {
+            self.current = ix;
+            if edited {
+                let node = &mut self.nodes[ix];
+                node.text = text;
+                node.timestamp = now;
+            }
+        } else {
+            self.nodes.push(UndoTreeNode {
+                transaction_id,
+                parent: Some(self.current),
+                text,
+                timestamp: now,
+            });
+            self.current = self.nodes.len() - 1;
+            self.nodes_by_transaction
+                .insert(transaction_id, self.current);
+            if self.nodes.len() > MAX_UNDO_TREE_NODES {
+                self.prune(MAX_UNDO_TREE_NODES - MAX_UNDO_TREE_NODES / 10);
+            }
+        }
+    }
+
+    /// Drops nodes until at most `max_len` remain. Branches that don't lead to
+    /// the current node are dropped first, starting with their oldest leaves,
+    /// and then the oldest nodes leading to the current node.
+    fn prune(&mut self, max_len: usize) {
+        let mut child_counts = vec![0; self.nodes.len()];
+        for node in &self.nodes {
+            if let Some(parent) = node.parent {
+                child_counts[parent] += 1;
+            }
+        }
+        let mut path = self.ancestors(self.current).collect::<Vec<_>>();
+        path.reverse();
+        let mut is_on_path = vec![false; self.nodes.len()];
+        for ix in &path {
+            is_on_path[*ix] = true;
+        }
+
+        let mut removed = vec![false; self.nodes.len()];
+        let mut remaining = self.nodes.len();
+        let mut leaves = (0..self.nodes.len())
+            .filter(|ix| child_counts[*ix] == 0 && !is_on_path[*ix])
+            .map(Reverse)
+            .collect::<BinaryHeap<_>>();
+        while remaining > max_len {
+            let Some(Reverse(ix)) = leaves.pop() else {
+                break;
+            };
+            removed[ix] = true;
+            remaining -= 1;
+            if let Some(parent) = self.nodes[ix].parent {
+                child_counts[parent] -= 1;
+                if child_counts[parent] == 0 && !is_on_path[parent] {
+                    leaves.push(Reverse(parent));
+                }
+            }
+        }
+        // Only the path to the current node remains, so its oldest nodes can be
+        // dropped without disconnecting the tree.
+        for ix in &path[..path.len() - 1] {
+            if remaining <= max_len {
+                break;
+            }
+            removed[*ix] = true;
+            remaining -= 1;
+        }
+
+        let mut new_ixs = vec![None; self.nodes.len()];
+        let mut nodes = Vec::with_capacity(remaining);
+        for (ix, mut node) in mem::take(&mut self.nodes).into_iter().enumerate() {
+            if !removed[ix] {
+                node.parent = node.parent.and_then(|parent| new_ixs[parent]);
+                new_ixs[ix] = Some(nodes.len());
+                nodes.push(node);
+            }
+        }
+        self.nodes = nodes;
+        self.current = new_ixs[self.current].unwrap_or(0);
+        self.nodes_by_transaction = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(ix, node)| (node.transaction_id, ix))
+            .collect();
+    }
+
+    /// Marks the given node as the one that the next recorded state restores.
+    pub(crate) fn start_restoring(&mut self, ix: usize) {
+        self.restoring = Some(ix);
+    }
+
+    /// Ends a restoration started with [`Self::start_restoring`], making the
+    /// given node current even if restoring it did not require any edits.
+    pub(crate) fn finish_restoring(&mut self, ix: usize) {
+        self.restoring = None;
+        self.current = ix;
+    }
+
+    /// Replaces the tree with a single branch whose nodes are the given states.
+    pub(crate) fn reset(
+        &mut self,
+        root: Rope,
+        states: impl IntoIterator<Item = (TransactionId, Rope)>,
+        current: usize,
+        now: Instant,
+    ) {
+        *self = Self::new(root, now);
+        for (transaction_id, text) in states {
+            self.nodes.push(UndoTreeNode {
+                transaction_id: Some(transaction_id),
+                parent: Some(self.nodes.len() - 1),
+                text,
+                timestamp: now,
+            });
+            self.nodes_by_transaction
+                .insert(Some(transaction_id), self.nodes.len() - 1);
+        }
+        self.current = current.min(self.nodes.len() - 1);
+        if self.nodes.len() > MAX_UNDO_TREE_NODES {
+            self.prune(MAX_UNDO_TREE_NODES);
+        }
+    }
+}
@@ -0,0 +1,606 @@
+use anyhow::Context as _;
+use db::kvp::KEY_VALUE_STORE;
+use editor::{actions::ToggleUndoTree, Editor, EditorMode};
+use gpui::{
+    div, px, rems, uniform_list, Action, AnyElement, AppContext, AsyncWindowContext, ClickEvent,
+    EventEmitter, FocusHandle, FocusableView, InteractiveElement, KeyContext, Model, ParentElement,
+    Pixels, Render, Styled, Subscription, Task, UniformListScrollHandle, View, ViewContext,
+    VisualContext, WeakView, WindowContext,
+};
+use language::Buffer;
+use menu::{Confirm, SelectNext, SelectPrev};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use text::UndoTree;
+use theme::ActiveTheme;
+use ui::{prelude::*, ListItem, ListItemSpacing};
+use util::{ResultExt, TryFutureExt};
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    Workspace,
+};
+
+const UNDO_TREE_PANEL_KEY: &str = "UndoTreePanel";
+
+/// The maximum number of lines shown when previewing a state.
+const PREVIEW_LINES: usize = 12;
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &ToggleUndoTree, cx| {
+            workspace.toggle_panel_focus::<UndoTreePanel>(cx);
+        });
+    })
+    .detach();
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedUndoTreePanel {
+    width: Option<Pixels>,
+}
+
+/// A panel that draws the undo tree of the active editor's buffer, previews the
+/// buffer's text at the selected state, and restores that state on confirm.
+pub struct UndoTreePanel {
+    focus_handle: FocusHandle,
+    position: DockPosition,
+    width: Option<Pixels>,
+    active_editor: Option<(View<Editor>, Model<Buffer>)>,
+    rows: Vec<UndoTreeRow>,
+    selected_index: Option<usize>,
+    preview: Option<Preview>,
+    scroll_handle: UniformListScrollHandle,
+    pending_serialization: Task<Option<()>>,
+    _buffer_subscription: Option<Subscription>,
+    _workspace_subscription: Subscription,
+}
+
+/// The lines previewed for the selected node, which are computed in the background and
+/// recomputed when another node is selected or the buffer changes.
+struct Preview {
+    node_ix: usize,
+    /// The version of the buffer the lines were computed against.
+    version: clock::Global,
+    /// The lines that differ from the buffer, or `None` while they are being computed.
+    lines: Option<Vec<(usize, String)>>,
+    _task: Task<()>,
+}
+
+/// A node of the undo tree, as it is displayed in the list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct UndoTreeRow {
+    node_ix: usize,
+    /// How many branches this node is nested within.
+    column: usize,
+}
+
+impl UndoTreePanel {
+    pub async fn load(
+        workspace: WeakView<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> anyhow::Result<View<Self>> {
+        let serialized_panel = cx
+            .background_executor()
+            .spawn(async move { KEY_VALUE_STORE.read_kvp(UNDO_TREE_PANEL_KEY) })
+            .await
+            .context("loading undo tree panel")
+            .log_err()
+            .flatten()
+            .map(|panel| serde_json::from_str::<SerializedUndoTreePanel>(&panel))
+            .transpose()
+            .log_err()
+            .flatten();
+
+        workspace.update(&mut cx, |workspace, cx| {
+            let panel = Self::new(workspace, cx);
+            if let Some(serialized_panel) = serialized_panel {
+                panel.update(cx, |panel, cx| {
+                    panel.width = serialized_panel.width.map(|px| px.round());
+                    cx.notify();
+                });
+            }
+            panel
+        })
+    }
+
+    fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let workspace_handle = cx.view().clone();
+        cx.new_view(|cx| {
+            let workspace_subscription =
+                cx.subscribe(&workspace_handle, |this, workspace, event, cx| {
+                    if let workspace::Event::ActiveItemChanged = event {
+                        let editor = workspace
+                            .read(cx)
+                            .active_item(cx)
+                            .and_then(|item| item.act_as::<Editor>(cx));
+                        this.set_active_editor(editor, cx);
+                    }
+                });
+            let mut this = Self {
+                focus_handle: cx.focus_handle(),
+                position: DockPosition::Right,
+                width: None,
+                active_editor: None,
+                rows: Vec::new(),
+                selected_index: None,
+                preview: None,
+                scroll_handle: UniformListScrollHandle::new(),
+                pending_serialization: Task::ready(None),
+                _buffer_subscription: None,
+                _workspace_subscription: workspace_subscription,
+            };
+            let editor = workspace
+                .active_item(cx)
+                .and_then(|item| item.act_as::<Editor>(cx));
+            this.set_active_editor(editor, cx);
+            this
+        })
+    }
+
+    fn serialize(&mut self, cx: &mut ViewContext<Self>) {
+        let width = self.width;
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        UNDO_TREE_PANEL_KEY.into(),
+                        serde_json::to_string(&SerializedUndoTreePanel { width })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    /// Shows the undo tree of the given editor's buffer, if it's a full editor
+    /// containing a single buffer.
+    fn set_active_editor(&mut self, editor: Option<View<Editor>>, cx: &mut ViewContext<Self>) {
+        let active_editor = editor
+            .filter(|editor| editor.read(cx).mode() == EditorMode::Full)
+            .and_then(|editor| {
+                let buffer = editor.read(cx).buffer().read(cx).as_singleton()?;
+                Some((editor, buffer))
+            });
+        let buffer_changed = active_editor.as_ref().map(|(_, buffer)| buffer)
+            != self.active_editor.as_ref().map(|(_, buffer)| buffer);
+        if buffer_changed {
+            self._buffer_subscription = active_editor
+                .as_ref()
+                .map(|(_, buffer)| cx.observe(buffer, |this, _, cx| this.update_rows(cx)));
+            self.selected_index = None;
+        }
+        self.active_editor = active_editor;
+        self.update_rows(cx);
+    }
+
+    fn update_rows(&mut self, cx: &mut ViewContext<Self>) {
+        self.rows = self
+            .active_editor
+            .as_ref()
+            .map(|(_, buffer)| tree_rows(buffer.read(cx).undo_tree()))
+            .unwrap_or_default();
+        self.selected_index = self.selected_index.filter(|ix| *ix < self.rows.len());
+        self.update_preview(cx);
+        cx.notify();
+    }
+
+    /// Computes the preview of the selected node in the background, unless it's up to date.
+    fn update_preview(&mut self, cx: &mut ViewContext<Self>) {
+        let Some((buffer, node_ix)) = self.active_editor.as_ref().zip(
+            self.selected_index
+                .and_then(|ix| self.rows.get(ix))
+                .map(|row| row.node_ix),
+        ) else {
+            self.preview = None;
+            return;
+        };
+        let buffer = buffer.1.read(cx);
+        let version = buffer.version();
+        if self.preview.as_ref().map_or(false, |preview| {
+            preview.node_ix == node_ix && preview.version == version
+        }) {
+            return;
+        }
+        let Some(node) = buffer.undo_tree().node(node_ix) else {
+            self.preview = None;
+            return;
+        };
+
+        let current_text = buffer.as_rope().clone();
+        let text = node.text.clone();
+        let task = cx.spawn(|this, mut cx| async move {
+            let lines = cx
+                .background_executor()
+                .spawn(async move {
+                    preview_lines(&current_text.to_string(), &text.to_string(), PREVIEW_LINES)
+                })
+                .await;
+            this.update(&mut cx, |this, cx| {
+                if let Some(preview) = this.preview.as_mut() {
+                    preview.lines = Some(lines);
+                    cx.notify();
+                }
+            })
+            .ok();
+        });
+        // Keep showing the node's previous lines until they are recomputed.
+        let lines = self
+            .preview
+            .take()
+            .filter(|preview| preview.node_ix == node_ix)
+            .and_then(|preview| preview.lines);
+        self.preview = Some(Preview {
+            node_ix,
+            version,
+            lines,
+            _task: task,
+        });
+    }
+
+    fn undo_tree<'a>(&self, cx: &'a AppContext) -> Option<&'a UndoTree> {
+        let (_, buffer) = self.active_editor.as_ref()?;
+        Some(buffer.read(cx).undo_tree())
+    }
+
+    fn current_row(&self, cx: &AppContext) -> Option<usize> {
+        let current = self.undo_tree(cx)?.current();
+        self.rows.iter().position(|row| row.node_ix == current)
+    }
+
+    fn select_next(&mut self, _: &SelectNext, cx: &mut ViewContext<Self>) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let ix = match self.selected_index.or_else(|| self.current_row(cx)) {
+            Some(ix) => (ix + 1).min(self.rows.len() - 1),
+            None => 0,
+        };
+        self.select(ix, cx);
+    }
+
+    fn select_prev(&mut self, _: &SelectPrev, cx: &mut ViewContext<Self>) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let ix = self
+            .selected_index
+            .or_else(|| self.current_row(cx))
+            .map_or(0, |ix| ix.saturating_sub(1));
+        self.select(ix, cx);
+    }
+
+    fn select(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        self.selected_index = Some(ix);
+        self.scroll_handle.scroll_to_item(ix);
+        self.update_preview(cx);
+        cx.notify();
+    }
+
+    fn confirm(&mut self, _: &Confirm, cx: &mut ViewContext<Self>) {
+        self.restore_selected(cx);
+    }
+
+    /// Returns the active editor's buffer to the selected state.
+    fn restore_selected(&mut self, cx: &mut ViewContext<Self>) {
+        let Some((editor, _)) = self.active_editor.clone() else {
+            return;
+        };
+        let Some(row) = self.selected_index.and_then(|ix| self.rows.get(ix)) else {
+            return;
+        };
+        let node_ix = row.node_ix;
+        editor.update(cx, |editor, cx| {
+            editor.restore_undo_tree_node(node_ix, cx);
+        });
+        self.selected_index = None;
+        self.update_preview(cx);
+        cx.notify();
+    }
+
+    fn dispatch_context(&self) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("UndoTreePanel");
+        dispatch_context.add("menu");
+        dispatch_context
+    }
+
+    fn render_row(&self, ix: usize, now: Instant, cx: &mut ViewContext<Self>) -> Option<ListItem> {
+        let undo_tree = self.undo_tree(cx)?;
+        let row = self.rows.get(ix)?;
+        let node = undo_tree.node(row.node_ix)?;
+        let is_current = row.node_ix == undo_tree.current();
+        let len_delta = node
+            .parent
+            .and_then(|parent| undo_tree.node(parent))
+            .map(|parent| node.text.len() as isize - parent.text.len() as isize);
+        let elapsed = now.saturating_duration_since(node.timestamp);
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(self.selected_index == Some(ix))
+                .on_click(cx.listener(move |this, event: &ClickEvent, cx| {
+                    this.select(ix, cx);
+                    if event.up.click_count > 1 {
+                        this.restore_selected(cx);
+                    }
+                }))
+                .child(
+                    h_flex()
+                        .pl(rems(row.column as f32))
+                        .gap_2()
+                        .child(
+                            Icon::new(if is_current {
+                                IconName::Check
+                            } else {
+                                IconName::Dash
+                            })
+                            .size(IconSize::Small)
+                            .color(if is_current {
+                                Color::Accent
+                            } else {
+                                Color::Muted
+                            }),
+                        )
+                        .child(Label::new(format!("#{}", row.node_ix)))
+                        .when_some(len_delta, |this, delta| {
+                            this.child(
+                                Label::new(format!("{delta:+} chars"))
+                                    .color(if delta < 0 {
+                                        Color::Deleted
+                                    } else {
+                                        Color::Created
+                                    })
+                                    .size(LabelSize::Small),
+                            )
+                        })
+                        .child(
+                            Label::new(format_elapsed(elapsed))
+                                .color(Color::Muted)
+                                .size(LabelSize::Small),
+                        ),
+                ),
+        )
+    }
+
+    fn render_preview(&self, cx: &mut ViewContext<Self>) -> AnyElement {
+        let Some(lines) = self
+            .preview
+            .as_ref()
+            .and_then(|preview| preview.lines.clone())
+        else {
+            return div().into_any_element();
+        };
+
+        v_flex()
+            .w_full()
+            .border_t_1()
+            .border_color(cx.theme().colors().border_variant)
+            .p_2()
+            .gap_0p5()
+            .when(lines.is_empty(), |this| {
+                this.child(
+                    Label::new("Same as the current state")
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+            })
+            .children(lines.into_iter().map(|(row, line)| {
+                h_flex()
+                    .gap_2()
+                    .font_buffer(cx)
+                    .text_ui_sm(cx)
+                    .child(
+                        div()
+                            .w(rems(2.5))
+                            .text_color(cx.theme().colors().editor_line_number)
+                            .child(format!("{:>4}", row + 1)),
+                    )
+                    .child(line)
+            }))
+            .into_any_element()
+    }
+}
+
+impl FocusableView for UndoTreePanel {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for UndoTreePanel {}
+
+impl Render for UndoTreePanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let now = Instant::now();
+        v_flex()
+            .id("undo-tree-panel")
+            .key_context(self.dispatch_context())
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_prev))
+            .on_action(cx.listener(Self::confirm))
+            .map(|this| {
+                if self.active_editor.is_none() {
+                    this.child(v_flex().size_full().justify_center().items_center().child(
+                        Label::new("Open a file to see its undo history").color(Color::Muted),
+                    ))
+                } else {
+                    this.child(
+                        uniform_list(
+                            cx.view().clone(),
+                            "undo-tree-rows",
+                            self.rows.len(),
+                            move |this, range, cx| {
+                                range
+                                    .filter_map(|ix| this.render_row(ix, now, cx))
+                                    .collect()
+                            },
+                        )
+                        .flex_grow()
+                        .track_scroll(self.scroll_handle.clone()),
+                    )
+                    .child(self.render_preview(cx))
+                }
+            })
+    }
+}
+
+impl Panel for UndoTreePanel {
+    fn persistent_name() -> &'static str {
+        "Undo Tree Panel"
+    }
+
+    fn position(&self, _: &WindowContext) -> DockPosition {
+        self.position
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        self.position = position;
+        cx.notify();
+    }
+
+    fn size(&self, _: &WindowContext) -> Pixels {
+        self.width.unwrap_or(px(240.))
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        self.width = size;
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, _: &WindowContext) -> Option<IconName> {
+        Some(IconName::HistoryRerun)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Undo Tree Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleUndoTree)
+    }
+}
+
+/// Lists the nodes of the tree depth-first, so that each node is followed by
+/// its descendants. Each node's first child continues its parent's column, and
+/// every other child starts a new, more deeply nested column.
+fn tree_rows(undo_tree: &UndoTree) -> Vec<UndoTreeRow> {
+    let mut rows = Vec::with_capacity(undo_tree.len());
+    let mut stack = vec![UndoTreeRow {
+        node_ix: 0,
+        column: 0,
+    }];
+    while let Some(row) = stack.pop() {
+        rows.push(row);
+        let children = undo_tree.children(row.node_ix).collect::<Vec<_>>();
+        for (i, child_ix) in children.into_iter().enumerate().rev() {
+            stack.push(UndoTreeRow {
+                node_ix: child_ix,
+                column: if i == 0 { row.column } else { row.column + 1 },
+            });
+        }
+    }
+    rows
+}
+
+/// Returns the lines of `text` that differ from `current_text`, along with a line
+/// of context before them, as pairs of zero-based row and line.
+fn preview_lines(current_text: &str, text: &str, max_lines: usize) -> Vec<(usize, String)> {
+    let old_lines = current_text.lines().collect::<Vec<_>>();
+    let new_lines = text.lines().collect::<Vec<_>>();
+    if old_lines == new_lines {
+        return Vec::new();
+    }
+
+    let prefix_len = old_lines
+        .iter()
+        .zip(&new_lines)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix_len = old_lines[prefix_len..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix_len..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let start = prefix_len.saturating_sub(1);
+    let end = (new_lines.len() - suffix_len)
+        .max(prefix_len + 1)
+        .min(new_lines.len())
+        .min(start + max_lines);
+    (start..end)
+        .map(|row| (row, new_lines[row].to_string()))
+        .collect()
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    if seconds < 5 {
+        "just now".to_string()
+    } else if seconds < 60 {
+        format!("{seconds}s ago")
+    } else if seconds < 60 * 60 {
+        format!("{}m ago", seconds / 60)
+    } else if seconds < 24 * 60 * 60 {
+        format!("{}h ago", seconds / (60 * 60))
+    } else {
+        format!("{}d ago", seconds / (24 * 60 * 60))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use text::{Buffer, BufferId};
+
+    #[test]
+    fn test_tree_rows() {
+        let mut buffer = Buffer::new(0, BufferId::new(1).unwrap(), "abc".into());
+        buffer.set_group_interval(Duration::ZERO);
+        buffer.edit([(0..1, "")]);
+        buffer.edit([(0..1, "")]);
+        buffer.undo();
+        buffer.edit([(0..0, "X")]);
+        buffer.undo();
+        buffer.undo();
+        buffer.edit([(3..3, "!")]);
+
+        // 0 "abc"
+        // ├─ 1 "bc"
+        // │  ├─ 2 "c"
+        // │  └─ 3 "Xbc"
+        // └─ 4 "abc!"
+        let rows = tree_rows(buffer.undo_tree())
+            .into_iter()
+            .map(|row| (row.node_ix, row.column))
+            .collect::<Vec<_>>();
+        assert_eq!(rows, vec![(0, 0), (1, 0), (2, 0), (3, 1), (4, 1)]);
+    }
+
+    #[test]
+    fn test_preview_lines() {
+        let current = "one\ntwo\nthree\nfour\n";
+        assert_eq!(preview_lines(current, current, 10), Vec::new());
+        assert_eq!(
+            preview_lines(current, "one\ntwo\nTHREE\nfour\n", 10),
+            vec![(1, "two".to_string()), (2, "THREE".to_string())]
+        );
+        assert_eq!(
+            preview_lines(current, "zero\none\ntwo\nthree\nfour\n", 10),
+            vec![(0, "zero".to_string())]
+        );
+        assert_eq!(
+            preview_lines(current, "one\ntwo\nthree\n", 10),
+            vec![(2, "three".to_string())]
+        );
+    }
+}
@@ -112,7 +112,7 @@ pub fn register(editor: &mut Editor, cx: &mut ViewContext<Vim>) {
     });
 
     Vim::action(editor, cx, |vim, action: &GoToLine, cx| {
-        vim.switch_mode(Mode::Normal, false, cx);
+        vim.switch_mode(Vim::default_mode(cx), false, cx);
         let result = vim.update_editor(cx, |vim, editor, cx| {
             action.range.head().buffer_row(vim, editor, cx)
         });
@@ -780,6 +780,8 @@ pub fn command_interceptor(mut input: &str, cx: &AppContext) -> Option<CommandIn
         } else {
             None
         }
+    } else if let Some(action) = parse_undo_tree_command(query) {
+        Some(action)
     } else {
         None
     };
@@ -811,6 +813,64 @@ pub fn command_interceptor(mut input: &str, cx: &AppContext) -> Option<CommandIn
     None
 }
 
+/// Parses `:earlier` and `:later`, whose argument is either a number of
+/// states or an amount of time like `10s`, `5m`, `2h` or `1d`.
+fn parse_undo_tree_command(query: &str) -> Option<Box<dyn Action>> {
+    let (is_earlier, argument) = if let Some(argument) = strip_command(query, "ea", "rlier") {
+        (true, argument)
+    } else if let Some(argument) = strip_command(query, "lat", "er") {
+        (false, argument)
+    } else {
+        return None;
+    };
+
+    let argument = argument.trim();
+    let unit_ix = argument
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(argument.len());
+    let count = if unit_ix == 0 {
+        1
+    } else {
+        argument[..unit_ix].parse::<u64>().ok()?
+    };
+    let seconds_per_unit = match &argument[unit_ix..] {
+        "" => None,
+        "s" => Some(1),
+        "m" => Some(60),
+        "h" => Some(60 * 60),
+        "d" => Some(24 * 60 * 60),
+        _ => return None,
+    };
+    let (steps, seconds) = match seconds_per_unit {
+        Some(seconds_per_unit) => (0, count.saturating_mul(seconds_per_unit)),
+        None => (count as usize, 0),
+    };
+
+    Some(if is_earlier {
+        editor::actions::UndoTreeEarlier { steps, seconds }.boxed_clone()
+    } else {
+        editor::actions::UndoTreeLater { steps, seconds }.boxed_clone()
+    })
+}
+
+/// Strips a possibly-abbreviated command name from the start of the query,
+/// returning its argument.
+fn strip_command<'a>(query: &'a str, prefix: &str, suffix: &str) -> Option<&'a str> {
+    let mut rest = query.strip_prefix(prefix)?;
+    for char in suffix.chars() {
+        match rest.strip_prefix(char) {
+            Some(next) => rest = next,
+            None => break,
+        }
+    }
+    if rest.is_empty() || rest.starts_with(|c: char| c.is_ascii_whitespace() || c.is_ascii_digit())
+    {
+        Some(rest)
+    } else {
+        None
+    }
+}
+
 fn generate_positions(string: &str, query: &str) -> Vec<usize> {
     let mut positions = Vec::new();
     let mut chars = query.chars();
@@ -1057,6 +1117,30 @@ mod test {
         assert_eq!(file_path.to_str().unwrap(), expected_path);
     }
 
+    #[gpui::test]
+    async fn test_earlier_and_later(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.update_buffer(|buffer, _| buffer.set_group_interval(std::time::Duration::ZERO));
+
+        cx.set_state("ˇabc", Mode::Normal);
+        cx.simulate_keystrokes("x x u");
+        cx.assert_state("ˇbc", Mode::Normal);
+        cx.simulate_keystrokes("i X escape");
+        cx.assert_state("ˇXbc", Mode::Normal);
+
+        // The state discarded by editing after undoing is still reachable.
+        cx.simulate_keystrokes(": e a r l i e r space 1 enter");
+        assert_eq!(cx.buffer_text(), "c");
+        cx.simulate_keystrokes("u");
+        assert_eq!(cx.buffer_text(), "bc");
+        cx.simulate_keystrokes(": l a t e r enter");
+        assert_eq!(cx.buffer_text(), "c");
+        cx.simulate_keystrokes(": e a r l i e r space 2 enter");
+        assert_eq!(cx.buffer_text(), "abc");
+        cx.simulate_keystrokes(": l a t e r space 1 h enter");
+        assert_eq!(cx.buffer_text(), "Xbc");
+    }
+
     #[gpui::test]
     async fn test_command_gf(cx: &mut TestAppContext) {
         let mut cx = VimTestContext::new(cx, true).await;
@@ -0,0 +1,625 @@
+use editor::{display_map::ToDisplayPoint, movement, scroll::Autoscroll, AnchorRangeExt, Editor};
+use gpui::{actions, ViewContext};
+use language::{Point, SelectionGoal};
+use regex::Regex;
+use search::{BufferSearchBar, SearchOptions};
+use std::ops::Range;
+use util::ResultExt;
+
+use crate::{
+    motion::Motion,
+    object::Object,
+    state::{Mode, Operator, Register},
+    Vim,
+};
+
+actions!(
+    vim,
+    [
+        HelixInsert,
+        HelixAppend,
+        HelixDelete,
+        HelixChange,
+        HelixYank,
+        HelixSelectLine,
+        HelixSelectRegex,
+        HelixSplitSelection,
+        HelixCollapseSelection,
+        HelixFlipSelections,
+    ]
+);
+
+/// What to do with the regex entered after `s` or `S` in helix normal mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HelixRegexSelection {
+    /// Select every match within the current selections.
+    Matches,
+    /// Split the current selections on every match.
+    Split,
+}
+
+impl HelixRegexSelection {
+    /// Returns the ranges within `text` that should become selections.
+    fn ranges(self, regex: &Regex, text: &str) -> Vec<Range<usize>> {
+        match self {
+            HelixRegexSelection::Matches => regex
+                .find_iter(text)
+                .map(|found| found.range())
+                .filter(|range| !range.is_empty())
+                .collect(),
+            HelixRegexSelection::Split => {
+                let mut ranges = Vec::new();
+                let mut piece_start = 0;
+                for found in regex.find_iter(text) {
+                    if found.start() > piece_start {
+                        ranges.push(piece_start..found.start());
+                    }
+                    piece_start = found.end();
+                }
+                if piece_start < text.len() {
+                    ranges.push(piece_start..text.len());
+                }
+                ranges
+            }
+        }
+    }
+}
+
+pub fn register(editor: &mut Editor, cx: &mut ViewContext<Vim>) {
+    Vim::action(editor, cx, Vim::helix_insert);
+    Vim::action(editor, cx, Vim::helix_append);
+    Vim::action(editor, cx, |vim, _: &HelixDelete, cx| {
+        vim.record_current_action(cx);
+        vim.helix_delete(cx);
+    });
+    Vim::action(editor, cx, |vim, _: &HelixChange, cx| {
+        vim.start_recording(cx);
+        vim.helix_delete(cx);
+        vim.switch_mode(Mode::Insert, true, cx);
+    });
+    Vim::action(editor, cx, Vim::helix_yank);
+    Vim::action(editor, cx, Vim::helix_select_line);
+    Vim::action(editor, cx, |vim, _: &HelixSelectRegex, cx| {
+        vim.helix_regex_prompt(HelixRegexSelection::Matches, cx)
+    });
+    Vim::action(editor, cx, |vim, _: &HelixSplitSelection, cx| {
+        vim.helix_regex_prompt(HelixRegexSelection::Split, cx)
+    });
+    Vim::action(editor, cx, Vim::helix_collapse_selection);
+    Vim::action(editor, cx, Vim::helix_flip_selections);
+}
+
+impl Vim {
+    /// In helix normal mode motions select first: word motions select the text
+    /// they move over, everything else moves the cursor and drops the selection.
+    pub(crate) fn helix_normal_motion(
+        &mut self,
+        motion: Motion,
+        times: Option<usize>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        match motion {
+            Motion::NextWordStart { .. }
+            | Motion::NextWordEnd { .. }
+            | Motion::NextSubwordStart { .. }
+            | Motion::NextSubwordEnd { .. } => {
+                let inclusive = matches!(
+                    motion,
+                    Motion::NextWordEnd { .. } | Motion::NextSubwordEnd { .. }
+                );
+                self.update_editor(cx, |_, editor, cx| {
+                    let text_layout_details = editor.text_layout_details(cx);
+                    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                        s.move_with(|map, selection| {
+                            let anchor = selection.head();
+                            let Some((mut head, _)) = motion.move_point(
+                                map,
+                                anchor,
+                                selection.goal,
+                                times,
+                                &text_layout_details,
+                            ) else {
+                                return;
+                            };
+                            if inclusive {
+                                head = movement::right(map, head);
+                            }
+                            selection.start = anchor;
+                            selection.end = head;
+                            selection.reversed = false;
+                            selection.goal = SelectionGoal::None;
+                        })
+                    })
+                });
+            }
+            Motion::PreviousWordStart { .. }
+            | Motion::PreviousWordEnd { .. }
+            | Motion::PreviousSubwordStart { .. }
+            | Motion::PreviousSubwordEnd { .. } => {
+                self.update_editor(cx, |_, editor, cx| {
+                    let text_layout_details = editor.text_layout_details(cx);
+                    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                        s.move_with(|map, selection| {
+                            // the character under the cursor is part of the selection,
+                            // so the selection always ends just after it.
+                            let end = if selection.is_empty() {
+                                movement::right(map, selection.head())
+                            } else {
+                                selection.head()
+                            };
+                            let Some((start, _)) = motion.move_point(
+                                map,
+                                movement::left(map, end),
+                                selection.goal,
+                                times,
+                                &text_layout_details,
+                            ) else {
+                                return;
+                            };
+                            selection.start = start;
+                            selection.end = end;
+                            selection.reversed = true;
+                            selection.goal = SelectionGoal::None;
+                        })
+                    })
+                });
+            }
+            _ => {
+                self.update_editor(cx, |_, editor, cx| {
+                    let text_layout_details = editor.text_layout_details(cx);
+                    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                        s.move_with(|map, selection| {
+                            let mut cursor = selection.head();
+                            if !selection.reversed && !selection.is_empty() {
+                                cursor = movement::left(map, cursor);
+                            }
+                            let (head, goal) = motion
+                                .move_point(
+                                    map,
+                                    cursor,
+                                    selection.goal,
+                                    times,
+                                    &text_layout_details,
+                                )
+                                .unwrap_or((cursor, selection.goal));
+                            selection.collapse_to(head, goal)
+                        })
+                    })
+                });
+            }
+        }
+    }
+
+    pub(crate) fn helix_object(&mut self, object: Object, cx: &mut ViewContext<Self>) {
+        let Some(Operator::Object { around }) = self.active_operator() else {
+            return;
+        };
+        self.pop_operator(cx);
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    if let Some(range) = object.range(map, selection.clone(), around) {
+                        selection.start = range.start;
+                        selection.end = range.end;
+                        selection.reversed = false;
+                        selection.goal = SelectionGoal::None;
+                    }
+                })
+            })
+        });
+    }
+
+    fn helix_insert(&mut self, _: &HelixInsert, cx: &mut ViewContext<Self>) {
+        self.start_recording(cx);
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|_, selection| selection.collapse_to(selection.start, selection.goal))
+            });
+        });
+        self.switch_mode(Mode::Insert, false, cx);
+    }
+
+    fn helix_append(&mut self, _: &HelixAppend, cx: &mut ViewContext<Self>) {
+        self.start_recording(cx);
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let end = if selection.is_empty() {
+                        movement::right(map, selection.end)
+                    } else {
+                        selection.end
+                    };
+                    selection.collapse_to(end, SelectionGoal::None)
+                })
+            });
+        });
+        self.switch_mode(Mode::Insert, false, cx);
+    }
+
+    /// Deletes the selected text, or the character under the cursor for empty
+    /// selections, storing it in the selected register.
+    fn helix_delete(&mut self, cx: &mut ViewContext<Self>) {
+        self.update_editor(cx, |vim, editor, cx| {
+            editor.transact(cx, |editor, cx| {
+                editor.change_selections(None, cx, |s| {
+                    s.move_with(|map, selection| {
+                        if selection.is_empty() {
+                            selection.end = movement::right(map, selection.end);
+                        }
+                    })
+                });
+                vim.copy_selections_content(editor, false, cx);
+                editor.insert("", cx);
+            });
+        });
+    }
+
+    fn helix_yank(&mut self, _: &HelixYank, cx: &mut ViewContext<Self>) {
+        self.update_editor(cx, |vim, editor, cx| {
+            vim.yank_selections_content(editor, false, cx);
+        });
+    }
+
+    /// Pastes the register after each selection, or before it, and selects the pasted text.
+    /// Text yanked from whole lines is pasted on the lines below or above the selections.
+    pub(crate) fn helix_paste(&mut self, before: bool, cx: &mut ViewContext<Self>) {
+        self.record_current_action(cx);
+        let count = self.take_count(cx).unwrap_or(1);
+        self.update_editor(cx, |vim, editor, cx| {
+            editor.transact(cx, |editor, cx| {
+                let selected_register = vim.selected_register.take();
+                let Some(Register {
+                    text,
+                    clipboard_selections,
+                }) = Vim::update_globals(cx, |globals, cx| {
+                    globals.read_register(selected_register, Some(editor), cx)
+                })
+                .filter(|register| !register.text.is_empty())
+                else {
+                    return;
+                };
+
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let selections = editor.selections.all::<Point>(cx);
+                // Text yanked from as many selections is pasted one piece per selection.
+                let texts = match clipboard_selections {
+                    Some(clipboard_selections)
+                        if clipboard_selections.len() == selections.len()
+                            && selections.len() > 1 =>
+                    {
+                        let mut start_offset = 0;
+                        clipboard_selections
+                            .iter()
+                            .map(|clipboard_selection| {
+                                let end_offset = start_offset + clipboard_selection.len;
+                                let text = text[start_offset..end_offset].to_string();
+                                start_offset = end_offset + 1;
+                                text
+                            })
+                            .collect()
+                    }
+                    _ => vec![text.to_string(); selections.len()],
+                };
+
+                let mut edits = Vec::new();
+                let mut pasted_ranges = Vec::new();
+                let mut pasted_len = 0;
+                for (selection, text) in selections.iter().zip(texts) {
+                    let text = text.repeat(count);
+                    let point = if !text.ends_with('\n') {
+                        if before {
+                            selection.start
+                        } else {
+                            selection.end
+                        }
+                    } else if before {
+                        Point::new(selection.start.row, 0)
+                    } else if selection.end.column == 0 && selection.end.row > selection.start.row {
+                        selection.end
+                    } else {
+                        Point::new(selection.end.row + 1, 0)
+                    };
+                    // The last line has no newline to paste whole lines after, so it gets one.
+                    let (offset, text, newline_len) = if point.row > snapshot.max_point().row {
+                        let text = format!("\n{}", &text[..text.len() - 1]);
+                        (snapshot.len(), text, 1)
+                    } else {
+                        (snapshot.point_to_offset(point), text, 0)
+                    };
+                    let start = offset + pasted_len + newline_len;
+                    pasted_ranges.push(start..offset + pasted_len + text.len());
+                    pasted_len += text.len();
+                    edits.push((offset..offset, text));
+                }
+
+                editor.edit(edits, cx);
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.select_ranges(pasted_ranges)
+                });
+            });
+        });
+    }
+
+    /// Extends each selection to cover whole lines. If a selection already
+    /// covers whole lines, the next line is added to it.
+    fn helix_select_line(&mut self, _: &HelixSelectLine, cx: &mut ViewContext<Self>) {
+        let times = self.take_count(cx).unwrap_or(1) as u32;
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let start = selection.start.to_point(map);
+                    let end = selection.end.to_point(map);
+                    let ends_at_line_start = end.column == 0 && end.row > start.row;
+                    let last_row = if ends_at_line_start {
+                        end.row - 1
+                    } else {
+                        end.row
+                    };
+                    let end_row = if ends_at_line_start && start.column == 0 {
+                        last_row + 1 + times
+                    } else {
+                        last_row + times
+                    };
+                    let end = if end_row > map.buffer_snapshot.max_point().row {
+                        map.buffer_snapshot.max_point()
+                    } else {
+                        Point::new(end_row, 0)
+                    };
+                    selection.start = Point::new(start.row, 0).to_display_point(map);
+                    selection.end = end.to_display_point(map);
+                    selection.reversed = false;
+                    selection.goal = SelectionGoal::None;
+                })
+            });
+        });
+    }
+
+    fn helix_collapse_selection(&mut self, _: &HelixCollapseSelection, cx: &mut ViewContext<Self>) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(None, cx, |s| {
+                s.move_with(|map, selection| {
+                    let mut head = selection.head();
+                    if !selection.reversed && !selection.is_empty() {
+                        head = movement::left(map, head);
+                    }
+                    selection.collapse_to(head, selection.goal)
+                })
+            });
+        });
+    }
+
+    fn helix_flip_selections(&mut self, _: &HelixFlipSelections, cx: &mut ViewContext<Self>) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(None, cx, |s| {
+                s.move_with(|_, selection| {
+                    if !selection.is_empty() {
+                        selection.reversed = !selection.reversed;
+                    }
+                })
+            });
+        });
+    }
+
+    /// Opens the buffer search bar to read the regex used by `s` and `S`.
+    fn helix_regex_prompt(&mut self, kind: HelixRegexSelection, cx: &mut ViewContext<Self>) {
+        let Some(pane) = self.pane(cx) else { return };
+        let prior_selections = self.editor_selections(cx);
+        pane.update(cx, |pane, cx| {
+            if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
+                search_bar.update(cx, |search_bar, cx| {
+                    if !search_bar.show(cx) {
+                        return;
+                    }
+                    search_bar.select_query(cx);
+                    cx.focus_self();
+                    search_bar.set_replacement(None, cx);
+                    search_bar.set_search_options(SearchOptions::REGEX, cx);
+
+                    self.search.prior_selections = prior_selections;
+                    self.search.prior_mode = self.mode;
+                    self.search.helix_regex_selection = Some(kind);
+                });
+            }
+        })
+    }
+
+    pub(crate) fn helix_regex_submit(
+        &mut self,
+        kind: HelixRegexSelection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(pane) = self.pane(cx) else { return };
+        let query = pane.update(cx, |pane, cx| {
+            let search_bar = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>()?;
+            search_bar.update(cx, |search_bar, cx| {
+                let query = search_bar.query(cx);
+                search_bar.dismiss(&Default::default(), cx);
+                Some(query)
+            })
+        });
+        let prior_selections: Vec<_> = self.search.prior_selections.drain(..).collect();
+        let regex = query
+            .filter(|query| !query.is_empty())
+            .and_then(|query| Regex::new(&query).log_err());
+
+        self.update_editor(cx, |_, editor, cx| {
+            let buffer = editor.buffer().read(cx).snapshot(cx);
+            let mut new_ranges = Vec::new();
+            if let Some(regex) = regex.as_ref() {
+                for range in &prior_selections {
+                    let range = range.to_offset(&buffer);
+                    let range = range.start.min(range.end)..range.start.max(range.end);
+                    let text = buffer.text_for_range(range.clone()).collect::<String>();
+                    new_ranges.extend(
+                        kind.ranges(regex, &text)
+                            .into_iter()
+                            .map(|found| range.start + found.start..range.start + found.end),
+                    );
+                }
+            }
+
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                if new_ranges.is_empty() {
+                    s.select_anchor_ranges(prior_selections)
+                } else {
+                    s.select_ranges(new_ranges)
+                }
+            });
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use crate::{state::Mode, test::VimTestContext};
+
+    #[gpui::test]
+    async fn test_helix_word_motions(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("ˇhello world foo", Mode::HelixNormal);
+        cx.simulate_keystrokes("w");
+        cx.assert_state("«hello ˇ»world foo", Mode::HelixNormal);
+        cx.simulate_keystrokes("w");
+        cx.assert_state("hello «world ˇ»foo", Mode::HelixNormal);
+        cx.simulate_keystrokes("b");
+        cx.assert_state("hello «ˇworld »foo", Mode::HelixNormal);
+
+        cx.set_state("ˇhello world", Mode::HelixNormal);
+        cx.simulate_keystrokes("e");
+        cx.assert_state("«helloˇ» world", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("l");
+        cx.assert_state("helloˇ world", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_helix_select_then_act(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("ˇhello world", Mode::HelixNormal);
+        cx.simulate_keystrokes("w d");
+        cx.assert_state("ˇworld", Mode::HelixNormal);
+
+        cx.set_state("ˇhello world", Mode::HelixNormal);
+        cx.simulate_keystrokes("e c h i");
+        cx.assert_state("hiˇ world", Mode::Insert);
+        cx.simulate_keystrokes("escape");
+        assert_eq!(cx.mode(), Mode::HelixNormal);
+
+        cx.set_state("ˇhello world", Mode::HelixNormal);
+        cx.simulate_keystrokes("w y");
+        cx.assert_state("«hello ˇ»world", Mode::HelixNormal);
+        cx.simulate_keystrokes("shift-s space enter");
+        cx.assert_state("«helloˇ» world", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_helix_select_line(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state(
+            indoc! {"
+                one
+                twˇo
+                three
+                four"},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("x");
+        cx.assert_state(
+            indoc! {"
+                one
+                «two
+                ˇ»three
+                four"},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("x");
+        cx.assert_state(
+            indoc! {"
+                one
+                «two
+                three
+                ˇ»four"},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("d");
+        cx.assert_state(
+            indoc! {"
+                one
+                ˇfour"},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_helix_select_all_and_regex(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("ˇfoo(a, b)\nfoo(c, d)", Mode::HelixNormal);
+        cx.simulate_keystrokes("%");
+        cx.assert_state("«foo(a, b)\nfoo(c, d)ˇ»", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("s f o o enter");
+        cx.assert_state("«fooˇ»(a, b)\n«fooˇ»(c, d)", Mode::HelixNormal);
+
+        cx.set_state("«a, b, cˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("shift-s , space enter");
+        cx.assert_state("«aˇ», «bˇ», «cˇ»", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_helix_paste_and_change_case_stay_in_helix(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("ˇhello world", Mode::HelixNormal);
+        cx.simulate_keystrokes("e ~");
+        cx.assert_state("ˇHELLO world", Mode::HelixNormal);
+
+        cx.set_state("ˇhello world", Mode::HelixNormal);
+        cx.simulate_keystrokes("w y p");
+        cx.assert_state("hello «hello ˇ»world", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_helix_visual_actions_return_to_helix(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("ˇhello\nworld", Mode::HelixNormal);
+        cx.simulate_keystrokes("v >");
+        assert_eq!(cx.mode(), Mode::HelixNormal);
+
+        cx.set_state("ˇhello\nworld", Mode::HelixNormal);
+        cx.simulate_keystrokes("v shift-j");
+        assert_eq!(cx.mode(), Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_helix_paste(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("ˇhello world", Mode::HelixNormal);
+        cx.simulate_keystrokes("w y shift-p");
+        cx.assert_state("«hello ˇ»hello world", Mode::HelixNormal);
+
+        cx.set_state("ˇhello world", Mode::HelixNormal);
+        cx.simulate_keystrokes("w y 2 p");
+        cx.assert_state("hello «hello hello ˇ»world", Mode::HelixNormal);
+
+        // Whole lines are pasted below or above the selected lines.
+        cx.set_state("ˇone\ntwo", Mode::HelixNormal);
+        cx.simulate_keystrokes("x y p");
+        cx.assert_state("one\n«one\nˇ»two", Mode::HelixNormal);
+
+        cx.set_state("one\ntwˇo", Mode::HelixNormal);
+        cx.simulate_keystrokes("shift-p");
+        cx.assert_state("one\n«one\nˇ»two", Mode::HelixNormal);
+
+        cx.set_state("one\ntwˇo", Mode::HelixNormal);
+        cx.simulate_keystrokes("p");
+        cx.assert_state("one\ntwo\n«oneˇ»", Mode::HelixNormal);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{motion::Motion, object::Object, state::Mode, Vim};
+use crate::{motion::Motion, object::Object, Vim};
 use collections::HashMap;
 use editor::{display_map::ToDisplayPoint, Bias, Editor};
 use gpui::actions;
@@ -28,7 +28,7 @@ pub(crate) fn register(editor: &mut Editor, cx: &mut ViewContext<Vim>) {
             });
         });
         if vim.mode.is_visual() {
-            vim.switch_mode(Mode::Normal, true, cx)
+            vim.switch_mode(Vim::default_mode(cx), true, cx)
         }
     });
 
@@ -46,7 +46,7 @@ pub(crate) fn register(editor: &mut Editor, cx: &mut ViewContext<Vim>) {
             });
         });
         if vim.mode.is_visual() {
-            vim.switch_mode(Mode::Normal, true, cx)
+            vim.switch_mode(Vim::default_mode(cx), true, cx)
         }
     });
 }
@@ -20,8 +20,13 @@ impl Vim {
         self.stop_recording_immediately(action.boxed_clone(), cx);
         if count <= 1 || Vim::globals(cx).dot_replaying {
             self.create_mark("^".into(), false, cx);
+            let mode = Vim::default_mode(cx);
             self.update_editor(cx, |_, editor, cx| {
                 editor.dismiss_menus_and_popups(false, cx);
+                // helix leaves the cursor after the inserted text, vim moves back onto it.
+                if mode == Mode::HelixNormal {
+                    return;
+                }
                 editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
                     s.move_cursors_with(|map, mut cursor, _| {
                         *cursor.column_mut() = cursor.column().saturating_sub(1);
@@ -29,7 +34,7 @@ impl Vim {
                     });
                 });
             });
-            self.switch_mode(Mode::Normal, false, cx);
+            self.switch_mode(mode, false, cx);
             return;
         }
 
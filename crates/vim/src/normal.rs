@@ -125,7 +125,7 @@ pub(crate) fn register(editor: &mut Editor, cx: &mut ViewContext<Vim>) {
             })
         });
         if vim.mode.is_visual() {
-            vim.switch_mode(Mode::Normal, true, cx)
+            vim.switch_mode(Vim::default_mode(cx), true, cx)
         }
     });
 
@@ -409,7 +409,7 @@ impl Vim {
             });
         });
         if self.mode.is_visual() {
-            self.switch_mode(Mode::Normal, true, cx)
+            self.switch_mode(Vim::default_mode(cx), true, cx)
         }
     }
 
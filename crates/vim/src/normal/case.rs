@@ -133,7 +133,7 @@ impl Vim {
                         ranges.push(start..end);
                         cursor_positions.push(start..start);
                     }
-                    Mode::Visual => {
+                    Mode::Visual | Mode::HelixNormal => {
                         ranges.push(selection.start..selection.end);
                         cursor_positions.push(selection.start..selection.start);
                     }
@@ -176,7 +176,7 @@ impl Vim {
                 })
             });
         });
-        self.switch_mode(Mode::Normal, true, cx)
+        self.switch_mode(Vim::default_mode(cx), true, cx)
     }
 }
 
@@ -23,6 +23,10 @@ impl_actions!(vim, [Paste]);
 
 impl Vim {
     pub fn paste(&mut self, action: &Paste, cx: &mut ViewContext<Self>) {
+        if self.mode == Mode::HelixNormal {
+            self.helix_paste(action.before, cx);
+            return;
+        }
         self.record_current_action(cx);
         self.store_visual_marks(cx);
         let count = self.take_count(cx).unwrap_or(1);
@@ -190,7 +194,7 @@ impl Vim {
                 })
             });
         });
-        self.switch_mode(Mode::Normal, true, cx);
+        self.switch_mode(Vim::default_mode(cx), true, cx);
     }
 }
 
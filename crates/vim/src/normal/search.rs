@@ -145,6 +145,7 @@ impl Vim {
                         prior_selections,
                         prior_operator: self.operator_stack.last().cloned(),
                         prior_mode: self.mode,
+                        helix_regex_selection: None,
                     }
                 });
             }
@@ -158,6 +159,10 @@ impl Vim {
     }
 
     pub fn search_submit(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(selection) = self.search.helix_regex_selection.take() {
+            self.helix_regex_submit(selection, cx);
+            return;
+        }
         self.store_visual_marks(cx);
         let Some(pane) = self.pane(cx) else { return };
         let result = pane.update(cx, |pane, cx| {
@@ -10,7 +10,7 @@ use editor::{
 use itertools::Itertools;
 
 use gpui::{actions, impl_actions, ViewContext};
-use language::{BufferSnapshot, CharKind, Point, Selection};
+use language::{BufferSnapshot, CharKind, Point, Selection, TextObject};
 use multi_buffer::MultiBufferRow;
 use serde::Deserialize;
 
@@ -29,6 +29,8 @@ pub enum Object {
     AngleBrackets,
     Argument,
     Tag,
+    Method,
+    Class,
 }
 
 #[derive(Clone, Deserialize, PartialEq)]
@@ -54,7 +56,9 @@ actions!(
         CurlyBrackets,
         AngleBrackets,
         Argument,
-        Tag
+        Tag,
+        Method,
+        Class
     ]
 );
 
@@ -100,6 +104,12 @@ pub fn register(editor: &mut Editor, cx: &mut ViewContext<Vim>) {
     Vim::action(editor, cx, |vim, _: &Argument, cx| {
         vim.object(Object::Argument, cx)
     });
+    Vim::action(editor, cx, |vim, _: &Method, cx| {
+        vim.object(Object::Method, cx)
+    });
+    Vim::action(editor, cx, |vim, _: &Class, cx| {
+        vim.object(Object::Class, cx)
+    });
 }
 
 impl Vim {
@@ -107,6 +117,7 @@ impl Vim {
         match self.mode {
             Mode::Normal => self.normal_object(object, cx),
             Mode::Visual | Mode::VisualLine | Mode::VisualBlock => self.visual_object(object, cx),
+            Mode::HelixNormal => self.helix_object(object, cx),
             Mode::Insert | Mode::Replace => {
                 // Shouldn't execute a text object in insert mode. Ignoring
             }
@@ -129,7 +140,9 @@ impl Object {
             | Object::AngleBrackets
             | Object::CurlyBrackets
             | Object::SquareBrackets
-            | Object::Argument => true,
+            | Object::Argument
+            | Object::Method
+            | Object::Class => true,
         }
     }
 
@@ -144,7 +157,9 @@ impl Object {
             | Object::SquareBrackets
             | Object::Tag
             | Object::CurlyBrackets
-            | Object::AngleBrackets => true,
+            | Object::AngleBrackets
+            | Object::Method
+            | Object::Class => true,
         }
     }
 
@@ -167,7 +182,9 @@ impl Object {
             | Object::AngleBrackets
             | Object::VerticalBars
             | Object::Tag
-            | Object::Argument => Mode::Visual,
+            | Object::Argument
+            | Object::Method
+            | Object::Class => Mode::Visual,
             Object::Paragraph => Mode::VisualLine,
         }
     }
@@ -215,6 +232,24 @@ impl Object {
                 surrounding_markers(map, relative_to, around, self.is_multiline(), '<', '>')
             }
             Object::Argument => argument(map, relative_to, around),
+            Object::Method => text_object(
+                map,
+                relative_to,
+                if around {
+                    TextObject::AroundFunction
+                } else {
+                    TextObject::InsideFunction
+                },
+            ),
+            Object::Class => text_object(
+                map,
+                relative_to,
+                if around {
+                    TextObject::AroundClass
+                } else {
+                    TextObject::InsideClass
+                },
+            ),
         }
     }
 
@@ -564,6 +599,30 @@ fn argument(
     }
 }
 
+/// Returns the range of the smallest text object of the given kind that contains
+/// `relative_to`, as described by the language's `textobjects` query.
+fn text_object(
+    map: &DisplaySnapshot,
+    relative_to: DisplayPoint,
+    target: TextObject,
+) -> Option<Range<DisplayPoint>> {
+    let snapshot = &map.buffer_snapshot;
+    let offset = relative_to.to_offset(map, Bias::Left);
+
+    let excerpt = snapshot.excerpt_containing(offset..offset)?;
+    let buffer_offset = excerpt.map_offset_to_buffer(offset);
+    let result = excerpt
+        .buffer()
+        .range_for_text_object(buffer_offset..buffer_offset, target)?;
+
+    if excerpt.contains_buffer_range(result.clone()) {
+        let result = excerpt.map_range_from_buffer(result);
+        Some(result.start.to_display_point(map)..result.end.to_display_point(map))
+    } else {
+        None
+    }
+}
+
 fn sentence(
     map: &DisplaySnapshot,
     relative_to: DisplayPoint,
@@ -1453,6 +1512,71 @@ mod test {
         cx.assert_state("let a = [«test::call(first_arg)ˇ»]", Mode::Visual);
     }
 
+    #[gpui::test]
+    async fn test_method_and_class_objects(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        let text = indoc! {"
+            impl A {
+                fn a() {
+                    let x = ˇ1;
+                }
+            }
+        "};
+
+        cx.set_state(text, Mode::Normal);
+        cx.simulate_keystrokes("v i f");
+        cx.assert_state(
+            indoc! {"
+                impl A {
+                    fn a() {
+                        «let x = 1;ˇ»
+                    }
+                }
+            "},
+            Mode::Visual,
+        );
+
+        cx.set_state(text, Mode::Normal);
+        cx.simulate_keystrokes("v a f");
+        cx.assert_state(
+            indoc! {"
+                impl A {
+                    «fn a() {
+                        let x = 1;
+                    }ˇ»
+                }
+            "},
+            Mode::Visual,
+        );
+
+        cx.set_state(text, Mode::Normal);
+        cx.simulate_keystrokes("v i c");
+        cx.assert_state(
+            indoc! {"
+                impl A {
+                    «fn a() {
+                        let x = 1;
+                    }ˇ»
+                }
+            "},
+            Mode::Visual,
+        );
+
+        cx.set_state(text, Mode::Normal);
+        cx.simulate_keystrokes("v a c");
+        cx.assert_state(
+            indoc! {"
+                «impl A {
+                    fn a() {
+                        let x = 1;
+                    }
+                }ˇ»
+            "},
+            Mode::Visual,
+        );
+    }
+
     #[gpui::test]
     async fn test_delete_surrounding_character_objects(cx: &mut gpui::TestAppContext) {
         let mut cx = NeovimBackedTestContext::new(cx).await;
@@ -54,6 +54,21 @@ impl VimTestContext {
         )
     }
 
+    pub async fn new_helix(cx: &mut gpui::TestAppContext) -> VimTestContext {
+        Self::init(cx);
+        let mut cx = Self::new_with_lsp(
+            EditorLspTestContext::new_rust(Default::default(), cx).await,
+            true,
+        );
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<HelixModeSetting>(cx, |s| *s = Some(true));
+            });
+        });
+        cx.run_until_parked();
+        cx
+    }
+
     pub fn new_with_lsp(mut cx: EditorLspTestContext, enabled: bool) -> VimTestContext {
         cx.update(|cx| {
             SettingsStore::update_global(cx, |store, cx| {
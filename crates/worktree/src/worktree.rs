@@ -7,7 +7,7 @@ use ::ignore::gitignore::{Gitignore, GitignoreBuilder};
 use anyhow::{anyhow, Context as _, Result};
 use clock::ReplicaId;
 use collections::{HashMap, HashSet, VecDeque};
-use fs::{copy_recursive, Fs, PathEvent, RemoveOptions, Watcher};
+use fs::{archive, copy_recursive, Fs, PathEvent, RemoveOptions, Watcher};
 use futures::{
     channel::{
         mpsc::{self, UnboundedSender},
@@ -1236,6 +1236,10 @@ impl LocalWorktree {
     }
 
     fn load_file(&self, path: &Path, cx: &ModelContext<Worktree>) -> Task<Result<LoadedFile>> {
+        if let Some((archive_path, inner_path)) = archive::split_archive_entry_path(path) {
+            return self.load_archive_entry(path, &archive_path, inner_path, cx);
+        }
+
         let path = Arc::from(path);
         let abs_path = self.absolutize(&path);
         let fs = self.fs.clone();
@@ -1300,6 +1304,48 @@ impl LocalWorktree {
         })
     }
 
+    /// Loads a file stored in an archive of the worktree, such as `lib.jar!/src/main.rs`.
+    fn load_archive_entry(
+        &self,
+        path: &Path,
+        archive_path: &Path,
+        inner_path: PathBuf,
+        cx: &ModelContext<Worktree>,
+    ) -> Task<Result<LoadedFile>> {
+        let path = Arc::from(path);
+        let abs_archive_path = self.absolutize(archive_path);
+        let fs = self.fs.clone();
+        let is_private = self.is_path_private(archive_path);
+
+        cx.spawn(|this, _| async move {
+            let abs_archive_path = abs_archive_path?;
+            let extracted_path = archive::extract_archive(fs.as_ref(), &abs_archive_path)
+                .await?
+                .file_path(&inner_path)
+                .with_context(|| format!("{inner_path:?} not found in {abs_archive_path:?}"))?;
+            let text = fs
+                .load(&extracted_path)
+                .await
+                .with_context(|| format!("{path:?} is not a text file"))?;
+            let worktree = this
+                .upgrade()
+                .ok_or_else(|| anyhow!("worktree was dropped"))?;
+            Ok(LoadedFile {
+                file: Arc::new(File {
+                    entry_id: None,
+                    worktree,
+                    path,
+                    mtime: None,
+                    is_local: true,
+                    is_deleted: false,
+                    is_private,
+                }),
+                text,
+                diff_base: None,
+            })
+        })
+    }
+
     /// Find the lowest path in the worktree's datastructures that is an ancestor
     fn lowest_ancestor(&self, path: &Path) -> PathBuf {
         let mut lowest_ancestor = None;
@@ -430,6 +430,7 @@ fn main() {
         file_finder::init(cx);
         tab_switcher::init(cx);
         outline::init(cx);
+        undo_tree::init(cx);
         project_symbols::init(cx);
         project_panel::init(Assets, cx);
         outline_panel::init(Assets, cx);
@@ -48,9 +48,10 @@ use workspace::CloseIntent;
 
 use paths::{local_settings_file_relative_path, local_tasks_file_relative_path};
 use terminal_view::terminal_panel::{self, TerminalPanel};
+use undo_tree::UndoTreePanel;
 use util::{asset_str, ResultExt};
 use uuid::Uuid;
-use vim::VimModeSetting;
+use vim::{HelixModeSetting, VimModeSetting};
 use welcome::{BaseKeymap, MultibufferHint};
 use workspace::{
     create_and_open_local_file, notifications::simple_message_notification::MessageNotification,
@@ -241,6 +242,7 @@ pub fn initialize_workspace(
 
             let project_panel = ProjectPanel::load(workspace_handle.clone(), cx.clone());
             let outline_panel = OutlinePanel::load(workspace_handle.clone(), cx.clone());
+            let undo_tree_panel = UndoTreePanel::load(workspace_handle.clone(), cx.clone());
             let terminal_panel = TerminalPanel::load(workspace_handle.clone(), cx.clone());
             let channels_panel =
                 collab_ui::collab_panel::CollabPanel::load(workspace_handle.clone(), cx.clone());
@@ -254,6 +256,7 @@ pub fn initialize_workspace(
             let (
                 project_panel,
                 outline_panel,
+                undo_tree_panel,
                 terminal_panel,
                 assistant_panel,
                 channels_panel,
@@ -262,6 +265,7 @@ pub fn initialize_workspace(
             ) = futures::try_join!(
                 project_panel,
                 outline_panel,
+                undo_tree_panel,
                 terminal_panel,
                 assistant_panel,
                 channels_panel,
@@ -273,6 +277,7 @@ pub fn initialize_workspace(
                 workspace.add_panel(assistant_panel, cx);
                 workspace.add_panel(project_panel, cx);
                 workspace.add_panel(outline_panel, cx);
+                workspace.add_panel(undo_tree_panel, cx);
                 workspace.add_panel(terminal_panel, cx);
                 workspace.add_panel(channels_panel, cx);
                 workspace.add_panel(chat_panel, cx);
@@ -804,13 +809,14 @@ pub fn handle_keymap_file_changes(
 ) {
     BaseKeymap::register(cx);
     VimModeSetting::register(cx);
+    HelixModeSetting::register(cx);
 
     let (base_keymap_tx, mut base_keymap_rx) = mpsc::unbounded();
     let mut old_base_keymap = *BaseKeymap::get_global(cx);
-    let mut old_vim_enabled = VimModeSetting::get_global(cx).0;
+    let mut old_vim_enabled = vim_keymap_enabled(cx);
     cx.observe_global::<SettingsStore>(move |cx| {
         let new_base_keymap = *BaseKeymap::get_global(cx);
-        let new_vim_enabled = VimModeSetting::get_global(cx).0;
+        let new_vim_enabled = vim_keymap_enabled(cx);
 
         if new_base_keymap != old_base_keymap || new_vim_enabled != old_vim_enabled {
             old_base_keymap = new_base_keymap;
@@ -862,7 +868,7 @@ pub fn load_default_keymap(cx: &mut AppContext) {
     }
 
     KeymapFile::load_asset(DEFAULT_KEYMAP_PATH, cx).unwrap();
-    if VimModeSetting::get_global(cx).0 {
+    if vim_keymap_enabled(cx) {
         KeymapFile::load_asset("keymaps/vim.json", cx).unwrap();
     }
 
@@ -871,6 +877,10 @@ pub fn load_default_keymap(cx: &mut AppContext) {
     }
 }
 
+fn vim_keymap_enabled(cx: &AppContext) -> bool {
+    VimModeSetting::get_global(cx).0 || HelixModeSetting::get_global(cx).0
+}
+
 pub fn open_new_ssh_project_from_project(
     workspace: &mut Workspace,
     paths: Vec<PathBuf>,